@@ -0,0 +1,118 @@
+use crate::config::AppConfig;
+use crate::runtime::RuntimeHandle;
+use crossbeam_channel::{RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Entries kept in memory for the History tab.
+const MAX_ENTRIES: usize = 1000;
+/// The log is rotated to `audit.log.1` past this size, so at most twice this is kept.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// One lifecycle action, stored as a JSON line in `audit.log`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: i64, // Unix seconds
+    pub user: String,
+    pub project: String,
+    pub action: String,
+}
+
+impl AuditEntry {
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        chrono::DateTime::from_timestamp(self.timestamp, 0).map(|t| t.with_timezone(&chrono::Local))
+    }
+}
+
+/// Per-install record of what was done to which project and when, so data
+/// loss can be traced back to the action that caused it.
+pub struct AuditLog {
+    path: PathBuf,
+    user: String,
+    entries: VecDeque<AuditEntry>, // Oldest first
+    writer: Sender<AuditEntry>,    // Appends to the file off the UI thread
+}
+
+impl AuditLog {
+    pub fn open(runtime: &RuntimeHandle) -> Self {
+        let path = AppConfig::config_dir().join("audit.log");
+        let mut entries: VecDeque<AuditEntry> = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        while entries.len() > MAX_ENTRIES {
+            entries.pop_front();
+        }
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        // Drains the queue before stopping, so actions taken on exit are kept
+        let (writer, queue) = crossbeam_channel::unbounded::<AuditEntry>();
+        let file = path.clone();
+        let cancel = runtime.cancel_token();
+        runtime.supervise_blocking("audit writer", move || {
+            let queue = queue.clone();
+            let file = file.clone();
+            let cancel = cancel.clone();
+            move || loop {
+                match queue.recv_timeout(Duration::from_millis(250)) {
+                    Ok(entry) => {
+                        if let Err(e) = append(&file, &entry) {
+                            log::error!("Failed to write {}: {}", file.display(), e);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) if !cancel.is_cancelled() => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        Self {
+            path,
+            user,
+            entries,
+            writer,
+        }
+    }
+
+    /// Append an action to the log file and the in-memory history.
+    pub fn record(&mut self, project: &str, action: impl Into<String>) {
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().timestamp(),
+            user: self.user.clone(),
+            project: project.to_string(),
+            action: action.into(),
+        };
+        log::info!("[audit] {}: {}", entry.project, entry.action);
+        self.writer.send(entry.clone()).ok();
+        self.entries.push_back(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn entries(&mut self) -> &[AuditEntry] {
+        self.entries.make_contiguous()
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+fn append(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if fs::metadata(path).is_ok_and(|m| m.len() > MAX_FILE_BYTES) {
+        fs::rename(path, path.with_extension("log.1"))?;
+    }
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
@@ -1,8 +1,11 @@
 #![allow(dead_code)]
+use crate::docker::compose::PHP_WORKER;
+use crate::error::DockStackError;
+use crate::plugins;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -14,11 +17,214 @@ pub struct AppConfig {
     pub window: WindowConfig,
     #[serde(default)]
     pub stop_on_exit: bool,
+    #[serde(default = "default_true")]
+    pub bind_localhost: bool, // Default for new projects
+    #[serde(default = "default_true")]
+    pub notifications: bool, // Notify when an operation ends while the window is in the background
+    #[serde(default = "default_true")]
+    pub crash_reports: bool, // Write a report to crash-reports/ when DockStack panics
+    #[serde(default)]
+    pub buffers: BufferConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    #[serde(default)]
+    pub hooks_enabled: bool, // Run the scripts in the hooks folder on stack events
+    #[serde(default)]
+    pub registries: Vec<RegistryConfig>, // Private registries DockStack logs in to
+    #[serde(default)]
+    pub mirror: MirrorConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub status_page: StatusPageConfig,
+}
+
+/// How the embedded terminal starts its shell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    pub windows_shell: String, // One of WINDOWS_SHELLS; empty for PowerShell
+    pub unix_shell: String,    // Path or name; empty for $SHELL
+    pub login_shell: bool,
+    pub extra_path: String, // Directories prepended to PATH, one per line
+    pub env: String,        // KEY=VALUE per line, e.g. DOCKER_HOST
+    pub record_transcripts: bool, // Write each session to the transcripts folder
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            windows_shell: String::new(),
+            unix_shell: String::new(),
+            login_shell: false,
+            extra_path: String::new(),
+            env: String::new(),
+            record_transcripts: true,
+        }
+    }
+}
+
+pub const WINDOWS_SHELLS: &[&str] = &["powershell", "cmd", "git-bash"];
+
+/// HTTP proxy for networks that reach the internet only through one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub enabled: bool, // Given to docker commands, and the default for new projects
+    pub http: String,  // e.g. `http://proxy.corp:3128`
+    pub https: String,
+    pub no_proxy: String, // Comma-separated hosts reached directly
+}
+
+impl ProxyConfig {
+    /// Variables for the proxies set, in both cases since tools disagree on which
+    /// they read. Empty while disabled.
+    pub fn env(&self) -> Vec<(String, String)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let vars = [
+            ("HTTP_PROXY", &self.http),
+            ("HTTPS_PROXY", &self.https),
+            ("NO_PROXY", &self.no_proxy),
+        ];
+        let mut env = Vec::new();
+        for (name, value) in vars {
+            let value = value.trim();
+            if !value.is_empty() {
+                env.push((name.to_string(), value.to_string()));
+                env.push((name.to_lowercase(), value.to_string()));
+            }
+        }
+        env
+    }
+}
+
+/// Pull-through cache for Docker Hub, for slow links or Hub's rate limit. The
+/// engine can use it for every Hub pull (`url`, set in the daemon's own
+/// config), or projects can name it in their images (`image_prefix`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirrorConfig {
+    pub enabled: bool,        // New projects pull through `image_prefix`
+    pub url: String,          // `registry-mirrors` entry, e.g. `https://mirror.corp`
+    pub image_prefix: String, // e.g. `harbor.corp/dockerhub` for a Harbor proxy cache
+}
+
+impl MirrorConfig {
+    pub fn prefix(&self) -> String {
+        self.image_prefix.trim().trim_end_matches('/').to_string()
+    }
+
+    /// `image_mirror` for a new project.
+    pub fn project_prefix(&self) -> String {
+        match self.enabled {
+            true => self.prefix(),
+            false => String::new(),
+        }
+    }
+
+    /// The `daemon.json` setting that makes the engine pull Docker Hub images
+    /// through `url`.
+    pub fn daemon_json(&self) -> String {
+        let config = serde_json::json!({ "registry-mirrors": [self.url.trim()] });
+        serde_json::to_string_pretty(&config).unwrap_or_default()
+    }
+}
+
+/// A private registry. Its password is kept in the OS keyring, not in this file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistryConfig {
+    pub host: String, // e.g. `ghcr.io` or `registry.corp:5000`; `docker.io` for Docker Hub
+    pub username: String,
+    pub verified_at: String, // Last successful `docker login`; empty until then
+}
+
+/// Sizes of the in-memory buffers between the backends and the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BufferConfig {
+    pub log_lines: usize,      // Logs tab; the oldest lines are dropped
+    pub terminal_lines: usize, // Embedded terminal scrollback
+    pub event_queue: usize,    // Pending Docker events; read at startup
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            log_lines: 20_000,
+            terminal_lines: 5000,
+            event_queue: 5000,
+        }
+    }
+}
+
+/// Opt-in cleanup of what Docker accumulates, run every `interval_hours`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    pub retention_hours: u32, // Only prune what is older than this; 0 = everything
+    pub images: bool,         // Dangling images
+    pub containers: bool,     // Stopped DockStack containers
+    pub build_cache: bool,
+    pub last_run: i64,        // Unix seconds of the last run; 0 = never
+    pub last_summary: String, // What the last run reclaimed
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            retention_hours: 72,
+            images: true,
+            containers: true,
+            build_cache: true,
+            last_run: 0,
+            last_summary: String::new(),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn is_due(&self, now: i64) -> bool {
+        self.enabled && now - self.last_run >= i64::from(self.interval_hours.max(1)) * 3600
+    }
+}
+
+/// Read-only web page with the active project's stack state, for teammates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusPageConfig {
+    pub enabled: bool,
+    pub port: u16, // On localhost only; others reach it through a port forward
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8099,
+        }
+    }
+}
+
+impl StatusPageConfig {
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThemeConfig {
     pub dark_mode: bool,
+    #[serde(default)]
+    pub high_contrast: bool, // Brighter borders and text, color-blind-safe status colors
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,8 +241,347 @@ pub struct ProjectConfig {
     pub directory: String,
     pub services: HashMap<String, ServiceConfig>,
     pub ssl_enabled: bool,
-    pub custom_ports: HashMap<String, u16>,
+    pub custom_ports: HashMap<String, u16>, // Host ports published instead of the services' own
     pub domain: String,
+    #[serde(default)]
+    pub directory_confirmed: bool, // User approved writing into a non-empty directory
+    #[serde(default = "default_startup_timeout")]
+    pub startup_timeout_secs: u64, // How long to wait for containers to become healthy
+    #[serde(default = "default_pull_policy")]
+    pub pull_policy: String, // `compose up --pull` value, one of PULL_POLICIES
+    #[serde(default = "default_file_sync")]
+    pub file_sync: String, // How code directories reach containers, one of FILE_SYNC_MODES
+    #[serde(default = "default_selinux_relabel")]
+    pub selinux_relabel: String, // Bind-mount relabel flag, one of SELINUX_RELABEL_MODES
+    #[serde(default = "default_web_topology")]
+    pub web_topology: String, // Which web servers serve the site, one of WEB_TOPOLOGIES
+    #[serde(default)]
+    pub log_files: Vec<LogSource>, // Host log files tailed into the Logs tab
+    #[serde(default)]
+    pub saved_requests: Vec<SavedRequest>, // API client collection
+    #[serde(default)]
+    pub expose_on_lan: bool, // Publish ports on all interfaces for phones/tablets
+    #[serde(default = "default_true")]
+    pub bind_localhost: bool, // Publish ports on 127.0.0.1 only
+    #[serde(default)]
+    pub use_proxy: bool, // Give containers and builds the proxy from the app settings
+    #[serde(default)]
+    pub image_mirror: String, // Prefix Docker Hub images are pulled through; empty = Docker Hub
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub last_opened: i64, // Unix seconds of the last switch to this project; 0 = never
+    #[serde(default)]
+    pub pinned: bool, // Listed first in the workspace menu and tray
+    #[serde(default)]
+    pub tags: Vec<ProjectTag>,
+    #[serde(default)]
+    pub archived: bool, // Hidden from the pickers and never started; config and data are kept
+    #[serde(default)]
+    pub icon: String, // Emoji shown next to the name; empty = default folder
+    #[serde(default)]
+    pub accent: Option<[u8; 3]>,
+    #[serde(default)]
+    pub env: String, // KEY=VALUE lines set in every service that doesn't set the key itself
+    #[serde(default)]
+    pub timezone: String, // IANA name such as `Europe/Berlin`; empty leaves containers on UTC
+    #[serde(default)]
+    pub locale: String, // e.g. `en_US.UTF-8`; empty keeps each image's own
+    #[serde(default)]
+    pub branch_env: Vec<BranchEnv>, // Applied while the project's git branch matches
+    #[serde(default)]
+    pub commit_generated: bool, // Generated files use relative paths so they can be committed
+    #[serde(default)]
+    pub lock_images: bool, // Pin images to the digests in dockstack.lock
+    #[serde(default)]
+    pub php_routes: Vec<PhpRoute>, // Parts of the site served by other PHP instances
+    #[serde(default)]
+    pub code_in_directory: bool, // The code is the project directory itself, not its www/ and app/
+    #[serde(default)]
+    pub web_root: String, // Subdirectory of the code served by the web server, e.g. `public`
+    #[serde(default)]
+    pub profiles: Vec<EnvProfile>,
+    #[serde(default)]
+    pub active_profile: String, // Name of the selected profile; empty = the base setup
+    #[serde(default)]
+    pub pending_renames: Vec<ServiceRename>, // Cleaned up before the stack next starts
+    #[serde(default)]
+    pub devcontainer_service: String, // Service VS Code attaches to; empty = first code service
+}
+
+/// A service renamed since the stack last started. Before the next start its
+/// old container is removed and, if asked, its data copied to the new volumes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRename {
+    pub container: String, // Container under the old name, e.g. `dockstack_ab12_custom-1f2e`
+    #[serde(default)]
+    pub volumes: Vec<VolumeCopy>,
+}
+
+/// A named volume to copy, both as declared in the compose file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VolumeCopy {
+    pub from: String,
+    pub to: String,
+}
+
+/// Extra environment for one service while the project is on a matching git branch,
+/// e.g. a separate database name per feature branch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BranchEnv {
+    pub branch: String, // Exact name, or a prefix ending in `*` such as `feature/*`
+    pub service: String,
+    #[serde(default)]
+    pub vars: String, // One KEY=VALUE per line
+}
+
+impl BranchEnv {
+    pub fn matches(&self, branch: &str) -> bool {
+        match self.branch.trim().strip_suffix('*') {
+            Some(prefix) => branch.starts_with(prefix),
+            None => branch == self.branch.trim(),
+        }
+    }
+
+    pub fn vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        env_lines(&self.vars)
+    }
+}
+
+/// Sends part of the site to another PHP-FPM service, e.g. `/legacy/` to PHP 7.4
+/// while the rest runs on 8.3.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhpRoute {
+    pub service: String, // `php` or another instance of it
+    #[serde(default)]
+    pub path: String, // URL prefix on the project domain, e.g. `/legacy/`
+    #[serde(default)]
+    pub host: String, // Server name sent wholly to the service instead, e.g. `legacy.shop.test`
+}
+
+/// A named variant of the project, such as `test` or `staging`, that can run a
+/// subset of its services with other ports and environment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvProfile {
+    pub name: String,
+    #[serde(default)]
+    pub services: Vec<String>, // Enabled services the profile runs; empty = all of them
+    #[serde(default)]
+    pub overrides: Vec<ProfileOverride>,
+}
+
+/// Port and environment of one service while its profile is active.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileOverride {
+    pub service: String,
+    #[serde(default)]
+    pub port: u16, // 0 keeps the service's own port
+    #[serde(default)]
+    pub vars: String, // One KEY=VALUE per line
+}
+
+/// `KEY=VALUE` pairs of a multi-line text, skipping blanks and `#` comments.
+fn env_lines(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .map(|(key, value)| (key.trim(), value))
+        .filter(|(key, _)| !key.is_empty() && !key.starts_with('#'))
+}
+
+/// User-defined label for organizing projects, e.g. a client or stack type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectTag {
+    pub name: String,
+    pub color: [u8; 3],
+}
+
+/// Settings for the project's compose network.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub subnet: String, // IPv4 CIDR; empty lets Docker pick one
+    #[serde(default)]
+    pub ipv6: bool,
+    #[serde(default)]
+    pub ipv6_subnet: String,
+    #[serde(default)]
+    pub external: Vec<String>, // Existing networks every service also joins
+}
+
+/// A request saved in the project's API client collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedRequest {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: String, // One `Name: value` per line
+    #[serde(default)]
+    pub body: String,
+}
+
+/// A host log file tailed alongside container logs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogSource {
+    pub path: String, // Absolute, or relative to the project directory
+    pub color: [u8; 3],
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl LogSource {
+    /// Short name shown as the line prefix in the Logs tab: the file name, or the
+    /// path as entered when another of `sources` has the same file name.
+    pub fn label(&self, sources: &[LogSource]) -> String {
+        let name = self.file_name();
+        let clashes = sources
+            .iter()
+            .any(|s| s.path != self.path && s.file_name() == name);
+        if clashes {
+            self.path.clone()
+        } else {
+            name
+        }
+    }
+
+    fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    pub fn resolve(&self, project_dir: &str) -> PathBuf {
+        Path::new(project_dir).join(&self.path)
+    }
+}
+
+/// A compose file read for import, before it becomes a project.
+#[derive(Debug, Clone)]
+pub struct ComposeImport {
+    pub name: String, // Name of the directory holding the file
+    pub directory: PathBuf,
+    pub services: Vec<(String, ServiceConfig)>, // Sorted by name
+}
+
+impl ComposeImport {
+    pub fn read(yaml_path: &Path) -> crate::error::Result<Self> {
+        let content = fs::read_to_string(yaml_path)
+            .map_err(|e| DockStackError::io(format!("Cannot read {}", yaml_path.display()), &e))?;
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&content)
+            .map_err(|e| DockStackError::Config(format!("Invalid compose file: {}", e)))?;
+
+        let directory = yaml_path.parent().unwrap_or(Path::new("."));
+        let project_name = directory
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let mut services = Vec::new();
+
+        if let Some(yaml_services) = yaml.get("services").and_then(|v| v.as_mapping()) {
+            for (name_val, svc_val) in yaml_services {
+                let name = name_val.as_str().unwrap_or("unknown").to_string();
+                let mut svc = ServiceConfig {
+                    enabled: true,
+                    is_custom: true,
+                    is_locked: false,
+                    display_name: Some(name.clone()),
+                    image: None,
+                    port: 0,
+                    version: "latest".to_string(),
+                    env_vars: HashMap::new(),
+                    settings: HashMap::new(),
+                    ..ServiceConfig::default()
+                };
+
+                if let Some(img) = svc_val.get("image").and_then(|v| v.as_str()) {
+                    // A colon before the last `/` is a registry port, e.g. `localhost:5000/app`
+                    let last_segment = img.rsplit('/').next().unwrap_or(img);
+                    match img.rsplit_once(':') {
+                        Some((image, tag)) if !img.contains('@') && last_segment.contains(':') => {
+                            svc.image = Some(image.to_string());
+                            svc.version = tag.to_string();
+                        }
+                        _ => svc.image = Some(img.to_string()),
+                    }
+                }
+
+                if let Some(ports) = svc_val.get("ports").and_then(|v| v.as_sequence()) {
+                    if let Some(p_str) = ports.first().and_then(|p| p.as_str()) {
+                        if let Some(host_port) =
+                            p_str.split(':').next().and_then(|p| p.parse::<u16>().ok())
+                        {
+                            svc.port = host_port;
+                        }
+                    }
+                }
+
+                if let Some(policy) = svc_val.get("restart").and_then(|v| v.as_str()) {
+                    if RESTART_POLICIES.contains(&policy) {
+                        svc.restart_policy = policy.to_string();
+                    }
+                }
+
+                services.push((name, svc));
+            }
+        }
+        services.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self {
+            name: project_name,
+            directory: directory.to_path_buf(),
+            services,
+        })
+    }
+
+    /// Published host ports of the imported services, for conflict checks.
+    pub fn host_ports(&self) -> Vec<(String, u16)> {
+        self.services
+            .iter()
+            .filter(|(_, svc)| svc.port != 0)
+            .map(|(name, svc)| (name.clone(), svc.port))
+            .collect()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_startup_timeout() -> u64 {
+    60
+}
+
+pub const PULL_POLICIES: &[&str] = &["missing", "always", "never"];
+
+fn default_pull_policy() -> String {
+    "missing".to_string()
+}
+
+/// "bind" mounts code directories directly; "mutagen" puts them in named volumes
+/// kept in sync by Mutagen, which is much faster on macOS.
+pub const FILE_SYNC_MODES: &[&str] = &["bind", "mutagen"];
+
+fn default_file_sync() -> String {
+    "bind".to_string()
+}
+
+/// "auto" adds `:z` only while SELinux is enforcing; "shared" always adds `:z`,
+/// "private" always adds `:Z` and "off" never relabels.
+pub const SELINUX_RELABEL_MODES: &[&str] = &["auto", "shared", "private", "off"];
+
+fn default_selinux_relabel() -> String {
+    "auto".to_string()
+}
+
+/// "nginx" and "apache" run that server alone; "nginx-apache" runs both, with
+/// Nginx in front proxying every request to Apache.
+pub const WEB_TOPOLOGIES: &[&str] = &["nginx", "apache", "nginx-apache"];
+
+fn default_web_topology() -> String {
+    "nginx".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +599,86 @@ pub struct ServiceConfig {
     pub is_locked: bool, // If true, DockStack won't regenerate its config files
     pub env_vars: HashMap<String, String>,
     pub settings: HashMap<String, String>,
+    #[serde(default = "default_restart_policy")]
+    pub restart_policy: String, // Compose `restart:` value
+    #[serde(default)]
+    pub bind_all_interfaces: bool, // Opt out of the project's localhost binding
+    #[serde(default)]
+    pub internal: bool, // No published ports; reachable only on the project network
+    #[serde(default)]
+    pub gpu: bool, // Reserve the host's NVIDIA GPUs
+    #[serde(default)]
+    pub run_as_host_user: bool, // Run as the project directory's owner so written files stay editable
+    #[serde(default = "default_mount_consistency")]
+    pub mount_consistency: String, // Code bind-mount flag, one of MOUNT_CONSISTENCIES
+    #[serde(default)]
+    pub volume_overlays: Vec<String>, // Code subdirectories backed by named volumes, e.g. vendor
+    #[serde(default)]
+    pub instance_of: Option<String>, // Registry service this is another instance of, e.g. `php`
+    #[serde(default)]
+    pub plugin: Option<String>, // Service plugin that generates it
+    #[serde(default)]
+    pub platform: String, // Compose `platform:`, one of PLATFORMS; empty = the engine's own
+    #[serde(default)]
+    pub registry: String, // Host of a configured registry a custom image is pulled from
+}
+
+/// Registry services a project can run more than one instance of.
+pub const INSTANCE_KINDS: &[&str] = &["php", "postgresql", "mysql", "redis"];
+
+impl ServiceConfig {
+    /// Registry service the config under `name` generates, e.g. `php` for a
+    /// second PHP instance named `php74` or `postgresql` for `postgres-analytics`.
+    pub fn kind<'a>(&'a self, name: &'a str) -> &'a str {
+        match self.instance_of.as_deref() {
+            Some(kind) if INSTANCE_KINDS.contains(&kind) => kind,
+            _ => name,
+        }
+    }
+}
+
+/// Platforms a service can be pinned to; amd64 runs under emulation on Apple Silicon.
+pub const PLATFORMS: &[&str] = &["linux/amd64", "linux/arm64"];
+
+/// Restart policies accepted by `docker compose`.
+pub const RESTART_POLICIES: &[&str] = &["no", "always", "on-failure", "unless-stopped"];
+
+fn default_restart_policy() -> String {
+    "unless-stopped".to_string()
+}
+
+/// Bind-mount consistency for code directories; only Docker Desktop on macOS acts on it.
+pub const MOUNT_CONSISTENCIES: &[&str] = &["default", "cached", "delegated"];
+
+fn default_mount_consistency() -> String {
+    "default".to_string()
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 0,
+            version: "latest".to_string(),
+            display_name: None,
+            image: None,
+            is_custom: false,
+            is_locked: false,
+            env_vars: HashMap::new(),
+            settings: HashMap::new(),
+            restart_policy: default_restart_policy(),
+            bind_all_interfaces: false,
+            internal: false,
+            gpu: false,
+            run_as_host_user: false,
+            mount_consistency: default_mount_consistency(),
+            volume_overlays: Vec::new(),
+            instance_of: None,
+            plugin: None,
+            platform: String::new(),
+            registry: String::new(),
+        }
+    }
 }
 
 impl Default for AppConfig {
@@ -63,13 +688,27 @@ impl Default for AppConfig {
             active_project_id: Some("default".to_string()),
             docker_path: "docker".to_string(),
             compose_path: "docker compose".to_string(),
-            theme: ThemeConfig { dark_mode: true },
+            theme: ThemeConfig {
+                dark_mode: true,
+                high_contrast: false,
+            },
             window: WindowConfig {
                 width: 1280.0,
                 height: 800.0,
                 minimize_to_tray: true,
             },
             stop_on_exit: false,
+            bind_localhost: true,
+            notifications: true,
+            crash_reports: true,
+            buffers: BufferConfig::default(),
+            terminal: TerminalConfig::default(),
+            proxy: ProxyConfig::default(),
+            hooks_enabled: false,
+            registries: Vec::new(),
+            mirror: MirrorConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            status_page: StatusPageConfig::default(),
         }
     }
 }
@@ -96,6 +735,7 @@ impl Default for ProjectConfig {
                     m
                 },
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -116,6 +756,7 @@ impl Default for ProjectConfig {
                     m
                 },
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -139,6 +780,7 @@ impl Default for ProjectConfig {
                     m.insert("memory_limit".to_string(), "256M".to_string());
                     m
                 },
+                ..ServiceConfig::default()
             },
         );
 
@@ -154,6 +796,7 @@ impl Default for ProjectConfig {
                 version: "2.4".to_string(),
                 env_vars: HashMap::new(),
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -169,6 +812,7 @@ impl Default for ProjectConfig {
                 version: "latest".to_string(),
                 env_vars: HashMap::new(),
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -189,6 +833,7 @@ impl Default for ProjectConfig {
                     m
                 },
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -212,6 +857,7 @@ impl Default for ProjectConfig {
                     m
                 },
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -227,6 +873,7 @@ impl Default for ProjectConfig {
                 version: "7".to_string(),
                 env_vars: HashMap::new(),
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
@@ -242,14 +889,151 @@ impl Default for ProjectConfig {
                 version: "latest".to_string(),
                 env_vars: HashMap::new(),
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
         services.insert(
-            "ssl".to_string(),
+            "python".to_string(),
             ServiceConfig {
-                enabled: false,
-                is_custom: false,
+                port: 8000,
+                version: "3.12-slim".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert(
+                        "command".to_string(),
+                        "python -m http.server 8000".to_string(),
+                    );
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "jupyter".to_string(),
+            ServiceConfig {
+                port: 8888,
+                version: "latest".to_string(),
+                env_vars: {
+                    let mut m = HashMap::new();
+                    m.insert("JUPYTER_TOKEN".to_string(), "dockstack".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "varnish".to_string(),
+            ServiceConfig {
+                port: 6081,
+                version: "7.6".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("cache_size".to_string(), "256M".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "dbgate".to_string(),
+            ServiceConfig {
+                port: 8084,
+                version: "latest".to_string(),
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "mssql".to_string(),
+            ServiceConfig {
+                port: 1433,
+                version: "2022-latest".to_string(),
+                env_vars: {
+                    let mut m = HashMap::new();
+                    m.insert(
+                        "MSSQL_SA_PASSWORD".to_string(),
+                        "DockStack!2024".to_string(),
+                    );
+                    m
+                },
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("accept_eula".to_string(), "false".to_string());
+                    m.insert("edition".to_string(), "Developer".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "opensearch".to_string(),
+            ServiceConfig {
+                port: 9200,
+                version: "2".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("heap_size".to_string(), "512m".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "solr".to_string(),
+            ServiceConfig {
+                port: 8983,
+                version: "9".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("heap_size".to_string(), "512m".to_string());
+                    m.insert("core".to_string(), "dockstack".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        // The password is generated when the service is first enabled
+        services.insert(
+            "sftp".to_string(),
+            ServiceConfig {
+                port: 2222,
+                version: "alpine".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("user".to_string(), "dockstack".to_string());
+                    m.insert("password".to_string(), String::new());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "mailpit".to_string(),
+            ServiceConfig {
+                port: 8025,
+                version: "latest".to_string(),
+                settings: {
+                    let mut m = HashMap::new();
+                    m.insert("smtp_port".to_string(), "1025".to_string());
+                    m
+                },
+                ..ServiceConfig::default()
+            },
+        );
+
+        services.insert(
+            "ssl".to_string(),
+            ServiceConfig {
+                enabled: false,
+                is_custom: false,
                 is_locked: false,
                 display_name: None,
                 image: None,
@@ -257,15 +1041,18 @@ impl Default for ProjectConfig {
                 version: "latest".to_string(),
                 env_vars: HashMap::new(),
                 settings: HashMap::new(),
+                ..ServiceConfig::default()
             },
         );
 
+        for plugin in plugins::installed().iter() {
+            services.insert(plugin.id.clone(), plugin.default_config());
+        }
+
         Self {
             id: "default".to_string(),
             name: "Default Project".to_string(),
-            directory: dirs::home_dir()
-                .unwrap_or_default()
-                .join("dockstack-projects")
+            directory: AppConfig::projects_root()
                 .join("default")
                 .to_string_lossy()
                 .to_string(),
@@ -273,6 +1060,38 @@ impl Default for ProjectConfig {
             ssl_enabled: false,
             custom_ports: HashMap::new(),
             domain: "dockstack.test".to_string(),
+            directory_confirmed: false,
+            startup_timeout_secs: default_startup_timeout(),
+            pull_policy: default_pull_policy(),
+            file_sync: default_file_sync(),
+            selinux_relabel: default_selinux_relabel(),
+            web_topology: default_web_topology(),
+            log_files: Vec::new(),
+            saved_requests: Vec::new(),
+            expose_on_lan: false,
+            bind_localhost: true,
+            use_proxy: false,
+            image_mirror: String::new(),
+            network: NetworkConfig::default(),
+            last_opened: 0,
+            pinned: false,
+            tags: Vec::new(),
+            archived: false,
+            icon: String::new(),
+            accent: None,
+            env: String::new(),
+            timezone: String::new(),
+            locale: String::new(),
+            branch_env: Vec::new(),
+            commit_generated: false,
+            lock_images: false,
+            php_routes: Vec::new(),
+            code_in_directory: false,
+            web_root: String::new(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
+            pending_renames: Vec::new(),
+            devcontainer_service: String::new(),
         }
     }
 }
@@ -286,6 +1105,26 @@ impl AppConfig {
         dir
     }
 
+    /// Point the projects pulling through the mirror at its current prefix;
+    /// with the prefix cleared they pull from Docker Hub again.
+    pub fn sync_mirror_prefix(&mut self) {
+        let prefix = self.mirror.prefix();
+        for project in self
+            .projects
+            .iter_mut()
+            .filter(|p| !p.image_mirror.is_empty())
+        {
+            project.image_mirror = prefix.clone();
+        }
+    }
+
+    /// Where new projects get their directories.
+    pub fn projects_root() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join("dockstack-projects")
+    }
+
     pub fn config_path() -> PathBuf {
         Self::config_dir().join("config.toml")
     }
@@ -294,8 +1133,12 @@ impl AppConfig {
         let path = Self::config_path();
         if path.exists() {
             match fs::read_to_string(&path) {
-                Ok(content) => match toml::from_str(&content) {
-                    Ok(config) => return config,
+                Ok(content) => match toml::from_str::<Self>(&content) {
+                    Ok(mut config) => {
+                        config.add_missing_builtin_services();
+                        config.sync_web_topologies();
+                        return config;
+                    }
                     Err(e) => {
                         log::error!("Failed to parse config: {}", e);
                     }
@@ -310,6 +1153,32 @@ impl AppConfig {
         config
     }
 
+    /// Give projects created by older versions the built-in services added since,
+    /// and every project the services of newly installed plugins.
+    fn add_missing_builtin_services(&mut self) {
+        let builtin = ProjectConfig::default().services;
+        for project in &mut self.projects {
+            for (name, svc) in &builtin {
+                project
+                    .services
+                    .entry(name.clone())
+                    .or_insert_with(|| svc.clone());
+            }
+        }
+    }
+
+    /// Projects saved with both web servers enabled from before the topology
+    /// setting keep both, with Nginx now in front of Apache.
+    fn sync_web_topologies(&mut self) {
+        for project in &mut self.projects {
+            let enabled = |name: &str| project.services.get(name).is_some_and(|s| s.enabled);
+            if enabled("nginx") && enabled("apache") && project.web_topology != "nginx-apache" {
+                log::info!("{}: Nginx now proxies to Apache", project.name);
+                project.web_topology = "nginx-apache".to_string();
+            }
+        }
+    }
+
     pub fn save(&self) {
         let path = Self::config_path();
         match toml::to_string_pretty(self) {
@@ -334,21 +1203,127 @@ impl AppConfig {
         self.projects.iter_mut().find(|p| p.id == id)
     }
 
+    /// Make `id` the active project and record when it was opened.
+    pub fn set_active_project(&mut self, id: &str) {
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == id) {
+            project.last_opened = chrono::Utc::now().timestamp();
+            self.active_project_id = Some(id.to_string());
+            self.save();
+        }
+    }
+
+    /// Pinned projects by name, then the rest most recently opened first.
+    /// The workspace menu and the tray list projects in this order; archived ones are left out.
+    pub fn projects_by_recency(&self) -> Vec<&ProjectConfig> {
+        let mut projects: Vec<_> = self.projects.iter().filter(|p| !p.archived).collect();
+        projects.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then_with(|| match a.pinned {
+                    true => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                    false => b.last_opened.cmp(&a.last_opened),
+                })
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        projects
+    }
+
+    /// Archived projects, by name.
+    pub fn archived_projects(&self) -> Vec<&ProjectConfig> {
+        let mut projects: Vec<_> = self.projects.iter().filter(|p| p.archived).collect();
+        projects.sort_by_key(|p| p.name.to_lowercase());
+        projects
+    }
+
+    /// Archive or restore a project. Archiving the active project switches to
+    /// the most recent remaining one.
+    pub fn set_archived(&mut self, id: &str, archived: bool) {
+        let Some(project) = self.projects.iter_mut().find(|p| p.id == id) else {
+            return;
+        };
+        project.archived = archived;
+        if archived && self.active_project_id.as_deref() == Some(id) {
+            self.active_project_id = self.projects_by_recency().first().map(|p| p.id.clone());
+        }
+        self.save();
+    }
+
+    /// Every tag used by a non-archived project, once per name (first color wins), sorted by name.
+    pub fn all_tags(&self) -> Vec<ProjectTag> {
+        let mut tags: Vec<ProjectTag> = Vec::new();
+        for tag in self
+            .projects
+            .iter()
+            .filter(|p| !p.archived)
+            .flat_map(|p| &p.tags)
+        {
+            if !tags.iter().any(|t| t.name.eq_ignore_ascii_case(&tag.name)) {
+                tags.push(tag.clone());
+            }
+        }
+        tags.sort_by_key(|t| t.name.to_lowercase());
+        tags
+    }
+
     pub fn add_project(&mut self, name: String) -> String {
         let id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-        let dir = dirs::home_dir()
-            .unwrap_or_default()
-            .join("dockstack-projects")
-            .join(&id);
+        let dir = AppConfig::projects_root().join(&id);
         fs::create_dir_all(&dir).ok();
         let project = ProjectConfig {
             id: id.clone(),
             name,
             directory: dir.to_string_lossy().to_string(),
+            bind_localhost: self.bind_localhost,
+            use_proxy: self.proxy.enabled,
+            image_mirror: self.mirror.project_prefix(),
+            ..ProjectConfig::default()
+        };
+        self.projects.push(project);
+        self.set_active_project(&id);
+        id
+    }
+
+    /// New active project in a directory DockStack prepared itself, e.g. a fresh
+    /// clone, so starting it needs no directory confirmation.
+    pub fn add_project_in(&mut self, name: String, directory: &Path) -> String {
+        let id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+        let project = ProjectConfig {
+            id: id.clone(),
+            name,
+            directory: directory.to_string_lossy().to_string(),
+            directory_confirmed: true,
+            bind_localhost: self.bind_localhost,
+            use_proxy: self.proxy.enabled,
+            image_mirror: self.mirror.project_prefix(),
             ..ProjectConfig::default()
         };
         self.projects.push(project);
-        self.active_project_id = Some(id.clone());
+        self.set_active_project(&id);
+        id
+    }
+
+    /// New active project running only `services`, e.g. ones rebuilt from
+    /// hand-started containers. Names taken by built-in services get a suffix.
+    pub fn add_project_with_services(
+        &mut self,
+        name: String,
+        services: Vec<(String, ServiceConfig)>,
+    ) -> String {
+        let id = self.add_project(name);
+        if let Some(project) = self.projects.iter_mut().find(|p| p.id == id) {
+            for svc in project.services.values_mut() {
+                svc.enabled = false;
+            }
+            for (name, svc) in services {
+                let mut key = name.clone();
+                let mut n = 2;
+                while project.services.contains_key(&key) {
+                    key = format!("{}_{}", name, n);
+                    n += 1;
+                }
+                project.services.insert(key, svc);
+            }
+        }
         self.save();
         id
     }
@@ -364,77 +1339,285 @@ impl AppConfig {
     pub fn import_from_compose(
         &mut self,
         yaml_path: &std::path::Path,
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(yaml_path)?;
-        let yaml: serde_yaml::Value = serde_yaml::from_str(&content)?;
+    ) -> crate::error::Result<String> {
+        let import = ComposeImport::read(yaml_path)?;
+        Ok(self.add_imported_project(import, HashMap::new()))
+    }
 
-        let project_dir = yaml_path.parent().unwrap_or(std::path::Path::new("."));
-        let project_name = project_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
+    /// New active project from a read compose file, publishing the services
+    /// named in `custom_ports` on those host ports instead of their own.
+    pub fn add_imported_project(
+        &mut self,
+        import: ComposeImport,
+        custom_ports: HashMap<String, u16>,
+    ) -> String {
+        let id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+        let project = ProjectConfig {
+            id: id.clone(),
+            name: format!("Imported: {}", import.name),
+            directory: import.directory.to_string_lossy().to_string(),
+            services: import.services.into_iter().collect(),
+            ssl_enabled: false,
+            custom_ports,
+            domain: format!("{}.test", import.name.to_lowercase().replace(' ', "-")),
+            bind_localhost: self.bind_localhost,
+            use_proxy: self.proxy.enabled,
+            image_mirror: self.mirror.project_prefix(),
+            ..ProjectConfig::default()
+        };
 
-        let mut services = HashMap::new();
+        self.projects.push(project);
+        self.set_active_project(&id);
+        id
+    }
+}
 
-        if let Some(yaml_services) = yaml.get("services").and_then(|v| v.as_mapping()) {
-            for (name_val, svc_val) in yaml_services {
-                let name = name_val.as_str().unwrap_or("unknown").to_string();
-                let mut svc = ServiceConfig {
-                    enabled: true,
-                    is_custom: true,
-                    is_locked: false,
-                    display_name: Some(name.clone()),
-                    image: None,
-                    port: 0,
-                    version: "latest".to_string(),
-                    env_vars: HashMap::new(),
-                    settings: HashMap::new(),
-                };
+impl ProjectConfig {
+    pub fn display_icon(&self) -> &str {
+        match self.icon.trim() {
+            "" => "📂",
+            icon => icon,
+        }
+    }
 
-                if let Some(img) = svc_val.get("image").and_then(|v| v.as_str()) {
-                    if img.contains(':') {
-                        let parts: Vec<&str> = img.split(':').collect();
-                        svc.image = Some(parts[0].to_string());
-                        svc.version = parts[1].to_string();
-                    } else {
-                        svc.image = Some(img.to_string());
-                    }
-                }
+    /// Icon and name, as shown in the window title and tray.
+    pub fn display_name(&self) -> String {
+        format!("{} {}", self.display_icon(), self.name)
+    }
 
-                if let Some(ports) = svc_val.get("ports").and_then(|v| v.as_sequence()) {
-                    if let Some(p_str) = ports[0].as_str() {
-                        if let Some(host_port) =
-                            p_str.split(':').next().and_then(|p| p.parse::<u16>().ok())
-                        {
-                            svc.port = host_port;
-                        }
-                    }
-                }
+    /// Branch checked out in the project directory, or in `www/` when the code
+    /// is its own repository. A detached HEAD shows as its short commit id.
+    pub fn git_branch(&self) -> Option<String> {
+        let dir = Path::new(&self.directory);
+        [dir.to_path_buf(), dir.join("www")]
+            .iter()
+            .find_map(|dir| read_git_head(&dir.join(".git")))
+    }
+
+    /// Variables every service of the project gets.
+    pub fn env_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        env_lines(&self.env)
+    }
+
+    /// The selected environment profile, if it still exists.
+    pub fn current_profile(&self) -> Option<&EnvProfile> {
+        self.profiles
+            .iter()
+            .find(|p| !self.active_profile.is_empty() && p.name == self.active_profile)
+    }
 
-                services.insert(name, svc);
+    /// The project as its active profile runs it: services outside the profile are
+    /// disabled, and the profile's ports and variables replace the services' own.
+    /// Custom ports apply first, so a profile's port still wins.
+    pub fn with_active_profile(&self) -> ProjectConfig {
+        let mut project = self.clone();
+        for (name, svc) in project.services.iter_mut() {
+            svc.port = self.host_port(name);
+        }
+        project.custom_ports.clear();
+        let Some(profile) = self.current_profile() else {
+            return project;
+        };
+        for (name, svc) in project.services.iter_mut() {
+            if !profile.services.is_empty() && !profile.services.contains(name) {
+                svc.enabled = false;
             }
         }
+        for o in &profile.overrides {
+            let Some(svc) = project.services.get_mut(&o.service) else {
+                continue;
+            };
+            if o.port != 0 {
+                svc.port = o.port;
+            }
+            for (key, value) in env_lines(&o.vars) {
+                svc.env_vars.insert(key.to_string(), value.to_string());
+            }
+        }
+        project
+    }
 
-        let id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-        let project = ProjectConfig {
-            id: id.clone(),
-            name: format!("Imported: {}", project_name),
-            directory: project_dir.to_string_lossy().to_string(),
-            services,
-            ssl_enabled: false,
-            custom_ports: HashMap::new(),
-            domain: format!("{}.test", project_name.to_lowercase().replace(' ', "-")),
+    /// Switch to `topology`, enabling the web servers it runs and disabling the other.
+    pub fn set_web_topology(&mut self, topology: &str) {
+        self.web_topology = topology.to_string();
+        for name in ["nginx", "apache"] {
+            if let Some(svc) = self.services.get_mut(name) {
+                svc.enabled = topology.contains(name);
+            }
+        }
+    }
+
+    /// Keep the topology in step after the web server `toggled` was switched:
+    /// one turned on replaces the other unless Nginx fronts Apache. Returns the
+    /// server turned off, if any.
+    pub fn sync_web_topology(&mut self, toggled: &str) -> Option<&'static str> {
+        let other = match toggled {
+            "nginx" => "apache",
+            "apache" => "nginx",
+            _ => return None,
         };
+        let enabled = |name: &str| self.services.get(name).is_some_and(|s| s.enabled);
+        match (enabled(toggled), enabled(other)) {
+            (true, true) if self.web_topology == "nginx-apache" => None,
+            (true, true) => {
+                self.set_web_topology(toggled);
+                Some(other)
+            }
+            (true, false) => {
+                self.web_topology = toggled.to_string();
+                None
+            }
+            (false, true) => {
+                self.web_topology = other.to_string();
+                None
+            }
+            (false, false) => None,
+        }
+    }
 
-        self.projects.push(project);
-        self.active_project_id = Some(id.clone());
-        self.save();
-        Ok(id)
+    /// Host port `service` is published on: its custom port when it has one,
+    /// else its own. 0 for an unknown service.
+    pub fn host_port(&self, service: &str) -> u16 {
+        match self.custom_ports.get(service) {
+            Some(port) if *port != 0 => *port,
+            _ => self.services.get(service).map_or(0, |s| s.port),
+        }
+    }
+
+    /// Publish `service` on `port`, replacing its custom port when it has one.
+    pub fn set_host_port(&mut self, service: &str, port: u16) {
+        if let Some(custom) = self.custom_ports.get_mut(service) {
+            *custom = port;
+        } else if let Some(svc) = self.services.get_mut(service) {
+            svc.port = port;
+        }
+    }
+
+    /// Add another instance of the registry service `kind`, copied from the
+    /// original with `version`. Returns its name, e.g. `php74`.
+    pub fn add_instance(&mut self, kind: &str, version: &str) -> Option<String> {
+        let digits: String = version
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .filter(char::is_ascii_digit)
+            .collect();
+        let name = self.insert_instance(kind, &format!("{}{}", kind, digits))?;
+        if let Some(svc) = self.services.get_mut(&name) {
+            svc.version = version.to_string();
+        }
+        Some(name)
+    }
+
+    /// Add another instance of the registry service `kind` under `name`, e.g.
+    /// `postgres-analytics`, on the next free host port. Returns the name used.
+    pub fn add_named_instance(&mut self, kind: &str, name: &str) -> Option<String> {
+        let name = self.insert_instance(kind, &service_name(name))?;
+        let mut port = self.services.get(&name)?.port;
+        while port != 0
+            && self
+                .services
+                .iter()
+                .any(|(other, s)| *other != name && s.port == port)
+        {
+            port = port.checked_add(1).unwrap_or(0); // 0 lets Docker pick one
+        }
+        if let Some(svc) = self.services.get_mut(&name) {
+            svc.port = port;
+        }
+        Some(name)
+    }
+
+    /// Add a custom service under `name`, made unique, on the next host port
+    /// no other service publishes. Returns the name used.
+    pub fn add_custom_service(&mut self, name: &str, mut svc: ServiceConfig) -> String {
+        let name = self.unique_service_name(&service_name(name));
+        while svc.port != 0 && self.services.keys().any(|s| self.host_port(s) == svc.port) {
+            svc.port = svc.port.checked_add(1).unwrap_or(0);
+        }
+        self.services.insert(name.clone(), svc);
+        name
+    }
+
+    /// Move the custom service or instance `from` to the key `to`, along with
+    /// routes, branch overlays, profiles and custom volumes named after it.
+    /// Returns the new name, cleaned up the same way as instance names.
+    pub fn rename_service(&mut self, from: &str, to: &str) -> Option<String> {
+        let to = service_name(to);
+        let movable = self
+            .services
+            .get(from)
+            .is_some_and(|s| s.is_custom || s.instance_of.is_some());
+        if !movable || to.is_empty() || self.service_name_taken(&to) {
+            return None;
+        }
+        let mut svc = self.services.remove(from)?;
+        if svc.display_name.as_deref() == Some(from) {
+            svc.display_name = Some(to.clone());
+        }
+        if let Some(volumes) = svc.settings.get_mut("volumes") {
+            *volumes = volumes
+                .lines()
+                .map(|mount| rename_volume(mount, from, &to))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        self.services.insert(to.clone(), svc);
+        if let Some(port) = self.custom_ports.remove(from) {
+            self.custom_ports.insert(to.clone(), port);
+        }
+
+        let rename = |service: &mut String| {
+            if service == from {
+                *service = to.clone();
+            }
+        };
+        self.php_routes
+            .iter_mut()
+            .for_each(|r| rename(&mut r.service));
+        self.branch_env
+            .iter_mut()
+            .for_each(|b| rename(&mut b.service));
+        for profile in &mut self.profiles {
+            profile.services.iter_mut().for_each(rename);
+            profile
+                .overrides
+                .iter_mut()
+                .for_each(|o| rename(&mut o.service));
+        }
+        Some(to)
+    }
+
+    /// Copy `kind`'s config under `name`, or `name-2`, `name-3`… when it is taken.
+    fn insert_instance(&mut self, kind: &str, name: &str) -> Option<String> {
+        if !INSTANCE_KINDS.contains(&kind) || name.is_empty() {
+            return None;
+        }
+        let mut svc = self.services.get(kind)?.clone();
+        let unique = self.unique_service_name(name);
+        svc.instance_of = Some(kind.to_string());
+        svc.display_name = Some(unique.clone());
+        svc.settings.remove("queue_worker");
+        self.services.insert(unique.clone(), svc);
+        Some(unique)
+    }
+
+    /// Whether `name` is a service key already, or one the compose file generates.
+    fn service_name_taken(&self, name: &str) -> bool {
+        name == PHP_WORKER || self.services.contains_key(name)
+    }
+
+    /// `name`, or `name-2`, `name-3`… when a service already has it or the
+    /// compose file generates it.
+    fn unique_service_name(&self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while self.service_name_taken(&unique) {
+            unique = format!("{}-{}", name, n);
+            n += 1;
+        }
+        unique
     }
-}
 
-impl ProjectConfig {
     pub fn enabled_services(&self) -> Vec<String> {
         self.services
             .iter()
@@ -443,3 +1626,51 @@ impl ProjectConfig {
             .collect()
     }
 }
+
+/// Lowercase service key with anything but letters, digits and `_` turned into
+/// dashes, e.g. `Postgres Analytics` → `postgres-analytics`.
+fn service_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    name.trim_matches('-').to_string()
+}
+
+/// A `source:target` mount line whose named volume is `from` or starts with
+/// `from_`, renamed to match `to`; other mounts are returned unchanged.
+fn rename_volume(mount: &str, from: &str, to: &str) -> String {
+    let Some((source, rest)) = mount.trim().split_once(':') else {
+        return mount.to_string();
+    };
+    let suffix = match source.strip_prefix(from) {
+        Some(suffix) if suffix.is_empty() || suffix.starts_with('_') => suffix,
+        _ => return mount.to_string(),
+    };
+    format!("{}{}:{}", to, suffix, rest)
+}
+
+/// Parse `HEAD` of the repository at `git` (a directory, or a worktree's `gitdir:` file).
+fn read_git_head(git: &Path) -> Option<String> {
+    let git_dir = if git.is_file() {
+        let pointer = fs::read_to_string(git).ok()?;
+        let target = pointer.trim().strip_prefix("gitdir:")?.trim();
+        git.parent()?.join(target)
+    } else {
+        git.to_path_buf()
+    };
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => Some(branch.to_string()),
+        None => head.get(..7).map(str::to_string),
+    }
+}
@@ -0,0 +1,363 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::{self, ComposeModel};
+use crate::docker::devcontainer;
+use crate::docker::kubernetes;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+type YamlMap = serde_yaml::Mapping;
+type YamlVal = serde_yaml::Value;
+
+/// Left in the generated job where the project's own build and test commands go.
+const PLACEHOLDER_STEP: &str = "echo \"Add the project's test commands here\"";
+
+/// CI systems a job can be exported for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CiFormat {
+    GitHubActions,
+    GitLabCi,
+}
+
+impl CiFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::GitHubActions => "GitHub Actions",
+            Self::GitLabCi => "GitLab CI",
+        }
+    }
+
+    /// Where the CI system looks for the file, relative to the repository root.
+    pub fn default_path(self) -> &'static str {
+        match self {
+            Self::GitHubActions => ".github/workflows/dockstack.yml",
+            Self::GitLabCi => ".gitlab-ci.yml",
+        }
+    }
+}
+
+/// A compose service as a CI container.
+struct CiService {
+    name: String,
+    image: String,
+    env: YamlMap,
+    ports: Vec<String>, // `host:container`, as compose publishes them
+    health: Option<YamlMap>,
+    command: Option<YamlVal>,
+    entrypoint: Option<YamlVal>,
+}
+
+/// What the project's services become in a CI job: the service holding the
+/// code is the job's own container, the others run beside it. Services built
+/// locally or mounting project files are left out and listed in `notes`.
+struct Plan {
+    job: Option<CiService>,
+    services: Vec<CiService>,
+    notes: Vec<String>,
+}
+
+fn plan(project: &ProjectConfig) -> Plan {
+    let model = ComposeModel::from_project(project);
+    let code = compose::code_mount_services(project);
+    let primary = devcontainer::primary_service(project).filter(|s| code.contains(s));
+    let mut plan = Plan {
+        job: None,
+        services: Vec::new(),
+        notes: Vec::new(),
+    };
+
+    for (name, service) in &model.services {
+        let Some(image) = service.get("image").and_then(YamlVal::as_str) else {
+            plan.notes
+                .push(format!("{} is built locally, so it is left out", name));
+            continue;
+        };
+        let mut env = YamlMap::new();
+        let mut dropped = Vec::new();
+        if let Some(YamlVal::Mapping(environment)) = service.get("environment") {
+            for (key, value) in environment {
+                match value.as_str() {
+                    // Filled in by docker compose from its own environment
+                    Some(text) if text.contains("${") => {
+                        dropped.extend(key.as_str().map(str::to_string));
+                    }
+                    _ => {
+                        env.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        if !dropped.is_empty() {
+            plan.notes.push(format!(
+                "{}: left out {}, which compose fills in from its environment",
+                name,
+                dropped.join(", ")
+            ));
+        }
+        let ports = match service.get("ports") {
+            Some(YamlVal::Sequence(ports)) => ports
+                .iter()
+                .filter_map(YamlVal::as_str)
+                .map(published_port)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let ci_service = CiService {
+            name: name.clone(),
+            image: image.to_string(),
+            env,
+            ports,
+            health: match service.get("healthcheck") {
+                Some(YamlVal::Mapping(health)) => Some(health.clone()),
+                _ => None,
+            },
+            command: service.get("command").cloned(),
+            entrypoint: service.get("entrypoint").cloned(),
+        };
+
+        if primary.as_ref() == Some(name) {
+            plan.notes.push(format!(
+                "The job runs in {}'s image, with the checkout in place of its code mount",
+                name
+            ));
+            plan.job = Some(ci_service);
+        } else if mounts_host_files(service, &model) {
+            plan.notes.push(format!(
+                "{} mounts files from the project directory, so it is left out",
+                name
+            ));
+        } else {
+            plan.services.push(ci_service);
+        }
+    }
+    plan
+}
+
+/// The CI file for the project's enabled services, same images and environment.
+pub fn config(project: &ProjectConfig, format: CiFormat) -> String {
+    let mut plan = plan(project);
+    let root = match format {
+        CiFormat::GitHubActions => github_actions(&mut plan),
+        CiFormat::GitLabCi => gitlab_ci(&plan),
+    };
+    let reach = if plan.job.is_none() && format == CiFormat::GitHubActions {
+        "on localhost at the ports DockStack publishes"
+    } else {
+        "by service name, e.g. mysql:3306, as between DockStack's containers"
+    };
+    let mut out = format!(
+        "# {} job exported by DockStack from {}, with the same service versions.\n\
+         # Services are reached {}.\n",
+        format.label(),
+        project.name,
+        reach
+    );
+    for note in plan.notes {
+        out.push_str(&format!("# - {}\n", note));
+    }
+    out.push_str(&serde_yaml::to_string(&YamlVal::Mapping(root)).unwrap_or_default());
+    out
+}
+
+/// Write the project's CI file to `dest`, creating its folder (e.g. `.github/workflows`).
+pub fn export(project: &ProjectConfig, format: CiFormat, dest: &Path) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(dest, config(project, format))
+}
+
+fn github_actions(plan: &mut Plan) -> YamlMap {
+    let mut job = YamlMap::new();
+    job.insert(y_str("runs-on"), y_str("ubuntu-latest"));
+    if let Some(primary) = &plan.job {
+        let mut container = YamlMap::new();
+        container.insert(y_str("image"), y_str(&primary.image));
+        if !primary.env.is_empty() {
+            container.insert(y_str("env"), YamlVal::Mapping(primary.env.clone()));
+        }
+        job.insert(y_str("container"), YamlVal::Mapping(container));
+    }
+
+    let mut services = YamlMap::new();
+    for service in &plan.services {
+        let mut s = YamlMap::new();
+        s.insert(y_str("image"), y_str(&service.image));
+        if !service.env.is_empty() {
+            s.insert(y_str("env"), YamlVal::Mapping(service.env.clone()));
+        }
+        if !service.ports.is_empty() {
+            let ports = service.ports.iter().map(|p| y_str(p)).collect();
+            s.insert(y_str("ports"), YamlVal::Sequence(ports));
+        }
+        if let Some(options) = service.health.as_ref().and_then(health_options) {
+            s.insert(y_str("options"), y_str(&options));
+        }
+        if service.command.is_some() || service.entrypoint.is_some() {
+            plan.notes.push(format!(
+                "{}: GitHub Actions runs service containers with their image's own command",
+                service.name
+            ));
+        }
+        services.insert(y_str(&service.name), YamlVal::Mapping(s));
+    }
+    if !services.is_empty() {
+        job.insert(y_str("services"), YamlVal::Mapping(services));
+    }
+    job.insert(
+        y_str("steps"),
+        YamlVal::Sequence(vec![
+            mapping(&[("uses", y_str("actions/checkout@v4"))]),
+            mapping(&[("name", y_str("Test")), ("run", y_str(PLACEHOLDER_STEP))]),
+        ]),
+    );
+
+    let mut jobs = YamlMap::new();
+    jobs.insert(y_str("test"), YamlVal::Mapping(job));
+    let mut root = YamlMap::new();
+    root.insert(y_str("name"), y_str("DockStack services"));
+    root.insert(
+        y_str("on"),
+        YamlVal::Sequence(vec![y_str("push"), y_str("pull_request")]),
+    );
+    root.insert(y_str("jobs"), YamlVal::Mapping(jobs));
+    root
+}
+
+fn gitlab_ci(plan: &Plan) -> YamlMap {
+    let mut job = YamlMap::new();
+    if let Some(primary) = &plan.job {
+        job.insert(y_str("image"), y_str(&primary.image));
+        if !primary.env.is_empty() {
+            job.insert(y_str("variables"), YamlVal::Mapping(primary.env.clone()));
+        }
+    }
+    let services: Vec<YamlVal> = plan
+        .services
+        .iter()
+        .map(|service| {
+            let mut s = YamlMap::new();
+            s.insert(y_str("name"), y_str(&service.image));
+            s.insert(y_str("alias"), y_str(&service.name));
+            if !service.env.is_empty() {
+                s.insert(y_str("variables"), YamlVal::Mapping(service.env.clone()));
+            }
+            // As lists, so GitLab does not split strings its own way
+            if let Some(entrypoint) = service.entrypoint.as_ref().and_then(kubernetes::words) {
+                s.insert(y_str("entrypoint"), entrypoint);
+            }
+            if let Some(command) = service.command.as_ref().and_then(kubernetes::words) {
+                s.insert(y_str("command"), command);
+            }
+            YamlVal::Mapping(s)
+        })
+        .collect();
+    if !services.is_empty() {
+        job.insert(y_str("services"), YamlVal::Sequence(services));
+    }
+    job.insert(
+        y_str("script"),
+        YamlVal::Sequence(vec![y_str(PLACEHOLDER_STEP)]),
+    );
+    let mut root = YamlMap::new();
+    root.insert(y_str("test"), YamlVal::Mapping(job));
+    root
+}
+
+/// Whether the service bind-mounts a host path rather than only named volumes.
+fn mounts_host_files(service: &YamlMap, model: &ComposeModel) -> bool {
+    let Some(YamlVal::Sequence(mounts)) = service.get("volumes") else {
+        return false;
+    };
+    mounts
+        .iter()
+        .filter_map(|m| m.as_str()?.split_once(':'))
+        .any(|(source, _)| !model.volumes.contains_key(source))
+}
+
+/// `host:container` of a compose port, without the address it binds to:
+/// `127.0.0.1:8080:80` gives `8080:80`.
+fn published_port(port: &str) -> String {
+    let parts: Vec<&str> = port.rsplitn(3, ':').collect();
+    match parts.as_slice() {
+        [container, host, ..] => format!("{}:{}", host, container),
+        _ => port.to_string(),
+    }
+}
+
+/// A compose healthcheck as `docker create` flags, which GitHub Actions takes
+/// as a service's `options`.
+fn health_options(health: &YamlMap) -> Option<String> {
+    let command = match health.get("test")? {
+        YamlVal::String(command) => command.clone(),
+        YamlVal::Sequence(test) => {
+            let words: Vec<&str> = test.iter().filter_map(YamlVal::as_str).collect();
+            match words.as_slice() {
+                ["CMD-SHELL", command] => command.to_string(),
+                ["CMD", args @ ..] => args.join(" "),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let mut options = format!("--health-cmd \"{}\"", command.replace('"', "\\\""));
+    for key in ["interval", "timeout", "retries"] {
+        if let Some(value) = health.get(key) {
+            let value = match value {
+                YamlVal::Number(n) => n.to_string(),
+                other => other.as_str().unwrap_or_default().to_string(),
+            };
+            options.push_str(&format!(" --health-{} {}", key, value));
+        }
+    }
+    Some(options)
+}
+
+fn mapping(entries: &[(&str, YamlVal)]) -> YamlVal {
+    YamlVal::Mapping(
+        entries
+            .iter()
+            .map(|(key, value)| (y_str(key), value.clone()))
+            .collect(),
+    )
+}
+
+fn y_str(s: &str) -> YamlVal {
+    YamlVal::String(s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::compose::tests::project;
+
+    #[test]
+    fn ci_jobs_run_the_stack_services() {
+        let project = project(&["mysql", "redis", "php", "nginx"]);
+        let parse = |format| -> YamlVal {
+            serde_yaml::from_str(&config(&project, format)).unwrap_or(YamlVal::Null)
+        };
+        let model = ComposeModel::from_project(&project);
+
+        let github = parse(CiFormat::GitHubActions);
+        let job = &github["jobs"]["test"];
+        assert_eq!(job["container"]["image"], model.services["php"]["image"]);
+        let mysql = &job["services"]["mysql"];
+        assert_eq!(mysql["image"], model.services["mysql"]["image"]);
+        assert_eq!(
+            mysql["env"]["MYSQL_DATABASE"],
+            model.services["mysql"]["environment"]["MYSQL_DATABASE"]
+        );
+        assert_eq!(mysql["ports"][0], y_str("3306:3306"));
+        let options = mysql["options"].as_str().unwrap_or_default();
+        assert!(options.starts_with("--health-cmd \"mysqladmin ping"));
+        // Needs the project's nginx config, which CI does not have
+        assert_eq!(job["services"].get("nginx"), None);
+
+        let gitlab = parse(CiFormat::GitLabCi);
+        let job = &gitlab["test"];
+        assert_eq!(job["image"], model.services["php"]["image"]);
+        assert_eq!(job["services"][1]["alias"], y_str("redis"));
+        assert_eq!(job["services"][1]["name"], model.services["redis"]["image"]);
+    }
+}
@@ -1,347 +1,2262 @@
-use crate::config::ProjectConfig;
+use crate::config::{AppConfig, PhpRoute, ProjectConfig, ServiceConfig, MOUNT_CONSISTENCIES};
+use crate::docker::image_lock::ImageLock;
+use crate::plugins::{self, ServicePlugin};
 use serde_yaml;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
 
 type YamlMap = serde_yaml::Mapping;
 type YamlVal = serde_yaml::Value;
 
+/// Label attached to every container, network and volume DockStack generates.
+pub const PROJECT_LABEL: &str = "com.dockstack.project";
+
+/// Compose service name of the PHP queue worker companion.
+pub const PHP_WORKER: &str = "php-worker";
+pub const DEFAULT_QUEUE_COMMAND: &str = "php artisan queue:work --sleep=3 --tries=3";
+/// SMTP port mail catchers listen on inside the project network.
+const MAIL_SMTP_PORT: u16 = 1025;
+/// SQL Server editions free to use for development, as `MSSQL_PID` values.
+pub const MSSQL_EDITIONS: &[&str] = &["Developer", "Express"];
+/// JVM heap of OpenSearch and Solr when the service sets none.
+pub const DEFAULT_SEARCH_HEAP: &str = "512m";
+/// Core Solr precreates when the service names none.
+pub const DEFAULT_SOLR_CORE: &str = "dockstack";
+
 const MANAGED_HEADER: &str = "# --- MANAGED BY DOCKSTACK --- #\n# Any manual changes outside this block may be overwritten unless you lock this service.\n\n";
 
-pub fn generate_compose(project: &ProjectConfig) -> String {
-    let mut root = YamlMap::new();
-    let mut services = YamlMap::new();
-    let mut volumes = YamlMap::new();
-    let mut networks = YamlMap::new();
+/// The compose file, with images pinned to the digests in `dockstack.lock`
+/// when the project locks them.
+pub fn generate_compose(project: &ProjectConfig) -> String {
+    let mut model = ComposeModel::from_project(project);
+    if project.lock_images {
+        ImageLock::load(project).pin(&mut model);
+    }
+    model.to_yaml()
+}
+
+/// Named volumes (unprefixed) declared by the generated compose file.
+pub fn named_volumes(project: &ProjectConfig) -> Vec<String> {
+    ComposeModel::from_project(project)
+        .volumes
+        .into_keys()
+        .collect()
+}
+
+/// Named volumes (unprefixed) mounted by `service`, in mount order, as if it were enabled.
+pub fn service_volumes(project: &ProjectConfig, service: &str) -> Vec<String> {
+    let mut project = project.clone();
+    if let Some(svc) = project.services.get_mut(service) {
+        svc.enabled = true;
+    }
+    let model = ComposeModel::from_project(&project);
+    let Some(YamlVal::Sequence(mounts)) =
+        model.services.get(service).and_then(|s| s.get("volumes"))
+    else {
+        return Vec::new();
+    };
+    mounts
+        .iter()
+        .filter_map(|m| m.as_str())
+        .filter_map(named_volume)
+        .filter(|v| model.volumes.contains_key(*v))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Command words that run compose against `project` from any directory,
+/// for shell aliases: `docker compose -p <name> -f <dir>/docker-compose.yml`.
+pub fn compose_invocation(project: &ProjectConfig, use_plugin: bool) -> Vec<String> {
+    let compose_file = Path::new(&project.directory).join("docker-compose.yml");
+    let mut words = if use_plugin {
+        vec!["docker".to_string(), "compose".to_string()]
+    } else {
+        vec!["docker-compose".to_string()]
+    };
+    words.extend([
+        "-p".to_string(),
+        compose_project_name(project),
+        "-f".to_string(),
+        compose_file.to_string_lossy().to_string(),
+    ]);
+    words
+}
+
+/// Host port nginx publishes HTTPS on: the SSL service's port, 443 unless remapped.
+pub fn https_port(project: &ProjectConfig) -> u16 {
+    project
+        .services
+        .get("ssl")
+        .map(|s| s.port)
+        .filter(|p| *p != 0)
+        .unwrap_or(443)
+}
+
+/// Project name docker compose derives from the directory when neither `-p` nor `name:` is set.
+/// Compose prefixes networks and named volumes with it.
+pub fn compose_project_name(project: &ProjectConfig) -> String {
+    Path::new(&project.directory)
+        .file_name()
+        .map(|n| {
+            n.to_string_lossy()
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Start of every container name in the project, e.g. `dockstack_ab12_`, or
+/// `dockstack_ab12_staging_` while the `staging` profile is active.
+pub fn container_prefix(project: &ProjectConfig) -> String {
+    let Some(profile) = project.current_profile() else {
+        return format!("dockstack_{}_", project.id);
+    };
+    let profile: String = profile
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("dockstack_{}_{}_", project.id, profile)
+}
+
+pub fn container_name(project: &ProjectConfig, service: &str) -> String {
+    format!("{}{}", container_prefix(project), service)
+}
+
+/// The compose document DockStack generates for a project.
+///
+/// Built without touching the file system; maps are ordered so the rendered
+/// YAML is byte-for-byte stable across runs.
+#[derive(Debug, Clone, Default)]
+pub struct ComposeModel {
+    pub services: BTreeMap<String, YamlMap>,
+    pub volumes: BTreeMap<String, YamlVal>,
+    pub networks: BTreeMap<String, YamlVal>,
+}
+
+impl ComposeModel {
+    pub fn from_project(project: &ProjectConfig) -> Self {
+        let mut model = Self::default();
+        let project = &with_placeholders(&project.with_active_profile());
+
+        for (name, svc) in sorted(&project.services) {
+            if !svc.enabled {
+                continue;
+            }
+            match svc.kind(name) {
+                "postgresql" => add_postgresql(&mut model, project, name, svc),
+                "mysql" => add_mysql(&mut model, project, name, svc),
+                "php" => add_php(&mut model, project, name, svc),
+                "apache" => add_apache(&mut model, project, svc),
+                "nginx" => add_nginx(&mut model, project, svc),
+                "phpmyadmin" => add_phpmyadmin(&mut model, project, svc),
+                "pgadmin" => add_pgadmin(&mut model, project, svc),
+                "redis" => add_redis(&mut model, project, name, svc),
+                "python" => add_python(&mut model, project, svc),
+                "jupyter" => add_jupyter(&mut model, project, svc),
+                "varnish" => add_varnish(&mut model, project, svc),
+                "adminer" => add_adminer(&mut model, project, svc),
+                "dbgate" => add_dbgate(&mut model, project, svc),
+                "mssql" => add_mssql(&mut model, project, svc),
+                "sftp" => add_sftp(&mut model, project, svc),
+                "mailpit" => add_mailpit(&mut model, project, svc),
+                "opensearch" => add_opensearch(&mut model, project, svc),
+                "solr" => add_solr(&mut model, project, svc),
+                // SSL is handled via nginx config, not as a separate service container.
+                // The SSL toggle enables HTTPS on the nginx reverse proxy.
+                "ssl" => {}
+                // Services of plugins no longer installed are left out
+                _ => match svc.plugin.as_deref() {
+                    Some(id) => {
+                        if let Some(plugin) = plugins::find(id) {
+                            add_plugin(&mut model, project, name, svc, &plugin);
+                        }
+                    }
+                    None => add_custom(&mut model, project, name, svc),
+                },
+            }
+        }
+
+        // Options that apply to every kind of service
+        let mut project_env: Vec<(&str, &str)> = project.env_vars().collect();
+        if let Some(timezone) = timezone(project) {
+            project_env.push(("TZ", timezone));
+        }
+        if let Some(locale) = locale(project) {
+            project_env.push(("LANG", locale));
+        }
+        let proxy = if project.use_proxy {
+            proxy_env(&model)
+        } else {
+            Vec::new()
+        };
+        project_env.extend(proxy.iter().map(|(key, value)| (*key, value.as_str())));
+        if !project_env.is_empty() {
+            for s in model.services.values_mut() {
+                add_default_env(s, &project_env);
+            }
+        }
+        for (name, _) in project.services.iter().filter(|(_, s)| s.enabled && s.gpu) {
+            if let Some(s) = model.services.get_mut(name) {
+                s.insert(y_str("deploy"), gpu_deploy());
+            }
+        }
+        for (name, svc) in project.services.iter().filter(|(_, s)| s.enabled) {
+            if let Some(s) = model
+                .services
+                .get_mut(name)
+                .filter(|_| !svc.platform.is_empty())
+            {
+                s.insert(y_str("platform"), y_str(&svc.platform));
+            }
+        }
+        if !project.image_mirror.trim().is_empty() {
+            for s in model
+                .services
+                .values_mut()
+                .filter(|s| !s.contains_key("build"))
+            {
+                if let Some(YamlVal::String(image)) = s.get_mut("image") {
+                    *image = mirror_image(&project.image_mirror, image);
+                }
+            }
+        }
+        if let Some(branch) = project.git_branch() {
+            for overlay in project.branch_env.iter().filter(|o| o.matches(&branch)) {
+                if let Some(s) = model.services.get_mut(&overlay.service) {
+                    apply_env(s, overlay.vars());
+                }
+            }
+        }
+        if let Some(user) = host_user(project) {
+            for (name, _) in project
+                .services
+                .iter()
+                .filter(|(_, s)| s.enabled && s.run_as_host_user)
+            {
+                if let Some(s) = model.services.get_mut(name) {
+                    s.insert(y_str("user"), y_str(&user));
+                }
+            }
+        }
+        for (name, svc) in sorted(&project.services) {
+            if let Some(s) = model.services.get_mut(name) {
+                tune_code_mounts(project, name, svc, s, &mut model.volumes);
+            }
+        }
+        if project.commit_generated {
+            make_mounts_relative(project, &mut model);
+        }
+        if let Some(flag) = relabel_flag(project) {
+            for s in model.services.values_mut() {
+                if let Some(YamlVal::Sequence(volumes)) = s.get_mut("volumes") {
+                    for mount in volumes.iter_mut() {
+                        if let YamlVal::String(m) = mount {
+                            *m = relabel_mount(project, m, flag);
+                        }
+                    }
+                }
+            }
+        }
+
+        add_networks(&mut model, project);
+        model
+    }
+
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(&YamlVal::Mapping(self.to_mapping())).unwrap_or_default()
+    }
+
+    fn to_mapping(&self) -> YamlMap {
+        let mut root = YamlMap::new();
+        let services = self
+            .services
+            .iter()
+            .map(|(name, s)| (y_str(name), YamlVal::Mapping(s.clone())))
+            .collect();
+        root.insert(y_str("services"), YamlVal::Mapping(services));
+        if !self.volumes.is_empty() {
+            root.insert(y_str("volumes"), ordered_mapping(&self.volumes));
+        }
+        root.insert(y_str("networks"), ordered_mapping(&self.networks));
+        root
+    }
+}
+
+fn ordered_mapping(map: &BTreeMap<String, YamlVal>) -> YamlVal {
+    YamlVal::Mapping(map.iter().map(|(k, v)| (y_str(k), v.clone())).collect())
+}
+
+/// `map`'s entries in key order, so generated files do not depend on hash order.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn network_name(project: &ProjectConfig) -> String {
+    format!("dockstack_{}", project.id)
+}
+
+fn add_postgresql(
+    model: &mut ComposeModel,
+    project: &ProjectConfig,
+    name: &str,
+    svc: &ServiceConfig,
+) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("postgres:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut env = YamlMap::new();
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 5432)];
+    insert_ports(&mut s, svc, ports);
+
+    let volume = data_volume(name, svc, "postgres_data");
+    let vols = vec![YamlVal::String(format!(
+        "{}:/var/lib/postgresql/data",
+        volume
+    ))];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck("pg_isready -U postgres", 10, 5, 5),
+    );
+
+    model.services.insert(name.to_string(), s);
+    model.volumes.insert(volume, volume_conf(project));
+}
+
+fn add_mysql(model: &mut ComposeModel, project: &ProjectConfig, name: &str, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("mysql:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut env = YamlMap::new();
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 3306)];
+    insert_ports(&mut s, svc, ports);
+
+    let volume = data_volume(name, svc, "mysql_data");
+    let vols = vec![YamlVal::String(format!("{}:/var/lib/mysql", volume))];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck("mysqladmin ping -h localhost", 10, 5, 5),
+    );
+
+    model.services.insert(name.to_string(), s);
+    model.volumes.insert(volume, volume_conf(project));
+}
+
+/// The `php` service, or another PHP-FPM instance of it under `name`.
+fn add_php(model: &mut ComposeModel, project: &ProjectConfig, name: &str, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("php:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut vols = vec![YamlVal::String(format!(
+        "{}:/var/www/html",
+        code_source(project, "www")
+    ))];
+    vols.push(YamlVal::String(format!(
+        "{}/php/php.ini:/usr/local/etc/php/conf.d/dockstack.ini",
+        project.directory
+    )));
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let mail = smtp_env(project);
+    if !mail.is_empty() {
+        let mut env = YamlMap::new();
+        for (k, v) in &mail {
+            env.insert(y_str(k), y_str(v));
+        }
+        s.insert(y_str("environment"), YamlVal::Mapping(env));
+    }
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    // Optional queue worker running from the same image and mounts
+    if name == "php"
+        && svc
+            .settings
+            .get("queue_worker")
+            .is_some_and(|v| v == "true")
+    {
+        let worker = php_worker(project, svc, &s);
+        model.services.insert(PHP_WORKER.to_string(), worker);
+    }
+
+    model.services.insert(name.to_string(), s);
+}
+
+/// The queue worker beside `php`: its image, mounts, environment and networks,
+/// with the worker's own command and restart policy. Nothing is published, and
+/// there is no healthcheck; a running PHP says nothing about the queue.
+fn php_worker(project: &ProjectConfig, svc: &ServiceConfig, php: &YamlMap) -> YamlMap {
+    let mut w = YamlMap::new();
+    w.insert(y_str("image"), y_str(&format!("php:{}", svc.version)));
+    w.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, PHP_WORKER)),
+    );
+    let restart = svc
+        .settings
+        .get("queue_restart")
+        .unwrap_or(&svc.restart_policy);
+    w.insert(y_str("restart"), y_str(restart));
+    w.insert(y_str("labels"), project_labels(project));
+    for key in ["volumes", "environment", "networks"] {
+        if let Some(value) = php.get(key) {
+            w.insert(y_str(key), value.clone());
+        }
+    }
+    w.insert(y_str("working_dir"), y_str("/var/www/html"));
+    let command = svc
+        .settings
+        .get("queue_command")
+        .map(|c| c.as_str())
+        .unwrap_or(DEFAULT_QUEUE_COMMAND);
+    w.insert(
+        y_str("command"),
+        YamlVal::Sequence(vec![y_str("sh"), y_str("-c"), y_str(command)]),
+    );
+    w.insert(y_str("depends_on"), YamlVal::Sequence(vec![y_str("php")]));
+    w
+}
+
+/// Named volume for the data of `name`: `base` for the registry service itself,
+/// `{name}_data` for another instance of it, so instances never share data.
+fn data_volume(name: &str, svc: &ServiceConfig, base: &str) -> String {
+    match svc.instance_of {
+        Some(_) => format!("{}_data", name),
+        None => base.to_string(),
+    }
+}
+
+fn add_apache(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("httpd:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "apache")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // Behind Nginx, Apache is reached over the project network only
+    if !nginx_fronts_apache(project) {
+        let ports = vec![port_mapping(project, svc, svc.port, 80)];
+        insert_ports(&mut s, svc, ports);
+    }
+
+    let vols = vec![
+        YamlVal::String(format!(
+            "{}:/usr/local/apache2/htdocs/",
+            code_source(project, "www")
+        )),
+        YamlVal::String("./apache/httpd.conf:/usr/local/apache2/conf/httpd.conf".to_string()),
+    ];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("apache".to_string(), s);
+}
+
+fn add_nginx(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("nginx:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "nginx")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut ports = vec![port_mapping(project, svc, svc.port, 80)];
+    if project.ssl_enabled {
+        ports.push(port_mapping(project, svc, https_port(project), 443));
+    }
+    insert_ports(&mut s, svc, ports);
+
+    let mut vols = vec![
+        YamlVal::String(format!(
+            "{}:/usr/share/nginx/html",
+            code_source(project, "www")
+        )),
+        YamlVal::String("./nginx/default.conf:/etc/nginx/conf.d/default.conf".to_string()),
+    ];
+    if project.ssl_enabled {
+        vols.push(YamlVal::String("./certs:/etc/nginx/certs:ro".to_string()));
+    }
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    // Nginx refuses to start while its proxy_pass host does not resolve
+    if nginx_fronts_apache(project) {
+        s.insert(
+            y_str("depends_on"),
+            YamlVal::Sequence(vec![y_str("apache")]),
+        );
+    }
+
+    model.services.insert("nginx".to_string(), s);
+}
+
+fn add_phpmyadmin(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("phpmyadmin:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "phpmyadmin")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut env = YamlMap::new();
+    env.insert(y_str("PMA_HOST"), y_str("mysql"));
+    env.insert(y_str("PMA_ARBITRARY"), y_str("1"));
+
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+
+    // Log straight into the project database with MySQL's own credentials
+    if let Some((user, password)) = mysql_credentials(project) {
+        env.insert(y_str("PMA_USER"), y_str(&user));
+        env.insert(y_str("PMA_PASSWORD"), y_str(&password));
+    }
+
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 80)];
+    insert_ports(&mut s, svc, ports);
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    let deps = vec![YamlVal::String("mysql".to_string())];
+    if project.services.get("mysql").is_some_and(|s| s.enabled) {
+        s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
+    }
+
+    model.services.insert("phpmyadmin".to_string(), s);
+}
+
+fn add_pgadmin(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("dpage/pgadmin4:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "pgadmin")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut env = YamlMap::new();
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 80)];
+    insert_ports(&mut s, svc, ports);
+
+    let mut vols = vec![YamlVal::String("pgadmin_data:/var/lib/pgadmin".to_string())];
+    if project
+        .services
+        .get("postgresql")
+        .is_some_and(|s| s.enabled)
+    {
+        vols.push(YamlVal::String(format!(
+            "{}/pgadmin/servers.json:/pgadmin4/servers.json:ro",
+            project.directory
+        )));
+    }
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    if project
+        .services
+        .get("postgresql")
+        .is_some_and(|s| s.enabled)
+    {
+        let deps = vec![YamlVal::String("postgresql".to_string())];
+        s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
+    }
+
+    model.services.insert("pgadmin".to_string(), s);
+    model
+        .volumes
+        .insert("pgadmin_data".to_string(), volume_conf(project));
+}
+
+fn add_redis(model: &mut ComposeModel, project: &ProjectConfig, name: &str, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("redis:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 6379)];
+    insert_ports(&mut s, svc, ports);
+
+    let volume = data_volume(name, svc, "redis_data");
+    let vols = vec![YamlVal::String(format!("{}:/data", volume))];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck("redis-cli ping", 10, 5, 5),
+    );
+
+    model.services.insert(name.to_string(), s);
+    model.volumes.insert(volume, volume_conf(project));
+}
+
+fn add_python(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("python:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "python")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+    s.insert(y_str("working_dir"), y_str("/app"));
+
+    let container_port = svc
+        .settings
+        .get("container_port")
+        .and_then(|p| p.trim().parse().ok())
+        .unwrap_or(8000);
+    let command = svc
+        .settings
+        .get("command")
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| format!("python -m http.server {}", container_port));
+    s.insert(
+        y_str("command"),
+        install_then_run("/app/requirements.txt", &command),
+    );
+
+    let mut env = YamlMap::new();
+    env.insert(y_str("PYTHONUNBUFFERED"), y_str("1"));
+    for (k, v) in &smtp_env(project) {
+        env.insert(y_str(k), y_str(v));
+    }
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, container_port)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![
+        YamlVal::String(format!("{}:/app", code_source(project, "app"))),
+        YamlVal::String("pip_cache:/root/.cache/pip".to_string()),
+    ];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("python".to_string(), s);
+    model
+        .volumes
+        .insert("pip_cache".to_string(), volume_conf(project));
+}
+
+fn add_jupyter(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("quay.io/jupyter/scipy-notebook:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "jupyter")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+    s.insert(
+        y_str("command"),
+        install_then_run("/home/jovyan/work/requirements.txt", "start-notebook.py"),
+    );
+
+    let mut env = YamlMap::new();
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 8888)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String(format!(
+        "{}/notebooks:/home/jovyan/work",
+        project.directory
+    ))];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("jupyter".to_string(), s);
+}
+
+fn add_varnish(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("varnish:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "varnish")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let mut env = YamlMap::new();
+    let size = svc
+        .settings
+        .get("cache_size")
+        .map(|v| v.as_str())
+        .unwrap_or("256M");
+    env.insert(y_str("VARNISH_SIZE"), y_str(size));
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 80)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String(
+        "./varnish/default.vcl:/etc/varnish/default.vcl:ro".to_string(),
+    )];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    if let Some(backend) = varnish_backend(project) {
+        let deps = vec![YamlVal::String(backend.to_string())];
+        s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
+    }
+
+    model.services.insert("varnish".to_string(), s);
+}
+
+fn add_adminer(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("adminer:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "adminer")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 8080)];
+    insert_ports(&mut s, svc, ports);
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("adminer".to_string(), s);
+}
+
+fn add_mssql(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("mcr.microsoft.com/mssql/server:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "mssql")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // Starting is refused until the EULA is accepted, see `mssql_start_problem`
+    let mut env = YamlMap::new();
+    env.insert(y_str("ACCEPT_EULA"), y_str("Y"));
+    let edition = svc
+        .settings
+        .get("edition")
+        .map(String::as_str)
+        .filter(|e| MSSQL_EDITIONS.contains(e))
+        .unwrap_or("Developer");
+    env.insert(y_str("MSSQL_PID"), y_str(edition));
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 1433)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String("mssql_data:/var/opt/mssql".to_string())];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    // `$$` keeps compose from interpolating the container's own variable
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck(
+            "/opt/mssql-tools18/bin/sqlcmd -C -S localhost -U sa \
+             -P \"$$MSSQL_SA_PASSWORD\" -Q \"SELECT 1\" || exit 1",
+            10,
+            5,
+            10,
+        ),
+    );
+
+    model.services.insert("mssql".to_string(), s);
+    model
+        .volumes
+        .insert("mssql_data".to_string(), volume_conf(project));
+}
+
+/// Why the enabled SQL Server service cannot start, if it cannot: its image
+/// exits at once without the EULA accepted or with a password SQL Server rejects.
+pub fn mssql_start_problem(project: &ProjectConfig) -> Option<String> {
+    let svc = project.services.get("mssql").filter(|s| s.enabled)?;
+    let accepted = svc.settings.get("accept_eula").is_some_and(|v| v == "true");
+    if !accepted {
+        return Some("Accept the SQL Server EULA in the SQL Server service settings.".to_string());
+    }
+    let password = svc
+        .env_vars
+        .get("MSSQL_SA_PASSWORD")
+        .map(String::as_str)
+        .unwrap_or_default();
+    mssql_password_problem(password).map(|problem| format!("SQL Server SA password {}.", problem))
+}
+
+/// SQL Server's password policy: 8 or more characters from at least three of
+/// uppercase letters, lowercase letters, digits and symbols.
+pub fn mssql_password_problem(password: &str) -> Option<&'static str> {
+    if password.chars().count() < 8 {
+        return Some("must be at least 8 characters long");
+    }
+    let classes = [
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ];
+    if classes.iter().filter(|&&has| has).count() < 3 {
+        return Some("needs three of: uppercase letters, lowercase letters, digits and symbols");
+    }
+    None
+}
+
+/// SFTP login of the `sftp` service: its user name and password.
+pub fn sftp_credentials(svc: &ServiceConfig) -> (String, String) {
+    let setting = |key: &str| {
+        svc.settings
+            .get(key)
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+    let user: String = setting("user")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect();
+    match user.as_str() {
+        "" => ("dockstack".to_string(), setting("password")),
+        _ => (user, setting("password")),
+    }
+}
+
+fn add_sftp(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("atmoz/sftp:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "sftp")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    let (user, _) = sftp_credentials(svc);
+
+    let ports = vec![port_mapping(project, svc, svc.port, 22)];
+    insert_ports(&mut s, svc, ports);
+
+    // The login is read from a file so the password stays out of the compose file.
+    // Host keys live in a volume so clients do not see them change on every start.
+    let vols = vec![
+        YamlVal::String(format!(
+            "{}:/home/{}/www",
+            code_source(project, "www"),
+            user
+        )),
+        YamlVal::String(format!(
+            "{}/sftp/users.conf:/etc/sftp/users.conf:ro",
+            project.directory
+        )),
+        YamlVal::String("sftp_host_keys:/etc/ssh".to_string()),
+    ];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("sftp".to_string(), s);
+    model
+        .volumes
+        .insert("sftp_host_keys".to_string(), volume_conf(project));
+}
+
+/// `heap_size` setting of a search service, e.g. `512m`.
+fn search_heap(svc: &ServiceConfig) -> &str {
+    svc.settings
+        .get("heap_size")
+        .map(|h| h.trim())
+        .filter(|h| !h.is_empty())
+        .unwrap_or(DEFAULT_SEARCH_HEAP)
+}
+
+fn add_opensearch(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("opensearchproject/opensearch:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "opensearch")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // Single node without the security plugin, like a local Elasticsearch
+    let heap = search_heap(svc);
+    let mut env = YamlMap::new();
+    env.insert(y_str("discovery.type"), y_str("single-node"));
+    env.insert(
+        y_str("OPENSEARCH_JAVA_OPTS"),
+        y_str(&format!("-Xms{} -Xmx{}", heap, heap)),
+    );
+    env.insert(y_str("DISABLE_SECURITY_PLUGIN"), y_str("true"));
+    env.insert(y_str("DISABLE_INSTALL_DEMO_CONFIG"), y_str("true"));
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 9200)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String(
+        "opensearch_data:/usr/share/opensearch/data".to_string(),
+    )];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck(
+            "curl -fs http://localhost:9200/_cluster/health || exit 1",
+            10,
+            5,
+            12,
+        ),
+    );
+
+    model.services.insert("opensearch".to_string(), s);
+    model
+        .volumes
+        .insert("opensearch_data".to_string(), volume_conf(project));
+}
+
+fn add_solr(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(y_str("image"), y_str(&format!("solr:{}", svc.version)));
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "solr")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // Creates the core on first start; later starts find it in the volume
+    let core = svc
+        .settings
+        .get("core")
+        .map(|c| c.trim())
+        .filter(|c| {
+            !c.is_empty()
+                && c.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+        .unwrap_or(DEFAULT_SOLR_CORE);
+    s.insert(
+        y_str("command"),
+        YamlVal::Sequence(vec![y_str("solr-precreate"), y_str(core)]),
+    );
+
+    let mut env = YamlMap::new();
+    env.insert(y_str("SOLR_HEAP"), y_str(search_heap(svc)));
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let ports = vec![port_mapping(project, svc, svc.port, 8983)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String("solr_data:/var/solr".to_string())];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    s.insert(
+        y_str("healthcheck"),
+        healthcheck(
+            &format!(
+                "wget -qO- http://localhost:8983/solr/{}/admin/ping || exit 1",
+                core
+            ),
+            10,
+            5,
+            12,
+        ),
+    );
+
+    model.services.insert("solr".to_string(), s);
+    model
+        .volumes
+        .insert("solr_data".to_string(), volume_conf(project));
+}
+
+fn add_dbgate(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("dbgate/dbgate:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "dbgate")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // One predefined connection per enabled database, instances included
+    let connections = dbgate_connections(project);
+    let mut env = YamlMap::new();
+    if !connections.is_empty() {
+        let ids: Vec<&str> = connections.iter().map(|c| c.id.as_str()).collect();
+        env.insert(y_str("CONNECTIONS"), y_str(&ids.join(",")));
+    }
+    for c in &connections {
+        env.insert(y_str(&format!("LABEL_{}", c.id)), y_str(&c.service));
+        env.insert(y_str(&format!("ENGINE_{}", c.id)), y_str(c.engine));
+        env.insert(y_str(&format!("SERVER_{}", c.id)), y_str(&c.service));
+        env.insert(y_str(&format!("PORT_{}", c.id)), y_str(&c.port.to_string()));
+        if let Some(user) = &c.user {
+            env.insert(y_str(&format!("USER_{}", c.id)), y_str(user));
+        }
+        if let Some(password) = &c.password {
+            env.insert(y_str(&format!("PASSWORD_{}", c.id)), y_str(password));
+        }
+        if let Some(database) = &c.database {
+            env.insert(y_str(&format!("DATABASE_{}", c.id)), y_str(database));
+        }
+    }
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    if !env.is_empty() {
+        s.insert(y_str("environment"), YamlVal::Mapping(env));
+    }
+
+    let ports = vec![port_mapping(project, svc, svc.port, 3000)];
+    insert_ports(&mut s, svc, ports);
+
+    let vols = vec![YamlVal::String("dbgate_data:/root/.dbgate".to_string())];
+    s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    if !connections.is_empty() {
+        let deps = connections.iter().map(|c| y_str(&c.service)).collect();
+        s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
+    }
+
+    model.services.insert("dbgate".to_string(), s);
+    model
+        .volumes
+        .insert("dbgate_data".to_string(), volume_conf(project));
+}
+
+/// A database DbGate connects to on the project network.
+struct DbConnection {
+    id: String, // Env var suffix, e.g. `postgres_analytics`
+    service: String,
+    engine: &'static str,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
+}
+
+/// Connections to the project's enabled databases, in service name order.
+fn dbgate_connections(project: &ProjectConfig) -> Vec<DbConnection> {
+    sorted(&project.services)
+        .into_iter()
+        .filter(|(_, svc)| svc.enabled)
+        .filter_map(|(name, svc)| {
+            let env = |key: &str| svc.env_vars.get(key).filter(|v| !v.is_empty()).cloned();
+            let (engine, port, user, password, database) = match svc.kind(name) {
+                "mysql" => (
+                    "mysql@dbgate-plugin-mysql",
+                    3306,
+                    Some("root".to_string()),
+                    env("MYSQL_ROOT_PASSWORD"),
+                    env("MYSQL_DATABASE"),
+                ),
+                "postgresql" => (
+                    "postgres@dbgate-plugin-postgres",
+                    5432,
+                    env("POSTGRES_USER").or(Some("postgres".to_string())),
+                    env("POSTGRES_PASSWORD"),
+                    env("POSTGRES_DB"),
+                ),
+                "mssql" => (
+                    "mssql@dbgate-plugin-mssql",
+                    1433,
+                    Some("sa".to_string()),
+                    env("MSSQL_SA_PASSWORD"),
+                    None,
+                ),
+                "redis" => ("redis@dbgate-plugin-redis", 6379, None, None, None),
+                _ => return None,
+            };
+            let id = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            Some(DbConnection {
+                id,
+                service: name.clone(),
+                engine,
+                port,
+                user,
+                password,
+                database,
+            })
+        })
+        .collect()
+}
+
+fn add_mailpit(model: &mut ComposeModel, project: &ProjectConfig, svc: &ServiceConfig) {
+    let mut s = YamlMap::new();
+    s.insert(
+        y_str("image"),
+        y_str(&format!("axllent/mailpit:{}", svc.version)),
+    );
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, "mailpit")),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // Accept any credentials so framework mail configs work unchanged
+    let mut env = YamlMap::new();
+    env.insert(y_str("MP_SMTP_AUTH_ACCEPT_ANY"), y_str("1"));
+    env.insert(y_str("MP_SMTP_AUTH_ALLOW_INSECURE"), y_str("1"));
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let mut ports = vec![port_mapping(project, svc, svc.port, 8025)];
+    match svc.settings.get("smtp_port").map(|p| p.trim()) {
+        None | Some("") => ports.push(port_mapping(project, svc, MAIL_SMTP_PORT, MAIL_SMTP_PORT)),
+        Some(port) => match port.parse::<u16>() {
+            Ok(host) if host != 0 => ports.push(port_mapping(project, svc, host, MAIL_SMTP_PORT)),
+            _ => log::warn!(
+                "Mailpit's SMTP port {:?} is not a port number; not publishing SMTP",
+                port
+            ),
+        },
+    }
+    insert_ports(&mut s, svc, ports);
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert("mailpit".to_string(), s);
+}
+
+/// A user-added service running an arbitrary image.
+///
+/// Besides the common fields it reads these settings: `container_port` (defaults
+/// to the host port), `volumes` (one `source:target[:mode]` per line) and `command`.
+fn add_custom(model: &mut ComposeModel, project: &ProjectConfig, name: &str, svc: &ServiceConfig) {
+    if !svc.is_custom {
+        return;
+    }
+    let image = svc
+        .image
+        .as_deref()
+        .map(str::trim)
+        .filter(|img| !img.is_empty());
+    let build = build_context(svc);
+    if build.is_none()
+        && svc
+            .settings
+            .get("build")
+            .is_some_and(|b| !b.trim().is_empty())
+    {
+        log::warn!(
+            "Custom service {} builds from outside the project directory; ignoring it",
+            name
+        );
+    }
+    if image.is_none() && build.is_none() {
+        log::warn!(
+            "Custom service {} has no image or build context; leaving it out",
+            name
+        );
+        return;
+    }
+    let mut s = YamlMap::new();
+    // With a build context the image names the built result
+    if let Some(image) = image {
+        let image = registry_image(&svc.registry, image);
+        s.insert(
+            y_str("image"),
+            y_str(&image_reference(&image, &svc.version)),
+        );
+    }
+    if let Some(context) = build {
+        let mut b = YamlMap::new();
+        b.insert(y_str("context"), y_str(context));
+        if project.use_proxy {
+            let mut args = YamlMap::new();
+            for name in PROXY_VARS {
+                args.insert(y_str(name), y_str(&format!("${{{}:-}}", name)));
+            }
+            b.insert(y_str("args"), YamlVal::Mapping(args));
+        }
+        s.insert(y_str("build"), YamlVal::Mapping(b));
+    }
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    if let Some(command) = svc.settings.get("command").filter(|c| !c.trim().is_empty()) {
+        s.insert(y_str("command"), y_str(command.trim()));
+    }
+
+    let mut env = YamlMap::new();
+    for (k, v) in &smtp_env(project) {
+        env.insert(y_str(k), y_str(v));
+    }
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    let container_port = svc
+        .settings
+        .get("container_port")
+        .and_then(|p| p.trim().parse().ok())
+        .unwrap_or(svc.port);
+    let ports = vec![port_mapping(project, svc, svc.port, container_port)];
+    insert_ports(&mut s, svc, ports);
+
+    let mounts: Vec<&str> = svc
+        .settings
+        .get("volumes")
+        .map(|v| v.lines().map(str::trim).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default();
+    if !mounts.is_empty() {
+        for volume in mounts.iter().filter_map(|m| named_volume(m)) {
+            model
+                .volumes
+                .insert(volume.to_string(), volume_conf(project));
+        }
+        let vols = mounts.into_iter().map(y_str).collect();
+        s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+    }
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
+
+    model.services.insert(name.to_string(), s);
+}
+
+/// A plugin's service: its template, plus what DockStack sets on every service.
+fn add_plugin(
+    model: &mut ComposeModel,
+    project: &ProjectConfig,
+    name: &str,
+    svc: &ServiceConfig,
+    plugin: &ServicePlugin,
+) {
+    let mut s = match plugin.render(&project.name, name, svc) {
+        Ok(s) => s,
+        Err(e) => {
+            log::warn!("Plugin {} for service {}: {}", plugin.id, name, e);
+            return;
+        }
+    };
+    s.insert(
+        y_str("container_name"),
+        y_str(&container_name(project, name)),
+    );
+    s.insert(y_str("restart"), y_str(&svc.restart_policy));
+    s.insert(y_str("labels"), project_labels(project));
+
+    // As a mapping, so project-wide variables can be added like everywhere else
+    let mut env = YamlMap::new();
+    match s.remove("environment") {
+        Some(YamlVal::Mapping(vars)) => env = vars,
+        Some(YamlVal::Sequence(vars)) => {
+            for (k, v) in vars.iter().filter_map(|v| v.as_str()?.split_once('=')) {
+                env.insert(y_str(k), y_str(v));
+            }
+        }
+        _ => {}
+    }
+    for (k, v) in sorted(&svc.env_vars) {
+        env.insert(y_str(k), y_str(v));
+    }
+    s.insert(y_str("environment"), YamlVal::Mapping(env));
+
+    if !s.contains_key("ports") {
+        let ports = vec![port_mapping(
+            project,
+            svc,
+            svc.port,
+            plugin.container_port(),
+        )];
+        insert_ports(&mut s, svc, ports);
+    }
+    if let Some(YamlVal::Sequence(mounts)) = s.get("volumes") {
+        for volume in mounts
+            .iter()
+            .filter_map(|m| m.as_str())
+            .filter_map(named_volume)
+        {
+            model
+                .volumes
+                .insert(volume.to_string(), volume_conf(project));
+        }
+    }
+
+    let nets = service_networks(project);
+    s.insert(y_str("networks"), YamlVal::Sequence(nets));
 
-    let network_name = format!("dockstack_{}", project.id);
+    model.services.insert(name.to_string(), s);
+}
 
-    for (name, svc) in &project.services {
-        if !svc.enabled {
-            continue;
-        }
-        match name.as_str() {
-            "postgresql" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("postgres:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_postgresql", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut env = YamlMap::new();
-                for (k, v) in &svc.env_vars {
-                    env.insert(y_str(k), y_str(v));
-                }
-                s.insert(y_str("environment"), YamlVal::Mapping(env));
+/// Directory with the Dockerfile of a custom service, relative to the project.
+/// A context that could leave the project directory is ignored.
+fn build_context(svc: &ServiceConfig) -> Option<&str> {
+    svc.settings
+        .get("build")
+        .map(|b| b.trim())
+        .filter(|b| !b.is_empty())
+        .filter(|b| {
+            Path::new(b)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        })
+}
+
+/// Enabled custom services that are built from a local context.
+pub fn build_services(project: &ProjectConfig) -> Vec<String> {
+    sorted(&project.services)
+        .into_iter()
+        .filter(|(_, svc)| svc.enabled && svc.is_custom && build_context(svc).is_some())
+        .map(|(name, _)| name.clone())
+        .collect()
+}
 
-                let ports = vec![YamlVal::String(format!("{}:5432", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
+/// `image:version`, unless `image` already pins a tag or digest.
+fn image_reference(image: &str, version: &str) -> String {
+    let last_segment = image.rsplit('/').next().unwrap_or(image);
+    let version = version.trim();
+    if image.contains('@') || last_segment.contains(':') || version.is_empty() {
+        image.to_string()
+    } else {
+        format!("{}:{}", image, version)
+    }
+}
 
-                let vols = vec![YamlVal::String(
-                    "postgres_data:/var/lib/postgresql/data".to_string(),
-                )];
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+/// Registry host an image reference names, e.g. `ghcr.io` for
+/// `ghcr.io/acme/api:1.2`; `None` for Docker Hub images like `mysql:8.0`.
+pub fn image_host(image: &str) -> Option<&str> {
+    let (first, _) = image.split_once('/')?;
+    (first.contains(['.', ':']) || first == "localhost").then_some(first)
+}
 
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
+/// `image` pulled from `registry`, unless it names a registry itself or
+/// `registry` is Docker Hub.
+fn registry_image(registry: &str, image: &str) -> String {
+    let registry = registry.trim().trim_end_matches('/');
+    if registry.is_empty() || registry == "docker.io" || image_host(image).is_some() {
+        image.to_string()
+    } else {
+        format!("{}/{}", registry, image)
+    }
+}
 
-                s.insert(
-                    y_str("healthcheck"),
-                    healthcheck("pg_isready -U postgres", 10, 5, 5),
-                );
+/// A Docker Hub image pulled through the mirror at `prefix`, which names
+/// official images under `library/` as Docker Hub does: `mysql:8.0` becomes
+/// `<prefix>/library/mysql:8.0`. Images from other registries are left alone.
+fn mirror_image(prefix: &str, image: &str) -> String {
+    let prefix = prefix.trim().trim_end_matches('/');
+    if image_host(image).is_some() {
+        image.to_string()
+    } else if image.contains('/') {
+        format!("{}/{}", prefix, image)
+    } else {
+        format!("{}/library/{}", prefix, image)
+    }
+}
 
-                services.insert(y_str("postgresql"), YamlVal::Mapping(s));
-                volumes.insert(y_str("postgres_data"), YamlVal::Mapping(YamlMap::new()));
-            }
-            "mysql" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("mysql:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_mysql", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut env = YamlMap::new();
-                for (k, v) in &svc.env_vars {
-                    env.insert(y_str(k), y_str(v));
-                }
-                s.insert(y_str("environment"), YamlVal::Mapping(env));
+/// Volume name when `mount` (`source:target[:mode]`) uses a named volume rather
+/// than a host path, which compose requires to be declared at the top level.
+fn named_volume(mount: &str) -> Option<&str> {
+    let (source, rest) = mount.split_once(':')?;
+    let drive_letter = cfg!(windows) && source.len() == 1 && rest.starts_with(['\\', '/']);
+    let is_path = drive_letter
+        || source.is_empty()
+        || source.starts_with(['.', '/', '~', '$'])
+        || source.contains(['/', '\\']);
+    (!is_path).then_some(source)
+}
 
-                let ports = vec![YamlVal::String(format!("{}:3306", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
+/// The project with the placeholders in its services' env vars and settings,
+/// and in its branch overlays, filled in.
+fn with_placeholders(project: &ProjectConfig) -> ProjectConfig {
+    let mut resolved = project.clone();
+    for svc in resolved.services.values_mut() {
+        for value in svc.env_vars.values_mut().chain(svc.settings.values_mut()) {
+            *value = expand_placeholders(project, value);
+        }
+    }
+    for overlay in &mut resolved.branch_env {
+        overlay.vars = expand_placeholders(project, &overlay.vars);
+    }
+    resolved.env = expand_placeholders(project, &project.env);
+    resolved
+}
 
-                let vols = vec![YamlVal::String("mysql_data:/var/lib/mysql".to_string())];
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+/// Replace `${PROJECT_NAME}`, `${DOMAIN}` and `${PORT:service}` in `value`. Other
+/// variables, and `$${…}` escapes, are left for docker compose to interpolate.
+fn expand_placeholders(project: &ProjectConfig, value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let escaped = rest[..start].ends_with('$');
+        expanded.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            expanded.push_str(&rest[start..]);
+            return expanded;
+        };
+        let name = &rest[start + 2..start + end];
+        match placeholder(project, name).filter(|_| !escaped) {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
 
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
+fn placeholder(project: &ProjectConfig, name: &str) -> Option<String> {
+    match name {
+        "PROJECT_NAME" => Some(project.name.clone()),
+        "DOMAIN" => Some(project.domain.clone()),
+        _ => {
+            let service = name.strip_prefix("PORT:")?;
+            project
+                .services
+                .contains_key(service)
+                .then(|| project.host_port(service).to_string())
+        }
+    }
+}
 
-                s.insert(
-                    y_str("healthcheck"),
-                    healthcheck("mysqladmin ping -h localhost", 10, 5, 5),
-                );
+/// Set `vars` in the service's environment, adding one if it has none.
+fn apply_env<'a>(service: &mut YamlMap, vars: impl Iterator<Item = (&'a str, &'a str)>) {
+    if !matches!(service.get("environment"), Some(YamlVal::Mapping(_))) {
+        service.insert(y_str("environment"), YamlVal::Mapping(YamlMap::new()));
+    }
+    if let Some(YamlVal::Mapping(env)) = service.get_mut("environment") {
+        for (key, value) in vars {
+            env.insert(y_str(key), y_str(value));
+        }
+    }
+}
 
-                services.insert(y_str("mysql"), YamlVal::Mapping(s));
-                volumes.insert(y_str("mysql_data"), YamlVal::Mapping(YamlMap::new()));
-            }
-            "php" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("php:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_php", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut vols = vec![YamlVal::String(format!(
-                    "{}/www:/var/www/html",
-                    project.directory
-                ))];
-                vols.push(YamlVal::String(format!(
-                    "{}/php/php.ini:/usr/local/etc/php/conf.d/dockstack.ini",
-                    project.directory
-                )));
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
-
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
-
-                services.insert(y_str("php"), YamlVal::Mapping(s));
-            }
-            "apache" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("httpd:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_apache", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let ports = vec![YamlVal::String(format!("{}:80", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
-
-                let vols = vec![
-                    YamlVal::String(format!(
-                        "{}/www:/usr/local/apache2/htdocs/",
-                        project.directory
-                    )),
-                    YamlVal::String(
-                        "./apache/httpd.conf:/usr/local/apache2/conf/httpd.conf".to_string(),
-                    ),
-                ];
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
-
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
-
-                services.insert(y_str("apache"), YamlVal::Mapping(s));
-            }
-            "nginx" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("nginx:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_nginx", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut ports = vec![YamlVal::String(format!("{}:80", svc.port))];
-                if project.ssl_enabled {
-                    ports.push(YamlVal::String("443:443".to_string()));
-                }
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
-
-                let mut vols = vec![
-                    YamlVal::String(format!("{}/www:/usr/share/nginx/html", project.directory)),
-                    YamlVal::String(
-                        "./nginx/default.conf:/etc/nginx/conf.d/default.conf".to_string(),
-                    ),
-                ];
-                if project.ssl_enabled {
-                    vols.push(YamlVal::String("./certs:/etc/nginx/certs:ro".to_string()));
-                }
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+/// Set the `vars` the service's environment doesn't set already; of repeated
+/// keys the first wins.
+fn add_default_env(service: &mut YamlMap, vars: &[(&str, &str)]) {
+    if !matches!(service.get("environment"), Some(YamlVal::Mapping(_))) {
+        service.insert(y_str("environment"), YamlVal::Mapping(YamlMap::new()));
+    }
+    if let Some(YamlVal::Mapping(env)) = service.get_mut("environment") {
+        for (key, value) in vars {
+            env.entry(y_str(key)).or_insert_with(|| y_str(value));
+        }
+    }
+}
 
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
+/// Proxy variables docker compose fills in from the environment DockStack runs
+/// it with, so proxy logins stay out of the compose file.
+const PROXY_VARS: [&str; 3] = ["HTTP_PROXY", "HTTPS_PROXY", "NO_PROXY"];
+
+/// Proxy variables for the containers of `model`, in both cases. They reach each
+/// other, and localhost, directly.
+fn proxy_env(model: &ComposeModel) -> Vec<(&'static str, String)> {
+    let mut direct: Vec<&str> = vec!["localhost", "127.0.0.1"];
+    direct.extend(model.services.keys().map(String::as_str));
+    let no_proxy = format!("{},${{NO_PROXY:-}}", direct.join(","));
+    vec![
+        ("HTTP_PROXY", "${HTTP_PROXY:-}".to_string()),
+        ("http_proxy", "${HTTP_PROXY:-}".to_string()),
+        ("HTTPS_PROXY", "${HTTPS_PROXY:-}".to_string()),
+        ("https_proxy", "${HTTPS_PROXY:-}".to_string()),
+        ("NO_PROXY", no_proxy.clone()),
+        ("no_proxy", no_proxy),
+    ]
+}
 
-                services.insert(y_str("nginx"), YamlVal::Mapping(s));
-            }
-            "phpmyadmin" => {
-                let mut s = YamlMap::new();
-                s.insert(
-                    y_str("image"),
-                    y_str(&format!("phpmyadmin:{}", svc.version)),
-                );
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_phpmyadmin", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut env = YamlMap::new();
-                env.insert(y_str("PMA_HOST"), y_str("mysql"));
-                env.insert(y_str("PMA_ARBITRARY"), y_str("1"));
-
-                for (k, v) in &svc.env_vars {
-                    env.insert(y_str(k), y_str(v));
-                }
+/// The project's timezone, if set. Anything but a zone name is ignored, as it
+/// lands in php.ini verbatim.
+fn timezone(project: &ProjectConfig) -> Option<&str> {
+    let timezone = project.timezone.trim();
+    let valid = timezone
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+    (!timezone.is_empty() && valid).then_some(timezone)
+}
 
-                s.insert(y_str("environment"), YamlVal::Mapping(env));
+/// The project's locale, if set and well-formed, e.g. `de_DE.UTF-8`.
+fn locale(project: &ProjectConfig) -> Option<&str> {
+    let locale = project.locale.trim();
+    let valid = locale
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'));
+    (!locale.is_empty() && valid).then_some(locale)
+}
 
-                let ports = vec![YamlVal::String(format!("{}:80", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
+/// `uid:gid` owning the project directory (the home directory before it exists);
+/// containers running as it leave files in `www/` that the host user can edit.
+fn host_user(project: &ProjectConfig) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = fs::metadata(&project.directory)
+            .ok()
+            .or_else(|| dirs::home_dir().and_then(|home| fs::metadata(home).ok()))?;
+        Some(format!("{}:{}", meta.uid(), meta.gid()))
+    }
+    #[cfg(not(unix))]
+    {
+        // Docker Desktop maps ownership of bind-mounted files itself
+        let _ = project;
+        None
+    }
+}
 
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
+/// Whether SELinux is enforcing here; bind mounts then need a relabel flag
+/// or containers cannot read the project files. Read once per run.
+pub fn selinux_enforcing() -> bool {
+    static ENFORCING: OnceLock<bool> = OnceLock::new();
+    *ENFORCING.get_or_init(|| {
+        fs::read_to_string("/sys/fs/selinux/enforce").is_ok_and(|s| s.trim() == "1")
+    })
+}
 
-                let deps = vec![YamlVal::String("mysql".to_string())];
-                if project.services.get("mysql").is_some_and(|s| s.enabled) {
-                    s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
-                }
+fn relabel_flag(project: &ProjectConfig) -> Option<&'static str> {
+    match project.selinux_relabel.as_str() {
+        "shared" => Some("z"),
+        "private" => Some("Z"),
+        "off" => None,
+        _ => selinux_enforcing().then_some("z"),
+    }
+}
 
-                services.insert(y_str("phpmyadmin"), YamlVal::Mapping(s));
-            }
-            "pgadmin" => {
-                let mut s = YamlMap::new();
-                s.insert(
-                    y_str("image"),
-                    y_str(&format!("dpage/pgadmin4:{}", svc.version)),
-                );
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_pgadmin", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let mut env = YamlMap::new();
-                for (k, v) in &svc.env_vars {
-                    env.insert(y_str(k), y_str(v));
-                }
-                s.insert(y_str("environment"), YamlVal::Mapping(env));
+/// `mount` with `flag` added to its options. Only bind mounts of project files
+/// are relabeled; SELinux refuses or breaks relabeling system paths.
+fn relabel_mount(project: &ProjectConfig, mount: &str, flag: &str) -> String {
+    let source = mount.split(':').next().unwrap_or_default();
+    let in_project = source.starts_with("./") || Path::new(source).starts_with(&project.directory);
+    if named_volume(mount).is_some() || !in_project {
+        return mount.to_string();
+    }
+    add_mount_option(mount, flag, &["z", "Z"])
+}
 
-                let ports = vec![YamlVal::String(format!("{}:80", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
+/// `mount` with `option` appended, unless one of `exclusive` is already set.
+fn add_mount_option(mount: &str, option: &str, exclusive: &[&str]) -> String {
+    let mut parts = mount.splitn(3, ':');
+    let (Some(source), Some(target), options) = (parts.next(), parts.next(), parts.next()) else {
+        return mount.to_string();
+    };
+    match options {
+        None => format!("{}:{}:{}", source, target, option),
+        Some(options) if options.split(',').any(|o| exclusive.contains(&o)) => mount.to_string(),
+        Some(options) => format!("{}:{}:{},{}", source, target, options, option),
+    }
+}
 
-                let vols = vec![YamlVal::String("pgadmin_data:/var/lib/pgadmin".to_string())];
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
+/// Project directories mounted into containers as application code.
+const CODE_DIRS: [&str; 2] = ["www", "app"];
+/// Stands for the project directory itself when `code_in_directory` is set.
+const ROOT_CODE_DIR: &str = "code";
 
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
+/// Host directory mounted as code in place of the project's `dir` (`www` or `app`).
+fn code_source(project: &ProjectConfig, dir: &str) -> String {
+    if project.code_in_directory {
+        project.directory.clone()
+    } else {
+        format!("{}/{}", project.directory, dir)
+    }
+}
 
-                if project
-                    .services
-                    .get("postgresql")
-                    .is_some_and(|s| s.enabled)
-                {
-                    let deps = vec![YamlVal::String("postgresql".to_string())];
-                    s.insert(y_str("depends_on"), YamlVal::Sequence(deps));
-                }
+/// Which of `CODE_DIRS` (or `ROOT_CODE_DIR`) a bind-mount source is, if any.
+fn code_dir<'a>(project: &ProjectConfig, source: &'a str) -> Option<&'a str> {
+    let rel = match Path::new(source).strip_prefix(&project.directory) {
+        Ok(rel) => rel.to_str()?,
+        Err(_) => source
+            .strip_prefix("./")
+            .or((source == ".").then_some(""))?,
+    };
+    if rel.is_empty() && project.code_in_directory {
+        return Some(ROOT_CODE_DIR);
+    }
+    CODE_DIRS.contains(&rel).then_some(rel)
+}
 
-                services.insert(y_str("pgadmin"), YamlVal::Mapping(s));
-                volumes.insert(y_str("pgadmin_data"), YamlVal::Mapping(YamlMap::new()));
-            }
-            "redis" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("redis:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_redis", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let ports = vec![YamlVal::String(format!("{}:6379", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
-
-                let vols = vec![YamlVal::String("redis_data:/data".to_string())];
-                s.insert(y_str("volumes"), YamlVal::Sequence(vols));
-
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
-
-                s.insert(
-                    y_str("healthcheck"),
-                    healthcheck("redis-cli ping", 10, 5, 5),
-                );
-
-                services.insert(y_str("redis"), YamlVal::Mapping(s));
-                volumes.insert(y_str("redis_data"), YamlVal::Mapping(YamlMap::new()));
-            }
-            "adminer" => {
-                let mut s = YamlMap::new();
-                s.insert(y_str("image"), y_str(&format!("adminer:{}", svc.version)));
-                s.insert(
-                    y_str("container_name"),
-                    y_str(&format!("dockstack_{}_adminer", project.id)),
-                );
-                s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                let ports = vec![YamlVal::String(format!("{}:8080", svc.port))];
-                s.insert(y_str("ports"), YamlVal::Sequence(ports));
-
-                let nets = vec![YamlVal::String(network_name.clone())];
-                s.insert(y_str("networks"), YamlVal::Sequence(nets));
-
-                services.insert(y_str("adminer"), YamlVal::Mapping(s));
-            }
-            "ssl" => {
-                // SSL is handled via nginx config, not as a separate service container.
-                // The SSL toggle enables HTTPS on the nginx reverse proxy.
-            }
-            _ => {
-                if svc.is_custom {
-                    if let Some(img) = &svc.image {
-                        let mut s = YamlMap::new();
-                        s.insert(y_str("image"), y_str(&format!("{}:{}", img, svc.version)));
-                        s.insert(
-                            y_str("container_name"),
-                            y_str(&format!("dockstack_{}_{}", project.id, name)),
-                        );
-                        s.insert(y_str("restart"), y_str("unless-stopped"));
-
-                        let mut env = YamlMap::new();
-                        for (k, v) in &svc.env_vars {
-                            env.insert(y_str(k), y_str(v));
-                        }
-                        s.insert(y_str("environment"), YamlVal::Mapping(env));
+/// Named volume backing `dir` below `service`'s code mount, e.g. `php_vendor`.
+fn overlay_volume(service: &str, dir: &str) -> String {
+    let dir: String = dir
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}_{}", service, dir)
+}
+
+/// Overlay directories that stay inside the code mount; others are ignored.
+fn overlay_dirs(svc: &ServiceConfig) -> impl Iterator<Item = &str> {
+    svc.volume_overlays
+        .iter()
+        .map(|dir| dir.trim().trim_matches('/'))
+        .filter(|dir| {
+            !dir.is_empty()
+                && Path::new(dir)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+        })
+}
 
-                        let ports = vec![YamlVal::String(format!("{}:{}", svc.port, svc.port))];
-                        s.insert(y_str("ports"), YamlVal::Sequence(ports));
+/// Apply the service's consistency flag and volume overlays to its code mounts,
+/// or swap them for the named volumes Mutagen syncs into.
+fn tune_code_mounts(
+    project: &ProjectConfig,
+    name: &str,
+    svc: &ServiceConfig,
+    service: &mut YamlMap,
+    volumes: &mut BTreeMap<String, YamlVal>,
+) {
+    let Some(YamlVal::Sequence(mounts)) = service.get_mut("volumes") else {
+        return;
+    };
+    let mut overlays = Vec::new();
+    for mount in mounts.iter_mut() {
+        let YamlVal::String(m) = mount else {
+            continue;
+        };
+        let Some((source, rest)) = m.split_once(':') else {
+            continue;
+        };
+        let Some(dir) = code_dir(project, source) else {
+            continue;
+        };
+        let target = rest.split(':').next().unwrap_or(rest).trim_end_matches('/');
+        for overlay in overlay_dirs(svc) {
+            let volume = overlay_volume(name, overlay);
+            overlays.push(YamlVal::String(format!(
+                "{}:{}/{}",
+                volume, target, overlay
+            )));
+            volumes.insert(volume, volume_conf(project));
+        }
+        if project.file_sync == "mutagen" {
+            let volume = format!("{}_sync", dir);
+            *m = format!("{}:{}", volume, rest);
+            volumes.insert(volume, volume_conf(project));
+        } else if svc.mount_consistency != "default"
+            && MOUNT_CONSISTENCIES.contains(&svc.mount_consistency.as_str())
+        {
+            *m = add_mount_option(m, &svc.mount_consistency, MOUNT_CONSISTENCIES);
+        }
+    }
+    mounts.extend(overlays);
+}
 
-                        let nets = vec![YamlVal::String(network_name.clone())];
-                        s.insert(y_str("networks"), YamlVal::Sequence(nets));
+/// Services that mount one of the project's code directories.
+pub fn code_mount_services(project: &ProjectConfig) -> Vec<String> {
+    let mut plain = project.clone();
+    plain.file_sync = "bind".to_string();
+    ComposeModel::from_project(&plain)
+        .services
+        .into_iter()
+        .filter(|(_, s)| {
+            let Some(YamlVal::Sequence(mounts)) = s.get("volumes") else {
+                return false;
+            };
+            mounts
+                .iter()
+                .filter_map(|m| m.as_str()?.split_once(':'))
+                .any(|(source, _)| code_dir(&plain, source).is_some())
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
 
-                        services.insert(y_str(name), YamlVal::Mapping(s));
-                    }
-                }
+/// Where `service` sees the project's code, e.g. `/var/www/html` for php.
+pub fn code_mount_target(project: &ProjectConfig, service: &str) -> Option<String> {
+    let mut plain = project.clone();
+    plain.file_sync = "bind".to_string();
+    let model = ComposeModel::from_project(&plain);
+    let Some(YamlVal::Sequence(mounts)) = model.services.get(service)?.get("volumes") else {
+        return None;
+    };
+    mounts
+        .iter()
+        .filter_map(|m| m.as_str()?.split_once(':'))
+        .find(|(source, _)| code_dir(&plain, source).is_some())
+        .map(|(_, rest)| rest.split(':').next().unwrap_or(rest).to_string())
+}
+
+/// A Mutagen session between a host code directory and the named volume
+/// that replaces its bind mount in `mutagen` file sync mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncSession {
+    pub name: String,
+    pub host: PathBuf,
+    pub container: String,
+    pub target: String,
+    pub ignores: Vec<String>, // Overlay directories, which live in their own volumes
+}
+
+/// One session per synced code directory, into the first container mounting it.
+pub fn sync_sessions(project: &ProjectConfig) -> Vec<SyncSession> {
+    if project.file_sync != "mutagen" {
+        return Vec::new();
+    }
+    let model = ComposeModel::from_project(project);
+    let ignores: Vec<String> = sorted(&project.services)
+        .into_iter()
+        .filter(|(_, s)| s.enabled)
+        .flat_map(|(_, s)| overlay_dirs(s).map(str::to_string).collect::<Vec<_>>())
+        .collect();
+    let mut sessions: Vec<SyncSession> = Vec::new();
+    for (name, service) in &model.services {
+        let Some(YamlVal::Sequence(mounts)) = service.get("volumes") else {
+            continue;
+        };
+        for mount in mounts.iter().filter_map(|m| m.as_str()) {
+            let Some((volume, rest)) = mount.split_once(':') else {
+                continue;
+            };
+            let Some(dir) = volume
+                .strip_suffix("_sync")
+                .filter(|d| CODE_DIRS.contains(d) || *d == ROOT_CODE_DIR)
+            else {
+                continue;
+            };
+            let session = format!("dockstack-{}-{}", project.id, dir);
+            if sessions.iter().any(|s| s.name == session) {
+                continue;
             }
+            let container = service
+                .get("container_name")
+                .and_then(|c| c.as_str())
+                .map_or_else(|| name.clone(), str::to_string);
+            sessions.push(SyncSession {
+                name: session,
+                host: match dir {
+                    ROOT_CODE_DIR => PathBuf::from(&project.directory),
+                    dir => Path::new(&project.directory).join(dir),
+                },
+                container,
+                target: rest.split(':').next().unwrap_or(rest).to_string(),
+                ignores: ignores.clone(),
+            });
         }
     }
+    sessions
+}
 
-    // Network
+fn add_networks(model: &mut ComposeModel, project: &ProjectConfig) {
     let mut net_conf = YamlMap::new();
     net_conf.insert(y_str("driver"), y_str("bridge"));
-    networks.insert(y_str(&network_name), YamlVal::Mapping(net_conf));
+    net_conf.insert(y_str("labels"), project_labels(project));
+    let net = &project.network;
+    if net.ipv6 {
+        net_conf.insert(y_str("enable_ipv6"), YamlVal::Bool(true));
+    }
+    let mut subnets = vec![net.subnet.as_str()];
+    if net.ipv6 {
+        subnets.push(net.ipv6_subnet.as_str());
+    }
+    let ipam_config: Vec<YamlVal> = subnets
+        .into_iter()
+        .filter(|subnet| is_valid_cidr(subnet))
+        .map(|subnet| {
+            let mut entry = YamlMap::new();
+            entry.insert(y_str("subnet"), y_str(subnet.trim()));
+            YamlVal::Mapping(entry)
+        })
+        .collect();
+    if !ipam_config.is_empty() {
+        let mut ipam = YamlMap::new();
+        ipam.insert(y_str("config"), YamlVal::Sequence(ipam_config));
+        net_conf.insert(y_str("ipam"), YamlVal::Mapping(ipam));
+    }
+    model
+        .networks
+        .insert(network_name(project), YamlVal::Mapping(net_conf));
+
+    for name in external_networks(project) {
+        let mut ext = YamlMap::new();
+        ext.insert(y_str("external"), YamlVal::Bool(true));
+        model
+            .networks
+            .insert(name.to_string(), YamlVal::Mapping(ext));
+    }
+}
+
+/// Why `docker compose config` rejected the compose file DockStack wrote.
+#[derive(Debug, Clone)]
+pub struct ComposeValidation {
+    pub path: String,
+    pub message: String,
+    pub line: Option<usize>, // 1-based line in the compose file, when known
+    pub excerpt: Vec<(usize, String)>, // Numbered lines around `line`
+}
+
+impl ComposeValidation {
+    /// Lines shown on each side of the offending line.
+    const CONTEXT_LINES: usize = 3;
+
+    pub fn new(path: &str, stderr: &str, yaml: &str) -> Self {
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let line = error_line(&message, yaml);
+        let excerpt = line
+            .map(|line| {
+                let first = line.saturating_sub(Self::CONTEXT_LINES).max(1);
+                yaml.lines()
+                    .enumerate()
+                    .map(|(i, l)| (i + 1, l.to_string()))
+                    .skip(first - 1)
+                    .take(line + Self::CONTEXT_LINES + 1 - first)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: path.to_string(),
+            message,
+            line,
+            excerpt,
+        }
+    }
+}
+
+/// Line a compose error points at: either a YAML parser `line N`, or the
+/// `services.<name>.<key>` path / `service "<name>"` it names.
+fn error_line(message: &str, yaml: &str) -> Option<usize> {
+    let parser_line = message
+        .split("line ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|n| n.parse().ok());
+    if parser_line.is_some() {
+        return parser_line;
+    }
+
+    if let Some(path) = message
+        .split_whitespace()
+        .find(|w| w.starts_with("services."))
+    {
+        let mut keys = path.trim_end_matches([':', ',']).split('.').skip(1);
+        let service = keys.next()?;
+        return service_key_line(yaml, service, keys.next());
+    }
+    let service = message.split("service \"").nth(1)?.split('"').next()?;
+    service_key_line(yaml, service, None)
+}
+
+/// 1-based line of `services.<service>[.<key>]` in YAML as serde_yaml writes it.
+fn service_key_line(yaml: &str, service: &str, key: Option<&str>) -> Option<usize> {
+    let lines: Vec<&str> = yaml.lines().collect();
+    let services = lines.iter().position(|l| *l == "services:")?;
+    let service_line = (services + 1..lines.len())
+        .take_while(|&i| lines[i].is_empty() || lines[i].starts_with(' '))
+        .find(|&i| {
+            lines[i]
+                .strip_prefix("  ")
+                .and_then(|l| l.strip_suffix(':'))
+                == Some(service)
+        })?;
+    let key_line = key.and_then(|key| {
+        let prefix = format!("{}:", key);
+        (service_line + 1..lines.len())
+            .take_while(|&i| lines[i].starts_with("    "))
+            .find(|&i| lines[i][4..].starts_with(&prefix))
+    });
+    Some(key_line.unwrap_or(service_line) + 1)
+}
+
+/// Outcome of inspecting a project directory before DockStack writes into it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DirectoryCheck {
+    Ok,
+    NeedsConfirmation(String),
+}
+
+/// Directories DockStack must never treat as a project root.
+const PROTECTED_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/home",
+    "/lib",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/sys",
+    "/tmp",
+    "/usr",
+    "/var",
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Users",
+    "C:\\",
+    "C:\\Windows",
+    "C:\\Program Files",
+    "C:\\Program Files (x86)",
+    "C:\\Users",
+];
+
+/// Reject paths that are relative, traverse upwards, or point at system/home roots.
+pub fn validate_project_directory(directory: &str) -> std::io::Result<()> {
+    let refuse = |reason: String| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "Refusing to use '{}' as a project directory: {}",
+                directory, reason
+            ),
+        ))
+    };
+
+    let path = Path::new(directory);
+    if directory.trim().is_empty() || !path.is_absolute() {
+        return refuse("path must be absolute".to_string());
+    }
+    if path.components().any(|c| matches!(c, Component::ParentDir)) {
+        return refuse("path must not contain '..'".to_string());
+    }
+    if path.parent().is_none() {
+        return refuse("path is a filesystem root".to_string());
+    }
+
+    let normalized = directory.trim_end_matches(['/', '\\']);
+    if PROTECTED_DIRS
+        .iter()
+        .any(|p| normalized.eq_ignore_ascii_case(p.trim_end_matches('\\')))
+    {
+        return refuse("path is a protected system directory".to_string());
+    }
+    if dirs::home_dir().is_some_and(|home| home == path) {
+        return refuse("path is your home directory".to_string());
+    }
+    if path == AppConfig::config_dir() {
+        return refuse("path is the DockStack configuration directory".to_string());
+    }
+
+    Ok(())
+}
+
+/// Check whether writing the stack into the project directory needs the user's approval.
+pub fn check_project_directory(project: &ProjectConfig) -> std::io::Result<DirectoryCheck> {
+    validate_project_directory(&project.directory)?;
+
+    let dir = Path::new(&project.directory);
+    if project.directory_confirmed || !dir.exists() {
+        return Ok(DirectoryCheck::Ok);
+    }
+
+    let compose_path = dir.join("docker-compose.yml");
+    if compose_path.exists() {
+        let existing = fs::read_to_string(&compose_path)?;
+        if is_managed_compose(project, &existing) {
+            return Ok(DirectoryCheck::Ok);
+        }
+        return Ok(DirectoryCheck::NeedsConfirmation(format!(
+            "{} already contains a docker-compose.yml that was not generated by DockStack. \
+             Continuing will overwrite it.",
+            project.directory
+        )));
+    }
+
+    if fs::read_dir(dir)?.next().is_some() {
+        return Ok(DirectoryCheck::NeedsConfirmation(format!(
+            "{} is not empty. DockStack will add docker-compose.yml and service config \
+             folders (nginx/, apache/, php/, www/) next to the existing files.",
+            project.directory
+        )));
+    }
+
+    Ok(DirectoryCheck::Ok)
+}
+
+fn is_managed_compose(project: &ProjectConfig, content: &str) -> bool {
+    content.contains("MANAGED BY DOCKSTACK")
+        || content.contains(&format!("dockstack_{}", project.id))
+}
+
+/// Exact contents `write_compose_file` puts in `docker-compose.yml`.
+pub fn render_compose_file(project: &ProjectConfig) -> String {
+    format!("{}{}", MANAGED_HEADER, generate_compose(project))
+}
+
+/// Files and directories under the project directory that `write_compose_file` creates.
+const GENERATED_PATHS: &[&str] = &[
+    "docker-compose.yml",
+    "www",
+    "nginx/default.conf",
+    "apache/httpd.conf",
+    "php/php.ini",
+    "pgadmin/servers.json",
+    "varnish/default.vcl",
+];
+
+/// Never meant for a repository: private keys, logins and volume archives.
+const PRIVATE_PATHS: &[&str] = &["certs/", "sftp/", "backups/"];
+
+/// `.gitignore` lines for what DockStack writes into the project directory. When
+/// generated files are committed only the private ones are listed.
+pub fn gitignore_entries(project: &ProjectConfig) -> Vec<String> {
+    let generated = GENERATED_PATHS
+        .iter()
+        .filter(|p| **p != "www" && !project.commit_generated);
+    generated
+        .chain(PRIVATE_PATHS)
+        .map(|p| format!("/{}", p))
+        .collect()
+}
+
+pub fn is_git_repo(project: &ProjectConfig) -> bool {
+    Path::new(&project.directory).join(".git").exists()
+}
+
+/// Entries of `gitignore_entries` the project's `.gitignore` does not have yet.
+pub fn missing_gitignore_entries(project: &ProjectConfig) -> Vec<String> {
+    let path = Path::new(&project.directory).join(".gitignore");
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let normalize = |line: &str| line.trim().trim_matches('/').to_string();
+    let present: Vec<String> = existing.lines().map(normalize).collect();
+    gitignore_entries(project)
+        .into_iter()
+        .filter(|entry| !present.contains(&normalize(entry)))
+        .collect()
+}
+
+/// Append the missing entries to `.gitignore`; returns the lines added.
+pub fn update_gitignore(project: &ProjectConfig) -> std::io::Result<Vec<String>> {
+    let missing = missing_gitignore_entries(project);
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+    let path = Path::new(&project.directory).join(".gitignore");
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("\n# Generated by DockStack\n");
+    for entry in &missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    fs::write(&path, content)?;
+    Ok(missing)
+}
 
-    root.insert(y_str("services"), YamlVal::Mapping(services));
-    if !volumes.is_empty() {
-        root.insert(y_str("volumes"), YamlVal::Mapping(volumes));
+/// Rewrite bind mounts under the project directory as `./…`, so the file works
+/// from any checkout.
+fn make_mounts_relative(project: &ProjectConfig, model: &mut ComposeModel) {
+    for service in model.services.values_mut() {
+        let Some(YamlVal::Sequence(mounts)) = service.get_mut("volumes") else {
+            continue;
+        };
+        for mount in mounts.iter_mut() {
+            let YamlVal::String(m) = mount else {
+                continue;
+            };
+            let Some((source, rest)) = m.split_once(':') else {
+                continue;
+            };
+            if let Ok(rel) = Path::new(source).strip_prefix(&project.directory) {
+                *m = format!("./{}:{}", rel.to_string_lossy().replace('\\', "/"), rest);
+            }
+        }
     }
-    root.insert(y_str("networks"), YamlVal::Mapping(networks));
+}
+
+/// Platforms an image is published for, e.g. `linux/arm64/v8`, from
+/// `docker manifest inspect --verbose`: a list for multi-platform images, a
+/// single entry otherwise. Attestations (`unknown/unknown`) are left out.
+pub fn manifest_platforms(json: &str) -> Vec<String> {
+    let entries = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Array(entries)) => entries,
+        Ok(entry) => vec![entry],
+        Err(_) => Vec::new(),
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let platform = &entry["Descriptor"]["platform"];
+            let os = platform["os"].as_str()?;
+            let arch = platform["architecture"].as_str()?;
+            let mut name = format!("{}/{}", os, arch);
+            if let Some(variant) = platform["variant"].as_str() {
+                name = format!("{}/{}", name, variant);
+            }
+            Some(name).filter(|n| !n.starts_with("unknown/"))
+        })
+        .collect()
+}
+
+/// Whether writing the compose file again recreates `path`.
+pub fn is_generated_path(project: &ProjectConfig, path: &Path) -> bool {
+    path.strip_prefix(&project.directory)
+        .is_ok_and(|rel| GENERATED_PATHS.iter().any(|p| rel == Path::new(p)))
+}
 
-    serde_yaml::to_string(&YamlVal::Mapping(root)).unwrap_or_default()
+/// Host paths bind-mounted into the project's containers, with the service using each.
+pub fn bind_mounts(project: &ProjectConfig) -> Vec<(String, PathBuf)> {
+    let model = ComposeModel::from_project(project);
+    let mut mounts = Vec::new();
+    for (name, service) in &model.services {
+        let Some(YamlVal::Sequence(volumes)) = service.get("volumes") else {
+            continue;
+        };
+        for mount in volumes.iter().filter_map(|v| v.as_str()) {
+            let Some((source, _)) = mount.split_once(':') else {
+                continue;
+            };
+            if named_volume(mount).is_some() || source.starts_with('$') {
+                continue;
+            }
+            mounts.push((name.clone(), Path::new(&project.directory).join(source)));
+        }
+    }
+    mounts
 }
 
 pub fn write_compose_file(project: &ProjectConfig) -> std::io::Result<String> {
+    validate_project_directory(&project.directory)?;
+    let project = &with_placeholders(project);
+
     let dir = Path::new(&project.directory);
     fs::create_dir_all(dir)?;
 
-    let compose = generate_compose(project);
     let path = dir.join("docker-compose.yml");
-    fs::write(&path, &compose)?;
+    fs::write(&path, render_compose_file(project))?;
 
     // Write nginx config if nginx is enabled
     if project.services.get("nginx").is_some_and(|s| s.enabled) {
@@ -356,6 +2271,25 @@ pub fn write_compose_file(project: &ProjectConfig) -> std::io::Result<String> {
     // Write default index.php if directory is empty
     write_default_index(project)?;
 
+    // Register the project database with pgAdmin
+    let pg_enabled = project
+        .services
+        .get("postgresql")
+        .is_some_and(|s| s.enabled);
+    if pg_enabled && project.services.get("pgadmin").is_some_and(|s| s.enabled) {
+        write_pgadmin_servers(project)?;
+    }
+
+    // Write varnish VCL if varnish is enabled
+    if project.services.get("varnish").is_some_and(|s| s.enabled) {
+        write_varnish_config(project)?;
+    }
+
+    // Write the SFTP login if sftp is enabled
+    if project.services.get("sftp").is_some_and(|s| s.enabled) {
+        write_sftp_users(project)?;
+    }
+
     // Write php config if php is enabled
     if project.services.get("php").is_some_and(|s| s.enabled) {
         write_php_config(project)?;
@@ -364,6 +2298,32 @@ pub fn write_compose_file(project: &ProjectConfig) -> std::io::Result<String> {
     Ok(path.to_string_lossy().to_string())
 }
 
+/// `sftp/users.conf`, one `user:password:uid:gid` line for the atmoz/sftp image.
+/// The uid defaults to 1000, the first user on most Linux hosts.
+fn write_sftp_users(project: &ProjectConfig) -> std::io::Result<()> {
+    let Some(svc) = project.services.get("sftp") else {
+        return Ok(());
+    };
+    let (user, password) = sftp_credentials(svc);
+    let uid = svc
+        .settings
+        .get("uid")
+        .map(|u| u.trim())
+        .filter(|u| !u.is_empty() && u.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or("1000");
+
+    let sftp_dir = Path::new(&project.directory).join("sftp");
+    fs::create_dir_all(&sftp_dir)?;
+    let content = format!(
+        "# MANAGED BY DOCKSTACK\n{}:{}:{}:{}\n",
+        user,
+        password.replace([':', '\n'], ""),
+        uid,
+        uid
+    );
+    fs::write(sftp_dir.join("users.conf"), content)
+}
+
 fn write_php_config(project: &ProjectConfig) -> std::io::Result<()> {
     let Some(svc) = project.services.get("php") else {
         return Ok(());
@@ -383,21 +2343,215 @@ fn write_php_config(project: &ProjectConfig) -> std::io::Result<()> {
         }
     }
 
-    let mem_limit = svc
-        .settings
-        .get("memory_limit")
-        .cloned()
-        .unwrap_or_else(|| "256M".to_string());
+    let mem_limit = svc
+        .settings
+        .get("memory_limit")
+        .cloned()
+        .unwrap_or_else(|| "256M".to_string());
+
+    let mut content = MANAGED_HEADER.to_string();
+    content.push_str(&format!("memory_limit = {}\n", mem_limit));
+    content.push_str("upload_max_filesize = 100M\n");
+    content.push_str("post_max_size = 100M\n");
+    content.push_str("max_execution_time = 300\n");
+    content.push_str("display_errors = On\n");
+    content.push_str("error_reporting = E_ALL\n");
+    if let Some(timezone) = timezone(project) {
+        content.push_str(&format!("date.timezone = {}\n", timezone));
+    }
+    if let Some(locale) = locale(project) {
+        // ICU wants the bare locale, without the `.UTF-8` encoding
+        let icu = locale.split(['.', '@']).next().unwrap_or(locale);
+        content.push_str(&format!("intl.default_locale = {}\n", icu));
+    }
+
+    if let Some(host) = mail_catcher(project) {
+        content.push_str("\n; Outgoing mail is captured by the project's mail catcher\n");
+        content.push_str(&format!("SMTP = {}\n", host));
+        content.push_str(&format!("smtp_port = {}\n", MAIL_SMTP_PORT));
+        if svc.version.contains("alpine") {
+            content.push_str(&format!(
+                "sendmail_path = \"/bin/busybox sendmail -t -S {}:{}\"\n",
+                host, MAIL_SMTP_PORT
+            ));
+        } else {
+            // Debian-based images ship no sendmail; mail() needs msmtp or similar
+            content.push_str("; sendmail_path: use SMTP via MAIL_HOST/MAIL_PORT env vars\n");
+        }
+    }
+
+    fs::write(ini_path, content)?;
+    Ok(())
+}
+
+/// Login phpMyAdmin should use: the app user if one is configured, otherwise root.
+fn mysql_credentials(project: &ProjectConfig) -> Option<(String, String)> {
+    let mysql = project.services.get("mysql").filter(|s| s.enabled)?;
+    let env = &mysql.env_vars;
+    match (env.get("MYSQL_USER"), env.get("MYSQL_PASSWORD")) {
+        (Some(user), Some(password)) if !user.is_empty() => Some((user.clone(), password.clone())),
+        _ => Some((
+            "root".to_string(),
+            env.get("MYSQL_ROOT_PASSWORD").cloned().unwrap_or_default(),
+        )),
+    }
+}
+
+/// pgAdmin only imports servers.json when its data volume is first created,
+/// so later edits apply after the pgadmin_data volume is reset.
+fn write_pgadmin_servers(project: &ProjectConfig) -> std::io::Result<()> {
+    // `postgresql` first, then its other instances
+    let mut databases: Vec<(&String, &ServiceConfig)> = sorted(&project.services)
+        .into_iter()
+        .filter(|(name, svc)| svc.enabled && svc.kind(name) == "postgresql")
+        .collect();
+    databases.sort_by_key(|(name, _)| *name != "postgresql");
+    if databases.is_empty() {
+        return Ok(());
+    }
+    let pgadmin_locked = project.services.get("pgadmin").is_some_and(|s| s.is_locked);
+    if pgadmin_locked {
+        return Ok(());
+    }
+
+    let pgadmin_dir = Path::new(&project.directory).join("pgadmin");
+    fs::create_dir_all(&pgadmin_dir)?;
+
+    let servers_path = pgadmin_dir.join("servers.json");
+    if servers_path.exists() {
+        let existing = fs::read_to_string(&servers_path)?;
+        if !existing.contains("MANAGED BY DOCKSTACK") {
+            return Ok(());
+        }
+    }
+
+    let mut servers = serde_json::Map::new();
+    for (i, (name, pg)) in databases.into_iter().enumerate() {
+        let env_or = |key: &str, default: &str| {
+            pg.env_vars
+                .get(key)
+                .filter(|v| !v.is_empty())
+                .cloned()
+                .unwrap_or_else(|| default.to_string())
+        };
+        let label = match name.as_str() {
+            "postgresql" => project.name.clone(),
+            _ => format!("{} ({})", project.name, name),
+        };
+        servers.insert(
+            (i + 1).to_string(),
+            serde_json::json!({
+                "Name": label,
+                "Group": "DockStack",
+                "Host": name,
+                "Port": 5432,
+                "MaintenanceDB": env_or("POSTGRES_DB", "postgres"),
+                "Username": env_or("POSTGRES_USER", "postgres"),
+                "SSLMode": "prefer",
+                "Comment": "MANAGED BY DOCKSTACK"
+            }),
+        );
+    }
+    let servers = serde_json::json!({ "Servers": servers });
+    let content = serde_json::to_string_pretty(&servers).map_err(std::io::Error::other)?;
+    fs::write(servers_path, content)?;
+    Ok(())
+}
+
+/// Hostname of the enabled mail catcher service, if any.
+fn mail_catcher(project: &ProjectConfig) -> Option<&'static str> {
+    Some("mailpit").filter(|name| project.services.get(*name).is_some_and(|s| s.enabled))
+}
+
+/// SMTP settings injected into app containers so captured email needs no setup.
+/// Service-level env vars are applied afterwards and take precedence.
+fn smtp_env(project: &ProjectConfig) -> Vec<(&'static str, String)> {
+    let Some(host) = mail_catcher(project) else {
+        return Vec::new();
+    };
+    vec![
+        ("MAIL_MAILER", "smtp".to_string()),
+        ("MAIL_HOST", host.to_string()),
+        ("MAIL_PORT", MAIL_SMTP_PORT.to_string()),
+        ("SMTP_HOST", host.to_string()),
+        ("SMTP_PORT", MAIL_SMTP_PORT.to_string()),
+        ("MAILER_DSN", format!("smtp://{}:{}", host, MAIL_SMTP_PORT)),
+    ]
+}
+
+/// Whether the project runs Nginx in front of Apache. Needs both enabled, so
+/// a profile leaving one out falls back to the other alone.
+fn nginx_fronts_apache(project: &ProjectConfig) -> bool {
+    let enabled = |name: &str| project.services.get(name).is_some_and(|s| s.enabled);
+    project.web_topology == "nginx-apache" && enabled("nginx") && enabled("apache")
+}
+
+/// The web server Varnish fronts: nginx if enabled, otherwise apache.
+fn varnish_backend(project: &ProjectConfig) -> Option<&'static str> {
+    ["nginx", "apache"]
+        .into_iter()
+        .find(|name| project.services.get(*name).is_some_and(|s| s.enabled))
+}
+
+fn write_varnish_config(project: &ProjectConfig) -> std::io::Result<()> {
+    let Some(svc) = project.services.get("varnish") else {
+        return Ok(());
+    };
+    if svc.is_locked {
+        return Ok(());
+    }
+
+    let varnish_dir = Path::new(&project.directory).join("varnish");
+    fs::create_dir_all(&varnish_dir)?;
+
+    let vcl_path = varnish_dir.join("default.vcl");
+    if vcl_path.exists() {
+        let existing = fs::read_to_string(&vcl_path)?;
+        if !existing.contains("MANAGED BY DOCKSTACK") {
+            return Ok(());
+        }
+    }
+
+    // Without a web server there is nothing to cache; point at a host that fails fast.
+    let backend = varnish_backend(project).unwrap_or("localhost");
+    let vcl = format!(
+        r#"{}vcl 4.1;
+
+backend default {{
+    .host = "{}";
+    .port = "80";
+}}
+
+sub vcl_recv {{
+    # Never cache admin areas or authenticated requests
+    if (req.http.Authorization || req.url ~ "^/(admin|wp-admin|login)") {{
+        return (pass);
+    }}
+    # Static assets are safe to cache regardless of cookies
+    if (req.url ~ "\.(css|js|png|jpe?g|gif|svg|ico|woff2?)(\?.*)?$") {{
+        unset req.http.Cookie;
+    }}
+}}
 
-    let mut content = MANAGED_HEADER.to_string();
-    content.push_str(&format!("memory_limit = {}\n", mem_limit));
-    content.push_str("upload_max_filesize = 100M\n");
-    content.push_str("post_max_size = 100M\n");
-    content.push_str("max_execution_time = 300\n");
-    content.push_str("display_errors = On\n");
-    content.push_str("error_reporting = E_ALL\n");
+sub vcl_backend_response {{
+    if (beresp.ttl <= 0s) {{
+        set beresp.ttl = 2m;
+    }}
+}}
 
-    fs::write(ini_path, content)?;
+sub vcl_deliver {{
+    # Show hits/misses in the browser's network tab
+    if (obj.hits > 0) {{
+        set resp.http.X-Cache = "HIT";
+    }} else {{
+        set resp.http.X-Cache = "MISS";
+    }}
+}}
+"#,
+        MANAGED_HEADER, backend
+    );
+
+    fs::write(vcl_path, vcl)?;
     Ok(())
 }
 
@@ -421,12 +2575,18 @@ fn write_nginx_config(project: &ProjectConfig) -> std::io::Result<()> {
     }
 
     let safe_domain = project.domain.chars().filter(|c| c.is_alphanumeric() || *c == '.' || *c == '-').collect::<String>();
+    // Redirects go to the host port, which differs from 443 when remapped
+    let https_suffix = match https_port(project) {
+        443 => String::new(),
+        port => format!(":{}", port),
+    };
+    let root = document_root(project, "/usr/share/nginx/html");
     let config = if project.ssl_enabled {
         format!(
             r#"{}server {{
     listen 80;
     server_name {};
-    return 301 https://$server_name$request_uri;
+    return 301 https://$server_name{}$request_uri;
 }}
 
 server {{
@@ -436,22 +2596,14 @@ server {{
     ssl_certificate /etc/nginx/certs/server.crt;
     ssl_certificate_key /etc/nginx/certs/server.key;
 
-    root /usr/share/nginx/html;
-    index index.php index.html;
-
-    location / {{
-        try_files $uri $uri/ /index.php?$query_string;
-    }}
-
-    location ~ \.php$ {{
-        fastcgi_pass php:9000;
-        fastcgi_index index.php;
-        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
-        include fastcgi_params;
-    }}
-}}
-"#,
-            MANAGED_HEADER, safe_domain, safe_domain
+{}}}
+{}"#,
+            MANAGED_HEADER,
+            safe_domain,
+            https_suffix,
+            safe_domain,
+            nginx_site(project, &root),
+            php_host_servers(project, &root)
         )
     } else {
         format!(
@@ -459,22 +2611,12 @@ server {{
     listen 80;
     server_name {};
 
-    root /usr/share/nginx/html;
-    index index.php index.html;
-
-    location / {{
-        try_files $uri $uri/ /index.php?$query_string;
-    }}
-
-    location ~ \.php$ {{
-        fastcgi_pass php:9000;
-        fastcgi_index index.php;
-        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
-        include fastcgi_params;
-    }}
-}}
-"#,
-            MANAGED_HEADER, safe_domain
+{}}}
+{}"#,
+            MANAGED_HEADER,
+            safe_domain,
+            nginx_site(project, &root),
+            php_host_servers(project, &root)
         )
     };
 
@@ -512,7 +2654,7 @@ ServerName {}
         MANAGED_HEADER, safe_domain
     );
     config.push_str(
-        r#"
+        &r#"
 LoadModule mpm_event_module modules/mod_mpm_event.so
 LoadModule authz_core_module modules/mod_authz_core.so
 LoadModule authz_host_module modules/mod_authz_host.so
@@ -528,14 +2670,14 @@ User daemon
 Group daemon
 
 ServerAdmin you@example.com
-DocumentRoot "/usr/local/apache2/htdocs"
+DocumentRoot "{document_root}"
 
 <Directory />
     AllowOverride none
     Require all denied
 </Directory>
 
-<Directory "/usr/local/apache2/htdocs">
+<Directory "{document_root}">
     Options Indexes FollowSymLinks
     AllowOverride All
     Require all granted
@@ -558,14 +2700,135 @@ DocumentRoot "/usr/local/apache2/htdocs"
 <FilesMatch \.php$>
     SetHandler "proxy:fcgi://php:9000"
 </FilesMatch>
-"#,
+"#
+        .replace(
+            "{document_root}",
+            &document_root(project, "/usr/local/apache2/htdocs"),
+        ),
     );
 
     fs::write(config_path, config)?;
     Ok(())
 }
 
+/// PHP routes whose service is enabled, with unsafe paths and hosts left out.
+fn php_routes(project: &ProjectConfig) -> impl Iterator<Item = &PhpRoute> {
+    let safe = |text: &str| {
+        text.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '-' | '_'))
+    };
+    project.php_routes.iter().filter(move |route| {
+        project
+            .services
+            .get(&route.service)
+            .is_some_and(|s| s.enabled && s.kind(&route.service) == "php")
+            && safe(&route.service)
+            && safe(&route.path)
+            && safe(&route.host)
+    })
+}
+
+/// nginx `location` passing `.php` requests to the PHP-FPM `service`.
+fn fastcgi_location(service: &str, indent: &str) -> String {
+    format!(
+        "{i}location ~ \\.php$ {{\n\
+         {i}    fastcgi_pass {}:9000;\n\
+         {i}    fastcgi_index index.php;\n\
+         {i}    fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;\n\
+         {i}    include fastcgi_params;\n\
+         {i}}}\n",
+        service,
+        i = indent
+    )
+}
+
+/// PHP `location` blocks of the project domain: path routes go to their
+/// service, everything else to `php`.
+/// Body of the main `server` block: the site served from `root` through PHP-FPM,
+/// or every request passed on to Apache when Nginx fronts it.
+fn nginx_site(project: &ProjectConfig, root: &str) -> String {
+    if nginx_fronts_apache(project) {
+        return r#"    location / {
+        proxy_pass http://apache:80;
+        proxy_set_header Host $host;
+        proxy_set_header X-Real-IP $remote_addr;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+        proxy_set_header X-Forwarded-Proto $scheme;
+    }
+"#
+        .to_string();
+    }
+    format!(
+        "    root {};\n    index index.php index.html;\n\n    location / {{\n        \
+         try_files $uri $uri/ /index.php?$query_string;\n    }}\n\n{}",
+        root,
+        php_locations(project)
+    )
+}
+
+fn php_locations(project: &ProjectConfig) -> String {
+    let mut locations = String::new();
+    let path_routes = php_routes(project).filter(|r| r.host.trim().is_empty());
+    for route in path_routes {
+        let path = format!("/{}/", route.path.trim().trim_matches('/'));
+        if path == "//" {
+            continue;
+        }
+        locations.push_str(&format!(
+            "    location ^~ {} {{\n        try_files $uri $uri/ {}index.php?$query_string;\n",
+            path, path
+        ));
+        locations.push_str(&fastcgi_location(&route.service, "        "));
+        locations.push_str("    }\n\n");
+    }
+    locations.push_str(&fastcgi_location("php", "    "));
+    locations
+}
+
+/// Extra `server` blocks for PHP routes that take over a whole host name.
+fn php_host_servers(project: &ProjectConfig, root: &str) -> String {
+    let listen = if project.ssl_enabled {
+        "listen 80;\n    listen 443 ssl;\n\n    \
+         ssl_certificate /etc/nginx/certs/server.crt;\n    \
+         ssl_certificate_key /etc/nginx/certs/server.key;"
+    } else {
+        "listen 80;"
+    };
+    php_routes(project)
+        .filter(|r| !r.host.trim().is_empty())
+        .map(|route| {
+            format!(
+                "\nserver {{\n    {}\n    server_name {};\n\n    root {};\n    \
+                 index index.php index.html;\n\n    location / {{\n        \
+                 try_files $uri $uri/ /index.php?$query_string;\n    }}\n\n{}}}\n",
+                listen,
+                route.host.trim(),
+                root,
+                fastcgi_location(&route.service, "    ")
+            )
+        })
+        .collect()
+}
+
+/// `base` extended by the project's web root, e.g. `/usr/share/nginx/html/public`.
+/// A web root that could leave the code directory is ignored.
+fn document_root(project: &ProjectConfig, base: &str) -> String {
+    let web_root = project.web_root.trim().trim_matches('/');
+    let safe = Path::new(web_root)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        && !web_root.contains([';', '"', '{', '}', ' ']);
+    if web_root.is_empty() || !safe {
+        return base.to_string();
+    }
+    format!("{}/{}", base, web_root)
+}
+
 fn write_default_index(project: &ProjectConfig) -> std::io::Result<()> {
+    // An existing codebase brings its own entry point
+    if project.code_in_directory {
+        return Ok(());
+    }
     let www_dir = Path::new(&project.directory).join("www");
     fs::create_dir_all(&www_dir)?;
 
@@ -606,6 +2869,104 @@ fn y_str(s: &str) -> YamlVal {
     YamlVal::String(s.to_string())
 }
 
+fn project_labels(project: &ProjectConfig) -> YamlVal {
+    let mut labels = YamlMap::new();
+    labels.insert(y_str(PROJECT_LABEL), y_str(&project.id));
+    YamlVal::Mapping(labels)
+}
+
+/// Networks a service joins: the project network plus any external ones.
+fn service_networks(project: &ProjectConfig) -> Vec<YamlVal> {
+    let network_name = network_name(project);
+    std::iter::once(network_name.as_str())
+        .chain(external_networks(project))
+        .map(y_str)
+        .collect()
+}
+
+fn external_networks(project: &ProjectConfig) -> impl Iterator<Item = &str> {
+    project
+        .network
+        .external
+        .iter()
+        .map(|n| n.trim())
+        .filter(|n| !n.is_empty())
+}
+
+/// Whether `s` is an IPv4 or IPv6 CIDR block such as `172.28.0.0/16`.
+pub fn is_valid_cidr(s: &str) -> bool {
+    let Some((addr, prefix)) = s.trim().split_once('/') else {
+        return false;
+    };
+    let Ok(prefix) = prefix.parse::<u8>() else {
+        return false;
+    };
+    match addr.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => prefix <= 32,
+        Ok(std::net::IpAddr::V6(_)) => prefix <= 128,
+        Err(_) => false,
+    }
+}
+
+/// Publish `ports` unless the service is internal-only.
+fn insert_ports(s: &mut YamlMap, svc: &ServiceConfig, ports: Vec<YamlVal>) {
+    if !svc.internal {
+        s.insert(y_str("ports"), YamlVal::Sequence(ports));
+    }
+}
+
+/// A `host:container` port entry. Bound to every interface explicitly in LAN mode,
+/// otherwise to localhost unless the project or service opts out.
+fn port_mapping(
+    project: &ProjectConfig,
+    svc: &ServiceConfig,
+    host: u16,
+    container: u16,
+) -> YamlVal {
+    if project.expose_on_lan {
+        YamlVal::String(format!("0.0.0.0:{}:{}", host, container))
+    } else if project.bind_localhost && !svc.bind_all_interfaces {
+        YamlVal::String(format!("127.0.0.1:{}:{}", host, container))
+    } else {
+        YamlVal::String(format!("{}:{}", host, container))
+    }
+}
+
+fn volume_conf(project: &ProjectConfig) -> YamlVal {
+    let mut conf = YamlMap::new();
+    conf.insert(y_str("labels"), project_labels(project));
+    YamlVal::Mapping(conf)
+}
+
+/// Container command that installs `requirements` (when present) before running `command`.
+fn install_then_run(requirements: &str, command: &str) -> YamlVal {
+    let script = format!(
+        "if [ -f {req} ]; then pip install -r {req}; fi; exec {cmd}",
+        req = requirements,
+        cmd = command
+    );
+    YamlVal::Sequence(vec![y_str("sh"), y_str("-c"), y_str(&script)])
+}
+
+/// `deploy.resources.reservations.devices` entry granting all NVIDIA GPUs.
+fn gpu_deploy() -> YamlVal {
+    let mut device = YamlMap::new();
+    device.insert(y_str("driver"), y_str("nvidia"));
+    device.insert(y_str("count"), y_str("all"));
+    device.insert(y_str("capabilities"), YamlVal::Sequence(vec![y_str("gpu")]));
+
+    let mut reservations = YamlMap::new();
+    reservations.insert(
+        y_str("devices"),
+        YamlVal::Sequence(vec![YamlVal::Mapping(device)]),
+    );
+    let mut resources = YamlMap::new();
+    resources.insert(y_str("reservations"), YamlVal::Mapping(reservations));
+    let mut deploy = YamlMap::new();
+    deploy.insert(y_str("resources"), YamlVal::Mapping(resources));
+    YamlVal::Mapping(deploy)
+}
+
 fn healthcheck(test: &str, interval: u32, timeout: u32, retries: u32) -> YamlVal {
     let mut hc = YamlMap::new();
     hc.insert(
@@ -620,3 +2981,969 @@ fn healthcheck(test: &str, interval: u32, timeout: u32, retries: u32) -> YamlVal
     );
     YamlVal::Mapping(hc)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::config::{BranchEnv, ComposeImport, EnvProfile, PhpRoute, ProfileOverride};
+    use std::io;
+    use std::path::PathBuf;
+
+    /// Project with a fixed id and directory and every builtin service disabled.
+    pub(crate) fn project(enabled: &[&str]) -> ProjectConfig {
+        let mut project = ProjectConfig {
+            id: "golden".to_string(),
+            name: "Golden".to_string(),
+            directory: "/srv/golden".to_string(),
+            // Keep the output independent of the host's SELinux mode
+            selinux_relabel: "off".to_string(),
+            ..ProjectConfig::default()
+        };
+        for (name, svc) in project.services.iter_mut() {
+            svc.enabled = enabled.contains(&name.as_str());
+        }
+        project
+    }
+
+    pub(crate) fn custom_service() -> ServiceConfig {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("MINIO_ROOT_USER".to_string(), "dockstack".to_string());
+        env_vars.insert("MINIO_ROOT_PASSWORD".to_string(), "dockstack".to_string());
+        let mut settings = HashMap::new();
+        settings.insert("container_port".to_string(), "9001".to_string());
+        settings.insert(
+            "command".to_string(),
+            "server /data --console-address :9001".to_string(),
+        );
+        settings.insert(
+            "volumes".to_string(),
+            "minio_data:/data\n./minio/config:/root/.minio:ro\n".to_string(),
+        );
+        ServiceConfig {
+            enabled: true,
+            port: 9000,
+            version: "RELEASE.2024-01-01T00-00-00Z".to_string(),
+            image: Some("minio/minio".to_string()),
+            is_custom: true,
+            env_vars,
+            settings,
+            ..ServiceConfig::default()
+        }
+    }
+
+    /// Compare the generated compose file with `tests/golden/compose/<name>.yml`.
+    /// Run with `UPDATE_GOLDEN=1` to rewrite the file after an intended change.
+    fn assert_golden(name: &str, project: &ProjectConfig) -> io::Result<()> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden/compose")
+            .join(format!("{}.yml", name));
+        let actual = generate_compose(project);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            return fs::write(&path, actual);
+        }
+        let expected = fs::read_to_string(&path)?;
+        assert!(
+            actual == expected,
+            "{} differs from the generated compose file; rerun with UPDATE_GOLDEN=1 \
+             if the change is intended.\n--- generated ---\n{}",
+            path.display(),
+            actual
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn php_nginx_mysql() -> io::Result<()> {
+        assert_golden(
+            "php_nginx_mysql",
+            &project(&["php", "nginx", "mysql", "phpmyadmin", "redis"]),
+        )
+    }
+
+    #[test]
+    fn php_nginx_ssl() -> io::Result<()> {
+        let mut project = project(&["php", "nginx", "mysql"]);
+        project.ssl_enabled = true;
+        assert_golden("php_nginx_ssl", &project)
+    }
+
+    #[test]
+    fn php_queue_worker() -> io::Result<()> {
+        let mut project = project(&["php", "apache", "mailpit"]);
+        if let Some(php) = project.services.get_mut("php") {
+            php.settings
+                .insert("queue_worker".to_string(), "true".to_string());
+        }
+        assert_golden("php_queue_worker", &project)
+    }
+
+    #[test]
+    fn postgres_admin_tools() -> io::Result<()> {
+        assert_golden(
+            "postgres_admin_tools",
+            &project(&["postgresql", "pgadmin", "adminer"]),
+        )
+    }
+
+    #[test]
+    fn phpmyadmin_without_mysql() -> io::Result<()> {
+        assert_golden("phpmyadmin_without_mysql", &project(&["phpmyadmin"]))
+    }
+
+    #[test]
+    fn python_data_science() -> io::Result<()> {
+        let mut project = project(&["python", "jupyter", "postgresql", "redis"]);
+        if let Some(jupyter) = project.services.get_mut("jupyter") {
+            jupyter.gpu = true;
+        }
+        assert_golden("python_data_science", &project)
+    }
+
+    #[test]
+    fn varnish_cache() -> io::Result<()> {
+        assert_golden("varnish_cache", &project(&["nginx", "php", "varnish"]))
+    }
+
+    #[test]
+    fn custom_service_with_mail() -> io::Result<()> {
+        let mut project = project(&["mailpit"]);
+        project
+            .services
+            .insert("minio".to_string(), custom_service());
+        assert_golden("custom_service_with_mail", &project)
+    }
+
+    #[test]
+    fn mailpit_publishes_only_a_valid_smtp_port() {
+        let mut project = project(&["mailpit"]);
+        let mut smtp_ports = |value: &str| {
+            if let Some(mailpit) = project.services.get_mut("mailpit") {
+                mailpit
+                    .settings
+                    .insert("smtp_port".to_string(), value.to_string());
+            }
+            let model = ComposeModel::from_project(&project);
+            match &model.services["mailpit"]["ports"] {
+                YamlVal::Sequence(ports) => ports[1..].to_vec(),
+                _ => Vec::new(),
+            }
+        };
+        assert_eq!(smtp_ports(""), [y_str("127.0.0.1:1025:1025")]);
+        assert_eq!(smtp_ports("2525"), [y_str("127.0.0.1:2525:1025")]);
+        assert!(smtp_ports("25x").is_empty());
+        assert!(smtp_ports("0").is_empty());
+    }
+
+    #[test]
+    fn custom_service_built_from_context() -> io::Result<()> {
+        let mut project = project(&[]);
+        let mut api = custom_service();
+        api.image = None;
+        api.settings
+            .insert("build".to_string(), "./services/api".to_string());
+        project.services.insert("api".to_string(), api);
+        let mut worker = custom_service();
+        worker.image = Some("golden/worker".to_string());
+        worker.version = "dev".to_string();
+        worker
+            .settings
+            .insert("build".to_string(), "./services/worker".to_string());
+        project.services.insert("worker".to_string(), worker);
+
+        assert_eq!(build_services(&project), ["api", "worker"]);
+        assert_golden("custom_service_built_from_context", &project)
+    }
+
+    #[test]
+    fn build_context_cannot_leave_the_project() {
+        let mut svc = custom_service();
+        for (context, expected) in [
+            ("./services/api", Some("./services/api")),
+            ("services/api", Some("services/api")),
+            ("../../etc", None),
+            ("services/../../etc", None),
+            ("/etc", None),
+        ] {
+            svc.settings
+                .insert("build".to_string(), context.to_string());
+            assert_eq!(build_context(&svc), expected, "{}", context);
+        }
+    }
+
+    #[test]
+    fn custom_image_keeps_explicit_tag_or_digest() {
+        assert_eq!(
+            image_reference("minio/minio", "latest"),
+            "minio/minio:latest"
+        );
+        assert_eq!(
+            image_reference("minio/minio:edge", "latest"),
+            "minio/minio:edge"
+        );
+        assert_eq!(
+            image_reference("localhost:5000/app", "1.2"),
+            "localhost:5000/app:1.2"
+        );
+        assert_eq!(
+            image_reference("app@sha256:abc", "latest"),
+            "app@sha256:abc"
+        );
+    }
+
+    #[test]
+    fn custom_image_is_pulled_from_its_registry() {
+        assert_eq!(image_host("ghcr.io/acme/api:1.2"), Some("ghcr.io"));
+        assert_eq!(image_host("localhost:5000/app"), Some("localhost:5000"));
+        assert_eq!(image_host("minio/minio"), None);
+        assert_eq!(image_host("mysql:8.0"), None);
+        assert_eq!(registry_image("ghcr.io", "acme/api"), "ghcr.io/acme/api");
+        assert_eq!(
+            registry_image("ghcr.io", "registry.corp/api"),
+            "registry.corp/api"
+        );
+        assert_eq!(registry_image("docker.io", "acme/api"), "acme/api");
+        assert_eq!(registry_image("", "acme/api"), "acme/api");
+
+        let mut project = project(&[]);
+        let mut svc = custom_service();
+        svc.image = Some("acme/api".to_string());
+        svc.version = "1.2".to_string();
+        svc.registry = "registry.corp:5000".to_string();
+        project.services.insert("app".to_string(), svc);
+        let model = ComposeModel::from_project(&project);
+        assert_eq!(
+            model.services["app"]["image"].as_str(),
+            Some("registry.corp:5000/acme/api:1.2")
+        );
+    }
+
+    #[test]
+    fn docker_hub_images_are_pulled_through_the_mirror() {
+        assert_eq!(
+            mirror_image("harbor.corp/dockerhub/", "mysql:8.0"),
+            "harbor.corp/dockerhub/library/mysql:8.0"
+        );
+        assert_eq!(
+            mirror_image("harbor.corp/dockerhub", "minio/minio:latest"),
+            "harbor.corp/dockerhub/minio/minio:latest"
+        );
+        assert_eq!(
+            mirror_image("harbor.corp/dockerhub", "ghcr.io/acme/api"),
+            "ghcr.io/acme/api"
+        );
+
+        let mut project = project(&["mysql"]);
+        project.image_mirror = "mirror.corp:5000".to_string();
+        let model = ComposeModel::from_project(&project);
+        let image = model.services["mysql"]["image"]
+            .as_str()
+            .unwrap_or_default();
+        assert!(
+            image.starts_with("mirror.corp:5000/library/mysql:"),
+            "{}",
+            image
+        );
+    }
+
+    #[test]
+    fn custom_volumes_declare_named_volumes_only() {
+        assert_eq!(named_volume("minio_data:/data"), Some("minio_data"));
+        assert_eq!(named_volume("./data:/data"), None);
+        assert_eq!(named_volume("/srv/data:/data:ro"), None);
+        assert_eq!(named_volume("${HOME}/data:/data"), None);
+        assert_eq!(named_volume("/data"), None);
+    }
+
+    #[test]
+    fn plugin_template_becomes_a_managed_service() -> Result<(), toml::de::Error> {
+        let plugin: ServicePlugin = toml::from_str(
+            r#"
+            id = "couchdb"
+            name = "CouchDB"
+            category = "database"
+            version = "3"
+            port = 5984
+            template = """
+            image: couchdb:{{version}}
+            environment:
+              COUCHDB_PASSWORD: "{{setting.password}}"
+            volumes:
+              - couchdb_data:/opt/couchdb/data
+            """
+
+            [env]
+            COUCHDB_USER = "admin"
+
+            [[settings]]
+            key = "password"
+            label = "Admin password"
+            default = "secret"
+            "#,
+        )?;
+        let project = project(&["mysql"]);
+        let mut svc = plugin.default_config();
+        svc.port = 15984;
+        let mut model = ComposeModel::from_project(&project);
+        add_plugin(&mut model, &project, "couchdb", &svc, &plugin);
+        let couchdb = &model.services["couchdb"];
+        assert_eq!(couchdb["image"], y_str("couchdb:3"));
+        assert_eq!(couchdb["environment"]["COUCHDB_PASSWORD"], y_str("secret"));
+        assert_eq!(couchdb["environment"]["COUCHDB_USER"], y_str("admin"));
+        assert_eq!(
+            couchdb["container_name"],
+            y_str(&container_name(&project, "couchdb"))
+        );
+        assert!(model.volumes.contains_key("couchdb_data"));
+        assert_eq!(
+            couchdb["ports"][0],
+            port_mapping(&project, &svc, 15984, 5984)
+        );
+
+        let broken = ServicePlugin {
+            template: "image: couchdb:{{tag}}".to_string(),
+            ..plugin
+        };
+        assert!(broken.render("Golden", "couchdb", &svc).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn platform_is_emitted_per_service() {
+        let mut project = project(&["mysql", "redis"]);
+        if let Some(mysql) = project.services.get_mut("mysql") {
+            mysql.platform = "linux/amd64".to_string();
+        }
+        let model = ComposeModel::from_project(&project);
+        assert_eq!(
+            model.services["mysql"]["platform"].as_str(),
+            Some("linux/amd64")
+        );
+        assert!(!model.services["redis"].contains_key("platform"));
+
+        let list = r#"[
+            {"Descriptor": {"platform": {"architecture": "amd64", "os": "linux"}}},
+            {"Descriptor": {"platform": {"architecture": "arm64", "os": "linux", "variant": "v8"}}},
+            {"Descriptor": {"platform": {"architecture": "unknown", "os": "unknown"}}}
+        ]"#;
+        assert_eq!(manifest_platforms(list), ["linux/amd64", "linux/arm64/v8"]);
+        let single = r#"{"Descriptor": {"platform": {"architecture": "amd64", "os": "linux"}}}"#;
+        assert_eq!(manifest_platforms(single), ["linux/amd64"]);
+        assert!(manifest_platforms("no such manifest").is_empty());
+    }
+
+    #[test]
+    fn network_options() -> io::Result<()> {
+        let mut project = project(&["nginx", "mysql", "redis"]);
+        project.expose_on_lan = true;
+        project.network.subnet = "172.28.0.0/16".to_string();
+        project.network.ipv6 = true;
+        project.network.ipv6_subnet = "fd00:28::/64".to_string();
+        project.network.external = vec!["shared_proxy".to_string()];
+        if let Some(redis) = project.services.get_mut("redis") {
+            redis.internal = true;
+        }
+        if let Some(mysql) = project.services.get_mut("mysql") {
+            mysql.restart_policy = "always".to_string();
+        }
+        assert_golden("network_options", &project)
+    }
+
+    #[test]
+    fn every_service() -> io::Result<()> {
+        let mut project = project(&[]);
+        for svc in project.services.values_mut() {
+            svc.enabled = true;
+        }
+        project.ssl_enabled = true;
+        project
+            .services
+            .insert("minio".to_string(), custom_service());
+        assert_golden("every_service", &project)
+    }
+
+    #[test]
+    fn output_does_not_depend_on_hash_order() {
+        let mut project = project(&[]);
+        for svc in project.services.values_mut() {
+            svc.enabled = true;
+        }
+        let expected = generate_compose(&project);
+        for _ in 0..8 {
+            // Re-collecting gives each map a fresh random hasher and iteration order
+            project.services = project
+                .services
+                .into_iter()
+                .map(|(name, mut svc)| {
+                    svc.env_vars = svc.env_vars.into_iter().collect();
+                    (name, svc)
+                })
+                .collect();
+            assert_eq!(generate_compose(&project), expected);
+        }
+    }
+
+    #[test]
+    fn validation_points_at_the_offending_line() {
+        let yaml = generate_compose(&project(&["nginx", "redis"]));
+        let issue = ComposeValidation::new(
+            "docker-compose.yml",
+            "validating docker-compose.yml: services.redis.ports.0 must be a string or number\n",
+            yaml.as_str(),
+        );
+        let Some(line) = issue.line else {
+            panic!("no line found for {:?}", issue.message);
+        };
+        assert_eq!(yaml.lines().nth(line - 1), Some("    ports:"));
+        assert!(issue.excerpt.iter().any(|(n, _)| *n == line));
+
+        let parse = ComposeValidation::new("f", "yaml: line 7: did not find expected key", &yaml);
+        assert_eq!(parse.line, Some(7));
+        assert_eq!(parse.excerpt.first().map(|(n, _)| *n), Some(4));
+    }
+
+    #[test]
+    fn named_volumes_follow_enabled_services() {
+        let project = project(&["postgresql", "mysql", "nginx"]);
+        assert_eq!(named_volumes(&project), ["mysql_data", "postgres_data"]);
+    }
+
+    #[test]
+    fn compose_invocation_targets_the_project_file() {
+        let project = project(&[]);
+        let compose_file = Path::new("/srv/golden").join("docker-compose.yml");
+        let compose_file = compose_file.to_string_lossy();
+        assert_eq!(
+            compose_invocation(&project, true),
+            ["docker", "compose", "-p", "golden", "-f", &compose_file]
+        );
+        assert_eq!(
+            compose_invocation(&project, false),
+            ["docker-compose", "-p", "golden", "-f", &compose_file]
+        );
+    }
+
+    #[test]
+    fn code_mount_tuning_and_mutagen_sessions() {
+        let mut project = project(&["php", "nginx"]);
+        if let Some(php) = project.services.get_mut("php") {
+            php.mount_consistency = "cached".to_string();
+            php.volume_overlays = vec!["vendor".to_string(), "../etc".to_string()];
+        }
+        let model = ComposeModel::from_project(&project);
+        let mounts = |service: &str| -> Vec<String> {
+            match model.services[service].get("volumes") {
+                Some(YamlVal::Sequence(m)) => m
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+        let php = mounts("php");
+        assert!(php.contains(&"/srv/golden/www:/var/www/html:cached".to_string()));
+        assert!(php.contains(&"php_vendor:/var/www/html/vendor".to_string()));
+        assert!(!php.iter().any(|m| m.contains("etc:")));
+        assert!(model.volumes.contains_key("php_vendor"));
+        assert_eq!(code_mount_services(&project), ["nginx", "php"]);
+        assert!(sync_sessions(&project).is_empty());
+
+        project.file_sync = "mutagen".to_string();
+        let model = ComposeModel::from_project(&project);
+        assert!(model.volumes.contains_key("www_sync"));
+        assert_eq!(
+            sync_sessions(&project),
+            [SyncSession {
+                name: "dockstack-golden-www".to_string(),
+                host: Path::new("/srv/golden").join("www"),
+                container: "dockstack_golden_nginx".to_string(),
+                target: "/usr/share/nginx/html".to_string(),
+                ignores: vec!["vendor".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn imported_images_split_the_tag_off_the_last_segment() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dockstack-import-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let file = dir.join("docker-compose.yml");
+        fs::write(
+            &file,
+            "services:\n  app:\n    image: localhost:5000/app:1\n  cache:\n    \
+             image: localhost:5000/redis\n  db:\n    image: mysql:8.0\n",
+        )?;
+        let import = ComposeImport::read(&file);
+        fs::remove_dir_all(&dir)?;
+
+        let images: Vec<_> = import
+            .map_err(io::Error::other)?
+            .services
+            .into_iter()
+            .map(|(_, svc)| (svc.image.unwrap_or_default(), svc.version))
+            .collect();
+        let image = |name: &str, version: &str| (name.to_string(), version.to_string());
+        assert_eq!(
+            images,
+            [
+                image("localhost:5000/app", "1"),
+                image("localhost:5000/redis", "latest"),
+                image("mysql", "8.0"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn branch_env_applies_on_matching_branch() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dockstack-branch-{}", std::process::id()));
+        fs::create_dir_all(dir.join(".git"))?;
+        fs::write(dir.join(".git/HEAD"), "ref: refs/heads/feature/login\n")?;
+        let mut project = project(&["mysql", "nginx"]);
+        project.directory = dir.to_string_lossy().to_string();
+        project.branch_env = vec![
+            BranchEnv {
+                branch: "feature/*".to_string(),
+                service: "mysql".to_string(),
+                vars: "MYSQL_DATABASE=login\n".to_string(),
+            },
+            BranchEnv {
+                branch: "main".to_string(),
+                service: "nginx".to_string(),
+                vars: "NGINX_HOST=main".to_string(),
+            },
+        ];
+        let branch = project.git_branch();
+        let model = ComposeModel::from_project(&project);
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(branch.as_deref(), Some("feature/login"));
+        let env = |service: &str| model.services[service].get("environment").cloned();
+        let mysql = env("mysql").and_then(|e| e.get("MYSQL_DATABASE").cloned());
+        assert_eq!(mysql, Some(y_str("login")));
+        assert_eq!(env("nginx"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn active_profile_sets_services_ports_env_and_names() {
+        let mut project = project(&["mysql", "nginx", "redis"]);
+        project.profiles = vec![EnvProfile {
+            name: "Staging".to_string(),
+            services: vec!["mysql".to_string(), "nginx".to_string()],
+            overrides: vec![ProfileOverride {
+                service: "mysql".to_string(),
+                port: 3307,
+                vars: "MYSQL_DATABASE=staging".to_string(),
+            }],
+        }];
+        let base = ComposeModel::from_project(&project);
+        assert!(base.services.contains_key("redis"));
+
+        project.active_profile = "Staging".to_string();
+        let model = ComposeModel::from_project(&project);
+        assert!(!model.services.contains_key("redis"));
+        let mysql = &model.services["mysql"];
+        assert_eq!(
+            mysql.get("container_name"),
+            Some(&y_str("dockstack_golden_staging_mysql"))
+        );
+        assert_eq!(mysql["ports"][0], y_str("127.0.0.1:3307:3306"));
+        assert_eq!(mysql["environment"]["MYSQL_DATABASE"], y_str("staging"));
+    }
+
+    #[test]
+    fn second_php_instance_serves_routed_paths_and_hosts() {
+        let mut project = project(&["nginx", "php"]);
+        let php74 = project.add_instance("php", "7.4-fpm");
+        assert_eq!(php74.as_deref(), Some("php74"));
+        project.php_routes = vec![
+            PhpRoute {
+                service: "php74".to_string(),
+                path: "legacy".to_string(),
+                host: String::new(),
+            },
+            PhpRoute {
+                service: "php74".to_string(),
+                path: String::new(),
+                host: "old.golden.test".to_string(),
+            },
+        ];
+        let model = ComposeModel::from_project(&project);
+        let php = &model.services["php74"];
+        assert_eq!(php["image"], y_str("php:7.4-fpm"));
+        assert_eq!(php["container_name"], y_str("dockstack_golden_php74"));
+
+        let locations = php_locations(&project);
+        assert!(locations.contains("location ^~ /legacy/ {"));
+        assert!(locations.contains("fastcgi_pass php74:9000;"));
+        assert!(locations.contains("\n    location ~ \\.php$ {\n        fastcgi_pass php:9000;"));
+        let servers = php_host_servers(&project, "/usr/share/nginx/html");
+        assert!(servers.contains("server_name old.golden.test;"));
+        assert!(servers.contains("fastcgi_pass php74:9000;"));
+    }
+
+    #[test]
+    fn named_instances_get_their_own_container_port_and_volume() {
+        let mut project = project(&["postgresql"]);
+        let analytics = project.add_named_instance("postgresql", "Postgres Analytics");
+        assert_eq!(analytics.as_deref(), Some("postgres-analytics"));
+        assert_eq!(project.add_named_instance("nginx", "proxy-2"), None);
+        assert_eq!(
+            project.add_named_instance("php", "PHP Worker").as_deref(),
+            Some("php-worker-2")
+        );
+        if let Some(svc) = project.services.get_mut("postgres-analytics") {
+            svc.env_vars
+                .insert("POSTGRES_DB".to_string(), "analytics".to_string());
+        }
+
+        let model = ComposeModel::from_project(&project);
+        let main = &model.services["postgresql"];
+        let other = &model.services["postgres-analytics"];
+        assert_eq!(
+            other["container_name"],
+            y_str("dockstack_golden_postgres-analytics")
+        );
+        assert_ne!(main["ports"], other["ports"]);
+        assert_eq!(
+            other["volumes"][0],
+            y_str("postgres-analytics_data:/var/lib/postgresql/data")
+        );
+        assert_eq!(
+            main["volumes"][0],
+            y_str("postgres_data:/var/lib/postgresql/data")
+        );
+        assert_eq!(other["environment"]["POSTGRES_DB"], y_str("analytics"));
+        assert!(model.volumes.contains_key("postgres-analytics_data"));
+    }
+
+    #[test]
+    fn python_falls_back_to_the_default_command_on_its_container_port() {
+        let mut project = project(&["python"]);
+        if let Some(python) = project.services.get_mut("python") {
+            python
+                .settings
+                .insert("command".to_string(), "  ".to_string());
+            python
+                .settings
+                .insert("container_port".to_string(), "5000".to_string());
+        }
+
+        let model = ComposeModel::from_project(&project);
+        let python = &model.services["python"];
+        let script = python["command"][2].as_str().unwrap_or_default();
+        assert!(script.ends_with("; exec python -m http.server 5000"));
+        let port = python["ports"][0].as_str().unwrap_or_default();
+        assert!(port.ends_with(":5000"));
+    }
+
+    #[test]
+    fn renamed_service_keeps_its_links_and_moves_its_volumes() {
+        let mut project = project(&["postgresql"]);
+        project
+            .services
+            .insert("minio".to_string(), custom_service());
+        project.branch_env.push(BranchEnv {
+            branch: "main".to_string(),
+            service: "minio".to_string(),
+            vars: String::new(),
+        });
+        assert_eq!(project.rename_service("postgresql", "db"), None);
+        assert_eq!(project.rename_service("minio", "postgresql"), None);
+        assert_eq!(project.rename_service("minio", PHP_WORKER), None);
+
+        let before = service_volumes(&project, "minio");
+        assert_eq!(
+            project.rename_service("minio", "Object Store").as_deref(),
+            Some("object-store")
+        );
+        assert!(!project.services.contains_key("minio"));
+        assert_eq!(project.branch_env[0].service, "object-store");
+        assert_eq!(before, ["minio_data"]);
+        assert_eq!(
+            service_volumes(&project, "object-store"),
+            ["object-store_data"]
+        );
+        let model = ComposeModel::from_project(&project);
+        assert_eq!(
+            model.services["object-store"]["container_name"],
+            y_str("dockstack_golden_object-store")
+        );
+        assert_eq!(
+            model.services["object-store"]["volumes"][1],
+            y_str("./minio/config:/root/.minio:ro")
+        );
+    }
+
+    #[test]
+    fn mssql_needs_the_eula_and_a_complex_password() {
+        let mut project = project(&["mssql"]);
+        assert!(mssql_start_problem(&project).is_some_and(|p| p.contains("EULA")));
+        if let Some(svc) = project.services.get_mut("mssql") {
+            svc.settings
+                .insert("accept_eula".to_string(), "true".to_string());
+        }
+        assert_eq!(mssql_start_problem(&project), None);
+
+        assert!(mssql_password_problem("Sh0rt!").is_some());
+        assert!(mssql_password_problem("alllowercase1").is_some());
+        assert_eq!(mssql_password_problem("lower-and-1234"), None);
+        let model = ComposeModel::from_project(&project);
+        let env = &model.services["mssql"]["environment"];
+        assert_eq!(env["ACCEPT_EULA"], y_str("Y"));
+        assert_eq!(env["MSSQL_PID"], y_str("Developer"));
+        assert!(model.volumes.contains_key("mssql_data"));
+    }
+
+    #[test]
+    fn sftp_serves_www_to_the_configured_user() -> io::Result<()> {
+        let mut project = project(&["sftp"]);
+        if let Some(svc) = project.services.get_mut("sftp") {
+            svc.settings
+                .insert("user".to_string(), "designer".to_string());
+            svc.settings
+                .insert("password".to_string(), "s3cret".to_string());
+            svc.settings.insert("uid".to_string(), "1001".to_string());
+        }
+        let model = ComposeModel::from_project(&project);
+        let sftp = &model.services["sftp"];
+        assert_eq!(
+            sftp["volumes"][0],
+            y_str("/srv/golden/www:/home/designer/www")
+        );
+        assert_eq!(sftp["ports"][0], y_str("127.0.0.1:2222:22"));
+        assert!(!generate_compose(&project).contains("s3cret"));
+
+        let dir = std::env::temp_dir().join(format!("dockstack-sftp-{}", std::process::id()));
+        project.directory = dir.to_string_lossy().to_string();
+        write_sftp_users(&project)?;
+        let users = fs::read_to_string(dir.join("sftp/users.conf"))?;
+        fs::remove_dir_all(&dir)?;
+        assert!(users.ends_with("\ndesigner:s3cret:1001:1001\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn dbgate_connects_to_every_enabled_database() {
+        let mut project = project(&["mysql", "postgresql", "redis", "dbgate"]);
+        project.add_named_instance("postgresql", "postgres-analytics");
+        let model = ComposeModel::from_project(&project);
+        let dbgate = &model.services["dbgate"];
+        let env = &dbgate["environment"];
+        assert_eq!(
+            env["CONNECTIONS"],
+            y_str("mysql,postgres_analytics,postgresql,redis")
+        );
+        assert_eq!(
+            env["SERVER_postgres_analytics"],
+            y_str("postgres-analytics")
+        );
+        assert_eq!(env["ENGINE_mysql"], y_str("mysql@dbgate-plugin-mysql"));
+        assert_eq!(env["USER_mysql"], y_str("root"));
+        assert_eq!(env["PORT_redis"], y_str("6379"));
+        assert!(env.get("USER_redis").is_none());
+        assert_eq!(dbgate["depends_on"].as_sequence().map(Vec::len), Some(4));
+    }
+
+    #[test]
+    fn nginx_proxies_to_apache_only_in_the_chained_topology() {
+        let mut project = project(&["php", "nginx"]);
+        if let Some(apache) = project.services.get_mut("apache") {
+            apache.enabled = true;
+        }
+        assert_eq!(project.sync_web_topology("apache"), Some("nginx"));
+        assert_eq!(project.web_topology, "apache");
+        assert!(nginx_site(&project, "/srv").contains("try_files"));
+
+        project.set_web_topology("nginx-apache");
+        let model = ComposeModel::from_project(&project);
+        assert!(model.services["apache"].get("ports").is_none());
+        assert_eq!(model.services["nginx"]["depends_on"][0], y_str("apache"));
+        let site = nginx_site(&project, "/srv");
+        assert!(site.contains("proxy_pass http://apache:80;"));
+        assert!(!site.contains("fastcgi_pass"));
+
+        if let Some(apache) = project.services.get_mut("apache") {
+            apache.enabled = false;
+        }
+        assert_eq!(project.sync_web_topology("apache"), None);
+        assert_eq!(project.web_topology, "nginx");
+    }
+
+    #[test]
+    fn custom_ports_replace_host_ports_below_profile_overrides() {
+        let mut project = project(&["nginx", "mysql"]);
+        project.custom_ports.insert("nginx".to_string(), 8088);
+        project.custom_ports.insert("mysql".to_string(), 3316);
+        project.profiles = vec![EnvProfile {
+            name: "Staging".to_string(),
+            services: Vec::new(),
+            overrides: vec![ProfileOverride {
+                service: "mysql".to_string(),
+                port: 3307,
+                vars: String::new(),
+            }],
+        }];
+        project.active_profile = "Staging".to_string();
+        let model = ComposeModel::from_project(&project);
+        assert_eq!(
+            model.services["nginx"]["ports"][0],
+            y_str("127.0.0.1:8088:80")
+        );
+        assert_eq!(
+            model.services["mysql"]["ports"][0],
+            y_str("127.0.0.1:3307:3306")
+        );
+
+        assert_eq!(project.host_port("nginx"), 8088);
+        project.set_host_port("nginx", 8089);
+        assert_eq!(project.custom_ports["nginx"], 8089);
+        assert_eq!(project.services["nginx"].port, 80);
+    }
+
+    #[test]
+    fn placeholders_resolve_at_generation_and_leave_compose_variables_alone() {
+        let mut project = project(&["mysql", "nginx"]);
+        project.name = "Shop".to_string();
+        project.domain = "shop.test".to_string();
+        project.custom_ports.insert("nginx".to_string(), 8088);
+        if let Some(mysql) = project.services.get_mut("mysql") {
+            let env = &mut mysql.env_vars;
+            env.insert("MYSQL_DATABASE".to_string(), "${PROJECT_NAME}".to_string());
+            env.insert(
+                "APP_URL".to_string(),
+                "http://${DOMAIN}:${PORT:nginx}".to_string(),
+            );
+            env.insert(
+                "KEEP".to_string(),
+                "$${DOMAIN} ${HOME} ${PORT:nope} ${DOMAIN".to_string(),
+            );
+        }
+        let model = ComposeModel::from_project(&project);
+        let env = &model.services["mysql"]["environment"];
+        assert_eq!(env["MYSQL_DATABASE"], y_str("Shop"));
+        assert_eq!(env["APP_URL"], y_str("http://shop.test:8088"));
+        assert_eq!(
+            env["KEEP"],
+            y_str("$${DOMAIN} ${HOME} ${PORT:nope} ${DOMAIN")
+        );
+    }
+
+    #[test]
+    fn project_env_reaches_every_service_unless_it_sets_the_key() {
+        let mut project = project(&["mysql", "redis"]);
+        project.env = "TZ=Europe/Berlin\nMYSQL_DATABASE=shared\n# note".to_string();
+        let model = ComposeModel::from_project(&project);
+        assert_eq!(
+            model.services["redis"]["environment"]["TZ"],
+            y_str("Europe/Berlin")
+        );
+        let mysql = &model.services["mysql"]["environment"];
+        assert_eq!(mysql["TZ"], y_str("Europe/Berlin"));
+        assert_ne!(mysql["MYSQL_DATABASE"], y_str("shared"));
+    }
+
+    #[test]
+    fn timezone_and_locale_default_every_service() {
+        let mut project = project(&["mysql", "redis"]);
+        project.env = "LANG=C.UTF-8".to_string();
+        project.timezone = " Europe/Berlin ".to_string();
+        project.locale = "de_DE.UTF-8".to_string();
+        if let Some(mysql) = project.services.get_mut("mysql") {
+            mysql.env_vars.insert("TZ".to_string(), "UTC".to_string());
+        }
+        let model = ComposeModel::from_project(&project);
+        let redis = &model.services["redis"]["environment"];
+        assert_eq!(redis["TZ"], y_str("Europe/Berlin"));
+        assert_eq!(redis["LANG"], y_str("C.UTF-8"));
+        assert_eq!(model.services["mysql"]["environment"]["TZ"], y_str("UTC"));
+
+        project.timezone = "Europe/Berlin\nextension=evil.so".to_string();
+        assert_eq!(timezone(&project), None);
+    }
+
+    #[test]
+    fn proxy_reaches_services_but_not_the_stack_itself() {
+        let mut project = project(&["mysql", "redis"]);
+        let model = ComposeModel::from_project(&project);
+        assert!(model.services["redis"].get("environment").is_none());
+
+        project.use_proxy = true;
+        let model = ComposeModel::from_project(&project);
+        let redis = &model.services["redis"]["environment"];
+        assert_eq!(redis["https_proxy"], y_str("${HTTPS_PROXY:-}"));
+        assert_eq!(
+            redis["NO_PROXY"],
+            y_str("localhost,127.0.0.1,mysql,redis,${NO_PROXY:-}")
+        );
+    }
+
+    #[test]
+    fn existing_codebase_is_mounted_from_the_project_directory() {
+        let mut project = project(&["nginx", "php"]);
+        project.code_in_directory = true;
+        project.web_root = "public".to_string();
+        let model = ComposeModel::from_project(&project);
+        let php = model.services["php"]["volumes"][0].clone();
+        assert_eq!(php, y_str("/srv/golden:/var/www/html"));
+        assert_eq!(
+            document_root(&project, "/usr/share/nginx/html"),
+            "/usr/share/nginx/html/public"
+        );
+        project.web_root = "../etc".to_string();
+        assert_eq!(document_root(&project, "/srv"), "/srv");
+    }
+
+    #[test]
+    fn commit_mode_writes_relative_mounts_and_ignores_private_files() {
+        let mut project = project(&["nginx", "php"]);
+        let ignored = gitignore_entries(&project);
+        assert!(ignored.contains(&"/docker-compose.yml".to_string()));
+        assert!(!ignored.contains(&"/www".to_string()));
+
+        project.commit_generated = true;
+        assert_eq!(
+            gitignore_entries(&project),
+            vec!["/certs/", "/sftp/", "/backups/"]
+        );
+        let model = ComposeModel::from_project(&project);
+        let volumes = model.services["nginx"]["volumes"].as_sequence().cloned();
+        let mounts: Vec<&str> = volumes
+            .iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert!(mounts.iter().any(|m| m.starts_with("./www:")));
+        assert!(!mounts.iter().any(|m| m.starts_with("/srv/golden")));
+    }
+
+    #[test]
+    fn relabel_touches_only_project_bind_mounts() {
+        let project = project(&[]);
+        let relabel = |mount| relabel_mount(&project, mount, "z");
+        assert_eq!(
+            relabel("./nginx/default.conf:/etc/nginx/conf.d/default.conf"),
+            "./nginx/default.conf:/etc/nginx/conf.d/default.conf:z"
+        );
+        assert_eq!(
+            relabel("/srv/golden/www:/var/www/html"),
+            "/srv/golden/www:/var/www/html:z"
+        );
+        assert_eq!(
+            relabel("./certs:/etc/nginx/certs:ro"),
+            "./certs:/etc/nginx/certs:ro,z"
+        );
+        assert_eq!(relabel("./data:/data:Z"), "./data:/data:Z");
+        assert_eq!(
+            relabel("mysql_data:/var/lib/mysql"),
+            "mysql_data:/var/lib/mysql"
+        );
+        assert_eq!(
+            relabel("/var/run/docker.sock:/var/run/docker.sock"),
+            "/var/run/docker.sock:/var/run/docker.sock"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::{self, ComposeModel};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Keys DockStack owns in `devcontainer.json`; anything else the user added
+/// (extensions, features, post-create commands) is kept when it is written again.
+const MANAGED_KEYS: &[&str] = &[
+    "name",
+    "dockerComposeFile",
+    "service",
+    "workspaceFolder",
+    "shutdownAction",
+];
+
+/// Services a Dev Container can attach to, those mounting the project's code
+/// first and runtimes such as php ahead of the web servers.
+pub fn services(project: &ProjectConfig) -> Vec<String> {
+    let code = compose::code_mount_services(project);
+    let mut services: Vec<String> = ComposeModel::from_project(project)
+        .services
+        .into_keys()
+        .collect();
+    services.sort_by_key(|s| (!code.contains(s), ["nginx", "apache"].contains(&s.as_str())));
+    services
+}
+
+/// The project's chosen service while it is still enabled, else the first of `services`.
+pub fn primary_service(project: &ProjectConfig) -> Option<String> {
+    let services = services(project);
+    services
+        .iter()
+        .find(|s| **s == project.devcontainer_service)
+        .or(services.first())
+        .cloned()
+}
+
+/// `devcontainer.json` attaching VS Code to `service` of the generated compose
+/// file. The compose file lives in the project directory, so VS Code derives
+/// the same compose project name as DockStack and reuses its containers; it
+/// leaves them running when the window closes.
+pub fn config(project: &ProjectConfig, service: &str) -> Value {
+    let workspace = compose::code_mount_target(project, service)
+        .or_else(|| {
+            let model = ComposeModel::from_project(project);
+            let dir = model.services.get(service)?.get("working_dir")?;
+            dir.as_str().map(str::to_string)
+        })
+        .unwrap_or_else(|| "/".to_string());
+    json!({
+        "name": project.name,
+        "dockerComposeFile": "../docker-compose.yml",
+        "service": service,
+        "workspaceFolder": workspace,
+        "shutdownAction": "none",
+    })
+}
+
+pub fn config_path(project: &ProjectConfig) -> PathBuf {
+    Path::new(&project.directory)
+        .join(".devcontainer")
+        .join("devcontainer.json")
+}
+
+/// Write `.devcontainer/devcontainer.json` for the primary service, keeping the
+/// user's own keys, and the compose file it points at if there is none yet.
+/// Returns the service VS Code attaches to.
+pub fn write(project: &ProjectConfig) -> io::Result<String> {
+    let Some(service) = primary_service(project) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the project has no enabled services",
+        ));
+    };
+    let path = config_path(project);
+    let mut content = match fs::read_to_string(&path) {
+        Ok(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(Value::Object(map)) => map,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} is not plain JSON (comments are not kept); remove it to \
+                         generate a new one",
+                        path.display()
+                    ),
+                ))
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => serde_json::Map::new(),
+        Err(e) => return Err(e),
+    };
+    if let Value::Object(generated) = config(project, &service) {
+        for key in MANAGED_KEYS {
+            if let Some(value) = generated.get(*key) {
+                content.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+
+    if !Path::new(&project.directory)
+        .join("docker-compose.yml")
+        .exists()
+    {
+        compose::write_compose_file(project)?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let text = serde_json::to_string_pretty(&Value::Object(content))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(&path, text + "\n")?;
+    Ok(service)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::compose::tests::project;
+
+    #[test]
+    fn devcontainer_attaches_to_the_code_service() {
+        let mut project = project(&["mysql", "php", "nginx"]);
+        assert_eq!(services(&project), ["php", "nginx", "mysql"]);
+        project.devcontainer_service = "nginx".to_string();
+        assert_eq!(primary_service(&project).as_deref(), Some("nginx"));
+        let php = config(&project, "php");
+        assert_eq!(php["dockerComposeFile"], "../docker-compose.yml");
+        assert_eq!(php["service"], "php");
+        assert_eq!(php["workspaceFolder"], "/var/www/html");
+        assert_eq!(config(&project, "mysql")["workspaceFolder"], "/");
+
+        project.devcontainer_service = "solr".to_string();
+        assert_eq!(primary_service(&project).as_deref(), Some("php"));
+    }
+}
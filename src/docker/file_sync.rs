@@ -0,0 +1,74 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::{self, SyncSession};
+use crate::error::{DockStackError, Result};
+use std::io;
+use std::process::Command;
+
+/// Create the project's Mutagen sessions, replacing any left by an earlier run.
+/// Blocking; the containers must already be up.
+pub fn start(project: &ProjectConfig) -> Result<String> {
+    let sessions = compose::sync_sessions(project);
+    for session in &sessions {
+        terminate(session);
+        let mut cmd = Command::new("mutagen");
+        cmd.args(["sync", "create", "--name", &session.name, "--ignore-vcs"]);
+        for ignore in &session.ignores {
+            cmd.arg(format!("--ignore={}", ignore));
+        }
+        cmd.arg(&session.host)
+            .arg(format!("docker://{}{}", session.container, session.target));
+        run(&mut cmd, &format!("mutagen sync create {}", session.name))?;
+    }
+    Ok(format!(
+        "Syncing {} with Mutagen",
+        sessions
+            .iter()
+            .map(|s| s.host.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// End the project's Mutagen sessions; ones that do not exist are skipped.
+pub fn stop(project: &ProjectConfig) {
+    for session in compose::sync_sessions(project) {
+        terminate(&session);
+    }
+}
+
+fn terminate(session: &SyncSession) {
+    let context = format!("mutagen sync terminate {}", session.name);
+    let result = run(
+        Command::new("mutagen").args(["sync", "terminate", &session.name]),
+        &context,
+    );
+    if let Err(e) = result {
+        log::debug!("{}", e);
+    }
+}
+
+fn run(cmd: &mut Command, context: &str) -> Result<()> {
+    let output = cmd.output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => DockStackError::Command {
+            context: context.to_string(),
+            detail: "mutagen is not installed; see \
+                     https://mutagen.io/documentation/introduction/installation"
+                .to_string(),
+        },
+        _ => DockStackError::io(context, &e),
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(DockStackError::Command {
+        context: context.to_string(),
+        detail: stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("mutagen exited with an error")
+            .trim()
+            .to_string(),
+    })
+}
@@ -0,0 +1,153 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::ComposeModel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Lockfile in the project directory, meant to be committed.
+pub const LOCK_FILE: &str = "dockstack.lock";
+
+const LOCK_HEADER: &str = "\
+# Image digests DockStack starts this project with, while image locking is on.
+# Commit this file so everyone runs the same images; Update Lock in Settings
+# resolves the tags again.
+
+";
+
+/// The digest each image reference of a project resolved to, e.g.
+/// `mysql:8.0` → `sha256:…`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageLock {
+    #[serde(default)]
+    pub images: BTreeMap<String, String>,
+}
+
+impl ImageLock {
+    pub fn path(project: &ProjectConfig) -> PathBuf {
+        Path::new(&project.directory).join(LOCK_FILE)
+    }
+
+    /// The project's lockfile; empty when there is none or it cannot be read.
+    pub fn load(project: &ProjectConfig) -> Self {
+        let path = Self::path(project);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            log::warn!("Ignoring {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, project: &ProjectConfig) -> io::Result<()> {
+        let content = toml::to_string(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(Self::path(project), format!("{}{}", LOCK_HEADER, content))
+    }
+
+    /// References of the project the lock does not cover yet.
+    pub fn missing(&self, project: &ProjectConfig) -> Vec<String> {
+        references(project)
+            .into_iter()
+            .filter(|r| !self.images.contains_key(r))
+            .collect()
+    }
+
+    /// Point every locked image in `model` at its digest. The tag stays for
+    /// readability; Docker goes by the digest.
+    pub fn pin(&self, model: &mut ComposeModel) {
+        for service in model.services.values_mut() {
+            let Some(serde_yaml::Value::String(image)) = service.get_mut("image") else {
+                continue;
+            };
+            if let Some(digest) = self.images.get(image.as_str()) {
+                *image = format!("{}@{}", image, digest);
+            }
+        }
+    }
+}
+
+/// Image references of the enabled services that a lock can pin: pulled
+/// images, not ones built locally or already pinned to a digest.
+pub fn references(project: &ProjectConfig) -> Vec<String> {
+    let mut references: Vec<String> = ComposeModel::from_project(project)
+        .services
+        .values()
+        .filter(|s| !s.contains_key("build"))
+        .filter_map(|s| s.get("image")?.as_str())
+        .filter(|image| !image.contains('@'))
+        .map(str::to_string)
+        .collect();
+    references.sort();
+    references.dedup();
+    references
+}
+
+/// Digest `reference` was pulled at, from `docker image inspect`'s RepoDigests
+/// (`mysql@sha256:…` for `mysql:8.0`).
+pub fn repo_digest(reference: &str, repo_digests: &[String]) -> Option<String> {
+    let repository = |name: &str| {
+        let name = name.strip_prefix("docker.io/").unwrap_or(name);
+        name.strip_prefix("library/").unwrap_or(name).to_string()
+    };
+    let last_segment = reference.rsplit('/').next().unwrap_or(reference);
+    let wanted = match last_segment.rsplit_once(':') {
+        Some((_, tag)) => &reference[..reference.len() - tag.len() - 1],
+        None => reference,
+    };
+    repo_digests
+        .iter()
+        .filter_map(|entry| entry.split_once('@'))
+        .find(|(repo, _)| repository(repo) == repository(wanted))
+        .map(|(_, digest)| digest.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::compose::tests::{custom_service, project};
+
+    #[test]
+    fn image_lock_pins_tags_to_digests() {
+        let mut project = project(&["mysql", "redis"]);
+        project.services.insert("app".to_string(), custom_service());
+        let mut model = ComposeModel::from_project(&project);
+        let image = |model: &ComposeModel, service: &str| {
+            model.services[service]["image"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mysql = image(&model, "mysql");
+        let mut lock = ImageLock::default();
+        lock.images
+            .insert(mysql.clone(), "sha256:0123abcd".to_string());
+        assert!(lock.missing(&project).contains(&image(&model, "redis")));
+        assert!(!lock.missing(&project).contains(&mysql));
+
+        lock.pin(&mut model);
+        assert_eq!(image(&model, "mysql"), format!("{}@sha256:0123abcd", mysql));
+        assert!(!image(&model, "redis").contains('@'));
+        assert_eq!(references(&project).len(), model.services.len());
+
+        let digests = [
+            "ghcr.io/acme/api@sha256:ffff".to_string(),
+            "mysql@sha256:0123abcd".to_string(),
+        ];
+        assert_eq!(
+            repo_digest("mysql:8.0", &digests).as_deref(),
+            Some("sha256:0123abcd")
+        );
+        assert_eq!(
+            repo_digest("docker.io/library/mysql", &digests).as_deref(),
+            Some("sha256:0123abcd")
+        );
+        assert_eq!(
+            repo_digest("ghcr.io/acme/api:1.2", &digests).as_deref(),
+            Some("sha256:ffff")
+        );
+        assert_eq!(repo_digest("redis:7", &digests), None);
+    }
+}
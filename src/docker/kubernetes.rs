@@ -0,0 +1,369 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::{self, ComposeModel};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+type YamlMap = serde_yaml::Mapping;
+type YamlVal = serde_yaml::Value;
+
+/// Storage each named volume asks for; clusters resize or reclaim it later.
+const VOLUME_SIZE: &str = "1Gi";
+
+/// Kubernetes manifests for the project's enabled services: a Deployment and,
+/// when it listens on a port, a Service per compose service, and a
+/// PersistentVolumeClaim per named volume. What has no Kubernetes equivalent
+/// here (bind mounts, images only built locally, compose interpolation) is
+/// listed in the header comment instead.
+pub fn manifests(project: &ProjectConfig) -> String {
+    let model = ComposeModel::from_project(project);
+    let app = compose::compose_project_name(project);
+    let mut documents = Vec::new();
+    let mut notes = Vec::new();
+
+    for name in model.volumes.keys() {
+        documents.push(volume_claim(&app, &object_name(name)));
+    }
+    for (service_name, service) in &model.services {
+        let name = &object_name(service_name);
+        if name != service_name {
+            notes.push(format!("{} is named {} in Kubernetes", service_name, name));
+        }
+        let (deployment, ports) = deployment(&app, name, service, &model, &mut notes);
+        documents.push(deployment);
+        if ports.is_empty() {
+            notes.push(format!("{} publishes no port, so it gets no Service", name));
+        } else {
+            documents.push(cluster_service(&app, name, &ports));
+        }
+    }
+
+    let mut out = format!(
+        "# Kubernetes manifests exported by DockStack from {} (experimental).\n\
+         # Review before applying: kubectl apply -f <this file>\n",
+        project.name
+    );
+    for note in notes {
+        out.push_str(&format!("# - {}\n", note));
+    }
+    for document in documents {
+        out.push_str("---\n");
+        out.push_str(&serde_yaml::to_string(&YamlVal::Mapping(document)).unwrap_or_default());
+    }
+    out
+}
+
+/// Write the project's manifests to `dest`.
+pub fn export(project: &ProjectConfig, dest: &Path) -> io::Result<()> {
+    fs::write(dest, manifests(project))
+}
+
+fn deployment(
+    app: &str,
+    name: &str,
+    service: &YamlMap,
+    model: &ComposeModel,
+    notes: &mut Vec<String>,
+) -> (YamlMap, Vec<u16>) {
+    let mut container = YamlMap::new();
+    container.insert(y_str("name"), y_str(name));
+    match service.get("image").and_then(YamlVal::as_str) {
+        Some(image) => container.insert(y_str("image"), y_str(image)),
+        None => {
+            notes.push(format!(
+                "{} is built locally: push its image and set it on the Deployment",
+                name
+            ));
+            container.insert(y_str("image"), y_str(&format!("{}-{}:latest", app, name)))
+        }
+    };
+    if let Some(entrypoint) = service.get("entrypoint").and_then(words) {
+        container.insert(y_str("command"), entrypoint);
+    }
+    if let Some(command) = service.get("command").and_then(words) {
+        container.insert(y_str("args"), command);
+    }
+    if let Some(dir) = service.get("working_dir") {
+        container.insert(y_str("workingDir"), dir.clone());
+    }
+
+    if let Some(YamlVal::Mapping(environment)) = service.get("environment") {
+        let mut env = Vec::new();
+        let mut dropped = Vec::new();
+        for (key, value) in environment {
+            let (Some(key), Some(value)) = (key.as_str(), scalar(value)) else {
+                continue;
+            };
+            // Filled in by docker compose from its own environment
+            if value.contains("${") {
+                dropped.push(key.to_string());
+                continue;
+            }
+            let mut var = YamlMap::new();
+            var.insert(y_str("name"), y_str(key));
+            var.insert(y_str("value"), y_str(&value));
+            env.push(YamlVal::Mapping(var));
+        }
+        if !dropped.is_empty() {
+            notes.push(format!(
+                "{}: left out {}, which compose fills in from its environment",
+                name,
+                dropped.join(", ")
+            ));
+        }
+        if !env.is_empty() {
+            container.insert(y_str("env"), YamlVal::Sequence(env));
+        }
+    }
+
+    let ports: Vec<u16> = match service.get("ports") {
+        Some(YamlVal::Sequence(ports)) => ports
+            .iter()
+            .filter_map(scalar)
+            .filter_map(|p| container_port(&p))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    };
+    if !ports.is_empty() {
+        let list = ports
+            .iter()
+            .map(|port| {
+                let mut p = YamlMap::new();
+                p.insert(y_str("containerPort"), YamlVal::Number((*port).into()));
+                YamlVal::Mapping(p)
+            })
+            .collect();
+        container.insert(y_str("ports"), YamlVal::Sequence(list));
+    }
+
+    let mut mounts = Vec::new();
+    let mut volumes = Vec::new();
+    let mut binds = Vec::new();
+    if let Some(YamlVal::Sequence(list)) = service.get("volumes") {
+        for mount in list.iter().filter_map(YamlVal::as_str) {
+            let mut parts = mount.split(':');
+            let (Some(source), Some(target)) = (parts.next(), parts.next()) else {
+                continue; // Anonymous volume
+            };
+            if !model.volumes.contains_key(source) {
+                binds.push(target.to_string());
+                continue;
+            }
+            let source = object_name(source);
+            let mut m = YamlMap::new();
+            m.insert(y_str("name"), y_str(&source));
+            m.insert(y_str("mountPath"), y_str(target));
+            if parts.any(|option| option.split(',').any(|o| o == "ro")) {
+                m.insert(y_str("readOnly"), YamlVal::Bool(true));
+            }
+            mounts.push(YamlVal::Mapping(m));
+            if !volumes
+                .iter()
+                .any(|v: &YamlVal| v["name"].as_str() == Some(&source))
+            {
+                volumes.push(mapping(&[
+                    ("name", y_str(&source)),
+                    (
+                        "persistentVolumeClaim",
+                        mapping(&[("claimName", y_str(&source))]),
+                    ),
+                ]));
+            }
+        }
+    }
+    if !binds.is_empty() {
+        notes.push(format!(
+            "{}: host folders mounted at {} are not exported; bake them into the image or \
+             use a ConfigMap",
+            name,
+            binds.join(", ")
+        ));
+    }
+    if !mounts.is_empty() {
+        container.insert(y_str("volumeMounts"), YamlVal::Sequence(mounts));
+    }
+
+    let mut pod = YamlMap::new();
+    pod.insert(
+        y_str("containers"),
+        YamlVal::Sequence(vec![YamlVal::Mapping(container)]),
+    );
+    if !volumes.is_empty() {
+        pod.insert(y_str("volumes"), YamlVal::Sequence(volumes));
+    }
+
+    let selector = mapping(&[("matchLabels", labels(app, name))]);
+    let template = mapping(&[
+        ("metadata", mapping(&[("labels", labels(app, name))])),
+        ("spec", YamlVal::Mapping(pod)),
+    ]);
+    let spec = mapping(&[
+        ("replicas", YamlVal::Number(1.into())),
+        ("selector", selector),
+        ("template", template),
+    ]);
+    let document = header("apps/v1", "Deployment", app, name, spec);
+    (document, ports)
+}
+
+fn cluster_service(app: &str, name: &str, ports: &[u16]) -> YamlMap {
+    let ports = ports
+        .iter()
+        .map(|port| {
+            mapping(&[
+                ("name", y_str(&format!("port-{}", port))),
+                ("port", YamlVal::Number((*port).into())),
+                ("targetPort", YamlVal::Number((*port).into())),
+            ])
+        })
+        .collect();
+    // Named after the compose service, so containers reach each other as before
+    let spec = mapping(&[
+        ("selector", labels(app, name)),
+        ("ports", YamlVal::Sequence(ports)),
+    ]);
+    header("v1", "Service", app, name, spec)
+}
+
+fn volume_claim(app: &str, name: &str) -> YamlMap {
+    let spec = mapping(&[
+        (
+            "accessModes",
+            YamlVal::Sequence(vec![y_str("ReadWriteOnce")]),
+        ),
+        (
+            "resources",
+            mapping(&[("requests", mapping(&[("storage", y_str(VOLUME_SIZE))]))]),
+        ),
+    ]);
+    header("v1", "PersistentVolumeClaim", app, name, spec)
+}
+
+fn header(api_version: &str, kind: &str, app: &str, name: &str, spec: YamlVal) -> YamlMap {
+    let metadata = mapping(&[("name", y_str(name)), ("labels", labels(app, name))]);
+    let mut document = YamlMap::new();
+    document.insert(y_str("apiVersion"), y_str(api_version));
+    document.insert(y_str("kind"), y_str(kind));
+    document.insert(y_str("metadata"), metadata);
+    document.insert(y_str("spec"), spec);
+    document
+}
+
+fn labels(app: &str, name: &str) -> YamlVal {
+    mapping(&[
+        ("app.kubernetes.io/name", y_str(name)),
+        ("app.kubernetes.io/part-of", y_str(app)),
+    ])
+}
+
+/// `name` as a Kubernetes object name: lowercase letters, digits and dashes.
+fn object_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    name.trim_matches('-').to_string()
+}
+
+/// Port the container listens on in a compose port mapping, e.g. `80` in
+/// `127.0.0.1:8080:80` or `53` in `53:53/udp`.
+fn container_port(mapping: &str) -> Option<u16> {
+    let last = mapping.rsplit(':').next()?;
+    last.split('/').next()?.parse().ok()
+}
+
+/// A compose command as a list: sequences as they are, strings split the way
+/// compose splits them, on spaces outside quotes.
+pub fn words(command: &YamlVal) -> Option<YamlVal> {
+    match command {
+        YamlVal::Sequence(list) => Some(YamlVal::Sequence(list.clone())),
+        YamlVal::String(text) => {
+            let mut words = Vec::new();
+            let mut word = String::new();
+            let mut quote = None;
+            for c in text.chars() {
+                match (quote, c) {
+                    (None, '"' | '\'') => quote = Some(c),
+                    (Some(q), c) if c == q => quote = None,
+                    (None, c) if c.is_whitespace() => {
+                        if !word.is_empty() {
+                            words.push(y_str(&std::mem::take(&mut word)));
+                        }
+                    }
+                    _ => word.push(c),
+                }
+            }
+            if !word.is_empty() {
+                words.push(y_str(&word));
+            }
+            Some(YamlVal::Sequence(words))
+        }
+        _ => None,
+    }
+}
+
+fn scalar(value: &YamlVal) -> Option<String> {
+    match value {
+        YamlVal::String(s) => Some(s.clone()),
+        YamlVal::Number(n) => Some(n.to_string()),
+        YamlVal::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn mapping(entries: &[(&str, YamlVal)]) -> YamlVal {
+    YamlVal::Mapping(
+        entries
+            .iter()
+            .map(|(key, value)| (y_str(key), value.clone()))
+            .collect(),
+    )
+}
+
+fn y_str(s: &str) -> YamlVal {
+    YamlVal::String(s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::compose::tests::project;
+
+    #[test]
+    fn kubernetes_export_keeps_service_names_and_volumes() {
+        let project = project(&["mysql", "redis"]);
+        let manifests = manifests(&project);
+        let documents: Vec<YamlVal> = manifests
+            .split("---\n")
+            .skip(1)
+            .filter_map(|doc| serde_yaml::from_str(doc).ok())
+            .collect();
+        let find = |kind: &str, name: &str| {
+            documents
+                .iter()
+                .find(|d| d["kind"] == y_str(kind) && d["metadata"]["name"] == y_str(name))
+                .cloned()
+                .unwrap_or(YamlVal::Null)
+        };
+        let mysql = find("Deployment", "mysql");
+        let container = &mysql["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(
+            container["image"],
+            ComposeModel::from_project(&project).services["mysql"]["image"]
+        );
+        assert_eq!(container["ports"][0]["containerPort"], YamlVal::from(3306));
+        assert_eq!(
+            find("Service", "redis")["spec"]["ports"][0]["port"],
+            YamlVal::from(6379)
+        );
+        let claim = &container["volumeMounts"][0]["name"];
+        assert_eq!(
+            find("PersistentVolumeClaim", claim.as_str().unwrap_or_default())["kind"],
+            y_str("PersistentVolumeClaim")
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::config::MaintenanceConfig;
+use crate::docker::compose;
+
+/// What one maintenance run removed.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceReport {
+    pub steps: Vec<(String, u64)>, // Label and bytes reclaimed, as Docker reports them
+    pub errors: Vec<String>,       // Steps that failed, and why
+}
+
+impl MaintenanceReport {
+    pub fn reclaimed(&self) -> u64 {
+        self.steps.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+/// `docker` arguments of each prune step turned on, with its label. Volumes are
+/// never pruned: they hold the projects' data.
+pub fn prune_commands(settings: &MaintenanceConfig) -> Vec<(&'static str, Vec<String>)> {
+    let until = match settings.retention_hours {
+        0 => Vec::new(),
+        hours => vec!["--filter".to_string(), format!("until={}h", hours)],
+    };
+    let command = |args: &[&str]| {
+        let mut command: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        command.extend(until.iter().cloned());
+        command
+    };
+    let mut commands = Vec::new();
+    if settings.containers {
+        let label = format!("label={}", compose::PROJECT_LABEL);
+        commands.push((
+            "Stopped containers",
+            command(&["container", "prune", "--force", "--filter", &label]),
+        ));
+    }
+    if settings.images {
+        commands.push(("Dangling images", command(&["image", "prune", "--force"])));
+    }
+    if settings.build_cache {
+        commands.push(("Build cache", command(&["builder", "prune", "--force"])));
+    }
+    commands
+}
+
+/// Bytes in the total line of prune output: `Total reclaimed space: 1.2GB`, or
+/// `Total:  1.2GB` from `docker builder prune`. 0 when nothing was removed.
+pub fn reclaimed_bytes(output: &str) -> u64 {
+    output
+        .lines()
+        .filter(|line| line.trim_start().starts_with("Total"))
+        .filter_map(|line| line.rsplit([':', ' ', '\t']).find(|t| !t.is_empty()))
+        .find_map(parse_size)
+        .unwrap_or(0)
+}
+
+/// A size as Docker prints it, in decimal units (`512MB`, `1.5GB`, `0B`).
+fn parse_size(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.trim().parse().ok()?;
+    let multiplier = match unit.to_ascii_lowercase().as_str() {
+        "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maintenance_prunes_only_what_is_turned_on() {
+        let settings = MaintenanceConfig {
+            images: false,
+            ..MaintenanceConfig::default()
+        };
+        let commands = prune_commands(&settings);
+        let labels: Vec<&str> = commands.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, ["Stopped containers", "Build cache"]);
+        let containers = commands[0].1.join(" ");
+        assert!(containers.contains(&format!("label={}", compose::PROJECT_LABEL)));
+        assert!(containers.ends_with("--filter until=72h"));
+        let everything = MaintenanceConfig {
+            retention_hours: 0,
+            ..MaintenanceConfig::default()
+        };
+        assert!(prune_commands(&everything)
+            .iter()
+            .all(|(_, args)| !args.iter().any(|a| a.starts_with("until="))));
+
+        let image_prune = "Deleted Images:\ndeleted: sha256:abc\n\nTotal reclaimed space: 1.5GB";
+        assert_eq!(reclaimed_bytes(image_prune), 1_500_000_000);
+        assert_eq!(reclaimed_bytes("ID\nabc\nTotal:\t512MB"), 512_000_000);
+        assert_eq!(reclaimed_bytes("Total reclaimed space: 0B"), 0);
+        assert_eq!(reclaimed_bytes(""), 0);
+
+        let due = MaintenanceConfig {
+            enabled: true,
+            last_run: 1_000_000,
+            ..MaintenanceConfig::default()
+        };
+        assert!(!due.is_due(1_000_000 + 3600));
+        assert!(due.is_due(1_000_000 + 24 * 3600));
+        assert!(!MaintenanceConfig::default().is_due(1_000_000));
+    }
+}
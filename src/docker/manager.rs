@@ -1,11 +1,34 @@
 #![allow(dead_code)]
-use crate::config::ProjectConfig;
+use crate::config::{
+    BufferConfig, ComposeImport, MaintenanceConfig, ProjectConfig, ProxyConfig, RegistryConfig,
+    ServiceConfig, ServiceRename, PULL_POLICIES, RESTART_POLICIES,
+};
+use crate::diagnostics;
 use crate::docker::compose;
+use crate::docker::file_sync;
+use crate::docker::image_lock::{self, ImageLock};
+use crate::docker::maintenance::{self, MaintenanceReport};
+use crate::doctor::{self, DockerFacts, DoctorReport};
+use crate::error::{DockStackError, Result as DsResult};
+use crate::image_scan::{self, ScanReport, Scanner, Severity};
+use crate::registry;
+use crate::runtime::{CancelToken, RuntimeHandle};
+use crate::utils::{format_bytes, push_capped};
 use crossbeam_channel::{Receiver, Sender};
-use std::collections::VecDeque;
-use std::io::{BufRead, BufReader};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::AsyncBufReadExt;
+use tokio::task::AbortHandle;
+
+/// Throwaway image used to tar/untar named volumes for snapshots.
+const SNAPSHOT_HELPER_IMAGE: &str = "alpine:3";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ServiceStatus {
@@ -16,6 +39,95 @@ pub enum ServiceStatus {
     Error(String),
 }
 
+/// Per-service lifecycle stage while `compose up` is running.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartStage {
+    Pending,
+    Pulling,
+    Pulled,
+    Creating,
+    Starting,
+    Started,
+    Healthy,
+    Failed,
+}
+
+impl StartStage {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Pending => "Waiting",
+            Self::Pulling => "Pulling image",
+            Self::Pulled => "Image ready",
+            Self::Creating => "Creating",
+            Self::Starting => "Starting",
+            Self::Started => "Started",
+            Self::Healthy => "Healthy",
+            Self::Failed => "Failed",
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Started | Self::Healthy | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StartProgress {
+    pub started_at: Instant,
+    pub finished_at: Option<Instant>,
+    pub services: Vec<(String, StartStage)>,
+    pub pull_only: bool, // Tracks a standalone `compose pull` instead of `up`
+}
+
+impl StartProgress {
+    fn pulling(project: &ProjectConfig) -> Self {
+        Self {
+            pull_only: true,
+            ..Self::new(project)
+        }
+    }
+
+    fn new(project: &ProjectConfig) -> Self {
+        let mut services: Vec<(String, StartStage)> = project
+            .services
+            .iter()
+            .filter(|(name, svc)| svc.enabled && name.as_str() != "ssl")
+            .map(|(name, _)| (name.clone(), StartStage::Pending))
+            .collect();
+        services.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            started_at: Instant::now(),
+            finished_at: None,
+            services,
+            pull_only: false,
+        }
+    }
+
+    pub fn set_stage(&mut self, service: &str, stage: StartStage) {
+        if let Some(entry) = self.services.iter_mut().find(|(name, _)| name == service) {
+            entry.1 = stage;
+        }
+    }
+
+    fn finish(&mut self, success: bool) {
+        self.finished_at = Some(Instant::now());
+        if !success {
+            // Images a failed pull did fetch are still usable
+            let pull_only = self.pull_only;
+            let settled = |s: &StartStage| s.is_done() || (pull_only && *s == StartStage::Pulled);
+            for (_, stage) in self.services.iter_mut().filter(|(_, s)| !settled(s)) {
+                *stage = StartStage::Failed;
+            }
+        }
+    }
+
+    pub fn elapsed(&self) -> std::time::Duration {
+        self.finished_at
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.started_at)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ContainerInfo {
     pub id: String,
@@ -24,6 +136,157 @@ pub struct ContainerInfo {
     pub status: String,
     pub ports: String,
     pub state: String,
+    pub service: String, // Compose service name from the com.docker.compose.service label
+    pub restart_count: u32,
+    pub exit_code: i64,
+    pub crash_log: Option<String>, // Tail of the logs, only fetched for crash-looping containers
+}
+
+/// One container's output followed on its own, shown in a Logs sub-tab.
+#[derive(Debug, Clone, Default)]
+pub struct AttachedOutput {
+    pub container: String,
+    pub label: String,
+    pub lines: VecDeque<String>,
+    pub ended: bool, // `docker logs -f` exited, e.g. the container was removed
+}
+
+/// Containers of one project, from a listing across all projects.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectContainers {
+    pub running: Vec<String>, // Names, to match `docker stats` rows
+    pub stopped: usize,
+}
+
+/// Restarts after which a failing container is reported as crash-looping.
+const CRASH_LOOP_RESTARTS: u32 = 3;
+
+impl ContainerInfo {
+    /// Whether this is the running container of the given compose service.
+    pub fn is_running_service(&self, service: &str) -> bool {
+        self.service == service && self.state.contains("running")
+    }
+
+    pub fn is_crash_looping(&self) -> bool {
+        self.restart_count >= CRASH_LOOP_RESTARTS
+            && (self.state == "restarting" || self.exit_code != 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResourceKind {
+    Container,
+    Network,
+    Volume,
+}
+
+impl ResourceKind {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Container => "Container",
+            Self::Network => "Network",
+            Self::Volume => "Volume",
+        }
+    }
+}
+
+/// A Docker resource created by DockStack for a project that no longer exists in the config.
+#[derive(Debug, Clone)]
+pub struct OrphanResource {
+    pub kind: ResourceKind,
+    pub name: String,
+    pub project_id: String,
+}
+
+/// A running container DockStack did not create, e.g. from a hand-written compose project.
+#[derive(Debug, Clone)]
+pub struct ForeignContainer {
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub compose_project: Option<String>,
+    pub compose_file: Option<PathBuf>, // First config file from the compose labels, if it exists
+}
+
+/// Metadata stored as `meta/manifest.toml` inside a snapshot archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub created_at: String,
+    pub volumes: Vec<String>,
+    pub project: ProjectConfig,
+}
+
+/// How far `stop_services` tears the stack down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopMode {
+    /// `compose stop`: containers are kept and start again quickly.
+    Stop,
+    /// `compose down`: containers and networks are removed, volumes kept.
+    Down,
+    /// `compose down -v`: also deletes the project's volumes and their data.
+    Destroy,
+}
+
+impl StopMode {
+    fn compose_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Stop => &["stop"],
+            Self::Down => &["down"],
+            Self::Destroy => &["down", "-v"],
+        }
+    }
+
+    fn progress_message(&self) -> &'static str {
+        match self {
+            Self::Stop => "[DockStack] Stopping services...",
+            Self::Down => "[DockStack] Removing containers and networks...",
+            Self::Destroy => "[DockStack] Removing containers, networks and volumes...",
+        }
+    }
+
+    /// Entry for the History tab.
+    pub fn audit_action(&self) -> &'static str {
+        match self {
+            Self::Stop => "Stopped stack",
+            Self::Down => "Took stack down (containers and networks removed)",
+            Self::Destroy => "Destroyed stack data (containers, networks and volumes removed)",
+        }
+    }
+}
+
+/// What waits on the user confirming a project directory before it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryAction {
+    Start,
+    Restart,
+}
+
+impl DirectoryAction {
+    /// Status shown while the confirmation is pending, i.e. the one before the action.
+    fn fallback_status(&self) -> ServiceStatus {
+        match self {
+            Self::Start => ServiceStatus::Stopped,
+            Self::Restart => ServiceStatus::Running,
+        }
+    }
+}
+
+/// The "Build Cache" row of `docker system df`, as Docker formats the sizes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildCacheUsage {
+    pub size: String,
+    pub reclaimable: String,
+}
+
+/// Local Docker engine as seen by the last `check_docker`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineInfo {
+    pub available: bool,
+    pub client_version: String,
+    pub server_version: String, // Empty while the daemon is unreachable
+    pub api_version: String,
+    pub platform: String, // Server OS/arch, e.g. "linux/amd64"
+    pub rootless: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -31,8 +294,43 @@ pub enum DockerEvent {
     Log(String),
     StatusChange(String, ServiceStatus),
     ContainerList(Vec<ContainerInfo>),
-    Error(String),
-    DockerAvailable(bool),
+    Error(DockStackError),
+    DockerAvailable(EngineInfo),
+    /// Project id, reason and the refused action; the UI must confirm before
+    /// DockStack writes into the directory.
+    ConfirmDirectory(String, String, DirectoryAction),
+    OrphanedResources(Vec<OrphanResource>),
+    ForeignContainers(Vec<ForeignContainer>),
+    /// Custom services rebuilt from `docker inspect` of hand-started containers.
+    ContainersCaptured(Vec<(String, ServiceConfig)>),
+    /// Archive a snapshot was just written to.
+    SnapshotCreated(PathBuf),
+    /// Project config recovered from a snapshot; volumes have already been restored.
+    SnapshotRestored(Box<ProjectConfig>),
+    /// `docker compose config` rejected the generated file; the start was aborted.
+    ComposeInvalid(compose::ComposeValidation),
+    DoctorReport(Box<DoctorReport>),
+    BuildCacheUsage(BuildCacheUsage),
+    /// `compose pull` succeeded; failures arrive as `Error`.
+    ImagesPulled,
+    /// A compose file read for import, and suggested moves of its taken host
+    /// ports: (service, port, free port).
+    ComposeImportRead(DsResult<Box<ComposeImport>>, Vec<(String, u16, u16)>),
+    /// Project id and the pending service renames that were cleaned up.
+    RenamesMigrated(String, Vec<ServiceRename>),
+    /// Project id whose `dockstack.lock` was just brought up to date.
+    ImageLockWritten(String),
+    /// Container name and its redacted Markdown report, ready to copy.
+    ContainerReport(String, String),
+    /// DockStack containers of every project, by project id.
+    Fleet(HashMap<String, ProjectContainers>),
+    /// Findings of `scan_images`, or why no scanner could run.
+    ImageScan(Result<Box<ScanReport>, DockStackError>),
+    /// Project id and the services it now runs under emulation, with their platform.
+    PlatformFallback(String, Vec<(String, String)>),
+    /// Registry host and whether `login_registry` got in.
+    RegistryLogin(String, Result<(), DockStackError>),
+    MaintenanceDone(MaintenanceReport),
 }
 
 pub struct DockerManager {
@@ -40,10 +338,20 @@ pub struct DockerManager {
     pub event_rx: Receiver<DockerEvent>,
     pub status: Arc<Mutex<ServiceStatus>>,
     pub logs: Arc<Mutex<VecDeque<String>>>,
+    pub log_capacity: Arc<AtomicUsize>, // Lines kept in `logs`; the oldest are dropped
     pub containers: Arc<Mutex<Vec<ContainerInfo>>>,
+    pub start_progress: Arc<Mutex<Option<StartProgress>>>,
     pub docker_available: Arc<Mutex<bool>>,
     pub use_compose_plugin: Arc<Mutex<bool>>,
-    pub background_tasks: Arc<Mutex<Vec<std::thread::JoinHandle<()>>>>,
+    engine_platform: Arc<Mutex<String>>, // From the last `check_docker`, e.g. "linux/arm64"
+    image_platforms: Arc<Mutex<HashMap<String, Vec<String>>>>, // Per image reference
+    runtime: RuntimeHandle,
+    log_followers: Arc<Mutex<HashMap<String, AbortHandle>>>, // `docker logs -f` per container
+    /// Attached containers in the order their sub-tabs were opened.
+    pub attached: Arc<Mutex<Vec<AttachedOutput>>>,
+    attach_tasks: Arc<Mutex<HashMap<String, AbortHandle>>>, // Followers of `attached`
+    proxy_env: Arc<Mutex<Vec<(String, String)>>>, // Given to compose for `${HTTP_PROXY}` and co
+    registries: Arc<Mutex<Vec<RegistryConfig>>>,  // Logged in to before images are pulled
 }
 
 impl DockerManager {
@@ -51,42 +359,113 @@ impl DockerManager {
     where
         F: FnOnce() + Send + 'static,
     {
-        let handle = std::thread::spawn(f);
-        let mut tasks = self.background_tasks.lock().unwrap();
-        tasks.retain(|h| !h.is_finished());
-        tasks.push(handle);
+        self.runtime.spawn_blocking(f);
     }
 
-    pub fn wait_all(&self) {
-        let mut tasks = self.background_tasks.lock().unwrap();
-        for h in tasks.drain(..) {
-            let _ = h.join();
-        }
+    /// Report an action refused up front, without touching any containers.
+    fn refuse(&self, err: DockStackError) {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) =
+            ServiceStatus::Error(err.to_string());
+        self.event_tx.send(DockerEvent::Error(err)).ok();
     }
 
-    pub fn new() -> Self {
-        let (event_tx, event_rx) = crossbeam_channel::bounded(5000);
+    /// `buffers` sizes the event queue and the log buffer. Log events only signal
+    /// that `logs` changed, so they are dropped rather than waited on when the queue is full.
+    pub fn new(runtime: RuntimeHandle, buffers: &BufferConfig) -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::bounded(buffers.event_queue);
         Self {
             event_tx,
             event_rx,
             status: Arc::new(Mutex::new(ServiceStatus::Stopped)),
             logs: Arc::new(Mutex::new(VecDeque::new())),
+            log_capacity: Arc::new(AtomicUsize::new(buffers.log_lines)),
             containers: Arc::new(Mutex::new(Vec::new())),
+            start_progress: Arc::new(Mutex::new(None)),
             docker_available: Arc::new(Mutex::new(false)),
             use_compose_plugin: Arc::new(Mutex::new(false)),
-            background_tasks: Arc::new(Mutex::new(Vec::new())),
+            engine_platform: Arc::new(Mutex::new(String::new())),
+            image_platforms: Arc::new(Mutex::new(HashMap::new())),
+            runtime,
+            log_followers: Arc::new(Mutex::new(HashMap::new())),
+            attached: Arc::new(Mutex::new(Vec::new())),
+            attach_tasks: Arc::new(Mutex::new(HashMap::new())),
+            proxy_env: Arc::new(Mutex::new(Vec::new())),
+            registries: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Use the proxy settings for the compose commands started from now on.
+    pub fn set_proxy(&self, proxy: &ProxyConfig) {
+        *self.proxy_env.lock().unwrap_or_else(|e| e.into_inner()) = proxy.env();
+    }
+
+    fn proxy_env(&self) -> Vec<(String, String)> {
+        self.proxy_env
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Log in to these registries before the pulls started from now on.
+    pub fn set_registries(&self, registries: &[RegistryConfig]) {
+        *self.registries.lock().unwrap_or_else(|e| e.into_inner()) = registries.to_vec();
+    }
+
+    fn registries(&self) -> Vec<RegistryConfig> {
+        self.registries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Save `password` to the keyring, unless empty, and check the registry's
+    /// credentials with `docker login`.
+    pub fn login_registry(&self, registry: RegistryConfig, password: String) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            let saved = match password.is_empty() {
+                true => Ok(()),
+                false => registry::set_password(&registry, &password),
+            };
+            let result = saved.and_then(|()| docker_login(&registry));
+            tx.send(DockerEvent::RegistryLogin(registry.host, result))
+                .ok();
+        });
+    }
+
+    /// Forget the registry's keyring password, off the UI thread.
+    pub fn remove_registry(&self, registry: RegistryConfig) {
+        self.spawn_task(move || {
+            if let Err(e) = registry::delete_password(&registry) {
+                log::warn!("{}: {}", registry.host, e);
+            }
+        });
+    }
+
     pub fn check_docker(&self) {
         let tx = self.event_tx.clone();
         let available = self.docker_available.clone();
         let plugin = self.use_compose_plugin.clone();
+        let engine_platform = self.engine_platform.clone();
 
         self.spawn_task(move || {
-            let result = Command::new("docker").arg("info").output();
-            let is_available = result.map(|o| o.status.success()).unwrap_or(false);
-            *available.lock().unwrap_or_else(|e| e.into_inner()) = is_available;
+            let mut engine = EngineInfo::default();
+            if let Ok(output) = Command::new("docker")
+                .args(["info", "--format", "{{json .SecurityOptions}}"])
+                .output()
+            {
+                engine.available = output.status.success();
+                engine.rootless = String::from_utf8_lossy(&output.stdout).contains("rootless");
+            }
+            // Prints the client half even when the daemon is down
+            if let Ok(output) = Command::new("docker")
+                .args(["version", "--format", "{{json .}}"])
+                .output()
+            {
+                parse_docker_version(&String::from_utf8_lossy(&output.stdout), &mut engine);
+            }
+            *available.lock().unwrap_or_else(|e| e.into_inner()) = engine.available;
+            *engine_platform.lock().unwrap_or_else(|e| e.into_inner()) = engine.platform.clone();
 
             let mut has_compose = false;
             if let Ok(output) = std::process::Command::new("docker")
@@ -100,261 +479,742 @@ impl DockerManager {
             }
             *plugin.lock().unwrap_or_else(|e| e.into_inner()) = has_compose;
 
-            tx.send(DockerEvent::DockerAvailable(is_available)).ok();
+            tx.send(DockerEvent::DockerAvailable(engine)).ok();
         });
     }
 
-    pub fn start_services(&self, project: &ProjectConfig) {
-        let enabled_count = project.services.values().filter(|s| s.enabled).count();
-        if enabled_count == 0 {
-            let msg =
-                "No services enabled! Please enable at least one service in the Services tab."
-                    .to_string();
-            *self.status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-            let tx = self.event_tx.clone();
-            tx.send(DockerEvent::Error(msg)).ok();
-            return;
-        }
-
-        let project = project.clone();
+    /// Launch Docker Desktop (macOS, Windows, Linux desktop installs) or the docker
+    /// system service. Availability is picked up by the next `check_docker`.
+    pub fn start_daemon(&self) {
         let tx = self.event_tx.clone();
-        let status = self.status.clone();
         let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            let (program, mut command) = daemon_start_command();
+            log(format!("[DockStack] Starting Docker ({})...", program));
+
+            match command.output() {
+                Ok(output) if output.status.success() => {}
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    log::warn!("Starting Docker failed: {}", stderr.trim());
+                    tx.send(DockerEvent::Error(DockStackError::from_stderr(
+                        "Failed to start Docker",
+                        &stderr,
+                    )))
+                    .ok();
+                }
+                Err(e) => {
+                    log::warn!("Could not run {}: {}", program, e);
+                    tx.send(DockerEvent::Error(DockStackError::io(
+                        format!("Could not run {}", program),
+                        &e,
+                    )))
+                    .ok();
+                }
+            }
+        });
+    }
 
+    /// Pull the images of the enabled services without starting anything; the
+    /// checklist in `start_progress` follows each service.
+    pub fn pull_images(&self, project: &ProjectConfig) {
+        match compose::check_project_directory(project) {
+            Ok(compose::DirectoryCheck::Ok) => {}
+            Ok(compose::DirectoryCheck::NeedsConfirmation(_)) => {
+                let err = DockStackError::Config(
+                    "Start the stack once to confirm the project directory before pulling images."
+                        .to_string(),
+                );
+                self.event_tx.send(DockerEvent::Error(err)).ok();
+                return;
+            }
+            Err(e) => {
+                let err = DockStackError::io("Project directory", &e);
+                self.event_tx.send(DockerEvent::Error(err)).ok();
+                return;
+            }
+        }
         {
-            let mut status_guard = status.lock().unwrap_or_else(|e| e.into_inner());
-            if matches!(*status_guard, ServiceStatus::Starting | ServiceStatus::Running | ServiceStatus::Stopping) {
-                return; // Prevent duplicate start loops
+            let mut progress = self
+                .start_progress
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if progress.as_ref().is_some_and(|p| p.finished_at.is_none()) {
+                return; // A start or pull is already being tracked
             }
-            *status_guard = ServiceStatus::Starting;
+            *progress = Some(StartProgress::pulling(project));
         }
 
-        tx.send(DockerEvent::StatusChange(
-            "all".to_string(),
-            ServiceStatus::Starting,
-        ))
-        .ok();
-
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        let progress = self.start_progress.clone();
         let use_compose_plugin = self.use_compose_plugin.clone();
+        let proxy_env = self.proxy_env();
+        let registries = self.registries();
 
         self.spawn_task(move || {
-            // Generate and write compose file
-            match compose::write_compose_file(&project) {
-                Ok(compose_path) => {
-                    let msg = format!("[DockStack] Compose file written: {}", compose_path);
-                    logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                    tx.send(DockerEvent::Log(msg)).ok();
-                }
-                Err(e) => {
-                    let msg = format!("[DockStack] Error writing compose file: {}", e);
-                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(e.to_string());
-                    tx.send(DockerEvent::Error(msg)).ok();
-                    return;
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            let finish = |success: bool| {
+                if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                    p.finish(success);
                 }
-            }
+            };
 
-            let msg = "[DockStack] Starting services...".to_string();
-            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-            tx.send(DockerEvent::Log(msg)).ok();
+            if let Err(e) = compose::write_compose_file(&project) {
+                finish(false);
+                tx.send(DockerEvent::Error(DockStackError::io(
+                    "Error writing compose file",
+                    &e,
+                )))
+                .ok();
+                return;
+            }
 
-            // Determine compose command
+            login_registries(&project, &registries, &log);
+            log("[DockStack] Pulling images...".to_string());
             let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
             let (program, args) = if use_plugin {
-                ("docker", vec!["compose", "up", "-d", "--remove-orphans"])
+                ("docker", vec!["compose", "pull"])
             } else {
-                ("docker-compose", vec!["up", "-d", "--remove-orphans"])
+                ("docker-compose", vec!["pull"])
             };
-
-            let mut cmd = Command::new(program);
-            cmd.args(&args)
+            let child = Command::new(program)
+                .args(&args)
+                .envs(proxy_env)
                 .current_dir(&project.directory)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
-
-            // Lazily ensure API connection versions match the running daemon
-            if let Ok(output) = Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]).output() {
-                if output.status.success() {
-                    let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !ver.is_empty() {
-                        cmd.env("DOCKER_API_VERSION", ver);
-                    }
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    finish(false);
+                    let err = DockStackError::spawn("Failed to pull images", program, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
                 }
-            }
-
-            match cmd.spawn() {
-                Ok(mut child) => {
-                    let mut stderr_content = String::new();
+            };
 
-                    // Read stderr
-                    if let Some(stderr) = child.stderr.take() {
-                        let reader = BufReader::new(stderr);
-                        for line in reader.lines().map_while(Result::ok) {
-                            stderr_content.push_str(&line);
-                            stderr_content.push('\n');
-                            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(line.clone());
-                            tx.send(DockerEvent::Log(line)).ok();
+            let mut stderr_content = String::new();
+            let prefix = compose::container_prefix(&project);
+            if let Some(stderr) = child.stderr.take() {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    if let Some((service, stage)) = parse_compose_progress(&line, &prefix) {
+                        if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut()
+                        {
+                            p.set_stage(&service, stage);
                         }
                     }
+                    stderr_content.push_str(&line);
+                    stderr_content.push('\n');
+                    log(line);
+                }
+            }
 
-                    match child.wait() {
-                        Ok(exit) => {
-                            if exit.success() {
-                                *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Running;
-                                let msg = "[DockStack] Services started successfully".to_string();
-                                logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                                tx.send(DockerEvent::Log(msg)).ok();
-                                tx.send(DockerEvent::StatusChange(
-                                    "all".to_string(),
-                                    ServiceStatus::Running,
-                                ))
-                                .ok();
-                            } else {
-                                let error_detail = if !stderr_content.trim().is_empty() {
-                                    stderr_content.trim().to_string()
-                                } else {
-                                    format!("Exit code: {}", exit)
-                                };
-
-                                let combined_log = format!(
-                                    "[DockStack] Failed to start services: {}\nCommand tried: {} {:?}",
-                                    error_detail, program, args
-                                );
-
-                                log::error!("{}", combined_log);
-                                logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(combined_log.clone());
-                                tx.send(DockerEvent::Log(combined_log)).ok(); // Send to logs tab
+            let success = child.wait().is_ok_and(|exit| exit.success());
+            finish(success);
+            if success {
+                log("[DockStack] Images pulled".to_string());
+                tx.send(DockerEvent::ImagesPulled).ok();
+            } else {
+                let err = DockStackError::from_stderr("Failed to pull images", &stderr_content);
+                tx.send(DockerEvent::Error(err)).ok();
+            }
+        });
+    }
 
-                                let short_msg = "Failed to start. Check Logs tab.".to_string();
-                                *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(short_msg.clone());
-                                tx.send(DockerEvent::Error(short_msg)).ok(); // Status update
-                            }
-                        }
-                        Err(e) => {
-                            let msg =
-                                format!("[DockStack] Failed to wait for docker process: {}", e);
-                            log::error!("{}", msg);
-                            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                            *status.lock().unwrap_or_else(|e| e.into_inner()) =
-                                ServiceStatus::Error("Process error. Check Logs.".to_string());
-                            tx.send(DockerEvent::Error(msg)).ok();
-                        }
+    /// Rewrite the project's compose file once its directory needs no confirmation.
+    /// With `required`, an unconfirmed directory is reported as an error; otherwise
+    /// the file is left for the next start to write.
+    pub fn regenerate_compose(&self, project: &ProjectConfig, required: bool) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            let written = match compose::check_project_directory(&project) {
+                Ok(compose::DirectoryCheck::Ok) => compose::write_compose_file(&project),
+                // Confirming here would start the stack; leave that to the Start button
+                Ok(compose::DirectoryCheck::NeedsConfirmation(reason)) => {
+                    if required {
+                        tx.send(DockerEvent::Error(DockStackError::Config(format!(
+                            "{} Start the stack once to confirm the directory.",
+                            reason
+                        ))))
+                        .ok();
                     }
+                    return;
                 }
+                Err(e) => Err(e),
+            };
+            match written {
+                Ok(path) => log::info!("Regenerated {}", path),
                 Err(e) => {
-                    let msg = format!(
-                        "[DockStack] Failed to execute docker compose command ({}): {}",
-                        program, e
-                    );
-                    log::error!("{}", msg);
-                    logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                    *status.lock().unwrap_or_else(|e| e.into_inner()) =
-                        ServiceStatus::Error("Exec error. Check Logs.".to_string());
-                    tx.send(DockerEvent::Error(msg)).ok();
+                    let err = DockStackError::io("Error writing compose file", &e);
+                    tx.send(DockerEvent::Error(err)).ok();
                 }
             }
         });
     }
 
-    pub fn stop_services(&self, project: &ProjectConfig) {
+    /// Resolve every image tag of the project again and rewrite `dockstack.lock`.
+    pub fn update_image_lock(&self, project: &ProjectConfig) {
         let project = project.clone();
         let tx = self.event_tx.clone();
-        let status = self.status.clone();
         let logs = self.logs.clone();
-
-        {
-            let mut status_guard = status.lock().unwrap_or_else(|e| e.into_inner());
-            if matches!(*status_guard, ServiceStatus::Stopping | ServiceStatus::Stopped | ServiceStatus::Starting) {
-                return;
+        let log_capacity = self.log_capacity.clone();
+        let registries = self.registries();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            login_registries(&project, &registries, &log);
+            match lock_images(&project, true, &log) {
+                Ok(count) => {
+                    log(format!(
+                        "[DockStack] {} pins {} images",
+                        image_lock::LOCK_FILE,
+                        count
+                    ));
+                    tx.send(DockerEvent::ImageLockWritten(project.id.clone()))
+                        .ok();
+                }
+                Err(e) => {
+                    let context = format!("Failed to write {}", image_lock::LOCK_FILE);
+                    tx.send(DockerEvent::Error(DockStackError::io(context, &e)))
+                        .ok();
+                }
             }
-            *status_guard = ServiceStatus::Stopping;
-        }
-
-        tx.send(DockerEvent::StatusChange(
-            "all".to_string(),
-            ServiceStatus::Stopping,
-        ))
-        .ok();
+        });
+    }
 
+    /// `compose build --no-cache` for one service built from a local context.
+    pub fn rebuild_image(&self, project: &ProjectConfig, service: &str) {
+        let project = project.clone();
+        let service = service.to_string();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
         let use_compose_plugin = self.use_compose_plugin.clone();
+        let proxy_env = self.proxy_env();
 
         self.spawn_task(move || {
-            let msg = "[DockStack] Stopping services...".to_string();
-            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-            tx.send(DockerEvent::Log(msg)).ok();
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            if let Err(e) = compose::write_compose_file(&project) {
+                let err = DockStackError::io("Error writing compose file", &e);
+                tx.send(DockerEvent::Error(err)).ok();
+                return;
+            }
 
-            // Detect compose
+            log(format!(
+                "[DockStack] Rebuilding {} without cache...",
+                service
+            ));
             let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
-            let (prog, args) = if use_plugin {
-                ("docker", vec!["compose", "down"])
+            let (program, args) = if use_plugin {
+                ("docker", vec!["compose", "build", "--no-cache"])
             } else {
-                ("docker-compose", vec!["down"])
+                ("docker-compose", vec!["build", "--no-cache"])
             };
-
-            let mut cmd = Command::new(prog);
-            cmd.args(&args)
+            let child = Command::new(program)
+                .args(&args)
+                .arg(&service)
+                .envs(proxy_env)
                 .current_dir(&project.directory)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    let err = DockStackError::spawn("Failed to build image", program, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
+                }
+            };
 
-            // Lazily ensure API connection versions match the running daemon
-            if let Ok(output) = Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]).output() {
-                if output.status.success() {
-                    let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !ver.is_empty() {
-                        cmd.env("DOCKER_API_VERSION", ver);
+            // BuildKit writes its progress to stderr
+            let mut stderr_content = String::new();
+            if let Some(stderr) = child.stderr.take() {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    stderr_content.push_str(&line);
+                    stderr_content.push('\n');
+                    log(line);
+                }
+            }
+
+            if child.wait().is_ok_and(|exit| exit.success()) {
+                log(format!("[DockStack] Rebuilt {}", service));
+            } else {
+                let err = DockStackError::from_stderr(
+                    format!("Failed to build {}", service),
+                    &stderr_content,
+                );
+                tx.send(DockerEvent::Error(err)).ok();
+            }
+        });
+    }
+
+    pub fn check_build_cache(&self) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            if let Some(usage) = build_cache_usage() {
+                tx.send(DockerEvent::BuildCacheUsage(usage)).ok();
+            }
+        });
+    }
+
+    /// `docker builder prune`. The build cache is shared by every project on the engine,
+    /// so this frees cache entries no image currently uses, whichever project made them.
+    pub fn prune_build_cache(&self) {
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log("[DockStack] Pruning build cache...".to_string());
+            match Command::new("docker")
+                .args(["builder", "prune", "--force"])
+                .output()
+            {
+                Ok(output) if output.status.success() => {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    if let Some(total) = stdout.lines().find(|l| l.starts_with("Total")) {
+                        log(format!("[DockStack] {}", total.trim()));
+                    }
+                }
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let err = DockStackError::from_stderr("Failed to prune build cache", &stderr);
+                    tx.send(DockerEvent::Error(err)).ok();
+                }
+                Err(e) => {
+                    let err = DockStackError::spawn("Failed to prune build cache", "docker", &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                }
+            }
+            if let Some(usage) = build_cache_usage() {
+                tx.send(DockerEvent::BuildCacheUsage(usage)).ok();
+            }
+        });
+    }
+
+    /// Run the prune steps turned on in `settings`, across every project on the engine.
+    pub fn run_maintenance(&self, settings: &MaintenanceConfig) {
+        let commands = maintenance::prune_commands(settings);
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log("[DockStack] Running scheduled maintenance...".to_string());
+            let mut report = MaintenanceReport::default();
+            for (label, args) in commands {
+                let context = format!("{} could not be pruned", label);
+                match command_output(Command::new("docker").args(&args), &context) {
+                    Ok(output) => {
+                        let bytes = maintenance::reclaimed_bytes(&output);
+                        log(format!(
+                            "[DockStack] {}: {} reclaimed",
+                            label,
+                            format_bytes(bytes)
+                        ));
+                        report.steps.push((label.to_string(), bytes));
+                    }
+                    Err(e) => {
+                        log(format!("[DockStack] {}", e));
+                        report.errors.push(e.to_string());
+                    }
+                }
+            }
+            tx.send(DockerEvent::MaintenanceDone(report)).ok();
+        });
+    }
+
+    /// Start the stack in the background. Returns whether the start was accepted,
+    /// i.e. the project may start and the stack was not already busy.
+    pub fn start_services(&self, project: &ProjectConfig) -> bool {
+        if let Some(err) = start_refusal(project) {
+            self.refuse(err);
+            return false;
+        }
+
+        let mut project = project.clone();
+        let tx = self.event_tx.clone();
+        let status = self.status.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        let engine_platform = self
+            .engine_platform
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let image_platforms = self.image_platforms.clone();
+        let registries = self.registries();
+
+        {
+            let mut status_guard = status.lock().unwrap_or_else(|e| e.into_inner());
+            if matches!(*status_guard, ServiceStatus::Starting | ServiceStatus::Running | ServiceStatus::Stopping) {
+                return false; // Prevent duplicate start loops
+            }
+            *status_guard = ServiceStatus::Starting;
+        }
+
+        tx.send(DockerEvent::StatusChange(
+            "all".to_string(),
+            ServiceStatus::Starting,
+        ))
+        .ok();
+
+        let use_compose_plugin = self.use_compose_plugin.clone();
+        let proxy_env = self.proxy_env();
+        let progress = self.start_progress.clone();
+        *progress.lock().unwrap_or_else(|e| e.into_inner()) = Some(StartProgress::new(&project));
+        let mut cancel = self.runtime.cancel_token();
+
+        self.spawn_task(move || {
+            if !directory_allowed(&project, &tx, &status, DirectoryAction::Start) {
+                *progress.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                return;
+            }
+
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            login_registries(&project, &registries, &log);
+
+            if project.lock_images {
+                match lock_images(&project, false, &log) {
+                    Ok(_) => {
+                        tx.send(DockerEvent::ImageLockWritten(project.id.clone())).ok();
                     }
+                    Err(e) => {
+                        log(format!("[DockStack] Could not write {}: {}", image_lock::LOCK_FILE, e))
+                    }
+                }
+            }
+
+            if is_arm64(&engine_platform) {
+                let fallbacks =
+                    platform_fallbacks(&project, &engine_platform, &image_platforms, &log);
+                if !fallbacks.is_empty() {
+                    for (name, platform) in &fallbacks {
+                        if let Some(svc) = project.services.get_mut(name) {
+                            svc.platform = platform.clone();
+                        }
+                    }
+                    tx.send(DockerEvent::PlatformFallback(project.id.clone(), fallbacks))
+                        .ok();
+                }
+            }
+
+            // Generate and write compose file
+            let compose_path = match compose::write_compose_file(&project) {
+                Ok(compose_path) => {
+                    log(format!("[DockStack] Compose file written: {}", compose_path));
+                    compose_path
+                }
+                Err(e) => {
+                    let err = DockStackError::io("Error writing compose file", &e);
+                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(e.to_string());
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
                 }
+            };
+
+            // Determine compose command
+            let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
+
+            // Catch a broken compose file before any container is touched
+            if let Some(issue) = validate_compose(&project.directory, &compose_path, use_plugin) {
+                log(format!("[DockStack] Compose file is invalid: {}", issue.message));
+                *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                    ServiceStatus::Error("Invalid compose file".to_string());
+                *progress.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                tx.send(DockerEvent::ComposeInvalid(issue)).ok();
+                return;
+            }
+
+            migrate_renames(&project, &tx, &log);
+
+            log("[DockStack] Starting services...".to_string());
+            let (program, mut args) = if use_plugin {
+                ("docker", vec!["compose", "up", "-d", "--remove-orphans"])
+            } else {
+                ("docker-compose", vec!["up", "-d", "--remove-orphans"])
+            };
+            // docker-compose v1 has no --pull flag
+            if use_plugin && PULL_POLICIES.contains(&project.pull_policy.as_str()) {
+                args.extend(["--pull", project.pull_policy.as_str()]);
+            }
+
+            let mut cmd = tokio::process::Command::new(program);
+            cmd.args(&args)
+                .envs(proxy_env)
+                .current_dir(&project.directory)
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            // Lazily ensure API connection versions match the running daemon
+            if let Some(version) = daemon_api_version() {
+                cmd.env("DOCKER_API_VERSION", version);
             }
 
             match cmd.spawn() {
-                Ok(mut child) => {
-                    if let Some(stderr) = child.stderr.take() {
-                        let reader = BufReader::new(stderr);
-                        for line in reader.lines().map_while(Result::ok) {
-                            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(line.clone());
-                            tx.send(DockerEvent::Log(line)).ok();
+                Ok(child) => {
+                    let mut stderr_content = String::new();
+                    let prefix = compose::container_prefix(&project);
+                    let runtime = tokio::runtime::Handle::current();
+
+                    let exit_status = runtime.block_on(child_lines(child, &mut cancel, |line| {
+                        if let Some((service, stage)) = parse_compose_progress(&line, &prefix) {
+                            if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                                p.set_stage(&service, stage);
+                            }
+                        }
+                        stderr_content.push_str(&line);
+                        stderr_content.push('\n');
+                        log(line);
+                    }));
+                    let mut health_failure = None;
+                    if exit_status.as_ref().is_ok_and(|e| e.success()) {
+                        let msg = format!(
+                            "[DockStack] Waiting up to {}s for containers to become healthy...",
+                            project.startup_timeout_secs
+                        );
+                        log(msg);
+                        let healthy = wait_for_healthy(&project, &progress, &mut cancel);
+                        health_failure = runtime.block_on(healthy).err();
+                    }
+                    if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                        p.finish(health_failure.is_none() && exit_status.as_ref().is_ok_and(|e| e.success()));
+                    }
+
+                    if let Some(failure) = health_failure {
+                        let message = format!("[DockStack] {}", failure);
+                        log::error!("{}", message);
+                        log(message);
+                        // The log keeps the failed service's last lines; the error gets the summary
+                        let err = match failure {
+                            DockStackError::Command { context, detail } => DockStackError::Command {
+                                context,
+                                detail: detail.lines().next().unwrap_or_default().to_string(),
+                            },
+                            other => other,
+                        };
+                        *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(err.to_string());
+                        tx.send(DockerEvent::Error(err)).ok();
+                        return;
+                    }
+
+                    match exit_status {
+                        Ok(exit) => {
+                            if exit.success() {
+                                *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Running;
+                                log("[DockStack] Services started successfully".to_string());
+                                sync_files(&project, &log, true);
+                                tx.send(DockerEvent::StatusChange(
+                                    "all".to_string(),
+                                    ServiceStatus::Running,
+                                ))
+                                .ok();
+                            } else {
+                                let error_detail = if !stderr_content.trim().is_empty() {
+                                    stderr_content.trim().to_string()
+                                } else {
+                                    format!("Exit code: {}", exit)
+                                };
+
+                                let combined_log = format!(
+                                    "[DockStack] Failed to start services: {}\nCommand tried: {} {:?}",
+                                    error_detail, program, args
+                                );
+
+                                log::error!("{}", combined_log);
+                                log(combined_log); // Send to logs tab
+
+                                let short_msg = "Failed to start. Check Logs tab.".to_string();
+                                *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(short_msg);
+                                let err = DockStackError::from_stderr("Failed to start services", &error_detail);
+                                tx.send(DockerEvent::Error(err)).ok(); // Status update
+                            }
+                        }
+                        Err(e) => {
+                            let msg =
+                                format!("[DockStack] Failed to wait for docker process: {}", e);
+                            log::error!("{}", msg);
+                            log(msg);
+                            *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                                ServiceStatus::Error("Process error. Check Logs.".to_string());
+                            let err = DockStackError::io("Failed to wait for docker process", &e);
+                            tx.send(DockerEvent::Error(err)).ok();
                         }
                     }
+                }
+                Err(e) => {
+                    if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                        p.finish(false);
+                    }
+                    let msg = format!(
+                        "[DockStack] Failed to execute docker compose command ({}): {}",
+                        program, e
+                    );
+                    log::error!("{}", msg);
+                    log(msg);
+                    *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                        ServiceStatus::Error("Exec error. Check Logs.".to_string());
+                    let err = DockStackError::spawn("Failed to run docker compose", program, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                }
+            }
+        });
+        true
+    }
+
+    pub fn stop_services(&self, project: &ProjectConfig, mode: StopMode) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let status = self.status.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+
+        {
+            let mut status_guard = status.lock().unwrap_or_else(|e| e.into_inner());
+            if matches!(
+                *status_guard,
+                ServiceStatus::Stopping | ServiceStatus::Starting
+            ) {
+                return;
+            }
+            // Down and Destroy also clean up containers left by an earlier Stop
+            if mode == StopMode::Stop && *status_guard == ServiceStatus::Stopped {
+                return;
+            }
+            *status_guard = ServiceStatus::Stopping;
+        }
+
+        tx.send(DockerEvent::StatusChange(
+            "all".to_string(),
+            ServiceStatus::Stopping,
+        ))
+        .ok();
+
+        let use_compose_plugin = self.use_compose_plugin.clone();
+        let mut cancel = self.runtime.cancel_token();
+
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log(mode.progress_message().to_string());
+            sync_files(&project, &log, false);
+
+            // Detect compose
+            let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
+            let (prog, args) = if use_plugin {
+                ("docker", vec!["compose"])
+            } else {
+                ("docker-compose", vec![])
+            };
+
+            let mut cmd = tokio::process::Command::new(prog);
+            cmd.args(&args)
+                .args(mode.compose_args())
+                .current_dir(&project.directory)
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            // Lazily ensure API connection versions match the running daemon
+            if let Some(version) = daemon_api_version() {
+                cmd.env("DOCKER_API_VERSION", version);
+            }
 
-                    match child.wait() {
+            match cmd.spawn() {
+                Ok(child) => {
+                    let mut stderr_content = String::new();
+                    let exited = child_lines(child, &mut cancel, |line| {
+                        stderr_content.push_str(&line);
+                        stderr_content.push('\n');
+                        log(line);
+                    });
+
+                    match tokio::runtime::Handle::current().block_on(exited) {
                         Ok(exit) => {
                             if exit.success() {
                                 *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Stopped;
-                                let msg = "[DockStack] Services stopped".to_string();
-                                logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                                tx.send(DockerEvent::Log(msg)).ok();
+                                log("[DockStack] Services stopped".to_string());
                                 tx.send(DockerEvent::StatusChange(
                                     "all".to_string(),
                                     ServiceStatus::Stopped,
                                 ))
                                 .ok();
                             } else {
-                                let msg =
-                                    format!("[DockStack] docker compose down failed: {}", exit);
-                                *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-                                tx.send(DockerEvent::Error(msg)).ok();
+                                let context = format!(
+                                    "docker compose {} failed",
+                                    mode.compose_args().join(" ")
+                                );
+                                let msg = format!("[DockStack] {}: {}", context, exit);
+                                *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                                    ServiceStatus::Error(msg);
+                                let err = DockStackError::from_stderr(context, &stderr_content);
+                                tx.send(DockerEvent::Error(err)).ok();
                             }
                         }
                         Err(e) => {
                             let msg = format!("[DockStack] Wait error: {}", e);
-                            *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-                            tx.send(DockerEvent::Error(msg)).ok();
+                            *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                                ServiceStatus::Error(msg);
+                            tx.send(DockerEvent::Error(DockStackError::io("Wait error", &e)))
+                                .ok();
                         }
                     }
                 }
                 Err(e) => {
                     let msg = format!("[DockStack] Failed to stop docker compose: {}", e);
-                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-                    tx.send(DockerEvent::Error(msg)).ok();
+                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg);
+                    let err = DockStackError::spawn("Failed to stop docker compose", prog, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
                 }
             }
         });
     }
 
     pub fn stop_services_sync(&self, project: &ProjectConfig) {
-        let msg = "[DockStack] Stopping services before exit...".to_string();
-        self.logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-        self.event_tx.send(DockerEvent::Log(msg)).ok();
+        let log = |msg: String| {
+            let mut lines = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+            push_capped(&mut lines, msg.clone(), &self.log_capacity);
+            self.event_tx.try_send(DockerEvent::Log(msg)).ok();
+        };
+        log("[DockStack] Stopping services before exit...".to_string());
+        sync_files(project, &log, false);
 
         let use_plugin = *self.use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
         let (prog, args) = if use_plugin {
@@ -368,39 +1228,49 @@ impl DockerManager {
             .current_dir(&project.directory)
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit());
-
-        if let Ok(output) = Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]).output() {
-            if output.status.success() {
-                let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !ver.is_empty() {
-                    cmd.env("DOCKER_API_VERSION", ver);
-                }
-            }
+        if let Some(version) = daemon_api_version() {
+            cmd.env("DOCKER_API_VERSION", version);
         }
 
         let _ = cmd.status();
     }
 
-    pub fn restart_services(&self, project: &ProjectConfig) {
+    /// Restart the stack in the background. Returns whether the restart was accepted.
+    pub fn restart_services(&self, project: &ProjectConfig) -> bool {
+        if let Some(err) = start_refusal(project) {
+            self.refuse(err);
+            return false;
+        }
         let project = project.clone();
         let tx = self.event_tx.clone();
         let status = self.status.clone();
         let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
 
         {
             let mut status_guard = status.lock().unwrap_or_else(|e| e.into_inner());
             if matches!(*status_guard, ServiceStatus::Stopping | ServiceStatus::Starting) {
-                return;
+                return false;
             }
             *status_guard = ServiceStatus::Stopping;
         }
 
         let use_compose_plugin = self.use_compose_plugin.clone();
+        let proxy_env = self.proxy_env();
+        let mut cancel = self.runtime.cancel_token();
 
         self.spawn_task(move || {
-            let msg = "[DockStack] Restarting services...".to_string();
-            logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-            tx.send(DockerEvent::Log(msg)).ok();
+            if !directory_allowed(&project, &tx, &status, DirectoryAction::Restart) {
+                return;
+            }
+
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log("[DockStack] Restarting services...".to_string());
+            sync_files(&project, &log, false);
 
             // Detect compose
             let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
@@ -411,33 +1281,34 @@ impl DockerManager {
                 ("docker-compose", vec!["down"])
             };
 
-            let mut stop = Command::new(prog_down);
+            let api_version = daemon_api_version();
+            let runtime = tokio::runtime::Handle::current();
+            let mut stop = tokio::process::Command::new(prog_down);
             stop.args(&args_down)
-                .current_dir(&project.directory);
-                
-            if let Ok(output) = Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]).output() {
-                if output.status.success() {
-                    let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !ver.is_empty() {
-                        stop.env("DOCKER_API_VERSION", ver.clone());
-                    }
-                }
+                .current_dir(&project.directory)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true);
+            if let Some(version) = &api_version {
+                stop.env("DOCKER_API_VERSION", version);
             }
-            
-            let stop_out = stop.output();
 
-            if let Err(e) = stop_out {
-                let msg = format!("[DockStack] Stop failed during restart: {}", e);
-                tx.send(DockerEvent::Error(msg)).ok();
+            let stopped = stop
+                .spawn()
+                .and_then(|child| runtime.block_on(child_lines(child, &mut cancel, drop)));
+            if let Err(e) = stopped {
+                let err = DockStackError::spawn("Stop failed during restart", prog_down, &e);
+                tx.send(DockerEvent::Error(err)).ok();
                 return;
             }
 
             // Regenerate compose
             if let Err(e) = compose::write_compose_file(&project) {
-                let msg = format!("[DockStack] Error writing compose file: {}", e);
-                tx.send(DockerEvent::Error(msg)).ok();
+                let err = DockStackError::io("Error writing compose file", &e);
+                tx.send(DockerEvent::Error(err)).ok();
                 return;
             }
+            migrate_renames(&project, &tx, &log);
 
             // Start
             *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Starting;
@@ -448,45 +1319,52 @@ impl DockerManager {
                 ("docker-compose", vec!["up", "-d", "--remove-orphans"])
             };
             
-            let mut up = Command::new(prog_up);
+            let mut up = tokio::process::Command::new(prog_up);
             up.args(&args_up)
-              .current_dir(&project.directory);
-              
-            if let Ok(output) = Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]).output() {
-                if output.status.success() {
-                    let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !ver.is_empty() {
-                        up.env("DOCKER_API_VERSION", ver);
-                    }
-                }
+                .envs(proxy_env)
+                .current_dir(&project.directory)
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            if let Some(version) = &api_version {
+                up.env("DOCKER_API_VERSION", version);
             }
 
-            match up.output() {
-                Ok(output) => {
-                    if output.status.success() {
+            let mut stderr = String::new();
+            let started = up.spawn().and_then(|child| {
+                runtime.block_on(child_lines(child, &mut cancel, |line| {
+                    stderr.push_str(&line);
+                    stderr.push('\n');
+                }))
+            });
+            match started {
+                Ok(exit) => {
+                    if exit.success() {
                         *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Running;
-                        let msg = "[DockStack] Services restarted successfully".to_string();
-                        logs.lock().unwrap_or_else(|e| e.into_inner()).push_back(msg.clone());
-                        tx.send(DockerEvent::Log(msg)).ok();
+                        log("[DockStack] Services restarted successfully".to_string());
+                        sync_files(&project, &log, true);
                         tx.send(DockerEvent::StatusChange(
                             "all".to_string(),
                             ServiceStatus::Running,
                         ))
                         .ok();
                     } else {
-                        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
                         let msg = format!("[DockStack] Restart failed: {}", stderr);
-                        *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-                        tx.send(DockerEvent::Error(msg)).ok();
+                        *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                            ServiceStatus::Error(msg);
+                        let err = DockStackError::from_stderr("Restart failed", &stderr);
+                        tx.send(DockerEvent::Error(err)).ok();
                     }
                 }
                 Err(e) => {
                     let msg = format!("[DockStack] Restart failed: {}", e);
-                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg.clone());
-                    tx.send(DockerEvent::Error(msg)).ok();
+                    *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(msg);
+                    let err = DockStackError::spawn("Restart failed", prog_up, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
                 }
             }
         });
+        true
     }
 
     pub fn refresh_containers(&self, project: &ProjectConfig) {
@@ -503,13 +1381,16 @@ impl DockerManager {
                 .arg("--filter")
                 .arg(format!("name=dockstack_{}_", project_id))
                 .arg("--format")
-                .arg("{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}|{{.Ports}}|{{.State}}")
+                .arg(concat!(
+                    "{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}|{{.Ports}}|{{.State}}|",
+                    r#"{{.Label "com.docker.compose.service"}}"#
+                ))
                 .output();
 
             match output {
                 Ok(out) => {
                     let stdout = String::from_utf8_lossy(&out.stdout);
-                    let list: Vec<ContainerInfo> = stdout
+                    let mut list: Vec<ContainerInfo> = stdout
                         .lines()
                         .filter(|l| !l.is_empty())
                         .map(|line| {
@@ -521,19 +1402,21 @@ impl DockerManager {
                                 status: parts.get(3).unwrap_or(&"").to_string(),
                                 ports: parts.get(4).unwrap_or(&"").to_string(),
                                 state: parts.get(5).unwrap_or(&"").to_string(),
+                                service: parts.get(6).unwrap_or(&"").to_string(),
+                                restart_count: 0,
+                                exit_code: 0,
+                                crash_log: None,
                             }
                         })
                         .collect();
+                    inspect_restarts(&mut list);
 
                     *containers.lock().unwrap_or_else(|e| e.into_inner()) = list.clone();
                     tx.send(DockerEvent::ContainerList(list)).ok();
                 }
                 Err(e) => {
-                    tx.send(DockerEvent::Error(format!(
-                        "Failed to list containers: {}",
-                        e
-                    )))
-                    .ok();
+                    let err = DockStackError::spawn("Failed to list containers", "docker", &e);
+                    tx.send(DockerEvent::Error(err)).ok();
                 }
             }
         });
@@ -543,10 +1426,12 @@ impl DockerManager {
         let project = project.clone();
         let tx = self.event_tx.clone();
         let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
 
         let use_compose_plugin = self.use_compose_plugin.clone();
+        let mut cancel = self.runtime.cancel_token();
 
-        self.spawn_task(move || {
+        self.runtime.spawn(async move {
             // Detect compose
             let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
             let (prog, args) = if use_plugin {
@@ -555,39 +1440,1974 @@ impl DockerManager {
                 ("docker-compose", vec!["logs", "-f", "--tail", "100"])
             };
 
-            let mut cmd = Command::new(prog);
+            let mut cmd = tokio::process::Command::new(prog);
             cmd.args(&args)
                 .current_dir(&project.directory)
+                .stdin(Stdio::null())
                 .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
+                .stderr(Stdio::null())
+                .kill_on_drop(true);
 
             match cmd.spawn() {
-                Ok(mut child) => {
-                    if let Some(stdout) = child.stdout.take() {
-                        let reader = BufReader::new(stdout);
-                        for line in reader.lines().map_while(Result::ok) {
-                            {
-                                let mut l = logs.lock().unwrap_or_else(|e| e.into_inner());
-                                l.push_back(line.clone());
-                                if l.len() > 5000 {
-                                    let drain_count = l.len() - 3000;
-                                    l.drain(0..drain_count);
-                                }
-                            }
-                            tx.send(DockerEvent::Log(line)).ok();
+                Ok(child) => {
+                    let followed = child_lines(child, &mut cancel, |line| {
+                        {
+                            let mut l = logs.lock().unwrap_or_else(|e| e.into_inner());
+                            push_capped(&mut l, line.clone(), &log_capacity);
                         }
-                    }
-                    child.wait().ok(); // Avoid zombie process
+                        tx.try_send(DockerEvent::Log(line)).ok();
+                    });
+                    followed.await.ok();
                 }
                 Err(e) => {
-                    tx.send(DockerEvent::Error(format!("Failed to stream logs: {}", e)))
-                        .ok();
+                    let err = DockStackError::spawn("Failed to stream logs", prog, &e);
+                    tx.send(DockerEvent::Error(err)).ok();
                 }
             }
         });
     }
 
-    pub fn clear_logs(&self) {
-        self.logs.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    /// Follow one container's output into the Logs tab, each line prefixed with `[label]`.
+    /// The follower runs until the container goes away, it is stopped, or the app exits.
+    pub fn follow_container_logs(&self, container: &str, label: &str) {
+        let mut followers = self.log_followers.lock().unwrap_or_else(|e| e.into_inner());
+        followers.retain(|_, task| !task.is_finished());
+        if followers.contains_key(container) {
+            return;
+        }
+
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        let tx = self.event_tx.clone();
+        let mut cancel = self.runtime.cancel_token();
+        let container_name = container.to_string();
+        let prefix = format!("[{}] ", label);
+        let task = self.runtime.spawn(async move {
+            let followed = follow_output(&container_name, &mut cancel, |line| {
+                let mut l = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut l, format!("{}{}", prefix, line), &log_capacity);
+            })
+            .await;
+            if let Err(e) = followed {
+                let msg = format!(
+                    "[DockStack] Cannot follow logs of {}: {}",
+                    container_name, e
+                );
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            }
+        });
+        followers.insert(container.to_string(), task);
+    }
+
+    /// Follow one container's output into its own buffer in `attached`, apart
+    /// from the stack output. Attaching again to a finished one restarts it.
+    pub fn attach_container(&self, container: &str, label: &str) {
+        let mut tasks = self.attach_tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.retain(|_, task| !task.is_finished());
+        if tasks.contains_key(container) {
+            return;
+        }
+        {
+            let mut attached = self.attached.lock().unwrap_or_else(|e| e.into_inner());
+            match attached.iter_mut().find(|a| a.container == container) {
+                // `--tail` replays the recent lines, so start over rather than repeat them
+                Some(output) => {
+                    output.ended = false;
+                    output.lines.clear();
+                }
+                None => attached.push(AttachedOutput {
+                    container: container.to_string(),
+                    label: label.to_string(),
+                    ..AttachedOutput::default()
+                }),
+            }
+        }
+
+        let attached = self.attached.clone();
+        let log_capacity = self.log_capacity.clone();
+        let mut cancel = self.runtime.cancel_token();
+        let container_name = container.to_string();
+        let task = self.runtime.spawn(async move {
+            let push = |line: String| {
+                let mut attached = attached.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(output) = attached.iter_mut().find(|a| a.container == container_name) {
+                    push_capped(&mut output.lines, line, &log_capacity);
+                }
+            };
+            if let Err(e) = follow_output(&container_name, &mut cancel, push).await {
+                push(format!(
+                    "[DockStack] Cannot follow logs of {}: {}",
+                    container_name, e
+                ));
+            }
+            let mut attached = attached.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(output) = attached.iter_mut().find(|a| a.container == container_name) {
+                output.ended = true;
+            }
+        });
+        tasks.insert(container.to_string(), task);
+    }
+
+    /// Stop following an attached container and drop its buffer.
+    pub fn detach_container(&self, container: &str) {
+        let mut tasks = self.attach_tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = tasks.remove(container) {
+            task.abort();
+        }
+        self.attached
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|a| a.container != container);
+    }
+
+    /// Stop every `docker logs -f` started by `follow_container_logs`.
+    pub fn stop_log_followers(&self) {
+        let mut followers = self.log_followers.lock().unwrap_or_else(|e| e.into_inner());
+        for (_, task) in followers.drain() {
+            task.abort();
+        }
+    }
+
+    /// Find DockStack containers, networks and volumes whose project id is not in `known_ids`.
+    pub fn scan_orphans(&self, known_ids: Vec<String>) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            let orphans = find_orphans(&known_ids);
+            tx.send(DockerEvent::OrphanedResources(orphans)).ok();
+        });
+    }
+
+    /// List running containers that no DockStack project owns.
+    pub fn discover_containers(&self) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            tx.send(DockerEvent::ForeignContainers(find_foreign_containers()))
+                .ok();
+        });
+    }
+
+    /// List the containers of every project for the All Projects overview.
+    pub fn refresh_fleet(&self) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            if let Some(fleet) = list_fleet() {
+                tx.send(DockerEvent::Fleet(fleet)).ok();
+            }
+        });
+    }
+
+    /// Start or stop a project other than the active one. `status` follows the
+    /// active project, so it is left alone; the outcome shows in the logs and the
+    /// next `Fleet` listing. Starting needs the directory confirmed already.
+    /// Returns whether the request was accepted.
+    pub fn set_background_project(&self, project: &ProjectConfig, run: bool) -> bool {
+        if run {
+            if let Some(err) = start_refusal(project) {
+                self.event_tx.send(DockerEvent::Error(err)).ok();
+                return false;
+            }
+            if !matches!(
+                compose::check_project_directory(project),
+                Ok(compose::DirectoryCheck::Ok)
+            ) {
+                let err = DockStackError::Config(format!(
+                    "Open {} and start it once to confirm its directory.",
+                    project.display_name()
+                ));
+                self.event_tx.send(DockerEvent::Error(err)).ok();
+                return false;
+            }
+        }
+
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        let use_compose_plugin = self.use_compose_plugin.clone();
+        let proxy_env = self.proxy_env();
+
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            let name = project.display_name();
+            if run {
+                if let Err(e) = compose::write_compose_file(&project) {
+                    let err = DockStackError::io("Error writing compose file", &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
+                }
+            }
+            sync_files(&project, &log, run);
+
+            let use_plugin = *use_compose_plugin.lock().unwrap_or_else(|e| e.into_inner());
+            let mut args = if use_plugin { vec!["compose"] } else { vec![] };
+            if run {
+                log(format!("[DockStack] Starting {}...", name));
+                args.extend(["up", "-d", "--remove-orphans"]);
+            } else {
+                log(format!("[DockStack] Stopping {}...", name));
+                args.push("stop");
+            }
+            let program = if use_plugin {
+                "docker"
+            } else {
+                "docker-compose"
+            };
+            let (verb, context) = if run {
+                ("started", format!("Failed to start {}", name))
+            } else {
+                ("stopped", format!("Failed to stop {}", name))
+            };
+            let result = run_checked(
+                Command::new(program)
+                    .args(&args)
+                    .envs(proxy_env)
+                    .current_dir(&project.directory),
+                &context,
+            );
+            match result {
+                Ok(_) => log(format!("[DockStack] {} {}", name, verb)),
+                Err(err) => {
+                    tx.send(DockerEvent::Error(err)).ok();
+                }
+            }
+            if let Some(fleet) = list_fleet() {
+                tx.send(DockerEvent::Fleet(fleet)).ok();
+            }
+        });
+        true
+    }
+
+    /// Assemble a Markdown report of one container from `docker inspect` and its
+    /// last log lines, for pasting into a chat or an issue.
+    pub fn container_report(&self, container: &str) {
+        let tx = self.event_tx.clone();
+        let container = container.to_string();
+        self.spawn_task(move || {
+            let inspected = run_checked(
+                Command::new("docker")
+                    .args(["inspect", "--type", "container"])
+                    .arg(&container),
+                "docker inspect failed",
+            );
+            let inspect = match inspected {
+                Ok(out) => serde_json::from_slice::<Vec<serde_json::Value>>(&out)
+                    .ok()
+                    .and_then(|mut list| list.pop())
+                    .unwrap_or_default(),
+                Err(err) => {
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
+                }
+            };
+            let logs = container_tail(&container, REPORT_LOG_LINES);
+            let report = diagnostics::redact(&container_report(&inspect, &logs));
+            tx.send(DockerEvent::ContainerReport(container, report))
+                .ok();
+        });
+    }
+
+    /// Reconstruct service definitions for containers started with `docker run`.
+    pub fn capture_containers(&self, names: Vec<String>) {
+        let tx = self.event_tx.clone();
+        self.spawn_task(move || {
+            let inspected = Command::new("docker").arg("inspect").args(&names).output();
+            let containers = match inspected {
+                Ok(out) if out.status.success() => {
+                    serde_json::from_slice::<Vec<serde_json::Value>>(&out.stdout)
+                        .unwrap_or_default()
+                }
+                Ok(out) => {
+                    let stderr = String::from_utf8_lossy(&out.stderr);
+                    let err = DockStackError::from_stderr("docker inspect failed", &stderr);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
+                }
+                Err(e) => {
+                    let err = DockStackError::spawn("docker inspect failed", "docker", &e);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    return;
+                }
+            };
+            let services = containers
+                .iter()
+                .filter_map(|container| captured_service(container, &image_config(container)))
+                .collect();
+            tx.send(DockerEvent::ContainersCaptured(services)).ok();
+        });
+    }
+
+    pub fn remove_orphans(&self, orphans: Vec<OrphanResource>, known_ids: Vec<String>) {
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            // Containers first so their networks and volumes are no longer in use
+            for kind in [
+                ResourceKind::Container,
+                ResourceKind::Network,
+                ResourceKind::Volume,
+            ] {
+                for orphan in orphans.iter().filter(|o| o.kind == kind) {
+                    let args: &[&str] = match kind {
+                        ResourceKind::Container => &["rm", "-f"],
+                        ResourceKind::Network => &["network", "rm"],
+                        ResourceKind::Volume => &["volume", "rm"],
+                    };
+                    let result = Command::new("docker").args(args).arg(&orphan.name).output();
+                    log(match result {
+                        Ok(out) if out.status.success() => format!(
+                            "[DockStack] Removed orphaned {} {}",
+                            kind.label().to_lowercase(),
+                            orphan.name
+                        ),
+                        Ok(out) => format!(
+                            "[DockStack] Failed to remove {} {}: {}",
+                            kind.label().to_lowercase(),
+                            orphan.name,
+                            String::from_utf8_lossy(&out.stderr).trim()
+                        ),
+                        Err(e) => format!(
+                            "[DockStack] Failed to remove {} {}: {}",
+                            kind.label().to_lowercase(),
+                            orphan.name,
+                            e
+                        ),
+                    });
+                }
+            }
+
+            let remaining = find_orphans(&known_ids);
+            tx.send(DockerEvent::OrphanedResources(remaining)).ok();
+        });
+    }
+
+    /// Check the host, Docker and the project's files, and report what is wrong.
+    pub fn run_doctor(&self, project: &ProjectConfig, known_ids: Vec<String>) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let use_plugin = *self
+            .use_compose_plugin
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let running_services = self
+            .containers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|c| c.state == "running")
+            .map(|c| c.service.clone())
+            .collect();
+        self.spawn_task(move || {
+            let (program, args): (&str, &[&str]) = if use_plugin {
+                ("docker", &["compose", "version", "--short"])
+            } else {
+                ("docker-compose", &["version", "--short"])
+            };
+            let version = |out: Vec<u8>| String::from_utf8_lossy(&out).trim().to_string();
+            let facts = DockerFacts {
+                daemon: run_checked(
+                    Command::new("docker").args(["version", "--format", "{{.Server.Version}}"]),
+                    "docker version",
+                )
+                .map(version),
+                compose: run_checked(Command::new(program).args(args), "compose version")
+                    .map(version),
+                running_services,
+                rootless: cfg!(target_os = "linux") && is_rootless(),
+                orphans: find_orphans(&known_ids),
+            };
+            let report = doctor::diagnose(&project, facts);
+            tx.send(DockerEvent::DoctorReport(Box::new(report))).ok();
+        });
+    }
+
+    /// Scan the images of the enabled services with Trivy, or Docker Scout when
+    /// Trivy is not installed, and report what they find.
+    pub fn scan_images(&self, project: &ProjectConfig) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            let found = |cmd: &mut Command| command_output(cmd, "Scanner check").is_ok();
+            let scanner = if found(Command::new("trivy").arg("--version")) {
+                Scanner::Trivy
+            } else if found(Command::new("docker").args(["scout", "version"])) {
+                Scanner::Scout
+            } else {
+                let err = DockStackError::Config(
+                    "Scanning images needs Trivy or the Docker Scout CLI plugin.".to_string(),
+                );
+                tx.send(DockerEvent::ImageScan(Err(err))).ok();
+                return;
+            };
+
+            let targets = image_scan::targets(&project);
+            let mut report = ScanReport::new(&project, scanner, targets.len());
+            for (image, services) in targets {
+                log(format!(
+                    "[DockStack] Scanning {} with {}",
+                    image,
+                    scanner.label()
+                ));
+                let (output, parse): (_, fn(&str, &str, &[String]) -> _) = match scanner {
+                    Scanner::Trivy => (
+                        command_output(
+                            Command::new("trivy")
+                                .args(["image", "--quiet", "--format", "json"])
+                                .args(["--scanners", "vuln"])
+                                .arg(&image),
+                            "Trivy failed",
+                        ),
+                        image_scan::parse_trivy,
+                    ),
+                    Scanner::Scout => (
+                        command_output(
+                            Command::new("docker")
+                                .args(["scout", "cves", "--format", "gitlab"])
+                                .arg(&image),
+                            "Docker Scout failed",
+                        ),
+                        image_scan::parse_scout,
+                    ),
+                };
+                let scanned = output.and_then(|json| parse(&json, &image, &services));
+                match scanned {
+                    Ok(findings) => report.add(findings),
+                    Err(e) => {
+                        log(format!("[DockStack] Could not scan {}: {}", image, e));
+                        report.errors.push(format!("{}: {}", image, e));
+                    }
+                }
+            }
+            log(format!(
+                "[DockStack] Image scan done: {} critical, {} high",
+                report.count(Severity::Critical),
+                report.count(Severity::High)
+            ));
+            tx.send(DockerEvent::ImageScan(Ok(Box::new(report)))).ok();
+        });
+    }
+
+    /// Archive the project's named volumes and config into `<project>/backups/`.
+    pub fn create_snapshot(&self, project: &ProjectConfig) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log("[DockStack] Creating snapshot...".to_string());
+
+            match write_snapshot(&project) {
+                Ok(path) => {
+                    log(format!("[DockStack] Snapshot saved: {}", path.display()));
+                    tx.send(DockerEvent::SnapshotCreated(path)).ok();
+                }
+                Err(e) => {
+                    let msg = format!("[DockStack] Snapshot failed: {}", e);
+                    log::error!("{}", msg);
+                    log(msg);
+                    tx.send(DockerEvent::Error(e)).ok();
+                }
+            }
+        });
+    }
+
+    /// Recreate the project's named volumes from a snapshot archive. The stack must be stopped.
+    pub fn restore_snapshot(&self, project: &ProjectConfig, archive: PathBuf) {
+        let status = self
+            .status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if !matches!(status, ServiceStatus::Stopped | ServiceStatus::Error(_)) {
+            let err =
+                DockStackError::Config("Stop the stack before restoring a snapshot.".to_string());
+            self.event_tx.send(DockerEvent::Error(err)).ok();
+            return;
+        }
+
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+
+        self.spawn_task(move || {
+            let log = |msg: String| {
+                let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+                push_capped(&mut lines, msg.clone(), &log_capacity);
+                tx.try_send(DockerEvent::Log(msg)).ok();
+            };
+            log(format!(
+                "[DockStack] Restoring snapshot {}...",
+                archive.display()
+            ));
+
+            match read_snapshot(&project, &archive) {
+                Ok(restored) => {
+                    log("[DockStack] Snapshot restored".to_string());
+                    tx.send(DockerEvent::SnapshotRestored(Box::new(restored)))
+                        .ok();
+                }
+                Err(e) => {
+                    let msg = format!("[DockStack] Snapshot restore failed: {}", e);
+                    log::error!("{}", msg);
+                    log(msg);
+                    tx.send(DockerEvent::Error(e)).ok();
+                }
+            }
+        });
+    }
+
+    /// Zip `files` together with Docker's version and info, the project's compose
+    /// file, the newest crash report and the DockStack log buffer into `dest`,
+    /// redacting secrets.
+    pub fn export_diagnostics(
+        &self,
+        project: Option<&ProjectConfig>,
+        dest: PathBuf,
+        mut files: Vec<(String, String)>,
+    ) {
+        let project = project.cloned();
+        let tx = self.event_tx.clone();
+        let logs = self.logs.clone();
+        let log_capacity = self.log_capacity.clone();
+
+        self.spawn_task(move || {
+            let output = |args: &[&str]| {
+                let context = format!("`docker {}` failed", args.join(" "));
+                command_output(Command::new("docker").args(args), &context)
+                    .unwrap_or_else(|e| e.to_string())
+            };
+            files.push(("docker-version.txt".to_string(), output(&["version"])));
+            files.push(("docker-info.txt".to_string(), output(&["info"])));
+            if let Some(project) = &project {
+                let compose_path = Path::new(&project.directory).join("docker-compose.yml");
+                let compose = fs::read_to_string(&compose_path)
+                    .unwrap_or_else(|e| format!("{}: {}", compose_path.display(), e));
+                files.push(("docker-compose.yml".to_string(), compose));
+                if let Ok(config) = toml::to_string_pretty(project) {
+                    files.push(("project.toml".to_string(), config));
+                }
+            }
+            let log_lines: Vec<String> = logs
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .cloned()
+                .collect();
+            files.push(("dockstack-logs.txt".to_string(), log_lines.join("\n")));
+            if let Some((name, report)) = diagnostics::latest_crash_report() {
+                files.push((format!("crash-reports/{}", name), report));
+            }
+
+            let msg = match diagnostics::write_bundle(&dest, &files) {
+                Ok(()) => format!("[DockStack] Diagnostics saved to {}", dest.display()),
+                Err(e) => {
+                    let err = DockStackError::io("Failed to write the diagnostics bundle", &e);
+                    let msg = format!("[DockStack] {}", err);
+                    tx.send(DockerEvent::Error(err)).ok();
+                    msg
+                }
+            };
+            let mut lines = logs.lock().unwrap_or_else(|e| e.into_inner());
+            push_capped(&mut lines, msg.clone(), &log_capacity);
+            tx.try_send(DockerEvent::Log(msg)).ok();
+        });
+    }
+
+    /// Resize the log buffer, dropping the oldest lines when it shrinks.
+    pub fn set_log_capacity(&self, lines: usize) {
+        self.log_capacity.store(lines, Ordering::Relaxed);
+        let mut logs = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+        while logs.len() > lines.max(1) {
+            logs.pop_front();
+        }
+    }
+
+    pub fn clear_logs(&self) {
+        self.logs.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Poll the project's containers after `up -d` until they are running (and healthy when a
+/// healthcheck exists). A failed service's error ends with its last log lines.
+async fn wait_for_healthy(
+    project: &ProjectConfig,
+    progress: &Arc<Mutex<Option<StartProgress>>>,
+    cancel: &mut CancelToken,
+) -> DsResult<()> {
+    let context = "Startup health check";
+    let failure = |detail: String| DockStackError::Command {
+        context: context.to_string(),
+        detail,
+    };
+    let deadline = Instant::now() + std::time::Duration::from_secs(project.startup_timeout_secs);
+    let prefix = compose::container_prefix(project);
+
+    loop {
+        let output = tokio::process::Command::new("docker")
+            .args(["ps", "-a", "--filter"])
+            .arg(format!("name={}", prefix))
+            .args(["--format", "{{.Names}}|{{.State}}|{{.Status}}"])
+            .output()
+            .await
+            .map_err(|e| DockStackError::spawn(context, "docker", &e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(DockStackError::from_stderr(context, &stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // `up -d` may return before compose has created every container
+        let mut pending = Vec::new();
+        let mut found = false;
+        for line in stdout.lines() {
+            let parts: Vec<&str> = line.splitn(3, '|').collect();
+            let (name, state, status) = match parts.as_slice() {
+                [name, state, status] => (*name, *state, *status),
+                _ => continue,
+            };
+            let service = name.strip_prefix(prefix.as_str()).unwrap_or(name);
+            found = true;
+
+            let failed =
+                matches!(state, "exited" | "dead" | "restarting") || status.contains("(unhealthy)");
+            if failed {
+                if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                    p.set_stage(service, StartStage::Failed);
+                }
+                return Err(failure(format!(
+                    "Service '{}' failed to start ({})\n{}",
+                    service,
+                    status,
+                    container_tail(name, 20)
+                )));
+            }
+
+            if status.contains("(health: starting)") || state != "running" {
+                pending.push(service.to_string());
+            } else if status.contains("(healthy)") {
+                if let Some(p) = progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                    p.set_stage(service, StartStage::Healthy);
+                }
+            }
+        }
+
+        if found && pending.is_empty() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let waiting_for = match found {
+                true => pending.join(", "),
+                false => format!("containers named {}*", prefix),
+            };
+            return Err(failure(format!(
+                "Timed out after {}s waiting for: {}",
+                project.startup_timeout_secs, waiting_for
+            )));
+        }
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                return Err(failure("cancelled".to_string()));
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_secs(1)) => {}
+        }
+    }
+}
+
+/// Run `docker logs -f` on a container, handing each line of either stream to
+/// `on_line` until the container goes away or the app exits. Errors only when
+/// docker cannot be started.
+async fn follow_output(
+    container: &str,
+    cancel: &mut CancelToken,
+    on_line: impl FnMut(String),
+) -> std::io::Result<()> {
+    let child = tokio::process::Command::new("docker")
+        .args(["logs", "-f", "--tail", "100"])
+        .arg(container)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    // Containers write to both streams. An exit status, or being cut short by
+    // shutdown, is no error for a follower.
+    child_lines(child, cancel, on_line).await.ok();
+    Ok(())
+}
+
+/// Hand each line `child` writes to its piped streams to `on_line` and return its
+/// exit status. The child is killed, and an `Interrupted` error returned, when the
+/// app shuts down first.
+async fn child_lines(
+    mut child: tokio::process::Child,
+    cancel: &mut CancelToken,
+    mut on_line: impl FnMut(String),
+) -> std::io::Result<std::process::ExitStatus> {
+    let mut stdout = child
+        .stdout
+        .take()
+        .map(|s| tokio::io::BufReader::new(s).lines());
+    let mut stderr = child
+        .stderr
+        .take()
+        .map(|s| tokio::io::BufReader::new(s).lines());
+    while stdout.is_some() || stderr.is_some() {
+        // A stream that ends or cannot be read is closed
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                child.kill().await.ok();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "DockStack is shutting down",
+                ));
+            }
+            line = next_line(&mut stdout), if stdout.is_some() => match line {
+                Some(line) => on_line(line),
+                None => stdout = None,
+            },
+            line = next_line(&mut stderr), if stderr.is_some() => match line {
+                Some(line) => on_line(line),
+                None => stderr = None,
+            },
+        }
+    }
+    child.wait().await
+}
+
+async fn next_line<R: tokio::io::AsyncBufRead + Unpin>(
+    lines: &mut Option<tokio::io::Lines<R>>,
+) -> Option<String> {
+    lines.as_mut()?.next_line().await.ok().flatten()
+}
+
+/// API version of the running daemon, so compose talks to it in a version it speaks.
+fn daemon_api_version() -> Option<String> {
+    command_output(
+        Command::new("docker").args(["version", "--format", "{{.Server.APIVersion}}"]),
+        "Daemon API version",
+    )
+    .ok()
+    .filter(|version| !version.is_empty())
+}
+
+/// DockStack's containers on this engine grouped by their project label, or
+/// `None` when docker cannot be asked.
+fn list_fleet() -> Option<HashMap<String, ProjectContainers>> {
+    let format = format!(
+        "{{{{.Label \"{}\"}}}}|{{{{.Names}}}}|{{{{.State}}}}",
+        compose::PROJECT_LABEL
+    );
+    let out = command_output(
+        Command::new("docker")
+            .args(["ps", "-a", "--filter"])
+            .arg(format!("label={}", compose::PROJECT_LABEL))
+            .arg("--format")
+            .arg(format),
+        "Failed to list DockStack containers",
+    )
+    .ok()?;
+    let mut fleet: HashMap<String, ProjectContainers> = HashMap::new();
+    for line in out.lines() {
+        let mut parts = line.split('|');
+        let (Some(id), Some(name), Some(state)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let entry = fleet.entry(id.to_string()).or_default();
+        if state == "running" {
+            entry.running.push(name.to_string());
+        } else {
+            entry.stopped += 1;
+        }
+    }
+    Some(fleet)
+}
+
+/// Log lines included in a container report.
+const REPORT_LOG_LINES: usize = 50;
+
+/// Markdown summary of one `docker inspect` entry and its log tail. Plain bullets
+/// and a code block, which render the same in chat apps and issue trackers.
+/// Environment variables are left out; they are where secrets live.
+fn container_report(inspect: &serde_json::Value, logs: &str) -> String {
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+    let name = text(&inspect["Name"]).trim_start_matches('/').to_string();
+    let config = &inspect["Config"];
+    let state = &inspect["State"];
+    let host = &inspect["HostConfig"];
+
+    let mut out = format!("**Container `{}`**", name);
+    if let Some(service) = config["Labels"]["com.docker.compose.service"].as_str() {
+        out.push_str(&format!(" (service `{}`)", service));
+    }
+    out.push('\n');
+    let image_id = text(&inspect["Image"]);
+    let image_id = image_id.trim_start_matches("sha256:");
+    out.push_str(&format!(
+        "- Image: `{}` (`{}`)\n",
+        text(&config["Image"]),
+        &image_id[..image_id.len().min(12)]
+    ));
+
+    let mut status = text(&state["Status"]);
+    if status != "running" {
+        status.push_str(&format!(
+            ", exit code {}",
+            state["ExitCode"].as_i64().unwrap_or(0)
+        ));
+    }
+    if state["OOMKilled"].as_bool() == Some(true) {
+        status.push_str(", OOM killed");
+    }
+    out.push_str(&format!("- State: {}\n", status));
+    out.push_str(&format!("- Started: {}\n", text(&state["StartedAt"])));
+    if status != "running" {
+        out.push_str(&format!("- Finished: {}\n", text(&state["FinishedAt"])));
+    }
+    out.push_str(&format!(
+        "- Restarts: {} (policy `{}`)\n",
+        inspect["RestartCount"].as_u64().unwrap_or(0),
+        text(&host["RestartPolicy"]["Name"])
+    ));
+    if let Some(health) = state["Health"]["Status"].as_str() {
+        let last = state["Health"]["Log"]
+            .as_array()
+            .and_then(|checks| checks.last())
+            .map(|check| text(&check["Output"]).trim().replace('\n', " "))
+            .unwrap_or_default();
+        if last.is_empty() {
+            out.push_str(&format!("- Health: {}\n", health));
+        } else {
+            out.push_str(&format!("- Health: {}, last check: `{}`\n", health, last));
+        }
+    }
+    let error = text(&state["Error"]);
+    if !error.is_empty() {
+        out.push_str(&format!("- Error: `{}`\n", error));
+    }
+
+    let mut ports = Vec::new();
+    if let Some(bindings) = inspect["NetworkSettings"]["Ports"].as_object() {
+        for (container_port, hosts) in bindings {
+            for binding in hosts.as_array().into_iter().flatten() {
+                ports.push(format!(
+                    "`{}:{}->{}`",
+                    text(&binding["HostIp"]),
+                    text(&binding["HostPort"]),
+                    container_port
+                ));
+            }
+        }
+    }
+    if !ports.is_empty() {
+        out.push_str(&format!("- Ports: {}\n", ports.join(", ")));
+    }
+    let mounts: Vec<String> = inspect["Mounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|m| {
+            let source = match m["Type"].as_str() {
+                Some("volume") => text(&m["Name"]),
+                _ => text(&m["Source"]),
+            };
+            let mode = if m["RW"].as_bool() == Some(false) {
+                "ro"
+            } else {
+                "rw"
+            };
+            format!("`{}:{}` ({})", source, text(&m["Destination"]), mode)
+        })
+        .collect();
+    if !mounts.is_empty() {
+        out.push_str(&format!("- Mounts: {}\n", mounts.join(", ")));
+    }
+    if let Some(networks) = inspect["NetworkSettings"]["Networks"].as_object() {
+        let networks: Vec<String> = networks
+            .iter()
+            .map(|(name, net)| format!("`{}` ({})", name, text(&net["IPAddress"])))
+            .collect();
+        out.push_str(&format!("- Networks: {}\n", networks.join(", ")));
+    }
+
+    // Output with a fence of its own would close ours early
+    let fence = if logs.contains("```") { "````" } else { "```" };
+    out.push_str(&format!(
+        "\nLast {} log lines:\n{}text\n{}\n{}\n",
+        REPORT_LOG_LINES, fence, logs, fence
+    ));
+    out
+}
+
+/// Resolve the project's image tags missing from `dockstack.lock`, or all of
+/// them when `refresh`, and save the lock if it changed. Tags that cannot be
+/// resolved stay unpinned. Returns how many images the lock pins.
+fn lock_images(
+    project: &ProjectConfig,
+    refresh: bool,
+    log: &dyn Fn(String),
+) -> std::io::Result<usize> {
+    let previous = ImageLock::load(project);
+    let mut lock = if refresh {
+        ImageLock::default()
+    } else {
+        previous.clone()
+    };
+    for reference in lock.missing(project) {
+        log(format!("[DockStack] Resolving {}", reference));
+        match resolve_digest(&reference) {
+            Ok(digest) => {
+                lock.images.insert(reference, digest);
+            }
+            Err(e) => log(format!("[DockStack] {}, so it starts unpinned", e)),
+        }
+    }
+    if lock != previous {
+        lock.save(project)?;
+        log(format!("[DockStack] Updated {}", image_lock::LOCK_FILE));
+    }
+    Ok(lock.images.len())
+}
+
+/// `docker login` with the registry's password from the keyring, given on stdin
+/// so it stays out of the process list.
+fn docker_login(registry: &RegistryConfig) -> DsResult<()> {
+    let password = registry::password(registry)?;
+    let context = format!("docker login to {} failed", registry.host);
+    let mut child = Command::new("docker")
+        .args([
+            "login",
+            registry.host.trim(),
+            "--username",
+            &registry.username,
+        ])
+        .arg("--password-stdin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| DockStackError::spawn(&context, "docker", &e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(password.as_bytes())
+            .map_err(|e| DockStackError::io(&context, &e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| DockStackError::io(&context, &e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(DockStackError::from_stderr(
+            context,
+            &String::from_utf8_lossy(&output.stderr),
+        ))
+    }
+}
+
+/// Log in to the registries the project pulls from. A failed login is only
+/// logged; the pull that needs it reports the error.
+fn login_registries(project: &ProjectConfig, registries: &[RegistryConfig], log: &dyn Fn(String)) {
+    for registry in registry::used_by(project, registries) {
+        log(format!("[DockStack] Logging in to {}", registry.host));
+        if let Err(e) = docker_login(&registry) {
+            log(format!("[DockStack] {}", e));
+        }
+    }
+}
+
+/// Whether the engine runs arm64 containers natively, as on Apple Silicon.
+fn is_arm64(engine_platform: &str) -> bool {
+    engine_platform.ends_with("/arm64") || engine_platform.ends_with("/aarch64")
+}
+
+/// Services of the project to run as linux/amd64 under emulation because
+/// their image has no build for the arm64 engine, each with that platform.
+/// Images without either build, or without the platform a service was set
+/// to, are only warned about. `cache` keeps the platforms of each reference.
+fn platform_fallbacks(
+    project: &ProjectConfig,
+    engine_platform: &str,
+    cache: &Mutex<HashMap<String, Vec<String>>>,
+    log: &dyn Fn(String),
+) -> Vec<(String, String)> {
+    let model = compose::ComposeModel::from_project(project);
+    let mut fallbacks = Vec::new();
+    for (name, service) in model
+        .services
+        .iter()
+        .filter(|(_, s)| !s.contains_key("build"))
+    {
+        let Some(image) = service.get("image").and_then(|i| i.as_str()) else {
+            continue;
+        };
+        let cached = cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(image)
+            .cloned();
+        let platforms = match cached {
+            Some(platforms) => platforms,
+            None => {
+                let inspect = ["manifest", "inspect", "--verbose", image];
+                let Ok(json) = command_output(Command::new("docker").args(inspect), image) else {
+                    continue; // Offline or not allowed to read it; compose reports that
+                };
+                let platforms = compose::manifest_platforms(&json);
+                cache
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(image.to_string(), platforms.clone());
+                platforms
+            }
+        };
+        let chosen = project
+            .services
+            .get(name)
+            .map(|s| s.platform.as_str())
+            .unwrap_or_default();
+        let wanted = if chosen.is_empty() {
+            engine_platform
+        } else {
+            chosen
+        };
+        let has = |platform: &str| platforms.iter().any(|p| p.starts_with(platform));
+        if platforms.is_empty() || has(wanted) {
+            continue;
+        }
+        if chosen.is_empty() && has("linux/amd64") {
+            log(format!(
+                "[DockStack] Warning: {} has no {} build, so {} runs as linux/amd64 \
+                 under emulation",
+                image, wanted, name
+            ));
+            fallbacks.push((name.clone(), "linux/amd64".to_string()));
+        } else {
+            log(format!(
+                "[DockStack] Warning: {} has no {} build ({} only), so {} may not start",
+                image,
+                wanted,
+                platforms.join(", "),
+                name
+            ));
+        }
+    }
+    fallbacks
+}
+
+/// Pull `reference` and return the registry digest it resolved to.
+fn resolve_digest(reference: &str) -> DsResult<String> {
+    let context = format!("Could not resolve {}", reference);
+    let failed = |detail: String| DockStackError::Command {
+        context: context.clone(),
+        detail,
+    };
+    run_checked(
+        Command::new("docker").args(["pull", "--quiet", reference]),
+        &context,
+    )?;
+    let json = run_checked(
+        Command::new("docker").args([
+            "image",
+            "inspect",
+            "--format",
+            "{{json .RepoDigests}}",
+            reference,
+        ]),
+        &context,
+    )?;
+    let digests: Vec<String> = serde_json::from_slice(&json).map_err(|e| failed(e.to_string()))?;
+    image_lock::repo_digest(reference, &digests)
+        .ok_or_else(|| failed("the image has no registry digest".to_string()))
+}
+
+/// Run `command` with `sh -c` in a running container. Returns its exit code
+/// and combined output.
+pub fn exec_in_container(container: &str, command: &str) -> DsResult<(i32, String)> {
+    let out = Command::new("docker")
+        .args(["exec", container, "sh", "-c", command])
+        .output()
+        .map_err(|e| DockStackError::spawn("Failed to run docker exec", "docker", &e))?;
+    let mut text = String::from_utf8_lossy(&out.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&out.stderr));
+    Ok((out.status.code().unwrap_or(-1), text))
+}
+
+/// Last `lines` lines of a container's combined output, for error reports.
+fn container_tail(container: &str, lines: usize) -> String {
+    match Command::new("docker")
+        .args(["logs", "--tail"])
+        .arg(lines.to_string())
+        .arg(container)
+        .output()
+    {
+        Ok(out) => {
+            let mut text = String::from_utf8_lossy(&out.stdout).to_string();
+            text.push_str(&String::from_utf8_lossy(&out.stderr));
+            text.trim_end().to_string()
+        }
+        Err(e) => format!("(could not read logs: {})", e),
+    }
+}
+
+/// Fill in restart counts and exit codes, which `docker ps` does not expose,
+/// and attach recent logs to containers stuck in a crash loop.
+fn inspect_restarts(list: &mut [ContainerInfo]) {
+    if list.is_empty() {
+        return;
+    }
+    let Ok(out) = Command::new("docker")
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{.Id}}|{{.RestartCount}}|{{.State.ExitCode}}")
+        .args(list.iter().map(|c| c.id.as_str()))
+        .output()
+    else {
+        return;
+    };
+
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let mut parts = line.split('|');
+        let (Some(id), Some(restarts), Some(code)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let Some(c) = list
+            .iter_mut()
+            .find(|c| !c.id.is_empty() && id.starts_with(&c.id))
+        else {
+            continue;
+        };
+        c.restart_count = restarts.trim().parse().unwrap_or(0);
+        c.exit_code = code.trim().parse().unwrap_or(0);
+        if c.is_crash_looping() {
+            c.crash_log = Some(container_tail(&c.name, 15));
+        }
+    }
+}
+
+/// Map a `compose up` progress line (e.g. ` Container dockstack_ab12_mysql  Started`
+/// or ` mysql Pulling`) to the service it refers to and its new stage. `prefix` is the
+/// project's `compose::container_prefix`.
+fn parse_compose_progress(line: &str, prefix: &str) -> Option<(String, StartStage)> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (service, state) = match words.as_slice() {
+        ["Container", name, state, ..] => (name.strip_prefix(prefix)?.to_string(), *state),
+        // `compose pull` adds detail, e.g. ` redis Skipped - Image is already present locally`
+        [service, state, ..] => (service.to_string(), *state),
+        _ => return None,
+    };
+
+    let stage = match state {
+        "Pulling" => StartStage::Pulling,
+        "Pulled" | "Skipped" => StartStage::Pulled,
+        "Creating" | "Created" | "Recreate" | "Recreated" => StartStage::Creating,
+        "Starting" | "Waiting" => StartStage::Starting,
+        "Started" | "Running" => StartStage::Started,
+        "Healthy" => StartStage::Healthy,
+        "Error" | "Unhealthy" => StartStage::Failed,
+        _ => return None,
+    };
+    Some((service, stage))
+}
+
+/// `Config` of the image a `docker inspect` entry runs; null when it cannot be read.
+fn image_config(container: &serde_json::Value) -> serde_json::Value {
+    let Some(image_ref) = container["Config"]["Image"].as_str() else {
+        return serde_json::Value::Null;
+    };
+    command_output(
+        Command::new("docker").args([
+            "image",
+            "inspect",
+            "--format",
+            "{{json .Config}}",
+            image_ref,
+        ]),
+        image_ref,
+    )
+    .ok()
+    .and_then(|json| serde_json::from_str(&json).ok())
+    .unwrap_or_default()
+}
+
+/// Custom service equivalent to one `docker inspect` entry. Environment and
+/// command inherited from the image, as given by `image_config`, are left out,
+/// as are anonymous volumes; only the first published port is kept.
+fn captured_service(
+    container: &serde_json::Value,
+    image_config: &serde_json::Value,
+) -> Option<(String, ServiceConfig)> {
+    let name: String = container["Name"]
+        .as_str()?
+        .trim_start_matches('/')
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let config = &container["Config"];
+    let host = &container["HostConfig"];
+    let image_ref = config["Image"].as_str()?;
+    let strings = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let last_segment = image_ref.rsplit('/').next().unwrap_or(image_ref);
+    let (image, version) = match image_ref.rsplit_once(':') {
+        Some((image, tag)) if !image_ref.contains('@') && last_segment.contains(':') => {
+            (image.to_string(), tag.to_string())
+        }
+        _ => (image_ref.to_string(), String::new()),
+    };
+    let mut svc = ServiceConfig {
+        enabled: true,
+        is_custom: true,
+        display_name: Some(name.clone()),
+        image: Some(image),
+        version,
+        ..ServiceConfig::default()
+    };
+
+    let image_env = strings(&image_config["Env"]);
+    for entry in strings(&config["Env"]) {
+        if image_env.contains(&entry) {
+            continue;
+        }
+        if let Some((key, value)) = entry.split_once('=') {
+            svc.env_vars.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    let cmd = strings(&config["Cmd"]);
+    if !cmd.is_empty() && cmd != strings(&image_config["Cmd"]) {
+        svc.settings.insert("command".to_string(), cmd.join(" "));
+    }
+
+    let published = host["PortBindings"].as_object().and_then(|bindings| {
+        bindings.iter().find_map(|(container_port, hosts)| {
+            let host_port = hosts.get(0)?["HostPort"].as_str()?.parse::<u16>().ok()?;
+            let container_port = container_port.split('/').next()?.parse::<u16>().ok()?;
+            Some((host_port, container_port))
+        })
+    });
+    match published {
+        Some((host_port, container_port)) => {
+            svc.port = host_port;
+            svc.settings
+                .insert("container_port".to_string(), container_port.to_string());
+        }
+        None => svc.internal = true,
+    }
+
+    let mounts: Vec<String> = container["Mounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|mount| {
+            let source = match mount["Type"].as_str()? {
+                "bind" => mount["Source"].as_str()?,
+                "volume" => mount["Name"].as_str().filter(|n| !is_anonymous_volume(n))?,
+                _ => return None,
+            };
+            let target = mount["Destination"].as_str()?;
+            let read_only = mount["RW"].as_bool() == Some(false);
+            Some(format!(
+                "{}:{}{}",
+                source,
+                target,
+                if read_only { ":ro" } else { "" }
+            ))
+        })
+        .collect();
+    if !mounts.is_empty() {
+        svc.settings
+            .insert("volumes".to_string(), mounts.join("\n"));
+    }
+
+    if let Some(policy) = host["RestartPolicy"]["Name"]
+        .as_str()
+        .filter(|p| RESTART_POLICIES.contains(p))
+    {
+        svc.restart_policy = policy.to_string();
+    }
+    Some((name, svc))
+}
+
+/// Docker names volumes created for a bare `VOLUME` or `-v /path` with 64 hex digits.
+fn is_anonymous_volume(name: &str) -> bool {
+    name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Read the "Build Cache" row of `docker system df`.
+fn build_cache_usage() -> Option<BuildCacheUsage> {
+    let output = Command::new("docker")
+        .args(["system", "df", "--format", "{{json .}}"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|row| row["Type"] == "Build Cache")
+        .map(|row| BuildCacheUsage {
+            size: row["Size"].as_str().unwrap_or_default().to_string(),
+            reclaimable: row["Reclaimable"].as_str().unwrap_or_default().to_string(),
+        })
+}
+
+/// Platform-specific way to bring the engine up, with a label for messages.
+fn daemon_start_command() -> (&'static str, Command) {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.args(["-a", "Docker"]);
+        ("Docker Desktop", command)
+    } else if cfg!(windows) {
+        let program_files =
+            std::env::var("ProgramFiles").unwrap_or_else(|_| r"C:\Program Files".to_string());
+        let exe = Path::new(&program_files)
+            .join("Docker")
+            .join("Docker")
+            .join("Docker Desktop.exe");
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(exe);
+        ("Docker Desktop", command)
+    } else if dirs::home_dir().is_some_and(|home| home.join(".docker/desktop").is_dir()) {
+        let mut command = Command::new("systemctl");
+        command.args(["--user", "start", "docker-desktop"]);
+        ("Docker Desktop", command)
+    } else {
+        // pkexec shows a graphical password prompt
+        let mut command = Command::new("pkexec");
+        command.args(["systemctl", "start", "docker"]);
+        ("the docker service", command)
+    }
+}
+
+/// Fill versions from `docker version --format '{{json .}}'`; `Server` is null without a daemon.
+fn parse_docker_version(json: &str, engine: &mut EngineInfo) {
+    let Ok(version) = serde_json::from_str::<serde_json::Value>(json.trim()) else {
+        return;
+    };
+    let field = |section: &str, key: &str| {
+        version[section][key]
+            .as_str()
+            .unwrap_or_default()
+            .to_string()
+    };
+    engine.client_version = field("Client", "Version");
+    engine.server_version = field("Server", "Version");
+    engine.api_version = match field("Server", "ApiVersion") {
+        api if api.is_empty() => field("Client", "ApiVersion"),
+        api => api,
+    };
+    let (os, arch) = (field("Server", "Os"), field("Server", "Arch"));
+    if !os.is_empty() {
+        engine.platform = format!("{}/{}", os, arch);
+    }
+}
+
+/// Why `project` must not be started at all, checked before any work is queued.
+fn start_refusal(project: &ProjectConfig) -> Option<DockStackError> {
+    if project.archived {
+        Some(DockStackError::ProjectArchived(project.name.clone()))
+    } else if !project.services.values().any(|s| s.enabled) {
+        Some(DockStackError::NoServicesEnabled)
+    } else {
+        compose::mssql_start_problem(project).map(DockStackError::Config)
+    }
+}
+
+/// Start or end the project's Mutagen sessions in `mutagen` file sync mode.
+fn sync_files(project: &ProjectConfig, log: &dyn Fn(String), running: bool) {
+    if project.file_sync != "mutagen" {
+        return;
+    }
+    log(if !running {
+        file_sync::stop(project);
+        "[DockStack] File sync stopped".to_string()
+    } else {
+        match file_sync::start(project) {
+            Ok(done) => format!("[DockStack] {}", done),
+            Err(e) => {
+                log::error!("File sync failed: {}", e);
+                format!("[DockStack] File sync failed: {}", e)
+            }
+        }
+    });
+}
+
+/// Remove the containers of renamed services and copy their data to the new
+/// volumes, before compose creates the renamed ones. Reports `RenamesMigrated`
+/// so the project stops carrying them; a rename whose data failed to copy is kept.
+fn migrate_renames(project: &ProjectConfig, tx: &Sender<DockerEvent>, log: &dyn Fn(String)) {
+    if project.pending_renames.is_empty() {
+        return;
+    }
+    let prefix = compose::compose_project_name(project);
+    let mut migrated = Vec::new();
+    for rename in &project.pending_renames {
+        let mut copied = true;
+        let removed = Command::new("docker")
+            .args(["rm", "-f", &rename.container])
+            .output()
+            .is_ok_and(|o| o.status.success());
+        if removed {
+            log(format!(
+                "[DockStack] Removed old container {}",
+                rename.container
+            ));
+        }
+        for copy in &rename.volumes {
+            let from = format!("{}_{}", prefix, copy.from);
+            let to = format!("{}_{}", prefix, copy.to);
+            if !volume_exists(&from) || volume_exists(&to) {
+                continue; // Nothing to copy, or never overwrite data
+            }
+            let result = run_checked(
+                Command::new("docker").args([
+                    "volume",
+                    "create",
+                    "--label",
+                    &format!("com.docker.compose.project={}", prefix),
+                    "--label",
+                    &format!("com.docker.compose.volume={}", copy.to),
+                    &to,
+                ]),
+                "Failed to create volume",
+            )
+            .and_then(|_| {
+                run_checked(
+                    Command::new("docker")
+                        .args(["run", "--rm", "-v"])
+                        .arg(format!("{}:/from:ro", from))
+                        .arg("-v")
+                        .arg(format!("{}:/to", to))
+                        .args([SNAPSHOT_HELPER_IMAGE, "cp", "-a", "/from/.", "/to/"]),
+                    "Failed to copy volume",
+                )
+            });
+            match result {
+                Ok(_) => log(format!("[DockStack] Copied volume {} to {}", from, to)),
+                Err(e) => {
+                    log::error!("Copying {} to {} failed: {}", from, to, e);
+                    log(format!("[DockStack] Copying volume {} failed: {}", from, e));
+                    copied = false;
+                }
+            }
+        }
+        if copied {
+            migrated.push(rename.clone());
+        }
+    }
+    tx.send(DockerEvent::RenamesMigrated(project.id.clone(), migrated))
+        .ok();
+}
+
+/// Run the project directory guard rails before any files are written.
+/// On failure the status falls back to `fallback` (or Error) and the UI is notified.
+fn directory_allowed(
+    project: &ProjectConfig,
+    tx: &Sender<DockerEvent>,
+    status: &Arc<Mutex<ServiceStatus>>,
+    action: DirectoryAction,
+) -> bool {
+    match compose::check_project_directory(project) {
+        Ok(compose::DirectoryCheck::Ok) => true,
+        Ok(compose::DirectoryCheck::NeedsConfirmation(reason)) => {
+            let fallback = action.fallback_status();
+            *status.lock().unwrap_or_else(|e| e.into_inner()) = fallback.clone();
+            tx.send(DockerEvent::StatusChange("all".to_string(), fallback))
+                .ok();
+            tx.send(DockerEvent::ConfirmDirectory(
+                project.id.clone(),
+                reason,
+                action,
+            ))
+            .ok();
+            false
+        }
+        Err(e) => {
+            log::error!("[DockStack] {}", e);
+            *status.lock().unwrap_or_else(|e| e.into_inner()) = ServiceStatus::Error(e.to_string());
+            tx.send(DockerEvent::Error(DockStackError::io(
+                "Project directory",
+                &e,
+            )))
+            .ok();
+            false
+        }
+    }
+}
+
+/// Query Docker for DockStack-owned resources, either by label or by the legacy
+/// `dockstack_<id>` naming scheme used before resources were labelled.
+fn find_orphans(known_ids: &[String]) -> Vec<OrphanResource> {
+    let label_fmt = format!("{{{{.Label \"{}\"}}}}", compose::PROJECT_LABEL);
+    let queries = [
+        (
+            ResourceKind::Container,
+            vec!["ps", "-a", "--format"],
+            format!("{{{{.Names}}}}|{}", label_fmt),
+        ),
+        (
+            ResourceKind::Network,
+            vec!["network", "ls", "--format"],
+            format!("{{{{.Name}}}}|{}", label_fmt),
+        ),
+        (
+            ResourceKind::Volume,
+            vec!["volume", "ls", "--format"],
+            format!("{{{{.Name}}}}|{}", label_fmt),
+        ),
+    ];
+
+    let mut orphans = Vec::new();
+    for (kind, args, format) in queries {
+        let output = match Command::new("docker").args(&args).arg(&format).output() {
+            Ok(out) if out.status.success() => out,
+            Ok(out) => {
+                log::warn!(
+                    "Failed to list docker {}s: {}",
+                    kind.label().to_lowercase(),
+                    String::from_utf8_lossy(&out.stderr).trim()
+                );
+                continue;
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to list docker {}s: {}",
+                    kind.label().to_lowercase(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut parts = line.splitn(2, '|');
+            let name = parts.next().unwrap_or("").trim();
+            let label = parts.next().unwrap_or("").trim();
+
+            let project_id = if !label.is_empty() && label != "<no value>" {
+                label.to_string()
+            } else if let Some(id) = legacy_project_id(name) {
+                id
+            } else {
+                continue;
+            };
+
+            if !known_ids.iter().any(|id| id == &project_id) {
+                orphans.push(OrphanResource {
+                    kind,
+                    name: name.to_string(),
+                    project_id,
+                });
+            }
+        }
+    }
+    orphans
+}
+
+/// A `{{.Label}}` value, or `None` when the container does not have the label.
+fn label(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|v| !v.is_empty() && *v != "<no value>")
+}
+
+fn find_foreign_containers() -> Vec<ForeignContainer> {
+    let format = format!(
+        "{{{{.Names}}}}|{{{{.Image}}}}|{{{{.Status}}}}|{{{{.Label \"{}\"}}}}\
+         |{{{{.Label \"com.docker.compose.project\"}}}}\
+         |{{{{.Label \"com.docker.compose.project.config_files\"}}}}\
+         |{{{{.Label \"com.docker.compose.project.working_dir\"}}}}",
+        compose::PROJECT_LABEL
+    );
+    let context = "Failed to list docker containers";
+    match command_output(
+        Command::new("docker").args(["ps", "--format", &format]),
+        context,
+    ) {
+        Ok(output) => parse_foreign_containers(&output),
+        Err(e) => {
+            log::warn!("{}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Containers of `docker ps` lines in `find_foreign_containers`' format that
+/// DockStack did not start, sorted by compose project and name.
+fn parse_foreign_containers(output: &str) -> Vec<ForeignContainer> {
+    let mut containers: Vec<ForeignContainer> = output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('|').collect();
+            let [name, image, status, owner, project, files, dir] = fields[..] else {
+                return None;
+            };
+            if label(owner).is_some() || legacy_project_id(name).is_some() {
+                return None;
+            }
+            // Compose records absolute paths, but older versions wrote them relative
+            let compose_file = label(files)
+                .and_then(|files| files.split(',').next())
+                .map(|file| Path::new(label(dir).unwrap_or_default()).join(file))
+                .filter(|path| path.is_file());
+            Some(ForeignContainer {
+                name: name.to_string(),
+                image: image.to_string(),
+                status: status.to_string(),
+                compose_project: label(project).map(str::to_string),
+                compose_file,
+            })
+        })
+        .collect();
+    containers.sort_by(|a, b| (&a.compose_project, &a.name).cmp(&(&b.compose_project, &b.name)));
+    containers
+}
+
+/// Extract `<id>` from names such as `dockstack_<id>_mysql` or `<dir>_dockstack_<id>`.
+fn legacy_project_id(name: &str) -> Option<String> {
+    let (_, rest) = name.split_once("dockstack_")?;
+    let id = rest.split('_').next()?;
+    if id.is_empty() {
+        None
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// Rootless Docker lists itself in SecurityOptions; Podman's and rootless
+/// Docker's sockets live under the user's /run/user directory.
+fn is_rootless() -> bool {
+    std::env::var("DOCKER_HOST").is_ok_and(|host| host.contains("/run/user/"))
+        || command_output(
+            Command::new("docker").args(["info", "--format", "{{.SecurityOptions}}"]),
+            "Docker security options",
+        )
+        .is_ok_and(|options| options.contains("rootless"))
+}
+
+/// Trimmed stdout on success, otherwise the error `run_checked` reports.
+fn command_output(cmd: &mut Command, context: &str) -> DsResult<String> {
+    let stdout = run_checked(cmd, context)?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+fn run_checked(cmd: &mut Command, context: &str) -> DsResult<Vec<u8>> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd
+        .output()
+        .map_err(|e| DockStackError::spawn(context, &program, &e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(DockStackError::from_stderr(
+            context,
+            &String::from_utf8_lossy(&output.stderr),
+        ))
+    }
+}
+
+/// Run `docker compose config --quiet` on the written compose file.
+///
+/// `None` when the file is valid, or when compose could not be run at all; `up`
+/// reports that case with its own error.
+fn validate_compose(
+    directory: &str,
+    compose_path: &str,
+    use_plugin: bool,
+) -> Option<compose::ComposeValidation> {
+    let mut cmd = if use_plugin {
+        let mut cmd = Command::new("docker");
+        cmd.arg("compose");
+        cmd
+    } else {
+        Command::new("docker-compose")
+    };
+    let output = cmd
+        .args(["config", "--quiet"])
+        .current_dir(directory)
+        .output()
+        .map_err(|e| log::warn!("Could not validate compose file: {}", e))
+        .ok()?;
+    if output.status.success() {
+        return None;
+    }
+    let yaml = fs::read_to_string(compose_path).unwrap_or_default();
+    Some(compose::ComposeValidation::new(
+        compose_path,
+        &String::from_utf8_lossy(&output.stderr),
+        &yaml,
+    ))
+}
+
+fn volume_exists(name: &str) -> bool {
+    Command::new("docker")
+        .args(["volume", "inspect", name])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Tar every existing named volume plus a manifest into a single archive.
+fn write_snapshot(project: &ProjectConfig) -> DsResult<PathBuf> {
+    let backups_dir = Path::new(&project.directory).join("backups");
+    let meta_dir = backups_dir.join(".snapshot-meta");
+    fs::create_dir_all(&meta_dir)
+        .map_err(|e| DockStackError::io("Failed to create backups dir", &e))?;
+
+    let prefix = compose::compose_project_name(project);
+    let volumes: Vec<String> = compose::named_volumes(project)
+        .into_iter()
+        .filter(|v| volume_exists(&format!("{}_{}", prefix, v)))
+        .collect();
+
+    let manifest = SnapshotManifest {
+        created_at: chrono::Local::now().to_rfc3339(),
+        volumes: volumes.clone(),
+        project: project.clone(),
+    };
+    let manifest_toml = toml::to_string_pretty(&manifest)
+        .map_err(|e| DockStackError::Config(format!("Failed to serialize manifest: {}", e)))?;
+    fs::write(meta_dir.join("manifest.toml"), manifest_toml)
+        .map_err(|e| DockStackError::io("Failed to write manifest", &e))?;
+
+    let file_name = format!(
+        "snapshot-{}.tar.gz",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+
+    let mut cmd = Command::new("docker");
+    cmd.args(["run", "--rm"]);
+    for v in &volumes {
+        cmd.arg("-v")
+            .arg(format!("{}_{}:/snapshot/volumes/{}:ro", prefix, v, v));
+    }
+    cmd.arg("-v")
+        .arg(format!("{}:/snapshot/meta:ro", meta_dir.display()))
+        .arg("-v")
+        .arg(format!("{}:/out", backups_dir.display()))
+        .args([SNAPSHOT_HELPER_IMAGE, "tar", "czf"])
+        .arg(format!("/out/{}", file_name))
+        .args(["-C", "/snapshot", "."]);
+
+    let result = run_checked(&mut cmd, "Snapshot failed");
+    fs::remove_dir_all(&meta_dir).ok();
+    result?;
+
+    Ok(backups_dir.join(file_name))
+}
+
+/// Restore volumes from an archive and return the project config stored alongside them.
+fn read_snapshot(project: &ProjectConfig, archive: &Path) -> DsResult<ProjectConfig> {
+    let mount = format!("{}:/in/snapshot.tar.gz:ro", archive.display());
+
+    let manifest_raw = run_checked(
+        Command::new("docker")
+            .args(["run", "--rm", "-v", &mount, SNAPSHOT_HELPER_IMAGE])
+            .args([
+                "tar",
+                "xzf",
+                "/in/snapshot.tar.gz",
+                "-O",
+                "./meta/manifest.toml",
+            ]),
+        "Failed to read snapshot manifest",
+    )?;
+    let manifest: SnapshotManifest = toml::from_str(&String::from_utf8_lossy(&manifest_raw))
+        .map_err(|e| DockStackError::Config(format!("Invalid snapshot manifest: {}", e)))?;
+
+    let prefix = compose::compose_project_name(project);
+    for v in &manifest.volumes {
+        // Volume names end up in a shell command below, so only accept compose-safe names
+        if v.is_empty()
+            || !v
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            log::warn!("Skipping snapshot volume with unexpected name: {}", v);
+            continue;
+        }
+
+        let full_name = format!("{}_{}", prefix, v);
+        if !volume_exists(&full_name) {
+            run_checked(
+                Command::new("docker")
+                    .args(["volume", "create", "--label"])
+                    .arg(format!("{}={}", compose::PROJECT_LABEL, project.id))
+                    .arg("--label")
+                    .arg(format!("com.docker.compose.project={}", prefix))
+                    .arg("--label")
+                    .arg(format!("com.docker.compose.volume={}", v))
+                    .arg(&full_name),
+                "Failed to create volume",
+            )?;
+        }
+
+        run_checked(
+            Command::new("docker")
+                .args(["run", "--rm", "-v"])
+                .arg(format!("{}:/data", full_name))
+                .args(["-v", &mount, SNAPSHOT_HELPER_IMAGE, "sh", "-c"])
+                .arg(format!(
+                    "find /data -mindepth 1 -delete && \
+                     tar xzf /in/snapshot.tar.gz -C /data --strip-components=3 ./volumes/{}",
+                    v
+                )),
+            &format!("Failed to restore volume {}", v),
+        )?;
+    }
+
+    let mut restored = manifest.project;
+    restored.id = project.id.clone();
+    restored.name = project.name.clone();
+    restored.directory = project.directory.clone();
+    restored.directory_confirmed = project.directory_confirmed;
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io;
+
+    #[test]
+    fn captured_service_keeps_what_the_image_does_not_set() {
+        let container = json!({
+            "Name": "/My.Api",
+            "Config": {
+                "Image": "registry.local:5000/team/api:1.4",
+                "Env": ["PATH=/usr/bin", "APP_ENV=dev", "DSN=mysql://db/app?x=1"],
+                "Cmd": ["node", "server.js"]
+            },
+            "HostConfig": {
+                "PortBindings": {"3000/tcp": [{"HostIp": "", "HostPort": "8080"}]},
+                "RestartPolicy": {"Name": "unless-stopped"}
+            },
+            "Mounts": [
+                {"Type": "bind", "Source": "/srv/api", "Destination": "/app", "RW": true},
+                {"Type": "volume", "Name": "api-data", "Destination": "/data", "RW": false},
+                {"Type": "volume", "Name": "a".repeat(64), "Destination": "/cache", "RW": true}
+            ]
+        });
+        let image_config = json!({"Env": ["PATH=/usr/bin"], "Cmd": ["node", "index.js"]});
+
+        let Some((name, svc)) = captured_service(&container, &image_config) else {
+            panic!("container was not captured");
+        };
+        assert_eq!(name, "my_api");
+        assert_eq!(svc.image.as_deref(), Some("registry.local:5000/team/api"));
+        assert_eq!(svc.version, "1.4");
+        let mut env: Vec<_> = svc.env_vars.iter().collect();
+        env.sort();
+        assert_eq!(
+            env,
+            [
+                (&"APP_ENV".to_string(), &"dev".to_string()),
+                (&"DSN".to_string(), &"mysql://db/app?x=1".to_string()),
+            ]
+        );
+        assert_eq!(svc.settings["command"], "node server.js");
+        assert_eq!(svc.port, 8080);
+        assert_eq!(svc.settings["container_port"], "3000");
+        assert!(!svc.internal);
+        assert_eq!(svc.settings["volumes"], "/srv/api:/app\napi-data:/data:ro");
+        assert_eq!(svc.restart_policy, "unless-stopped");
+    }
+
+    #[test]
+    fn captured_service_without_ports_or_tag() {
+        let container = json!({
+            "Name": "/cache",
+            "Config": {"Image": "localhost:5000/cache@sha256:abc", "Cmd": ["redis-server"]},
+            "HostConfig": {"PortBindings": {}, "RestartPolicy": {"Name": ""}}
+        });
+        let image_config = json!({"Cmd": ["redis-server"]});
+
+        let Some((_, svc)) = captured_service(&container, &image_config) else {
+            panic!("container was not captured");
+        };
+        assert_eq!(
+            svc.image.as_deref(),
+            Some("localhost:5000/cache@sha256:abc")
+        );
+        assert_eq!(svc.version, "");
+        assert!(svc.internal);
+        assert!(!svc.settings.contains_key("command"));
+        assert!(!svc.settings.contains_key("volumes"));
+        assert!(captured_service(&json!({"Name": "/bare"}), &image_config).is_none());
+    }
+
+    #[test]
+    fn foreign_containers_skip_dockstack_ones() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dockstack-foreign-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("docker-compose.yml"), "services: {}\n")?;
+        let none = "<no value>";
+        let output = [
+            format!("dockstack_abc_mysql|mysql:8|Up 2 hours|{none}|{none}|{none}|{none}"),
+            "golden-nginx|nginx:1.27|Up 5 minutes|golden|golden|compose.yml|/srv".to_string(),
+            format!(
+                "shop-db-1|postgres:16|Up 3 hours (healthy)|{none}|shop|docker-compose.yml|{}",
+                dir.display()
+            ),
+            format!("shop-app-1|shop/app:dev|Up 3 hours|{none}|shop|compose.yml|/missing"),
+            format!("adminer|adminer|Exited (0) 1 day ago|{none}|{none}|{none}|{none}"),
+            "not a ps line".to_string(),
+        ]
+        .join("\n");
+        let containers = parse_foreign_containers(&output);
+        fs::remove_dir_all(&dir)?;
+
+        let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["adminer", "shop-app-1", "shop-db-1"]);
+        assert_eq!(containers[0].compose_project, None);
+        assert_eq!(containers[0].status, "Exited (0) 1 day ago");
+        assert_eq!(containers[1].image, "shop/app:dev");
+        assert_eq!(containers[1].compose_file, None);
+        assert_eq!(containers[2].compose_project.as_deref(), Some("shop"));
+        assert_eq!(
+            containers[2].compose_file,
+            Some(dir.join("docker-compose.yml"))
+        );
+        Ok(())
     }
 }
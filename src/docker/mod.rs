@@ -1,2 +1,8 @@
+pub mod ci;
 pub mod compose;
+pub mod devcontainer;
+pub mod file_sync;
+pub mod image_lock;
+pub mod kubernetes;
+pub mod maintenance;
 pub mod manager;
@@ -0,0 +1,481 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose;
+use crate::docker::manager::OrphanResource;
+use crate::error::DockStackError;
+use crate::port_scanner::PortScanner;
+use crate::ssl::SslManager;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Certificates expiring sooner than this are flagged.
+const CERT_WARN_DAYS: i64 = 30;
+/// Kernel default for `net.ipv4.ip_unprivileged_port_start`.
+const PRIVILEGED_PORT_END: u16 = 1024;
+/// Added to a privileged port to pick its replacement: 80 → 8080, 443 → 8443.
+const HIGH_PORT_OFFSET: u16 = 8000;
+const UNPRIVILEGED_PORT_START: &str = "/proc/sys/net/ipv4/ip_unprivileged_port_start";
+/// Where `allow_ports_from` persists the setting across reboots.
+const SYSCTL_DROP_IN: &str = "/etc/sysctl.d/60-dockstack-ports.conf";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    #[default]
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pass => "OK",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+/// One-click remedy offered next to a failed check.
+#[derive(Debug, Clone)]
+pub enum DoctorFix {
+    CreateDirectory(String),
+    /// Rewrite docker-compose.yml and the service config files.
+    RegenerateFiles,
+    UsePort {
+        service: String,
+        port: u16,
+    },
+    /// Move several services at once, e.g. off privileged ports.
+    UsePorts(Vec<(String, u16)>),
+    /// Lower `net.ipv4.ip_unprivileged_port_start` to this port, via pkexec.
+    AllowPortsFrom(u16),
+    GenerateCertificate,
+    RemoveOrphans(Vec<OrphanResource>),
+    /// Append DockStack's generated and private paths to `.gitignore`.
+    UpdateGitignore,
+}
+
+impl DoctorFix {
+    pub fn label(&self) -> String {
+        match self {
+            Self::CreateDirectory(_) => "Create Directory".to_string(),
+            Self::RegenerateFiles => "Regenerate Files".to_string(),
+            Self::UsePort { port, .. } => format!("Use Port {}", port),
+            Self::UsePorts(_) => "Use High Ports".to_string(),
+            Self::AllowPortsFrom(port) => format!("Allow Ports From {}", port),
+            Self::GenerateCertificate => "Generate Certificate".to_string(),
+            Self::RemoveOrphans(_) => "Remove Orphans".to_string(),
+            Self::UpdateGitignore => "Add to .gitignore".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub hint: Option<&'static str>, // What to do about a failure that has no one-click fix
+    pub fix: Option<DoctorFix>,
+}
+
+impl DoctorCheck {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    /// A failed check from an error, with the error's hint.
+    fn failed(name: &str, err: &DockStackError) -> Self {
+        Self {
+            hint: err.hint(),
+            ..Self::new(name, CheckStatus::Fail, err.to_string())
+        }
+    }
+
+    fn with_fix(mut self, fix: DoctorFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// What the Docker side of the report needs; gathered by `DockerManager::run_doctor`.
+#[derive(Debug, Clone)]
+pub struct DockerFacts {
+    pub daemon: Result<String, DockStackError>, // Server version, or why `docker version` failed
+    pub compose: Result<String, DockStackError>,
+    pub running_services: Vec<String>, // Compose services of this project that are up
+    pub rootless: bool,                // Rootless Docker or Podman, which cannot bind low ports
+    pub orphans: Vec<OrphanResource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorReport {
+    pub project: String,
+    pub created_at: chrono::DateTime<chrono::Local>,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn worst(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Plain-text version for bug reports.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "DockStack doctor report").ok();
+        writeln!(out, "Version: {}", env!("CARGO_PKG_VERSION")).ok();
+        writeln!(
+            out,
+            "Platform: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+        .ok();
+        writeln!(out, "Project: {}", self.project).ok();
+        writeln!(out, "Created: {}", self.created_at.to_rfc3339()).ok();
+        writeln!(out).ok();
+        for check in &self.checks {
+            writeln!(
+                out,
+                "[{:<4}] {}: {}",
+                check.status.label(),
+                check.name,
+                check.detail
+            )
+            .ok();
+            if let Some(hint) = check.hint {
+                writeln!(out, "       {}", hint).ok();
+            }
+        }
+        out
+    }
+}
+
+/// Run every check for `project`. Blocking: probes ports, the file system and openssl.
+pub fn diagnose(project: &ProjectConfig, facts: DockerFacts) -> DoctorReport {
+    let mut checks = vec![
+        match &facts.daemon {
+            Ok(version) => DoctorCheck::new(
+                "Docker daemon",
+                CheckStatus::Pass,
+                format!("Running, server {}", version),
+            ),
+            Err(e) => DoctorCheck::failed("Docker daemon", e),
+        },
+        match &facts.compose {
+            Ok(version) => DoctorCheck::new("Docker Compose", CheckStatus::Pass, version.as_str()),
+            Err(e) => DoctorCheck::failed("Docker Compose", e),
+        },
+    ];
+
+    let dir = Path::new(&project.directory);
+    if !dir.is_dir() {
+        checks.push(
+            DoctorCheck::new(
+                "Project directory",
+                CheckStatus::Fail,
+                format!("{} does not exist", project.directory),
+            )
+            .with_fix(DoctorFix::CreateDirectory(project.directory.clone())),
+        );
+    } else {
+        checks.push(DoctorCheck::new(
+            "Project directory",
+            CheckStatus::Pass,
+            project.directory.as_str(),
+        ));
+        checks.push(check_compose_file(project));
+        checks.extend(check_mounts(project));
+        if compose::is_git_repo(project) {
+            checks.push(check_gitignore(project));
+        }
+    }
+
+    checks.extend(check_ports(project, &facts.running_services));
+    if facts.rootless {
+        checks.extend(check_privileged_ports(project));
+    }
+
+    if project.ssl_enabled {
+        checks.push(check_certificate(project));
+    }
+
+    checks.push(if facts.orphans.is_empty() {
+        DoctorCheck::new("Orphaned resources", CheckStatus::Pass, "None")
+    } else {
+        DoctorCheck::new(
+            "Orphaned resources",
+            CheckStatus::Warn,
+            format!(
+                "{} container(s), network(s) or volume(s) left by deleted projects",
+                facts.orphans.len()
+            ),
+        )
+        .with_fix(DoctorFix::RemoveOrphans(facts.orphans))
+    });
+
+    DoctorReport {
+        project: project.name.clone(),
+        created_at: chrono::Local::now(),
+        checks,
+    }
+}
+
+fn check_compose_file(project: &ProjectConfig) -> DoctorCheck {
+    let path = Path::new(&project.directory).join("docker-compose.yml");
+    let name = "Compose file";
+    match fs::read_to_string(&path) {
+        Err(_) => DoctorCheck::new(name, CheckStatus::Warn, "Not generated yet")
+            .with_fix(DoctorFix::RegenerateFiles),
+        Ok(existing) if existing == compose::render_compose_file(project) => {
+            DoctorCheck::new(name, CheckStatus::Pass, "Up to date")
+        }
+        Ok(_) => DoctorCheck::new(
+            name,
+            CheckStatus::Warn,
+            "Out of date: the project settings changed since it was written",
+        )
+        .with_fix(DoctorFix::RegenerateFiles),
+    }
+}
+
+fn check_gitignore(project: &ProjectConfig) -> DoctorCheck {
+    let name = ".gitignore";
+    let missing = compose::missing_gitignore_entries(project);
+    if missing.is_empty() {
+        return DoctorCheck::new(name, CheckStatus::Pass, "Covers DockStack's files");
+    }
+    DoctorCheck::new(
+        name,
+        CheckStatus::Warn,
+        format!("Does not ignore {}", missing.join(", ")),
+    )
+    .with_fix(DoctorFix::UpdateGitignore)
+}
+
+/// Missing bind-mount sources make Docker create empty root-owned directories in their place.
+fn check_mounts(project: &ProjectConfig) -> Vec<DoctorCheck> {
+    let missing: Vec<_> = compose::bind_mounts(project)
+        .into_iter()
+        .filter(|(_, path)| !path.exists())
+        .collect();
+    if missing.is_empty() {
+        return vec![DoctorCheck::new(
+            "Volume mounts",
+            CheckStatus::Pass,
+            "All bind-mounted paths exist",
+        )];
+    }
+    missing
+        .into_iter()
+        .map(|(service, path)| {
+            let check = DoctorCheck::new(
+                format!("Volume mount ({})", service),
+                CheckStatus::Warn,
+                format!("{} does not exist", path.display()),
+            );
+            if compose::is_generated_path(project, &path) {
+                check.with_fix(DoctorFix::RegenerateFiles)
+            } else if path.extension().is_none() {
+                check.with_fix(DoctorFix::CreateDirectory(
+                    path.to_string_lossy().to_string(),
+                ))
+            } else {
+                check
+            }
+        })
+        .collect()
+}
+
+fn check_ports(project: &ProjectConfig, running_services: &[String]) -> Vec<DoctorCheck> {
+    let mut services: Vec<(&String, u16)> = project
+        .services
+        .iter()
+        .filter(|(_, s)| s.enabled && !s.internal)
+        .map(|(name, _)| (name, project.host_port(name)))
+        .filter(|(_, port)| *port != 0)
+        .collect();
+    services.sort_by(|a, b| a.0.cmp(b.0));
+
+    let conflicts: Vec<DoctorCheck> = services
+        .into_iter()
+        // Our own running containers hold their ports
+        .filter(|(name, _)| !running_services.contains(name))
+        .filter_map(|(name, port)| {
+            let (available, suggestion) = PortScanner::suggest_port(port);
+            (!available).then(|| {
+                DoctorCheck::new(
+                    format!("Port {} ({})", port, name),
+                    CheckStatus::Fail,
+                    "Already in use by another program",
+                )
+                .with_fix(DoctorFix::UsePort {
+                    service: name.clone(),
+                    port: suggestion,
+                })
+            })
+        })
+        .collect();
+    if conflicts.is_empty() {
+        vec![DoctorCheck::new("Ports", CheckStatus::Pass, "No conflicts")]
+    } else {
+        conflicts
+    }
+}
+
+/// Rootless engines cannot publish ports below the kernel's unprivileged start.
+fn check_privileged_ports(project: &ProjectConfig) -> Vec<DoctorCheck> {
+    let name = "Privileged ports";
+    let start = unprivileged_port_start().unwrap_or(PRIVILEGED_PORT_END);
+    let mut low: Vec<(String, u16)> = project
+        .services
+        .iter()
+        .filter(|(_, s)| s.enabled && !s.internal)
+        .map(|(service, _)| (service.clone(), project.host_port(service)))
+        .filter(|(_, port)| *port != 0 && *port < start)
+        .collect();
+    low.sort();
+    let Some(lowest) = low.iter().map(|(_, port)| *port).min() else {
+        return vec![DoctorCheck::new(
+            name,
+            CheckStatus::Pass,
+            format!(
+                "Rootless engine; every published port is {} or above",
+                start
+            ),
+        )];
+    };
+
+    let listed = low
+        .iter()
+        .map(|(service, port)| format!("{} ({})", port, service))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let remap = low
+        .iter()
+        .map(|(service, port)| {
+            let (_, suggestion) = PortScanner::suggest_port(port + HIGH_PORT_OFFSET);
+            (service.clone(), suggestion)
+        })
+        .collect();
+    let remap_check = DoctorCheck::new(
+        name,
+        CheckStatus::Fail,
+        format!(
+            "The rootless engine cannot publish {}; ports below {} need root",
+            listed, start
+        ),
+    )
+    .with_fix(DoctorFix::UsePorts(remap));
+
+    let sysctl = format!("net.ipv4.ip_unprivileged_port_start={}", lowest);
+    let sysctl_check = DoctorCheck::new(
+        "Unprivileged port start",
+        CheckStatus::Warn,
+        format!(
+            "Or keep the ports: run `sudo sysctl -w {}` and add it to /etc/sysctl.d/ \
+             (rootless Docker also works with \
+             `sudo setcap cap_net_bind_service=ep $(which rootlesskit)`)",
+            sysctl
+        ),
+    );
+    let sysctl_check = if which::which("pkexec").is_ok() {
+        sysctl_check.with_fix(DoctorFix::AllowPortsFrom(lowest))
+    } else {
+        sysctl_check
+    };
+    vec![remap_check, sysctl_check]
+}
+
+fn unprivileged_port_start() -> Option<u16> {
+    fs::read_to_string(UNPRIVILEGED_PORT_START)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Let unprivileged users bind from `port` up, now and after reboots. pkexec
+/// asks for the admin password for the sysctl and for the drop-in; blocking.
+pub fn allow_ports_from(port: u16) -> io::Result<()> {
+    let setting = format!("net.ipv4.ip_unprivileged_port_start={}", port);
+    let output = Command::new("pkexec")
+        .args(["sysctl", "-w", &setting])
+        .output()?;
+    pkexec_result(&output)?;
+
+    let mut tee = Command::new("pkexec")
+        .args(["tee", SYSCTL_DROP_IN])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = tee.stdin.take() {
+        writeln!(stdin, "{}", setting)?;
+    }
+    pkexec_result(&tee.wait_with_output()?)
+}
+
+/// The last line pkexec or its command printed to stderr, as the error of a failed run.
+fn pkexec_result(output: &Output) -> io::Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(io::Error::other(
+        stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("pkexec was cancelled")
+            .trim()
+            .to_string(),
+    ))
+}
+
+fn check_certificate(project: &ProjectConfig) -> DoctorCheck {
+    let name = "SSL certificate";
+    if !SslManager::certs_exist(&project.directory) {
+        return DoctorCheck::new(
+            name,
+            CheckStatus::Fail,
+            "SSL is enabled but no certificate exists",
+        )
+        .with_fix(DoctorFix::GenerateCertificate);
+    }
+    let Some(expiry) = SslManager::cert_expiry(&project.directory) else {
+        return DoctorCheck::new(
+            name,
+            CheckStatus::Pass,
+            "Present (install openssl to check its expiry)",
+        );
+    };
+    let days_left = (expiry - chrono::Utc::now()).num_days();
+    let detail = format!("Expires {} ({} days)", expiry.format("%Y-%m-%d"), days_left);
+    if days_left < 0 {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Fail,
+            format!("Expired {}", expiry.format("%Y-%m-%d")),
+        )
+        .with_fix(DoctorFix::GenerateCertificate)
+    } else if days_left < CERT_WARN_DAYS {
+        DoctorCheck::new(name, CheckStatus::Warn, detail).with_fix(DoctorFix::GenerateCertificate)
+    } else {
+        DoctorCheck::new(name, CheckStatus::Pass, detail)
+    }
+}
@@ -0,0 +1,199 @@
+use std::io;
+
+/// Errors surfaced by DockStack's backend modules.
+///
+/// Each variant is a kind the UI can react to; `hint()` gives the remediation
+/// shown next to the message instead of raw stderr.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DockStackError {
+    #[error("No services enabled! Please enable at least one service in the Services tab.")]
+    NoServicesEnabled,
+    #[error("{0} is archived")]
+    ProjectArchived(String),
+    #[error("{context}: Docker daemon is not reachable")]
+    DockerUnavailable { context: String },
+    #[error("{context}: `{program}` was not found")]
+    ComposeMissing { context: String, program: String },
+    #[error("{context}: {}", port_message(*.port))]
+    PortInUse { context: String, port: Option<u16> },
+    #[error("{context}: {}", privileged_message(*.port))]
+    PrivilegedPort { context: String, port: Option<u16> },
+    #[error("{context}: permission denied ({detail})")]
+    PermissionDenied { context: String, detail: String },
+    #[error("{context}: image could not be pulled ({detail})")]
+    ImagePull { context: String, detail: String },
+    #[error("{context}: {detail}")]
+    Io { context: String, detail: String },
+    #[error("{context}: {detail}")]
+    Command { context: String, detail: String },
+    #[error("{0}")]
+    Config(String),
+    #[error("System tray unavailable: {0}")]
+    TrayUnavailable(String),
+}
+
+pub type Result<T> = std::result::Result<T, DockStackError>;
+
+fn port_message(port: Option<u16>) -> String {
+    match port {
+        Some(port) => format!("port {} is already in use", port),
+        None => "a port is already in use".to_string(),
+    }
+}
+
+fn privileged_message(port: Option<u16>) -> String {
+    match port {
+        Some(port) => format!("port {} needs root privileges", port),
+        None => "a privileged port needs root privileges".to_string(),
+    }
+}
+
+impl DockStackError {
+    /// Failure to run an external program, e.g. `docker compose` or `docker-compose`.
+    pub fn spawn(context: impl Into<String>, program: &str, err: &io::Error) -> Self {
+        let context = context.into();
+        match err.kind() {
+            io::ErrorKind::NotFound if program.contains("compose") => Self::ComposeMissing {
+                context,
+                program: program.to_string(),
+            },
+            io::ErrorKind::NotFound => Self::DockerUnavailable { context },
+            _ => Self::io(context, err),
+        }
+    }
+
+    pub fn io(context: impl Into<String>, err: &io::Error) -> Self {
+        let context = context.into();
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied {
+                context,
+                detail: err.to_string(),
+            },
+            _ => Self::Io {
+                context,
+                detail: err.to_string(),
+            },
+        }
+    }
+
+    /// Classify a failed docker command by its stderr.
+    pub fn from_stderr(context: impl Into<String>, stderr: &str) -> Self {
+        let context = context.into();
+        let lower = stderr.to_lowercase();
+        let detail = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        if lower.contains("port is already allocated") || lower.contains("address already in use") {
+            Self::PortInUse {
+                context,
+                port: conflicting_port(stderr),
+            }
+        } else if lower.contains("cannot connect to the docker daemon")
+            || lower.contains("is the docker daemon running")
+        {
+            Self::DockerUnavailable { context }
+        } else if lower.contains("cannot expose privileged port") {
+            // Rootless Docker and Podman, which also mention "permission denied"
+            Self::PrivilegedPort {
+                context,
+                port: privileged_port(&lower),
+            }
+        } else if lower.contains("permission denied") {
+            Self::PermissionDenied { context, detail }
+        } else if lower.contains("'compose' is not a docker command")
+            || lower.contains("unknown command \"compose\"")
+        {
+            Self::ComposeMissing {
+                context,
+                program: "docker compose".to_string(),
+            }
+        } else if lower.contains("pull access denied")
+            || lower.contains("manifest unknown")
+            || lower.contains("manifest for")
+        {
+            Self::ImagePull { context, detail }
+        } else {
+            Self::Command { context, detail }
+        }
+    }
+
+    /// Short label for the error's kind.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::NoServicesEnabled => "Nothing to start",
+            Self::ProjectArchived(_) => "Project archived",
+            Self::DockerUnavailable { .. } => "Docker unavailable",
+            Self::ComposeMissing { .. } => "Docker Compose missing",
+            Self::PortInUse { .. } => "Port conflict",
+            Self::PrivilegedPort { .. } => "Privileged port",
+            Self::PermissionDenied { .. } => "Permission denied",
+            Self::ImagePull { .. } => "Image pull failed",
+            Self::Io { .. } => "File system error",
+            Self::Command { .. } => "Command failed",
+            Self::Config(_) => "Configuration error",
+            Self::TrayUnavailable(_) => "System tray unavailable",
+        }
+    }
+
+    /// What the user can do about it, when we know.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NoServicesEnabled => Some("Enable at least one service in the Services tab."),
+            Self::ProjectArchived(_) => {
+                Some("Restore it under Settings → Projects before starting it.")
+            }
+            Self::DockerUnavailable { .. } => {
+                Some("Start Docker Desktop or the docker service, then retry.")
+            }
+            Self::ComposeMissing { .. } => Some(
+                "Install the Compose plugin: https://docs.docker.com/compose/install/ \
+                 (Linux: install the docker-compose-plugin package).",
+            ),
+            Self::PortInUse { .. } => Some(
+                "Stop the program using the port or change the service port in the Services tab.",
+            ),
+            Self::PrivilegedPort { .. } => Some(
+                "Rootless Docker cannot publish ports below 1024. Run the Doctor to move them to \
+                 high ports (80 → 8080, 443 → 8443) or to allow them on this machine.",
+            ),
+            Self::PermissionDenied { .. } => Some(
+                "On Linux, add your user to the docker group (sudo usermod -aG docker $USER) \
+                 and log in again; otherwise check the project directory permissions.",
+            ),
+            Self::ImagePull { .. } => {
+                Some("Check the service version tag and your network or registry login.")
+            }
+            Self::TrayUnavailable(_) => Some(
+                "On Linux the tray needs libayatana-appindicator3 and, on GNOME, the \
+                 AppIndicator extension. DockStack keeps running without it.",
+            ),
+            Self::Io { .. } | Self::Command { .. } | Self::Config(_) => None,
+        }
+    }
+}
+
+/// Host port from messages like `Bind for 0.0.0.0:8080 failed: port is already allocated`.
+fn conflicting_port(stderr: &str) -> Option<u16> {
+    stderr
+        .split(|c: char| c.is_whitespace())
+        .filter_map(|word| word.rsplit_once(':'))
+        .find_map(|(_, port)| {
+            port.trim_end_matches(|c: char| !c.is_ascii_digit())
+                .parse()
+                .ok()
+        })
+}
+
+/// Port from rootlesskit's `cannot expose privileged port 80, you can add ...`.
+fn privileged_port(stderr: &str) -> Option<u16> {
+    let (_, rest) = stderr.split_once("privileged port ")?;
+    rest.split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,302 @@
+use crate::config::{ProjectConfig, ServiceConfig};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Kinds of codebase DockStack can set a stack up for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framework {
+    Laravel,
+    Symfony,
+    WordPress,
+    Php,
+    Django,
+    Rails,
+    Node,
+}
+
+impl Framework {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Laravel => "Laravel",
+            Self::Symfony => "Symfony",
+            Self::WordPress => "WordPress",
+            Self::Php => "PHP",
+            Self::Django => "Django",
+            Self::Rails => "Rails",
+            Self::Node => "Node.js",
+        }
+    }
+}
+
+/// A framework found in a codebase and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub framework: Framework,
+    pub code_dir: &'static str, // "" for the directory itself, else `www` or `app`
+    pub version: Option<String>, // Runtime version the code asks for, e.g. PHP `8.2`
+}
+
+/// Look for framework markers in `dir`, then in its `www/` and `app/`.
+pub fn detect(dir: &Path) -> Option<Detection> {
+    ["", "www", "app"].into_iter().find_map(|code_dir| {
+        let (framework, version) = detect_in(&dir.join(code_dir))?;
+        Some(Detection {
+            framework,
+            code_dir,
+            version,
+        })
+    })
+}
+
+/// Detect the framework of the project's code and set its services, runtime
+/// version and web root to match.
+pub fn configure(project: &mut ProjectConfig) -> Option<Framework> {
+    let detection = detect(Path::new(&project.directory))?;
+    log::info!(
+        "Detected {} in {}",
+        detection.framework.label(),
+        Path::new(&project.directory)
+            .join(detection.code_dir)
+            .display()
+    );
+    apply(project, &detection);
+    Some(detection.framework)
+}
+
+fn detect_in(dir: &Path) -> Option<(Framework, Option<String>)> {
+    let has = |name: &str| dir.join(name).exists();
+    // PHP frameworks first: Laravel and Symfony ship a package.json too
+    let framework = if has("artisan") && has("composer.json") {
+        Framework::Laravel
+    } else if has("composer.json") && (has("symfony.lock") || has("bin/console")) {
+        Framework::Symfony
+    } else if has("wp-config.php") || has("wp-config-sample.php") || has("wp-includes") {
+        Framework::WordPress
+    } else if has("manage.py") {
+        Framework::Django
+    } else if has("Gemfile") {
+        Framework::Rails
+    } else if has("package.json") {
+        Framework::Node
+    } else if has("composer.json") {
+        Framework::Php
+    } else {
+        return None;
+    };
+    let version = match framework {
+        Framework::Laravel | Framework::Symfony | Framework::WordPress | Framework::Php => {
+            composer_php_version(dir)
+        }
+        Framework::Django => version_file(dir, ".python-version"),
+        Framework::Rails => version_file(dir, ".ruby-version"),
+        Framework::Node => version_file(dir, ".nvmrc")
+            .or_else(|| node_engine_version(dir))
+            .map(|v| v.split('.').next().unwrap_or(&v).to_string()),
+    };
+    Some((framework, version))
+}
+
+fn apply(project: &mut ProjectConfig, detection: &Detection) {
+    project.code_in_directory = detection.code_dir.is_empty();
+    for svc in project.services.values_mut() {
+        svc.enabled = false;
+    }
+    let version = detection.version.as_deref();
+    let (services, web_root): (&[&str], &str) = match detection.framework {
+        Framework::Laravel | Framework::Symfony => (&["php", "nginx", "mysql"], "public"),
+        Framework::WordPress => (&["php", "nginx", "mysql"], ""),
+        Framework::Php => (&["php", "nginx"], ""),
+        Framework::Django => (&["python", "postgresql"], ""),
+        Framework::Rails | Framework::Node => (&["postgresql"], ""),
+    };
+    for name in services {
+        if let Some(svc) = project.services.get_mut(*name) {
+            svc.enabled = true;
+        }
+    }
+    project.sync_web_topology("nginx");
+    project.web_root = web_root.to_string();
+
+    let source = match detection.code_dir {
+        "" => ".",
+        dir => dir,
+    };
+    match detection.framework {
+        Framework::Laravel | Framework::Symfony | Framework::WordPress | Framework::Php => {
+            if let (Some(php), Some(version)) = (project.services.get_mut("php"), version) {
+                php.version = format!("{}-fpm", version);
+            }
+        }
+        Framework::Django => {
+            if let Some(python) = project.services.get_mut("python") {
+                if let Some(version) = version {
+                    python.version = format!("{}-slim", version);
+                }
+                python.settings.insert(
+                    "command".to_string(),
+                    "python manage.py runserver 0.0.0.0:8000".to_string(),
+                );
+            }
+        }
+        Framework::Rails => {
+            let command = "sh -c \"cd /app && bundle install && bin/rails server -b 0.0.0.0\"";
+            let rails = app_service("Rails", "ruby", version.unwrap_or("3.3"), source, command);
+            project.services.insert("rails".to_string(), rails);
+        }
+        Framework::Node => {
+            let command = "sh -c \"cd /app && npm install && npm run dev\"";
+            let mut node =
+                app_service("Node.js", "node", version.unwrap_or("lts"), source, command);
+            node.volume_overlays = vec!["node_modules".to_string()];
+            project.services.insert("node".to_string(), node);
+        }
+    }
+}
+
+/// Custom service running the code mounted at `/app` on port 3000.
+fn app_service(
+    name: &str,
+    image: &str,
+    version: &str,
+    source: &str,
+    command: &str,
+) -> ServiceConfig {
+    let mut settings = HashMap::new();
+    settings.insert("command".to_string(), command.to_string());
+    settings.insert("container_port".to_string(), "3000".to_string());
+    settings.insert("volumes".to_string(), format!("{}:/app", source));
+    ServiceConfig {
+        enabled: true,
+        is_custom: true,
+        display_name: Some(name.to_string()),
+        image: Some(image.to_string()),
+        version: version.to_string(),
+        port: 3000,
+        settings,
+        ..ServiceConfig::default()
+    }
+}
+
+/// `config.platform.php`, else the first version in `require.php`, as `major.minor`.
+fn composer_php_version(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("composer.json")).ok()?;
+    let composer: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let constraint = composer
+        .pointer("/config/platform/php")
+        .or_else(|| composer.pointer("/require/php"))?
+        .as_str()?;
+    major_minor(constraint)
+}
+
+/// `engines.node` of package.json, e.g. `>=18` → `18`.
+fn node_engine_version(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&content).ok()?;
+    major_minor(package.pointer("/engines/node")?.as_str()?)
+}
+
+/// First line of a version file such as `.ruby-version`, as `major.minor`.
+fn version_file(dir: &Path, name: &str) -> Option<String> {
+    let content = fs::read_to_string(dir.join(name)).ok()?;
+    major_minor(content.lines().next()?)
+}
+
+/// The first version in a constraint, e.g. `^8.2|^8.3` → `8.2` or `>=18` → `18`.
+fn major_minor(constraint: &str) -> Option<String> {
+    let start = constraint.find(|c: char| c.is_ascii_digit())?;
+    let version: String = constraint[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let parts: Vec<&str> = version
+        .split('.')
+        .filter(|p| !p.is_empty())
+        .take(2)
+        .collect();
+    Some(parts.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::path::PathBuf;
+
+    #[test]
+    fn major_minor_takes_the_first_version_of_a_constraint() {
+        assert_eq!(major_minor("^8.2|^8.3").as_deref(), Some("8.2"));
+        assert_eq!(major_minor(">=18").as_deref(), Some("18"));
+        assert_eq!(major_minor("~7.4.33").as_deref(), Some("7.4"));
+        assert_eq!(major_minor("3.12.1\n").as_deref(), Some("3.12"));
+        assert_eq!(major_minor("lts/*"), None);
+    }
+
+    #[test]
+    fn composer_php_version_prefers_the_platform() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("dockstack-composer-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join("composer.json"),
+            r#"{"require": {"php": "^8.1"}, "config": {"platform": {"php": "8.3.4"}}}"#,
+        )?;
+        let platform = composer_php_version(&dir);
+        fs::write(
+            dir.join("composer.json"),
+            r#"{"require": {"php": ">=8.2"}}"#,
+        )?;
+        let required = composer_php_version(&dir);
+        fs::write(dir.join("composer.json"), r#"{"require": {}}"#)?;
+        let unconstrained = composer_php_version(&dir);
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(platform.as_deref(), Some("8.3"));
+        assert_eq!(required.as_deref(), Some("8.2"));
+        assert_eq!(unconstrained, None);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_in_recognizes_framework_markers() -> io::Result<()> {
+        let root = std::env::temp_dir().join(format!("dockstack-detect-{}", std::process::id()));
+        let codebase = |name: &str, files: &[(&str, &str)]| -> io::Result<PathBuf> {
+            let dir = root.join(name);
+            fs::create_dir_all(dir.join("bin"))?;
+            for (file, content) in files {
+                fs::write(dir.join(file), content)?;
+            }
+            Ok(dir)
+        };
+        let composer = r#"{"require": {"php": "^8.2"}}"#;
+        let laravel = codebase(
+            "laravel",
+            &[
+                ("artisan", ""),
+                ("composer.json", composer),
+                ("package.json", "{}"),
+            ],
+        )?;
+        let symfony = codebase("symfony", &[("composer.json", "{}"), ("bin/console", "")])?;
+        let node = codebase(
+            "node",
+            &[("package.json", r#"{"engines": {"node": ">=20.1"}}"#)],
+        )?;
+        let rails = codebase("rails", &[("Gemfile", ""), (".ruby-version", "3.2.2\n")])?;
+        let empty = codebase("empty", &[])?;
+
+        let detected = [&laravel, &symfony, &node, &rails, &empty].map(|dir| detect_in(dir));
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(
+            detected,
+            [
+                Some((Framework::Laravel, Some("8.2".to_string()))),
+                Some((Framework::Symfony, None)),
+                Some((Framework::Node, Some("20".to_string()))),
+                Some((Framework::Rails, Some("3.2".to_string()))),
+                None,
+            ]
+        );
+        Ok(())
+    }
+}
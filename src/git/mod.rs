@@ -0,0 +1,238 @@
+use crate::config::AppConfig;
+use crate::error::DockStackError;
+use crate::runtime::RuntimeHandle;
+use crossbeam_channel::{Receiver, Sender};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Compose files a repository may bring, in the order docker compose looks for them.
+const COMPOSE_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// A cloned repository, moved into its project directory.
+#[derive(Debug, Clone)]
+pub struct ClonedRepo {
+    pub url: String,
+    pub name: String,
+    pub directory: PathBuf,
+    /// The repository's own compose file; without one the code was cloned into `www/`.
+    pub compose_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub enum GitEvent {
+    /// Latest `git clone --progress` line, e.g. `Receiving objects:  42% (420/1000)`.
+    Progress(String),
+    Cloned(Result<ClonedRepo, DockStackError>),
+}
+
+/// Clones project repositories with the `git` binary on the runtime's blocking pool.
+pub struct GitClient {
+    pub git_available: bool,
+    pub event_tx: Sender<GitEvent>,
+    pub event_rx: Receiver<GitEvent>,
+    runtime: RuntimeHandle,
+}
+
+impl GitClient {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::bounded(256);
+        Self {
+            git_available: which::which("git").is_ok(),
+            event_tx,
+            event_rx,
+            runtime,
+        }
+    }
+
+    /// Clone `url` into a new directory under the projects root.
+    pub fn clone_project(&self, url: &str) {
+        let url = url.trim().to_string();
+        let tx = self.event_tx.clone();
+        self.runtime.spawn_blocking(move || {
+            tx.send(GitEvent::Cloned(clone_project(&url, &tx))).ok();
+        });
+    }
+}
+
+fn clone_project(url: &str, tx: &Sender<GitEvent>) -> Result<ClonedRepo, DockStackError> {
+    // Git would read a leading dash as one of its own options
+    if url.is_empty() || url.starts_with('-') {
+        return Err(DockStackError::Config(format!(
+            "\"{}\" is not a repository URL",
+            url
+        )));
+    }
+    let name = repo_name(url);
+    let root = AppConfig::projects_root();
+    let directory = free_directory(&root, &name);
+    let staging = root.join(format!(".{}.clone", name));
+    fs::create_dir_all(&root)
+        .map_err(|e| DockStackError::io(format!("Cannot create {}", root.display()), &e))?;
+    if staging.exists() {
+        fs::remove_dir_all(&staging).ok();
+    }
+
+    let context = format!("Failed to clone {}", url);
+    let mut child = Command::new("git")
+        .args(["clone", "--progress", "--", url])
+        .arg(&staging)
+        .env("GIT_TERMINAL_PROMPT", "0") // Fail instead of waiting for a password
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| DockStackError::spawn(&context, "git", &e))?;
+
+    // Progress lines are redrawn with `\r`, so split on both line endings
+    let mut last_line = String::new();
+    if let Some(stderr) = child.stderr.take() {
+        let mut reader = BufReader::new(stderr);
+        let mut chunk = Vec::new();
+        while reader.read_until(b'\r', &mut chunk).is_ok_and(|n| n > 0) {
+            for line in String::from_utf8_lossy(&chunk).split(['\r', '\n']) {
+                let line = line.trim();
+                if !line.is_empty() {
+                    last_line = line.to_string();
+                    tx.send(GitEvent::Progress(last_line.clone())).ok();
+                }
+            }
+            chunk.clear();
+        }
+    }
+    let status = child
+        .wait()
+        .map_err(|e| DockStackError::spawn(&context, "git", &e))?;
+    if !status.success() {
+        fs::remove_dir_all(&staging).ok();
+        return Err(DockStackError::Command {
+            context,
+            detail: last_line,
+        });
+    }
+
+    let compose_file = place_clone(&staging, &directory).map_err(|e| {
+        fs::remove_dir_all(&staging).ok();
+        DockStackError::io(format!("Cannot move {}", staging.display()), &e)
+    })?;
+    log::info!("Cloned {} into {}", url, directory.display());
+    Ok(ClonedRepo {
+        url: url.to_string(),
+        name,
+        directory,
+        compose_file,
+    })
+}
+
+/// Move the clone to `directory` when it brings a compose file (returned), or
+/// to `directory/www` so DockStack's own stack serves it.
+fn place_clone(staging: &Path, directory: &Path) -> std::io::Result<Option<PathBuf>> {
+    let compose = COMPOSE_FILES.iter().find(|f| staging.join(f).is_file());
+    match compose {
+        Some(file) => {
+            fs::rename(staging, directory)?;
+            Ok(Some(directory.join(file)))
+        }
+        None => {
+            fs::create_dir_all(directory)?;
+            fs::rename(staging, directory.join("www"))?;
+            Ok(None)
+        }
+    }
+}
+
+/// `https://host/org/shop.git` or `git@host:org/shop.git` → `shop`.
+fn repo_name(url: &str) -> String {
+    let last = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    let name: String = last
+        .trim_end_matches(".git")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    match name.trim_start_matches('.') {
+        "" => "repository".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// `root/name`, or `root/name-2`, `root/name-3`… when it is taken.
+fn free_directory(root: &Path, name: &str) -> PathBuf {
+    let mut directory = root.join(name);
+    let mut n = 2;
+    while directory.exists() {
+        directory = root.join(format!("{}-{}", name, n));
+        n += 1;
+    }
+    directory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn repo_names_come_from_the_last_path_segment() {
+        assert_eq!(repo_name("https://github.com/acme/shop.git"), "shop");
+        assert_eq!(repo_name("https://github.com/acme/shop/"), "shop");
+        assert_eq!(repo_name("git@github.com:acme/web-app.git"), "web-app");
+        assert_eq!(repo_name("git@host:shop"), "shop");
+        assert_eq!(repo_name("https://host/acme/../.hidden"), "hidden");
+        assert_eq!(repo_name("https://host/acme/$(rm -rf)"), "rm-rf");
+        assert_eq!(repo_name("https://host/"), "host");
+        assert_eq!(repo_name(""), "repository");
+    }
+
+    #[test]
+    fn free_directory_skips_taken_names() -> io::Result<()> {
+        let root = std::env::temp_dir().join(format!("dockstack-free-{}", std::process::id()));
+        fs::create_dir_all(root.join("shop"))?;
+        fs::create_dir_all(root.join("shop-2"))?;
+        let taken = free_directory(&root, "shop");
+        let free = free_directory(&root, "blog");
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(taken, root.join("shop-3"));
+        assert_eq!(free, root.join("blog"));
+        Ok(())
+    }
+
+    #[test]
+    fn clones_with_a_compose_file_become_the_project_directory() -> io::Result<()> {
+        let root = std::env::temp_dir().join(format!("dockstack-place-{}", std::process::id()));
+        let (with, without) = (root.join(".with.clone"), root.join(".without.clone"));
+        fs::create_dir_all(&with)?;
+        fs::create_dir_all(&without)?;
+        fs::write(with.join("compose.yml"), "services: {}\n")?;
+        fs::write(without.join("index.php"), "<?php\n")?;
+
+        let compose_file = place_clone(&with, &root.join("with"))?;
+        let none = place_clone(&without, &root.join("without"))?;
+        let served = root.join("without/www/index.php").is_file();
+        let staged = with.exists() || without.exists();
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(compose_file, Some(root.join("with/compose.yml")));
+        assert_eq!(none, None);
+        assert!(served);
+        assert!(!staged);
+        Ok(())
+    }
+
+    #[test]
+    fn urls_that_look_like_options_are_refused() {
+        let (tx, _rx) = crossbeam_channel::unbounded();
+        let result = clone_project("--upload-pack=touch /tmp/pwned", &tx);
+        assert!(matches!(result, Err(DockStackError::Config(_))));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::config::{AppConfig, ProjectConfig};
+use crate::docker::compose;
+use crate::docker::manager;
+use crate::error::{DockStackError, Result as DsResult};
+use crate::runtime::RuntimeHandle;
+use crossbeam_channel::{Receiver, Sender};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Budget of a single hook call, so a runaway loop ends instead of holding a
+/// worker thread forever.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Something that happened to the active project, handed to hook scripts.
+#[derive(Debug, Clone)]
+pub enum HookEvent {
+    StackStarted,
+    StackStopped,
+    /// Container name and its compose service.
+    ContainerUnhealthy(String, String),
+    /// Snapshot archive just written.
+    BackupFinished(PathBuf),
+}
+
+impl HookEvent {
+    /// Script function called for the event, e.g. `on_stack_started(event)`.
+    pub fn function(&self) -> &'static str {
+        match self {
+            Self::StackStarted => "on_stack_started",
+            Self::StackStopped => "on_stack_stopped",
+            Self::ContainerUnhealthy(..) => "on_container_unhealthy",
+            Self::BackupFinished(_) => "on_backup_finished",
+        }
+    }
+
+    /// The `event` argument scripts get: the project, plus what the event is about.
+    fn to_map(&self, project: &ProjectConfig) -> Map {
+        let mut map = Map::new();
+        map.insert("project".into(), project.name.clone().into());
+        map.insert("project_id".into(), project.id.clone().into());
+        map.insert("directory".into(), project.directory.clone().into());
+        match self {
+            Self::StackStarted | Self::StackStopped => {}
+            Self::ContainerUnhealthy(container, service) => {
+                map.insert("container".into(), container.clone().into());
+                map.insert("service".into(), service.clone().into());
+            }
+            Self::BackupFinished(path) => {
+                map.insert("path".into(), path.display().to_string().into());
+            }
+        }
+        map
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum HookOutput {
+    /// Title and body a script asked to show.
+    Notify(String, String),
+    /// Why a script failed; the context names the script.
+    Failed(DockStackError),
+}
+
+/// Runs the user's Rhai scripts on the runtime's blocking pool.
+pub struct HookRunner {
+    pub event_tx: Sender<HookOutput>,
+    pub event_rx: Receiver<HookOutput>,
+    runtime: RuntimeHandle,
+}
+
+impl HookRunner {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::bounded(64);
+        Self {
+            event_tx,
+            event_rx,
+            runtime,
+        }
+    }
+
+    /// Call the event's function in every script that defines it. Scripts are
+    /// read again each time, so edits apply without a restart.
+    pub fn fire(&self, event: HookEvent, project: &ProjectConfig) {
+        let project = project.clone();
+        let tx = self.event_tx.clone();
+        self.runtime.spawn_blocking(move || {
+            let scripts: Vec<PathBuf> = scripts()
+                .into_iter()
+                .filter(|path| defines(path, event.function()))
+                .collect();
+            if scripts.is_empty() {
+                return;
+            }
+            let engine = engine(&project, &tx);
+            for path in scripts {
+                let script = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                log::info!("Running {} of {}", event.function(), script);
+                if let Err(e) = run(&engine, &path, &event, &project) {
+                    log::warn!("{}", e);
+                    tx.send(HookOutput::Failed(e)).ok();
+                }
+            }
+        });
+    }
+}
+
+pub fn hooks_dir() -> PathBuf {
+    AppConfig::config_dir().join("hooks")
+}
+
+/// `.rhai` files in the hooks folder, by name.
+pub fn scripts() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(hooks_dir())
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    paths.retain(|p| p.extension().is_some_and(|e| e == "rhai"));
+    paths.sort();
+    paths
+}
+
+/// Cheap check before compiling: whether the script mentions the function.
+fn defines(path: &Path, function: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|source| source.contains(function))
+}
+
+fn run(engine: &Engine, path: &Path, event: &HookEvent, project: &ProjectConfig) -> DsResult<()> {
+    let script = path.file_name().unwrap_or_default().to_string_lossy();
+    let context = format!("Hook script {}", script);
+    let failed = |detail: String| DockStackError::Command {
+        context: context.clone(),
+        detail,
+    };
+    let source = fs::read_to_string(path).map_err(|e| DockStackError::io(&context, &e))?;
+    let ast = engine.compile(&source).map_err(|e| failed(e.to_string()))?;
+    let function = event.function();
+    if !ast
+        .iter_functions()
+        .any(|f| f.name == function && f.params.len() == 1)
+    {
+        return Ok(());
+    }
+    engine
+        .call_fn::<Dynamic>(&mut Scope::new(), &ast, function, (event.to_map(project),))
+        .map(|_| ())
+        .map_err(|e| failed(e.to_string()))
+}
+
+/// An engine without module loading or host access beyond the hook API:
+/// `exec(service, command)`, `notify(title, body)`, `write_file(path, text)`
+/// inside the project directory, and `print` to the App Log.
+fn engine(project: &ProjectConfig, tx: &Sender<HookOutput>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| log::info!("[hook] {}", text));
+    engine.on_debug(|text, _, _| log::debug!("[hook] {}", text));
+
+    let exec_project = project.clone();
+    engine.register_fn(
+        "exec",
+        move |service: &str, command: &str| -> Result<Map, Box<EvalAltResult>> {
+            let container = compose::container_name(&exec_project, service);
+            let (code, output) =
+                manager::exec_in_container(&container, command).map_err(|e| e.to_string())?;
+            let mut result = Map::new();
+            result.insert("code".into(), (code as i64).into());
+            result.insert("output".into(), output.into());
+            Ok(result)
+        },
+    );
+
+    let notify_tx = tx.clone();
+    engine.register_fn("notify", move |title: &str, body: &str| {
+        notify_tx
+            .send(HookOutput::Notify(title.to_string(), body.to_string()))
+            .ok();
+    });
+
+    let directory = PathBuf::from(&project.directory);
+    engine.register_fn(
+        "write_file",
+        move |path: &str, text: &str| -> Result<(), Box<EvalAltResult>> {
+            let target = project_file(&directory, path).map_err(|e| e.to_string())?;
+            fs::write(&target, text).map_err(|e| format!("{}: {}", target.display(), e))?;
+            Ok(())
+        },
+    );
+    engine
+}
+
+/// `path` under the project directory, with its parent folders created.
+/// Absolute paths, `..` and symlinks leading out of the directory are refused.
+fn project_file(directory: &Path, path: &str) -> DsResult<PathBuf> {
+    let outside = || {
+        DockStackError::Config(format!(
+            "write_file: {} is not a path inside the project directory",
+            path
+        ))
+    };
+    let relative = Path::new(path);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    let name = relative
+        .file_name()
+        .filter(|_| inside)
+        .ok_or_else(outside)?;
+    let target = directory.join(relative);
+    let parent = target.parent().unwrap_or(directory);
+    fs::create_dir_all(parent).map_err(|e| DockStackError::io(parent.display().to_string(), &e))?;
+    let canonical = |p: &Path| {
+        p.canonicalize()
+            .map_err(|e| DockStackError::io(p.display().to_string(), &e))
+    };
+    let parent = canonical(parent)?;
+    if !parent.starts_with(canonical(directory)?) {
+        return Err(outside());
+    }
+    let target = parent.join(name);
+    if fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink()) {
+        return Err(outside());
+    }
+    Ok(target)
+}
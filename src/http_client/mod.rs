@@ -0,0 +1,119 @@
+use crate::config::SavedRequest;
+use crate::error::{DockStackError, Result};
+use crate::runtime::RuntimeHandle;
+use crossbeam_channel::{Receiver, Sender};
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+pub const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// Response bodies larger than this are truncated.
+const MAX_BODY_BYTES: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub enum HttpEvent {
+    Response(Result<HttpResponse>),
+}
+
+/// Sends requests from the API client panel on the runtime's blocking pool.
+pub struct HttpClient {
+    pub event_tx: Sender<HttpEvent>,
+    pub event_rx: Receiver<HttpEvent>,
+    runtime: RuntimeHandle,
+}
+
+impl HttpClient {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::bounded(16);
+        Self {
+            event_tx,
+            event_rx,
+            runtime,
+        }
+    }
+
+    pub fn send(&self, request: SavedRequest) {
+        let tx = self.event_tx.clone();
+        self.runtime.spawn_blocking(move || {
+            tx.send(HttpEvent::Response(execute(&request))).ok();
+        });
+    }
+}
+
+fn execute(request: &SavedRequest) -> Result<HttpResponse> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(30))
+        .build();
+
+    let mut req = agent.request(&request.method, request.url.trim());
+    for line in request.headers.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if !name.trim().is_empty() {
+            req = req.set(name.trim(), value.trim());
+        }
+    }
+
+    let started = Instant::now();
+    let result = if request.body.is_empty() {
+        req.call()
+    } else {
+        req.send_string(&request.body)
+    };
+    let response = match result {
+        Ok(resp) => resp,
+        // Error statuses are still responses worth inspecting
+        Err(ureq::Error::Status(_, resp)) => resp,
+        Err(e) => {
+            return Err(DockStackError::Command {
+                context: format!("{} {} failed", request.method, request.url.trim()),
+                detail: e.to_string(),
+            })
+        }
+    };
+
+    let status = response.status();
+    let status_text = response.status_text().to_string();
+    let headers = response
+        .headers_names()
+        .into_iter()
+        .map(|name| {
+            let value = response.header(&name).unwrap_or_default().to_string();
+            (name, value)
+        })
+        .collect();
+    let is_json = response.content_type().contains("json");
+
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_BODY_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| DockStackError::io("Failed to read response body", &e))?;
+    let elapsed = started.elapsed();
+
+    let mut body = String::from_utf8_lossy(&bytes).to_string();
+    if is_json {
+        if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+            body = serde_json::to_string_pretty(&value).unwrap_or(body);
+        }
+    }
+
+    Ok(HttpResponse {
+        status,
+        status_text,
+        headers,
+        body,
+        elapsed,
+    })
+}
@@ -0,0 +1,107 @@
+use crate::runtime::RuntimeHandle;
+use crossbeam_channel::{Receiver, Sender};
+use std::time::Duration;
+
+const SEARCH_URL: &str = "https://hub.docker.com/v2/search/repositories/";
+/// Results asked for per search; Docker Hub ranks the best matches first.
+const PAGE_SIZE: usize = 25;
+
+/// One Docker Hub repository found by a search.
+#[derive(Debug, Clone)]
+pub struct HubImage {
+    pub name: String, // `redis` for official images, else `namespace/name`
+    pub description: String,
+    pub stars: u64,
+    pub pulls: u64,
+    pub official: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum HubEvent {
+    /// The query searched for and what Docker Hub returned.
+    Results(String, Result<Vec<HubImage>, String>),
+}
+
+/// Searches Docker Hub on the runtime's blocking pool.
+pub struct HubClient {
+    pub event_tx: Sender<HubEvent>,
+    pub event_rx: Receiver<HubEvent>,
+    runtime: RuntimeHandle,
+}
+
+impl HubClient {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::bounded(16);
+        Self {
+            event_tx,
+            event_rx,
+            runtime,
+        }
+    }
+
+    pub fn search(&self, query: &str) {
+        let query = query.trim().to_string();
+        let tx = self.event_tx.clone();
+        self.runtime.spawn_blocking(move || {
+            let results = search(&query);
+            tx.send(HubEvent::Results(query, results)).ok();
+        });
+    }
+}
+
+fn search(query: &str) -> Result<Vec<HubImage>, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(15))
+        .build();
+    let body = agent
+        .get(SEARCH_URL)
+        .query("query", query)
+        .query("page_size", &PAGE_SIZE.to_string())
+        .call()
+        .map_err(|e| format!("Docker Hub search failed: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read Docker Hub response: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Unexpected Docker Hub response: {}", e))?;
+    let results = value["results"].as_array().cloned().unwrap_or_default();
+    Ok(results
+        .iter()
+        .filter_map(|repo| {
+            Some(HubImage {
+                name: repo["repo_name"].as_str()?.to_string(),
+                description: repo["short_description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                stars: repo["star_count"].as_u64().unwrap_or(0),
+                pulls: repo["pull_count"].as_u64().unwrap_or(0),
+                official: repo["is_official"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect())
+}
+
+/// `1234567` → `1.2M`, the way Docker Hub shows pull counts.
+pub fn compact_count(n: u64) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{:.1}K", n as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1}M", n as f64 / 1e6),
+        _ => format!("{:.1}B", n as f64 / 1e9),
+    }
+}
+
+/// Service name for an image: its last path segment, in lowercase letters,
+/// digits and dashes, e.g. `n8nio/n8n` → `n8n`.
+pub fn service_name(image: &str) -> String {
+    let last = image.rsplit('/').next().unwrap_or(image);
+    let name: String = last
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    match name.trim_matches('-') {
+        "" => "image".to_string(),
+        name => name.to_string(),
+    }
+}
@@ -0,0 +1,264 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose::{self, ComposeModel};
+use crate::error::{DockStackError, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Self::Critical,
+        Self::High,
+        Self::Medium,
+        Self::Low,
+        Self::Unknown,
+    ];
+
+    /// Severity as either scanner spells it, e.g. `CRITICAL` or `critical`.
+    pub fn parse(text: &str) -> Self {
+        match text.to_ascii_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "medium" => Self::Medium,
+            "low" | "negligible" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Critical => "CRITICAL",
+            Self::High => "HIGH",
+            Self::Medium => "MEDIUM",
+            Self::Low => "LOW",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Vulnerability scanners DockStack can drive, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scanner {
+    Trivy,
+    Scout,
+}
+
+impl Scanner {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Trivy => "Trivy",
+            Self::Scout => "Docker Scout",
+        }
+    }
+}
+
+/// One vulnerable package in an image.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub image: String,
+    pub services: Vec<String>, // Services running the image
+    pub id: String,            // e.g. CVE-2024-1234
+    pub severity: Severity,
+    pub package: String,
+    pub installed: String,
+    pub fixed: String, // Version with the fix; empty while there is none
+    pub title: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanReport {
+    pub project: String,
+    pub project_id: String,
+    pub scanner: Scanner,
+    pub created_at: chrono::DateTime<chrono::Local>,
+    pub images: usize,
+    pub findings: Vec<Finding>, // Most severe first
+    pub errors: Vec<String>,    // Images that could not be scanned, and why
+}
+
+impl ScanReport {
+    pub fn new(project: &ProjectConfig, scanner: Scanner, images: usize) -> Self {
+        Self {
+            project: project.name.clone(),
+            project_id: project.id.clone(),
+            scanner,
+            created_at: chrono::Local::now(),
+            images,
+            findings: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// Services whose image has critical findings, with how many.
+    pub fn critical_services(&self) -> BTreeMap<String, usize> {
+        let mut services = BTreeMap::new();
+        for finding in self
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Critical)
+        {
+            for service in &finding.services {
+                *services.entry(service.clone()).or_insert(0) += 1;
+            }
+        }
+        services
+    }
+
+    /// Add an image's findings, keeping the list most severe first.
+    pub fn add(&mut self, findings: Vec<Finding>) {
+        self.findings.extend(findings);
+        self.findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.image.cmp(&b.image))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    }
+}
+
+/// Images of the project's enabled services, each with the services running
+/// it. Images built from a local context go by the name compose gives them.
+pub fn targets(project: &ProjectConfig) -> Vec<(String, Vec<String>)> {
+    let model = ComposeModel::from_project(project);
+    let mut images: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, service) in &model.services {
+        let image = match service.get("image").and_then(|i| i.as_str()) {
+            Some(image) => image.to_string(),
+            None => format!("{}-{}", compose::compose_project_name(project), name),
+        };
+        images.entry(image).or_default().push(name.clone());
+    }
+    images.into_iter().collect()
+}
+
+/// Findings in `trivy image --format json` output.
+pub fn parse_trivy(json: &str, image: &str, services: &[String]) -> Result<Vec<Finding>> {
+    let report: Value = serde_json::from_str(json).map_err(|e| unreadable("Trivy", e))?;
+    let mut findings = Vec::new();
+    let results = report["Results"].as_array().into_iter().flatten();
+    for vulnerability in results.flat_map(|r| r["Vulnerabilities"].as_array().into_iter().flatten())
+    {
+        let text = |key: &str| vulnerability[key].as_str().unwrap_or_default().to_string();
+        findings.push(Finding {
+            image: image.to_string(),
+            services: services.to_vec(),
+            id: text("VulnerabilityID"),
+            severity: Severity::parse(&text("Severity")),
+            package: text("PkgName"),
+            installed: text("InstalledVersion"),
+            fixed: text("FixedVersion"),
+            title: text("Title"),
+        });
+    }
+    Ok(findings)
+}
+
+/// Findings in `docker scout cves --format gitlab` output, which follows
+/// GitLab's container scanning report.
+pub fn parse_scout(json: &str, image: &str, services: &[String]) -> Result<Vec<Finding>> {
+    let report: Value = serde_json::from_str(json).map_err(|e| unreadable("Docker Scout", e))?;
+    let mut findings = Vec::new();
+    for vulnerability in report["vulnerabilities"].as_array().into_iter().flatten() {
+        let dependency = &vulnerability["location"]["dependency"];
+        let id = vulnerability["identifiers"][0]["value"]
+            .as_str()
+            .or(vulnerability["name"].as_str())
+            .or(vulnerability["id"].as_str())
+            .unwrap_or_default();
+        let solution = vulnerability["solution"].as_str().unwrap_or_default();
+        findings.push(Finding {
+            image: image.to_string(),
+            services: services.to_vec(),
+            id: id.to_string(),
+            severity: Severity::parse(vulnerability["severity"].as_str().unwrap_or_default()),
+            package: dependency["package"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            installed: dependency["version"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            // e.g. "Upgrade openssl to 3.0.13"
+            fixed: solution
+                .rsplit_once(" to ")
+                .map_or("", |(_, version)| version)
+                .to_string(),
+            title: vulnerability["description"]
+                .as_str()
+                .unwrap_or_default()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+    Ok(findings)
+}
+
+fn unreadable(scanner: &str, e: serde_json::Error) -> DockStackError {
+    DockStackError::Command {
+        context: format!("Unreadable {} report", scanner),
+        detail: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::compose::tests::project;
+
+    #[test]
+    fn image_scan_reports_findings_per_service() -> Result<()> {
+        let project = project(&["mysql", "php", "nginx"]);
+        let targets = targets(&project);
+        let mysql_image = ComposeModel::from_project(&project).services["mysql"]["image"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let services = vec!["mysql".to_string()];
+        assert!(targets.contains(&(mysql_image.clone(), services.clone())));
+
+        let trivy = r#"{"Results": [{"Target": "mysql", "Vulnerabilities": [
+            {"VulnerabilityID": "CVE-2024-0001", "PkgName": "openssl",
+             "InstalledVersion": "3.0.2", "FixedVersion": "3.0.13",
+             "Severity": "CRITICAL", "Title": "Buffer overflow"},
+            {"VulnerabilityID": "CVE-2024-0002", "PkgName": "zlib",
+             "InstalledVersion": "1.2.11", "Severity": "LOW"}]}]}"#;
+        let scout = r#"{"vulnerabilities": [{"severity": "High",
+            "identifiers": [{"type": "cve", "value": "CVE-2024-0003"}],
+            "location": {"dependency": {"package": {"name": "curl"}, "version": "7.88"}},
+            "solution": "Upgrade curl to 8.4.0", "description": "Heap overflow\nDetails"}]}"#;
+        let mut report = ScanReport::new(&project, Scanner::Trivy, 2);
+        let php = ["php".to_string()];
+        report.add(parse_scout(scout, "php:8.3-fpm", &php)?);
+        report.add(parse_trivy(trivy, &mysql_image, &services)?);
+
+        let ids: Vec<&str> = report.findings.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, ["CVE-2024-0001", "CVE-2024-0003", "CVE-2024-0002"]);
+        assert_eq!(report.findings[0].fixed, "3.0.13");
+        assert_eq!(report.findings[1].fixed, "8.4.0");
+        assert_eq!(report.findings[1].title, "Heap overflow");
+        assert_eq!(report.findings[2].fixed, "");
+        assert_eq!(report.count(Severity::Low), 1);
+        assert_eq!(report.critical_services().get("mysql"), Some(&1));
+        assert!(!report.critical_services().contains_key("php"));
+        assert!(parse_trivy("not json", &mysql_image, &services).is_err());
+        Ok(())
+    }
+}
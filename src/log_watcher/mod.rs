@@ -0,0 +1,142 @@
+use crate::config::{LogSource, ProjectConfig};
+use crate::utils::push_capped;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+
+/// Tails the active project's host log files into the shared log buffer.
+///
+/// Lines are prefixed with `[<file name>]`, or the path when file names clash,
+/// so the Logs tab can color and filter them per source.
+pub struct LogWatcher {
+    logs: Arc<Mutex<VecDeque<String>>>,
+    capacity: Arc<AtomicUsize>, // Shared with `DockerManager::log_capacity`
+    watcher: Option<RecommendedWatcher>,
+    project_id: String,
+    sources: Vec<LogSource>,
+}
+
+impl LogWatcher {
+    pub fn new(logs: Arc<Mutex<VecDeque<String>>>, capacity: Arc<AtomicUsize>) -> Self {
+        Self {
+            logs,
+            capacity,
+            watcher: None,
+            project_id: String::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Restart the watcher if the project or its log sources changed.
+    /// Cheap to call every frame.
+    pub fn sync(&mut self, project: &ProjectConfig) {
+        if self.project_id == project.id && self.sources == project.log_files {
+            return;
+        }
+        self.project_id = project.id.clone();
+        self.sources = project.log_files.clone();
+        self.watcher = None;
+
+        let files: Vec<(PathBuf, String)> = self
+            .sources
+            .iter()
+            .filter(|s| s.enabled)
+            .map(|s| (s.resolve(&project.directory), s.label(&self.sources)))
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+
+        // Only new lines are shown; start from the current end of each file.
+        let mut offsets: HashMap<PathBuf, u64> = files
+            .iter()
+            .map(|(path, _)| {
+                let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                (path.clone(), len)
+            })
+            .collect();
+
+        let logs = self.logs.clone();
+        let capacity = self.capacity.clone();
+        let tracked = files.clone();
+        let handler = move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                return;
+            }
+            for path in &event.paths {
+                let Some((_, label)) = tracked.iter().find(|(p, _)| p == path) else {
+                    continue;
+                };
+                let offset = offsets.entry(path.clone()).or_insert(0);
+                read_new_lines(path, offset, label, &logs, &capacity);
+            }
+        };
+
+        let mut watcher = match notify::recommended_watcher(handler) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("Could not start log file watcher: {}", e);
+                return;
+            }
+        };
+
+        // Watch the parent directories so files that are created or rotated later are picked up.
+        let mut dirs: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|(path, _)| path.parent().map(|p| p.to_path_buf()))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                log::warn!("Cannot watch {}: {}", dir.display(), e);
+            }
+        }
+        self.watcher = Some(watcher);
+    }
+}
+
+fn read_new_lines(
+    path: &Path,
+    offset: &mut u64,
+    label: &str,
+    logs: &Arc<Mutex<VecDeque<String>>>,
+    capacity: &AtomicUsize,
+) {
+    let Ok(mut file) = File::open(path) else {
+        return;
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len < *offset {
+        // Truncated or rotated: start over
+        *offset = 0;
+    }
+    if file.seek(SeekFrom::Start(*offset)).is_err() {
+        return;
+    }
+
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    let mut l = logs.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                // Leave a partially written last line for the next event
+                if !line.ends_with('\n') {
+                    break;
+                }
+                *offset += n as u64;
+                push_capped(&mut l, format!("[{}] {}", label, line.trim_end()), capacity);
+            }
+        }
+    }
+}
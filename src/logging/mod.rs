@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Records kept for the App Log tab; older ones are dropped.
+const CAPACITY: usize = 2000;
+
+static RECORDS: Mutex<VecDeque<AppLogRecord>> = Mutex::new(VecDeque::new());
+/// Bumped whenever `RECORDS` changes, so the App Log tab copies it only then.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// One `log` record as shown in the App Log tab.
+#[derive(Debug, Clone)]
+pub struct AppLogRecord {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for AppLogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.time.format("%H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Writes to env_logger as before and mirrors every record it lets through
+/// into the in-memory buffer.
+struct TeeLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for TeeLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        let entry = AppLogRecord {
+            time: chrono::Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        let mut records = RECORDS.lock().unwrap_or_else(|e| e.into_inner());
+        if records.len() == CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(entry);
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+        drop(records);
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the logger. `RUST_LOG` still controls what is recorded.
+pub fn init() {
+    let inner = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("warn,dockstack=info"),
+    )
+    .format_timestamp_secs()
+    .build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(TeeLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Copy of the buffered records at `min_level` or more severe, oldest first.
+pub fn snapshot(min_level: Option<log::Level>) -> Vec<AppLogRecord> {
+    RECORDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|r| min_level.is_none_or(|min| r.level <= min))
+        .cloned()
+        .collect()
+}
+
+pub fn clear() {
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Changes whenever a record is added or the buffer cleared.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
@@ -1,11 +1,28 @@
+mod audit;
 mod config;
+mod diagnostics;
 mod docker;
+mod doctor;
+mod error;
+mod framework;
+mod git;
+mod hooks;
+mod http_client;
+mod hub;
+mod image_scan;
+mod log_watcher;
+mod logging;
 mod monitor;
+mod plugins;
 mod port_scanner;
+mod registry;
+mod runtime;
 mod services;
 mod ssl;
+mod status_page;
 mod terminal;
 mod tray;
+mod tunnel;
 mod ui;
 mod utils;
 
@@ -19,13 +36,10 @@ fn main() -> eframe::Result<()> {
         }
     }
 
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("warn,dockstack=info"),
-    )
-    .format_timestamp_secs()
-    .init();
+    logging::init();
 
     log::info!("Starting DockStack v0.1.0");
+    diagnostics::install_panic_hook();
 
     // Check and set DOCKER_API_VERSION for compatibility with older engines
     if let Ok(output) = std::process::Command::new("docker")
@@ -67,6 +81,6 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "DockStack",
         options,
-        Box::new(|cc| Ok(Box::new(DockStackApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(DockStackApp::new(cc)?))),
     )
 }
@@ -1,13 +1,24 @@
 #![allow(dead_code)]
+use crate::error::DockStackError;
+use crate::runtime::{CancelToken, RuntimeHandle, WorkerHandle};
 use crossbeam_channel::{Receiver, Sender};
+use serde::Serialize;
 use std::collections::VecDeque;
-use std::process::Command;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::{Arc, Mutex};
-use std::thread;
 use std::time::Duration;
 use sysinfo::System;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
 
-#[derive(Debug, Clone, Default)]
+/// Wait before restarting the stats stream after it exits.
+const STATS_RESTART_DELAY: Duration = Duration::from_secs(5);
+/// Samples kept for export, one per second: the last ten minutes.
+const HISTORY_SAMPLES: usize = 600;
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SystemStats {
     pub cpu_usage: f32,
     pub memory_used: u64,
@@ -15,7 +26,7 @@ pub struct SystemStats {
     pub memory_percent: f32,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ContainerStats {
     pub name: String,
     pub cpu_percent: String,
@@ -25,10 +36,42 @@ pub struct ContainerStats {
     pub block_io: String,
 }
 
+impl ContainerStats {
+    /// Memory in use, from Docker's `12.5MiB / 1GiB`; 0 when it cannot be read.
+    pub fn memory_bytes(&self) -> u64 {
+        let used = self.mem_usage.split('/').next().unwrap_or_default().trim();
+        let split = used
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(used.len());
+        let (number, unit) = used.split_at(split);
+        let factor: f64 = match unit.trim() {
+            "B" => 1.0,
+            "kB" | "KB" => 1e3,
+            "KiB" => 1024.0,
+            "MB" => 1e6,
+            "MiB" => 1024.0 * 1024.0,
+            "GB" => 1e9,
+            "GiB" => 1024.0 * 1024.0 * 1024.0,
+            _ => return 0,
+        };
+        (number.parse::<f64>().unwrap_or(0.0) * factor) as u64
+    }
+}
+
+/// System and container stats at one point in time, retained for export.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSample {
+    pub timestamp: String, // RFC 3339
+    pub system: SystemStats,
+    pub containers: Vec<ContainerStats>,
+}
+
 #[derive(Debug, Clone)]
 pub enum MonitorEvent {
     SystemUpdate(SystemStats),
     ContainerUpdate(Vec<ContainerStats>),
+    /// Where `export_history` wrote to, and how many samples, or why it could not.
+    HistoryExported(PathBuf, crate::error::Result<usize>),
 }
 
 pub struct ResourceMonitor {
@@ -36,146 +79,391 @@ pub struct ResourceMonitor {
     pub container_stats: Arc<Mutex<Vec<ContainerStats>>>,
     pub cpu_history: Arc<Mutex<VecDeque<f32>>>,
     pub mem_history: Arc<Mutex<VecDeque<f32>>>,
+    pub history: Arc<Mutex<VecDeque<MetricsSample>>>,
     pub event_tx: Sender<MonitorEvent>,
     pub event_rx: Receiver<MonitorEvent>,
-    running: Arc<Mutex<bool>>,
-    sys_thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
-    cont_thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    runtime: RuntimeHandle,
+    tasks: Mutex<Vec<WorkerHandle>>,
 }
 
 impl ResourceMonitor {
-    pub fn new() -> Self {
+    pub fn new(runtime: RuntimeHandle) -> Self {
         let (event_tx, event_rx) = crossbeam_channel::bounded(1000);
         Self {
             system_stats: Arc::new(Mutex::new(SystemStats::default())),
             container_stats: Arc::new(Mutex::new(Vec::new())),
             cpu_history: Arc::new(Mutex::new(VecDeque::from(vec![0.0; 60]))),
             mem_history: Arc::new(Mutex::new(VecDeque::from(vec![0.0; 60]))),
+            history: Arc::new(Mutex::new(VecDeque::new())),
             event_tx,
             event_rx,
-            running: Arc::new(Mutex::new(false)),
-            sys_thread: Arc::new(Mutex::new(None)),
-            cont_thread: Arc::new(Mutex::new(None)),
+            runtime,
+            tasks: Mutex::new(Vec::new()),
         }
     }
 
     pub fn start(&self) {
-        let running = self.running.clone();
-        {
-            let mut r = running.lock().unwrap_or_else(|e| e.into_inner());
-            if *r {
-                return;
-            }
-            *r = true;
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if !tasks.is_empty() {
+            return;
         }
 
-        // System stats thread
+        // System stats task
         let sys_stats = self.system_stats.clone();
         let cpu_history = self.cpu_history.clone();
         let mem_history = self.mem_history.clone();
+        let history = self.history.clone();
+        let latest_containers = self.container_stats.clone();
         let tx = self.event_tx.clone();
-        let running_sys = self.running.clone();
-
-        let sys_handle = thread::spawn(move || {
-            let mut sys = System::new_all();
-            while *running_sys.lock().unwrap_or_else(|e| e.into_inner()) {
-                sys.refresh_cpu_usage();
-                sys.refresh_memory();
-
-                let cpu = sys.global_cpu_usage();
-                let mem_used = sys.used_memory();
-                let mem_total = sys.total_memory();
-                let mem_pct = if mem_total > 0 {
-                    (mem_used as f32 / mem_total as f32) * 100.0
-                } else {
-                    0.0
-                };
-
-                let stats = SystemStats {
-                    cpu_usage: cpu,
-                    memory_used: mem_used,
-                    memory_total: mem_total,
-                    memory_percent: mem_pct,
-                };
-
-                *sys_stats.lock().unwrap_or_else(|e| e.into_inner()) = stats.clone();
-
-                {
-                    let mut hist = cpu_history.lock().unwrap_or_else(|e| e.into_inner());
-                    hist.push_back(cpu);
-                    if hist.len() > 60 {
-                        hist.pop_front();
+        let cancel = self.runtime.cancel_token();
+
+        tasks.push(self.runtime.supervise("monitor.system", move || {
+            let sys_stats = sys_stats.clone();
+            let cpu_history = cpu_history.clone();
+            let mem_history = mem_history.clone();
+            let history = history.clone();
+            let latest_containers = latest_containers.clone();
+            let tx = tx.clone();
+            let mut cancel = cancel.clone();
+            async move {
+                let mut sys = System::new_all();
+                let mut ticker = tokio::time::interval(Duration::from_secs(1));
+                loop {
+                    tokio::select! {
+                        _ = cancel.cancelled() => break,
+                        _ = ticker.tick() => {}
                     }
-                }
-                {
-                    let mut hist = mem_history.lock().unwrap_or_else(|e| e.into_inner());
-                    hist.push_back(mem_pct);
-                    if hist.len() > 60 {
-                        hist.pop_front();
+                    sys.refresh_cpu_usage();
+                    sys.refresh_memory();
+
+                    let cpu = sys.global_cpu_usage();
+                    let mem_used = sys.used_memory();
+                    let mem_total = sys.total_memory();
+                    let mem_pct = if mem_total > 0 {
+                        (mem_used as f32 / mem_total as f32) * 100.0
+                    } else {
+                        0.0
+                    };
+
+                    let stats = SystemStats {
+                        cpu_usage: cpu,
+                        memory_used: mem_used,
+                        memory_total: mem_total,
+                        memory_percent: mem_pct,
+                    };
+
+                    *sys_stats.lock().unwrap_or_else(|e| e.into_inner()) = stats.clone();
+
+                    {
+                        let mut hist = cpu_history.lock().unwrap_or_else(|e| e.into_inner());
+                        hist.push_back(cpu);
+                        if hist.len() > 60 {
+                            hist.pop_front();
+                        }
+                    }
+                    {
+                        let mut hist = mem_history.lock().unwrap_or_else(|e| e.into_inner());
+                        hist.push_back(mem_pct);
+                        if hist.len() > 60 {
+                            hist.pop_front();
+                        }
+                    }
+
+                    {
+                        let sample = MetricsSample {
+                            timestamp: chrono::Local::now().to_rfc3339(),
+                            system: stats.clone(),
+                            containers: latest_containers
+                                .lock()
+                                .unwrap_or_else(|e| e.into_inner())
+                                .clone(),
+                        };
+                        let mut hist = history.lock().unwrap_or_else(|e| e.into_inner());
+                        hist.push_back(sample);
+                        if hist.len() > HISTORY_SAMPLES {
+                            hist.pop_front();
+                        }
                     }
-                }
 
-                tx.send(MonitorEvent::SystemUpdate(stats)).ok();
-                thread::sleep(Duration::from_secs(1));
+                    tx.try_send(MonitorEvent::SystemUpdate(stats)).ok();
+                }
             }
-        });
-        *self.sys_thread.lock().unwrap() = Some(sys_handle);
+        }));
 
-        // Container stats thread
+        // Container stats task: one long-lived `docker stats` stream
         let container_stats = self.container_stats.clone();
         let tx2 = self.event_tx.clone();
-        let running_cont = self.running.clone();
-
-        let cont_handle = thread::spawn(move || {
-            while *running_cont.lock().unwrap_or_else(|e| e.into_inner()) {
-                let output = Command::new("docker")
-                    .args([
-                        "stats",
-                        "--no-stream",
-                        "--format",
-                        "{{.Name}}|{{.CPUPerc}}|{{.MemUsage}}|{{.MemPerc}}|{{.NetIO}}|{{.BlockIO}}",
-                    ])
-                    .output();
-
-                if let Ok(out) = output {
-                    let stdout = String::from_utf8_lossy(&out.stdout);
-                    let stats: Vec<ContainerStats> = stdout
-                        .lines()
-                        .filter(|l| !l.is_empty())
-                        .map(|line| {
-                            let parts: Vec<&str> = line.splitn(6, '|').collect();
-                            ContainerStats {
-                                name: parts.first().unwrap_or(&"").to_string(),
-                                cpu_percent: parts.get(1).unwrap_or(&"").to_string(),
-                                mem_usage: parts.get(2).unwrap_or(&"").to_string(),
-                                mem_percent: parts.get(3).unwrap_or(&"").to_string(),
-                                net_io: parts.get(4).unwrap_or(&"").to_string(),
-                                block_io: parts.get(5).unwrap_or(&"").to_string(),
+        let cancel = self.runtime.cancel_token();
+
+        tasks.push(self.runtime.supervise("monitor.containers", move || {
+            let container_stats = container_stats.clone();
+            let tx2 = tx2.clone();
+            let mut cancel = cancel.clone();
+            async move {
+                while !cancel.is_cancelled() {
+                    let spawned = Command::new("docker")
+                        .arg("stats")
+                        .arg("--format")
+                        .arg("{{json .}}")
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::piped())
+                        .stderr(Stdio::null())
+                        .kill_on_drop(true)
+                        .spawn();
+
+                    match spawned {
+                        Ok(mut child) => {
+                            if let Some(stdout) = child.stdout.take() {
+                                read_stats_stream(stdout, &container_stats, &tx2, &mut cancel)
+                                    .await;
                             }
-                        })
-                        .collect();
+                            child.kill().await.ok();
+                        }
+                        Err(e) => log::warn!("Failed to start docker stats: {}", e),
+                    }
 
-                    *container_stats.lock().unwrap_or_else(|e| e.into_inner()) = stats.clone();
-                    tx2.send(MonitorEvent::ContainerUpdate(stats)).ok();
+                    // The stream ended: Docker restarted, went away, or we are stopping
+                    tokio::select! {
+                        _ = cancel.cancelled() => break,
+                        _ = tokio::time::sleep(STATS_RESTART_DELAY) => {}
+                    }
                 }
-
-                thread::sleep(Duration::from_secs(2));
             }
-        });
-        *self.cont_thread.lock().unwrap() = Some(cont_handle);
+        }));
     }
 
+    /// Stop both monitor tasks; dropping the stats child kills `docker stats`.
     pub fn stop(&self) {
-        *self.running.lock().unwrap_or_else(|e| e.into_inner()) = false;
-        if let Some(h) = self.sys_thread.lock().unwrap_or_else(|e| e.into_inner()).take() {
-            let _ = h.join();
-        }
-        if let Some(h) = self.cont_thread.lock().unwrap_or_else(|e| e.into_inner()).take() {
-            let _ = h.join();
+        for task in self
+            .tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+        {
+            task.abort();
         }
     }
 
+    /// Write the retained samples to `path` in the background: JSON for a `.json`
+    /// file, CSV otherwise. The outcome arrives as `HistoryExported`.
+    pub fn export_history(&self, path: PathBuf) {
+        let history = self.history.clone();
+        let tx = self.event_tx.clone();
+        self.runtime.spawn_blocking(move || {
+            let samples: Vec<MetricsSample> = history
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .cloned()
+                .collect();
+            let result = write_history(&path, &samples).map(|()| samples.len());
+            tx.send(MonitorEvent::HistoryExported(path, result)).ok();
+        });
+    }
+
     pub fn is_running(&self) -> bool {
-        *self.running.lock().unwrap_or_else(|e| e.into_inner())
+        !self
+            .tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+}
+
+fn write_history(path: &Path, samples: &[MetricsSample]) -> crate::error::Result<()> {
+    let content = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(samples)
+            .map_err(|e| DockStackError::Config(format!("Cannot encode metrics: {}", e)))?
+    } else {
+        history_csv(samples)
+    };
+    std::fs::write(path, content)
+        .map_err(|e| DockStackError::io(format!("Cannot write {}", path.display()), &e))
+}
+
+/// One row per sample for the host, then one per container in that sample.
+/// Container values keep Docker's formatting, e.g. `12.5MiB / 1GiB`.
+fn history_csv(samples: &[MetricsSample]) -> String {
+    let mut out =
+        String::from("timestamp,source,cpu_percent,mem_usage,mem_percent,net_io,block_io\n");
+    for sample in samples {
+        let system = &sample.system;
+        writeln!(
+            out,
+            "{},host,{:.1},{},{:.1},,",
+            sample.timestamp, system.cpu_usage, system.memory_used, system.memory_percent
+        )
+        .ok();
+        for c in &sample.containers {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                sample.timestamp,
+                csv_field(&c.name),
+                csv_field(c.cpu_percent.trim_end_matches('%')),
+                csv_field(&c.mem_usage),
+                csv_field(c.mem_percent.trim_end_matches('%')),
+                csv_field(&c.net_io),
+                csv_field(&c.block_io)
+            )
+            .ok();
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parse the streaming `docker stats` output. Each refresh starts by moving the
+/// cursor home, which marks the end of the previous snapshot.
+async fn read_stats_stream(
+    stdout: impl AsyncRead + Unpin,
+    container_stats: &Arc<Mutex<Vec<ContainerStats>>>,
+    tx: &Sender<MonitorEvent>,
+    cancel: &mut CancelToken,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    let mut frame: Vec<ContainerStats> = Vec::new();
+    let mut started = false;
+    loop {
+        let line = tokio::select! {
+            _ = cancel.cancelled() => return,
+            line = lines.next_line() => line,
+        };
+        let Ok(Some(line)) = line else {
+            return;
+        };
+        let new_frame = line.contains("\x1b[H");
+        let json = strip_ansi(&line);
+        let parsed = parse_stats_line(&json);
+        // Older clients without cursor codes: a repeated name starts the next snapshot
+        let repeated = parsed
+            .as_ref()
+            .is_some_and(|p| frame.iter().any(|s| s.name == p.name));
+        // An empty snapshot is still published so stopped containers drop off
+        if (new_frame && started) || (repeated && !frame.is_empty()) {
+            let stats = std::mem::take(&mut frame);
+            *container_stats.lock().unwrap_or_else(|e| e.into_inner()) = stats.clone();
+            tx.try_send(MonitorEvent::ContainerUpdate(stats)).ok();
+        }
+        if let Some(stats) = parsed {
+            frame.push(stats);
+        }
+        started = true;
+    }
+}
+
+fn parse_stats_line(line: &str) -> Option<ContainerStats> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    let field = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    Some(ContainerStats {
+        name: field("Name"),
+        cpu_percent: field("CPUPerc"),
+        mem_usage: field("MemUsage"),
+        mem_percent: field("MemPerc"),
+        net_io: field("NetIO"),
+        block_io: field("BlockIO"),
+    })
+}
+
+/// Remove the terminal control sequences `docker stats` interleaves with its output.
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequence: ESC [ params final-byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::AppRuntime;
+
+    fn stats_line(name: &str) -> String {
+        format!(
+            r#"{{"Name":"{}","CPUPerc":"1.5%","MemUsage":"10MiB / 1GiB"}}"#,
+            name
+        )
+    }
+
+    /// Container names of each snapshot `read_stats_stream` publishes for `output`.
+    fn frames(output: &str) -> std::io::Result<Vec<Vec<String>>> {
+        let app_runtime = AppRuntime::new()?;
+        let mut cancel = app_runtime.handle().cancel_token();
+        let container_stats = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tokio::runtime::Builder::new_current_thread()
+            .build()?
+            .block_on(read_stats_stream(
+                output.as_bytes(),
+                &container_stats,
+                &tx,
+                &mut cancel,
+            ));
+        Ok(rx
+            .try_iter()
+            .filter_map(|event| match event {
+                MonitorEvent::ContainerUpdate(stats) => {
+                    Some(stats.into_iter().map(|s| s.name).collect())
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn stats_stream_splits_snapshots_at_cursor_home() -> std::io::Result<()> {
+        let output = format!(
+            "\x1b[2J\x1b[H{}\x1b[K\n{}\x1b[K\n\x1b[H{}\x1b[K\n\x1b[J\x1b[H\n\x1b[H\n",
+            stats_line("web"),
+            stats_line("db"),
+            stats_line("web"),
+        );
+        assert_eq!(frames(&output)?, [vec!["web", "db"], vec!["web"], vec![]]);
+        Ok(())
+    }
+
+    #[test]
+    fn stats_stream_without_cursor_codes_splits_on_a_repeated_name() -> std::io::Result<()> {
+        let output = [stats_line("web"), stats_line("db"), stats_line("web")].join("\n");
+        assert_eq!(frames(&output)?, [vec!["web", "db"]]);
+        Ok(())
+    }
+
+    #[test]
+    fn strip_ansi_leaves_the_json() {
+        let line = format!("\x1b[2J\x1b[H{}\x1b[K", stats_line("web"));
+        assert_eq!(strip_ansi(&line), stats_line("web"));
+        let Some(stats) = parse_stats_line(&strip_ansi(&line)) else {
+            panic!("stats line was not parsed");
+        };
+        assert_eq!(stats.name, "web");
+        assert_eq!(stats.cpu_percent, "1.5%");
+        assert_eq!(stats.mem_usage, "10MiB / 1GiB");
     }
 }
@@ -0,0 +1,231 @@
+use crate::config::{AppConfig, ServiceConfig};
+use crate::error::{DockStackError, Result};
+use crate::services;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// Service categories a plugin can be listed under in the Services tab.
+pub const CATEGORIES: &[&str] = &[
+    "web_server",
+    "database",
+    "runtime",
+    "cache",
+    "search",
+    "admin",
+    "security",
+];
+
+/// Shared so the UI can list the plugins every frame without copying them.
+static INSTALLED: LazyLock<Mutex<Arc<Vec<ServicePlugin>>>> = LazyLock::new(Mutex::default);
+
+/// A service type described by a TOML descriptor in the plugins folder; see
+/// "Service plugins" in the README for the format.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServicePlugin {
+    pub id: String, // Service name in every project, e.g. `couchdb`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_icon")]
+    pub icon: String,
+    pub category: String, // One of CATEGORIES
+    #[serde(default = "latest")]
+    pub version: String,
+    pub port: u16, // Default host port
+    #[serde(default)]
+    pub container_port: Option<u16>, // When it differs from `port`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub settings: Vec<PluginSetting>,
+    pub template: String,
+    #[serde(skip)]
+    pub path: PathBuf, // Descriptor it was loaded from
+}
+
+/// A setting shown with the service in the Services tab and available to
+/// the template as `{{setting.<key>}}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginSetting {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub default: String,
+    #[serde(default)]
+    pub help: String,
+}
+
+fn default_icon() -> String {
+    "🧩".to_string()
+}
+
+fn latest() -> String {
+    "latest".to_string()
+}
+
+impl ServicePlugin {
+    /// Config new projects get for this service, disabled until turned on.
+    pub fn default_config(&self) -> ServiceConfig {
+        ServiceConfig {
+            enabled: false,
+            port: self.port,
+            version: self.version.clone(),
+            display_name: Some(self.name.clone()),
+            env_vars: self.env.clone().into_iter().collect(),
+            settings: self
+                .settings
+                .iter()
+                .map(|s| (s.key.clone(), s.default.clone()))
+                .collect(),
+            plugin: Some(self.id.clone()),
+            ..ServiceConfig::default()
+        }
+    }
+
+    pub fn container_port(&self) -> u16 {
+        self.container_port.unwrap_or(self.port)
+    }
+
+    /// The template filled in for `svc`, named `name` in `project`: the body of
+    /// its compose service.
+    pub fn render(
+        &self,
+        project: &str,
+        name: &str,
+        svc: &ServiceConfig,
+    ) -> Result<serde_yaml::Mapping> {
+        let mut vars: HashMap<String, String> = HashMap::new();
+        vars.insert("name".to_string(), name.to_string());
+        vars.insert("project".to_string(), project.to_string());
+        vars.insert("version".to_string(), svc.version.clone());
+        vars.insert("port".to_string(), svc.port.to_string());
+        vars.insert(
+            "container_port".to_string(),
+            self.container_port().to_string(),
+        );
+        for setting in &self.settings {
+            let value = svc
+                .settings
+                .get(&setting.key)
+                .filter(|v| !v.is_empty())
+                .cloned()
+                .unwrap_or_else(|| setting.default.clone());
+            vars.insert(format!("setting.{}", setting.key), value);
+        }
+        let mut text = self.template.clone();
+        for (key, value) in &vars {
+            text = text.replace(&format!("{{{{{}}}}}", key), value);
+        }
+        if let Some(start) = text.find("{{") {
+            let end = text[start..]
+                .find("}}")
+                .map_or(text.len(), |e| start + e + 2);
+            return Err(DockStackError::Config(format!(
+                "unknown placeholder {}",
+                &text[start..end]
+            )));
+        }
+        match serde_yaml::from_str(&text) {
+            Ok(serde_yaml::Value::Mapping(service)) => Ok(service),
+            Ok(_) => Err(DockStackError::Config(
+                "the template is not a mapping of compose keys".to_string(),
+            )),
+            Err(e) => Err(DockStackError::Config(format!("invalid template: {}", e))),
+        }
+    }
+}
+
+pub fn plugins_dir() -> PathBuf {
+    AppConfig::config_dir().join("plugins")
+}
+
+/// Read every descriptor in the plugins folder and make them the installed
+/// plugins. Invalid descriptors are logged and left out.
+pub fn load() {
+    let mut plugins = Vec::new();
+    let dir = plugins_dir();
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    for path in paths
+        .into_iter()
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+    {
+        match read(&path) {
+            Ok(plugin) if plugins.iter().any(|p: &ServicePlugin| p.id == plugin.id) => {
+                log::warn!(
+                    "Ignoring plugin {}: id {} is taken",
+                    path.display(),
+                    plugin.id
+                );
+            }
+            Ok(plugin) => {
+                log::info!("Loaded service plugin {} ({})", plugin.name, plugin.id);
+                plugins.push(plugin);
+            }
+            Err(e) => log::warn!("Ignoring plugin {}: {}", path.display(), e),
+        }
+    }
+    *INSTALLED.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(plugins);
+}
+
+fn read(path: &Path) -> Result<ServicePlugin> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| DockStackError::io("Failed to read the descriptor", &e))?;
+    let mut plugin: ServicePlugin =
+        toml::from_str(&content).map_err(|e| DockStackError::Config(e.to_string()))?;
+    plugin.path = path.to_path_buf();
+    validate(&plugin)?;
+    Ok(plugin)
+}
+
+fn validate(plugin: &ServicePlugin) -> Result<()> {
+    let valid_id = !plugin.id.is_empty()
+        && plugin
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_id {
+        return Err(DockStackError::Config(format!(
+            "id {:?} must be lowercase letters, digits, - or _",
+            plugin.id
+        )));
+    }
+    if services::builtin_services()
+        .iter()
+        .any(|s| s.name == plugin.id)
+    {
+        return Err(DockStackError::Config(format!(
+            "{} is a built-in service",
+            plugin.id
+        )));
+    }
+    if !CATEGORIES.contains(&plugin.category.as_str()) {
+        return Err(DockStackError::Config(format!(
+            "category {:?} must be one of {}",
+            plugin.category,
+            CATEGORIES.join(", ")
+        )));
+    }
+    plugin
+        .render(&plugin.id, &plugin.id, &plugin.default_config())
+        .map(|_| ())
+}
+
+/// Plugins loaded from the plugins folder.
+pub fn installed() -> Arc<Vec<ServicePlugin>> {
+    INSTALLED.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn find(id: &str) -> Option<ServicePlugin> {
+    INSTALLED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+}
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::TcpListener;
 use std::process::Command;
 
@@ -58,13 +58,33 @@ impl PortScanner {
         }
     }
 
-    /// Scan all service ports for a project
+    /// Free host ports for the `(service, port)` pairs whose port is in use, or
+    /// already claimed by an earlier pair, as `(service, port, suggestion)`.
+    /// Suggestions never collide with each other.
+    pub fn suggest_remaps(ports: &[(String, u16)]) -> Vec<(String, u16, u16)> {
+        let mut claimed = HashSet::new();
+        let mut remaps = Vec::new();
+        for (service, port) in ports {
+            if claimed.insert(*port) && Self::is_port_available(*port) {
+                continue;
+            }
+            let free = (port.saturating_add(1)..u16::MAX)
+                .find(|p| !claimed.contains(p) && Self::is_port_available(*p));
+            if let Some(suggestion) = free {
+                claimed.insert(suggestion);
+                remaps.push((service.clone(), *port, suggestion));
+            }
+        }
+        remaps
+    }
+
+    /// Scan all published service ports for a project; internal-only services are skipped
     pub fn scan_project_ports(
         services: &HashMap<String, crate::config::ServiceConfig>,
     ) -> Vec<PortInfo> {
         let ports: Vec<u16> = services
             .iter()
-            .filter(|(_, v)| v.enabled)
+            .filter(|(_, v)| v.enabled && !v.internal)
             .map(|(_, v)| v.port)
             .collect();
         Self::scan_ports(&ports)
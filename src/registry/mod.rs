@@ -0,0 +1,61 @@
+use crate::config::{ProjectConfig, RegistryConfig};
+use crate::docker::compose::{self, ComposeModel};
+use crate::error::{DockStackError, Result};
+
+/// Keyring service the registry passwords are stored under, one entry per
+/// `username@host`.
+const KEYRING_SERVICE: &str = "dockstack-registry";
+
+fn entry(registry: &RegistryConfig) -> Result<keyring::Entry> {
+    let user = format!("{}@{}", registry.username, registry.host);
+    keyring::Entry::new(KEYRING_SERVICE, &user)
+        .map_err(|e| keyring_error("The keyring is unavailable", e))
+}
+
+fn keyring_error(context: &str, e: keyring::Error) -> DockStackError {
+    DockStackError::Command {
+        context: context.to_string(),
+        detail: e.to_string(),
+    }
+}
+
+/// The registry's password from the OS keyring.
+pub fn password(registry: &RegistryConfig) -> Result<String> {
+    entry(registry)?.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => {
+            DockStackError::Config(format!("No password saved for {}", registry.host))
+        }
+        e => keyring_error("Could not read the password", e),
+    })
+}
+
+pub fn set_password(registry: &RegistryConfig, password: &str) -> Result<()> {
+    entry(registry)?
+        .set_password(password)
+        .map_err(|e| keyring_error("Could not save the password", e))
+}
+
+/// Forget the registry's password; fine when none was saved.
+pub fn delete_password(registry: &RegistryConfig) -> Result<()> {
+    match entry(registry)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(keyring_error("Could not remove the password", e)),
+    }
+}
+
+/// Registries the project's enabled services pull from. Docker Hub counts for
+/// images that name no registry, so a Hub login covers private Hub repositories.
+pub fn used_by(project: &ProjectConfig, registries: &[RegistryConfig]) -> Vec<RegistryConfig> {
+    let model = ComposeModel::from_project(project);
+    let hosts: Vec<&str> = model
+        .services
+        .values()
+        .filter_map(|s| s.get("image")?.as_str())
+        .map(|image| compose::image_host(image).unwrap_or("docker.io"))
+        .collect();
+    registries
+        .iter()
+        .filter(|r| hosts.contains(&r.host.trim()))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,243 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::watch;
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio::time::Instant;
+
+/// A panicked worker is restarted after this delay, doubling up to `RESTART_MAX`.
+const RESTART_MIN: Duration = Duration::from_secs(1);
+const RESTART_MAX: Duration = Duration::from_secs(60);
+/// A worker that ran this long before panicking restarts from `RESTART_MIN` again.
+const RESTART_RESET: Duration = Duration::from_secs(300);
+
+/// The single tokio runtime that runs all of DockStack's background work.
+///
+/// Owned by the app; backends get a cheap `RuntimeHandle` clone instead of
+/// spawning their own OS threads.
+pub struct AppRuntime {
+    runtime: Option<Runtime>, // Taken on drop so stuck blocking work cannot hang exit
+    handle: Handle,
+    cancel: watch::Sender<bool>,
+    tasks: Arc<TaskSupervisor>,
+}
+
+/// Spawns tracked tasks onto the app runtime.
+#[derive(Clone)]
+pub struct RuntimeHandle {
+    handle: Handle,
+    cancel: watch::Receiver<bool>,
+    tasks: Arc<TaskSupervisor>,
+}
+
+/// Owns every background task by name so shutdown can join them newest
+/// first and report the ones that do not stop.
+#[derive(Default)]
+pub struct TaskSupervisor {
+    tasks: Mutex<Vec<(&'static str, JoinHandle<()>)>>,
+}
+
+/// Stops a supervised worker, including the run currently in progress.
+pub struct WorkerHandle {
+    supervisor: AbortHandle,
+    current: Arc<Mutex<WorkerRun>>,
+}
+
+#[derive(Default)]
+struct WorkerRun {
+    stopped: bool,
+    run: Option<AbortHandle>,
+}
+
+/// Resolves once the app is shutting down. Long-running tasks select on it.
+#[derive(Clone)]
+pub struct CancelToken(watch::Receiver<bool>);
+
+impl AppRuntime {
+    pub fn new() -> std::io::Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("dockstack-worker")
+            .enable_all()
+            .build()?;
+        let (cancel, _) = watch::channel(false);
+        Ok(Self {
+            handle: runtime.handle().clone(),
+            runtime: Some(runtime),
+            cancel,
+            tasks: Arc::new(TaskSupervisor::default()),
+        })
+    }
+
+    pub fn handle(&self) -> RuntimeHandle {
+        RuntimeHandle {
+            handle: self.handle.clone(),
+            cancel: self.cancel.subscribe(),
+            tasks: self.tasks.clone(),
+        }
+    }
+
+    /// Cancel long-running tasks and wait up to `timeout` for all tracked tasks
+    /// to finish, newest first, so workers stop before what they were started on.
+    pub fn shutdown(&self, timeout: Duration) {
+        self.cancel.send_replace(true);
+        let Some(runtime) = &self.runtime else {
+            return;
+        };
+        let tasks: Vec<_> = self
+            .tasks
+            .tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+        let abandoned = runtime.block_on(async {
+            let deadline = Instant::now() + timeout;
+            let mut abandoned = Vec::new();
+            for (name, task) in tasks.into_iter().rev() {
+                if tokio::time::timeout_at(deadline, task).await.is_err() {
+                    abandoned.push(name);
+                }
+            }
+            abandoned
+        });
+        if !abandoned.is_empty() {
+            log::warn!(
+                "Background tasks still running after {:?}; abandoning them: {}",
+                timeout,
+                abandoned.join(", ")
+            );
+        }
+    }
+}
+
+impl Drop for AppRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl RuntimeHandle {
+    /// Run a future on the runtime. The task is awaited on shutdown.
+    pub fn spawn<F>(&self, future: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.track("task", self.handle.spawn(future))
+    }
+
+    /// Run blocking work (e.g. `std::process` calls) on the runtime's blocking pool.
+    pub fn spawn_blocking<F>(&self, f: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.tasks
+            .track("blocking task", self.handle.spawn_blocking(f))
+    }
+
+    /// Run a long-lived worker built by `make`, starting a fresh one with
+    /// backoff whenever it panics. Returning normally ends the worker.
+    pub fn supervise<F, Fut>(&self, name: &'static str, make: F) -> WorkerHandle
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.supervise_with(name, move |handle| handle.spawn(make()))
+    }
+
+    /// `supervise` for a worker that blocks a thread, e.g. on a channel.
+    pub fn supervise_blocking<F, W>(&self, name: &'static str, make: F) -> WorkerHandle
+    where
+        F: Fn() -> W + Send + 'static,
+        W: FnOnce() + Send + 'static,
+    {
+        self.supervise_with(name, move |handle| handle.spawn_blocking(make()))
+    }
+
+    fn supervise_with<S>(&self, name: &'static str, spawn: S) -> WorkerHandle
+    where
+        S: Fn(&Handle) -> JoinHandle<()> + Send + 'static,
+    {
+        let handle = self.handle.clone();
+        let mut cancel = self.cancel_token();
+        let current = Arc::new(Mutex::new(WorkerRun::default()));
+        let slot = current.clone();
+        let supervisor = self.tasks.track(
+            name,
+            self.handle.spawn(async move {
+                let mut backoff = RESTART_MIN;
+                loop {
+                    let started = Instant::now();
+                    let run = spawn(&handle);
+                    {
+                        // `abort` may have run on another thread since the last check
+                        let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+                        if slot.stopped {
+                            run.abort();
+                            break;
+                        }
+                        slot.run = Some(run.abort_handle());
+                    }
+                    match run.await {
+                        Err(e) if e.is_panic() => {}
+                        _ => break, // Finished or stopped
+                    }
+                    if started.elapsed() > RESTART_RESET {
+                        backoff = RESTART_MIN;
+                    }
+                    log::error!("Worker '{}' panicked; restarting in {:?}", name, backoff);
+                    tokio::select! {
+                        _ = cancel.cancelled() => break,
+                        _ = tokio::time::sleep(backoff) => {}
+                    }
+                    backoff = (backoff * 2).min(RESTART_MAX);
+                }
+            }),
+        );
+        WorkerHandle {
+            supervisor,
+            current,
+        }
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        CancelToken(self.cancel.clone())
+    }
+}
+
+impl TaskSupervisor {
+    fn track(&self, name: &'static str, task: JoinHandle<()>) -> AbortHandle {
+        let abort = task.abort_handle();
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.retain(|(_, t)| !t.is_finished());
+        tasks.push((name, task));
+        abort
+    }
+}
+
+impl WorkerHandle {
+    /// Stop the worker without restarting it. Blocking workers only stop
+    /// once they return; they should poll the cancel token.
+    pub fn abort(&self) {
+        self.supervisor.abort();
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        current.stopped = true;
+        if let Some(run) = current.run.take() {
+            run.abort();
+        }
+    }
+}
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub async fn cancelled(&mut self) {
+        // An error means the runtime owner is gone, which is also a shutdown
+        self.0.wait_for(|cancelled| *cancelled).await.ok();
+    }
+}
@@ -0,0 +1,151 @@
+use crate::config::{AppConfig, ServiceConfig};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+/// Catalog shipped with DockStack. Plausible is left out: it needs ClickHouse
+/// and a secret key, more than one service with sane defaults can carry.
+const BUNDLED: &str = include_str!("catalog.json");
+
+/// A ready-made community service, added to a project as a custom service.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String, // Service name once added, e.g. `n8n`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub icon: String,
+    pub image: String,
+    #[serde(default = "latest")]
+    pub version: String,
+    pub port: u16, // Default host port
+    #[serde(default)]
+    pub container_port: Option<u16>, // When it differs from `port`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub requires: Vec<String>, // Registry services to enable alongside, e.g. `mysql`
+}
+
+fn latest() -> String {
+    "latest".to_string()
+}
+
+impl CatalogEntry {
+    /// Custom service running this entry, published on `host_port`.
+    pub fn service(&self, host_port: u16) -> ServiceConfig {
+        let mut settings = HashMap::new();
+        let container_port = self.container_port.unwrap_or(self.port);
+        settings.insert("container_port".to_string(), container_port.to_string());
+        if !self.volumes.is_empty() {
+            settings.insert("volumes".to_string(), self.volumes.join("\n"));
+        }
+        if !self.command.is_empty() {
+            settings.insert("command".to_string(), self.command.clone());
+        }
+        ServiceConfig {
+            enabled: true,
+            is_custom: true,
+            display_name: Some(self.name.clone()),
+            image: Some(self.image.clone()),
+            version: self.version.clone(),
+            port: host_port,
+            env_vars: self.env.clone().into_iter().collect(),
+            settings,
+            ..ServiceConfig::default()
+        }
+    }
+}
+
+/// `catalog.json` in the config directory: entries added to the bundled
+/// catalog, or replacing bundled ones with the same id.
+pub fn user_catalog_path() -> PathBuf {
+    AppConfig::config_dir().join("catalog.json")
+}
+
+/// Entries shipped with DockStack, without the user's additions.
+pub fn bundled() -> Vec<CatalogEntry> {
+    parse(BUNDLED).unwrap_or_else(|e| {
+        log::error!("Bundled service catalog is invalid: {}", e);
+        Vec::new()
+    })
+}
+
+/// The bundled catalog merged with the user's, sorted by category then name.
+pub fn load() -> Vec<CatalogEntry> {
+    let mut entries = bundled();
+    let path = user_catalog_path();
+    if let Ok(content) = fs::read_to_string(&path) {
+        match parse(&content) {
+            Ok(user) => merge(&mut entries, user),
+            Err(e) => log::warn!("Ignoring {}: {}", path.display(), e),
+        }
+    }
+    entries.sort_by(|a, b| {
+        a.category
+            .cmp(&b.category)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    entries
+}
+
+fn parse(json: &str) -> Result<Vec<CatalogEntry>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+fn merge(entries: &mut Vec<CatalogEntry>, user: Vec<CatalogEntry>) {
+    for entry in user {
+        match entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => entries.push(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectConfig;
+    use crate::docker::compose::ComposeModel;
+
+    #[test]
+    fn catalog_entries_become_custom_services() {
+        let entries = bundled();
+        assert!(entries.len() >= 10);
+        let mut project = ProjectConfig::default();
+        for svc in project.services.values_mut() {
+            svc.enabled = false;
+        }
+        for entry in &entries {
+            project.add_custom_service(&entry.id, entry.service(entry.port));
+            for required in &entry.requires {
+                if let Some(svc) = project.services.get_mut(required) {
+                    svc.enabled = true;
+                }
+            }
+        }
+        let model = ComposeModel::from_project(&project);
+        let matomo = &model.services["matomo"];
+        assert_eq!(matomo["image"].as_str(), Some("matomo:latest"));
+        assert_eq!(
+            matomo["environment"]["MATOMO_DATABASE_HOST"].as_str(),
+            Some("mysql")
+        );
+        assert!(model.services.contains_key("mysql"));
+        assert!(model.volumes.contains_key("matomo_data"));
+        // Grafana and Gitea both default to 3000; the second moves up
+        assert_ne!(project.host_port("grafana"), project.host_port("gitea"));
+        assert_eq!(
+            model.services["keycloak"]["command"].as_str(),
+            Some("start-dev")
+        );
+    }
+}
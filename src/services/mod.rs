@@ -1,5 +1,11 @@
 #![allow(dead_code)]
 
+pub mod catalog;
+
+use crate::config::{ProjectConfig, ServiceConfig};
+use crate::plugins;
+use crate::utils;
+
 #[derive(Debug, Clone)]
 pub struct ServiceInfo {
     pub name: String,
@@ -7,7 +13,7 @@ pub struct ServiceInfo {
     pub description: String,
     pub default_port: u16,
     pub category: ServiceCategory,
-    pub icon: &'static str,
+    pub icon: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +23,7 @@ pub enum ServiceCategory {
     Runtime,
     Admin,
     Cache,
+    Search,
     Security,
     Custom,
 }
@@ -29,13 +36,15 @@ impl ServiceCategory {
             Self::Runtime => "⚙️ Runtime",
             Self::Admin => "🔧 Admin Tools",
             Self::Cache => "💾 Cache",
+            Self::Search => "🔎 Search",
             Self::Security => "🔒 Security",
             Self::Custom => "🧩 Custom Services",
         }
     }
 }
 
-pub fn get_service_registry() -> Vec<ServiceInfo> {
+/// Services DockStack generates itself, without the installed plugins.
+pub fn builtin_services() -> Vec<ServiceInfo> {
     vec![
         ServiceInfo {
             name: "postgresql".to_string(),
@@ -43,7 +52,7 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Advanced open source relational database".to_string(),
             default_port: 5432,
             category: ServiceCategory::Database,
-            icon: "🐘",
+            icon: "🐘".to_string(),
         },
         ServiceInfo {
             name: "mysql".to_string(),
@@ -51,7 +60,15 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Popular open source relational database".to_string(),
             default_port: 3306,
             category: ServiceCategory::Database,
-            icon: "🐬",
+            icon: "🐬".to_string(),
+        },
+        ServiceInfo {
+            name: "mssql".to_string(),
+            display_name: "SQL Server".to_string(),
+            description: "Microsoft SQL Server for Linux".to_string(),
+            default_port: 1433,
+            category: ServiceCategory::Database,
+            icon: "🗃️".to_string(),
         },
         ServiceInfo {
             name: "redis".to_string(),
@@ -59,7 +76,39 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "In-memory data structure store".to_string(),
             default_port: 6379,
             category: ServiceCategory::Cache,
-            icon: "⚡",
+            icon: "⚡".to_string(),
+        },
+        ServiceInfo {
+            name: "opensearch".to_string(),
+            display_name: "OpenSearch".to_string(),
+            description: "Search and analytics engine, single node".to_string(),
+            default_port: 9200,
+            category: ServiceCategory::Search,
+            icon: "🔎".to_string(),
+        },
+        ServiceInfo {
+            name: "solr".to_string(),
+            display_name: "Solr".to_string(),
+            description: "Apache Solr search server with a precreated core".to_string(),
+            default_port: 8983,
+            category: ServiceCategory::Search,
+            icon: "☀️".to_string(),
+        },
+        ServiceInfo {
+            name: "mailpit".to_string(),
+            display_name: "Mailpit".to_string(),
+            description: "Catches outgoing email with a web inbox".to_string(),
+            default_port: 8025,
+            category: ServiceCategory::Admin,
+            icon: "📧".to_string(),
+        },
+        ServiceInfo {
+            name: "varnish".to_string(),
+            display_name: "Varnish".to_string(),
+            description: "HTTP caching proxy in front of the web server".to_string(),
+            default_port: 6081,
+            category: ServiceCategory::Cache,
+            icon: "🚀".to_string(),
         },
         ServiceInfo {
             name: "nginx".to_string(),
@@ -67,7 +116,7 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "High performance web server & reverse proxy".to_string(),
             default_port: 80,
             category: ServiceCategory::WebServer,
-            icon: "🌐",
+            icon: "🌐".to_string(),
         },
         ServiceInfo {
             name: "apache".to_string(),
@@ -75,7 +124,7 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "The most widely used web server".to_string(),
             default_port: 8080,
             category: ServiceCategory::WebServer,
-            icon: "🎯",
+            icon: "🎯".to_string(),
         },
         ServiceInfo {
             name: "php".to_string(),
@@ -83,7 +132,31 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "PHP FastCGI Process Manager".to_string(),
             default_port: 9000,
             category: ServiceCategory::Runtime,
-            icon: "🐘",
+            icon: "🐘".to_string(),
+        },
+        ServiceInfo {
+            name: "python".to_string(),
+            display_name: "Python".to_string(),
+            description: "Python app server with requirements.txt install".to_string(),
+            default_port: 8000,
+            category: ServiceCategory::Runtime,
+            icon: "🐍".to_string(),
+        },
+        ServiceInfo {
+            name: "jupyter".to_string(),
+            display_name: "Jupyter".to_string(),
+            description: "JupyterLab notebooks with the scientific Python stack".to_string(),
+            default_port: 8888,
+            category: ServiceCategory::Runtime,
+            icon: "📓".to_string(),
+        },
+        ServiceInfo {
+            name: "sftp".to_string(),
+            display_name: "SFTP".to_string(),
+            description: "File access to www/ for SFTP clients and editors".to_string(),
+            default_port: 2222,
+            category: ServiceCategory::Admin,
+            icon: "📁".to_string(),
         },
         ServiceInfo {
             name: "phpmyadmin".to_string(),
@@ -91,7 +164,7 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Web interface for MySQL administration".to_string(),
             default_port: 8081,
             category: ServiceCategory::Admin,
-            icon: "🔧",
+            icon: "🔧".to_string(),
         },
         ServiceInfo {
             name: "pgadmin".to_string(),
@@ -99,7 +172,7 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Web interface for PostgreSQL administration".to_string(),
             default_port: 8082,
             category: ServiceCategory::Admin,
-            icon: "🔧",
+            icon: "🔧".to_string(),
         },
         ServiceInfo {
             name: "adminer".to_string(),
@@ -107,7 +180,15 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Universal database management in single PHP file".to_string(),
             default_port: 8083,
             category: ServiceCategory::Admin,
-            icon: "🗄️",
+            icon: "🗄️".to_string(),
+        },
+        ServiceInfo {
+            name: "dbgate".to_string(),
+            display_name: "DbGate".to_string(),
+            description: "Database UI connected to every enabled database".to_string(),
+            default_port: 8084,
+            category: ServiceCategory::Admin,
+            icon: "🧭".to_string(),
         },
         ServiceInfo {
             name: "ssl".to_string(),
@@ -115,11 +196,167 @@ pub fn get_service_registry() -> Vec<ServiceInfo> {
             description: "Self-signed HTTPS reverse proxy".to_string(),
             default_port: 443,
             category: ServiceCategory::Security,
-            icon: "🔐",
+            icon: "🔐".to_string(),
         },
     ]
 }
 
+pub fn get_service_registry() -> Vec<ServiceInfo> {
+    let installed = plugins::installed();
+    let plugins = installed.iter().map(|plugin| ServiceInfo {
+        category: plugin_category(&plugin.category),
+        name: plugin.id.clone(),
+        display_name: plugin.name.clone(),
+        description: plugin.description.clone(),
+        default_port: plugin.port,
+        icon: plugin.icon.clone(),
+    });
+    builtin_services().into_iter().chain(plugins).collect()
+}
+
+/// Category of a plugin's service, from its descriptor's `category` key.
+fn plugin_category(key: &str) -> ServiceCategory {
+    match key {
+        "web_server" => ServiceCategory::WebServer,
+        "database" => ServiceCategory::Database,
+        "runtime" => ServiceCategory::Runtime,
+        "cache" => ServiceCategory::Cache,
+        "search" => ServiceCategory::Search,
+        "security" => ServiceCategory::Security,
+        _ => ServiceCategory::Admin,
+    }
+}
+
 pub fn get_service_info(name: &str) -> Option<ServiceInfo> {
     get_service_registry().into_iter().find(|s| s.name == name)
 }
+
+/// Adminer URL that pre-fills the driver, server, user and database of the
+/// project's enabled database. Adminer never accepts passwords via the URL.
+pub fn adminer_login_url(project: &ProjectConfig) -> Option<String> {
+    let adminer = project.services.get("adminer").filter(|s| s.enabled)?;
+    let enabled = |name: &str| project.services.get(name).filter(|s| s.enabled);
+
+    let (driver, server, user, db) = if let Some(mysql) = enabled("mysql") {
+        let env = &mysql.env_vars;
+        let user = env
+            .get("MYSQL_USER")
+            .filter(|u| !u.is_empty())
+            .map(|u| u.as_str())
+            .unwrap_or("root");
+        let db = env.get("MYSQL_DATABASE").map(|d| d.as_str()).unwrap_or("");
+        ("server", "mysql", user, db)
+    } else if let Some(pg) = enabled("postgresql") {
+        let env = &pg.env_vars;
+        let user = env
+            .get("POSTGRES_USER")
+            .map(|u| u.as_str())
+            .unwrap_or("postgres");
+        let db = env.get("POSTGRES_DB").map(|d| d.as_str()).unwrap_or(user);
+        ("pgsql", "postgresql", user, db)
+    } else {
+        return None;
+    };
+
+    Some(format!(
+        "http://localhost:{}/?{}={}&username={}&db={}",
+        adminer.port,
+        driver,
+        server,
+        utils::url_encode(user),
+        utils::url_encode(db)
+    ))
+}
+
+/// A service that is of little use without one of `any_of` running.
+pub struct Requirement {
+    pub service: &'static str,
+    pub any_of: &'static [&'static str],
+    pub reason: &'static str,
+}
+
+pub const REQUIREMENTS: &[Requirement] = &[
+    Requirement {
+        service: "phpmyadmin",
+        any_of: &["mysql"],
+        reason: "phpMyAdmin manages a MySQL server",
+    },
+    Requirement {
+        service: "pgadmin",
+        any_of: &["postgresql"],
+        reason: "pgAdmin manages a PostgreSQL server",
+    },
+    Requirement {
+        service: "adminer",
+        any_of: &["mysql", "postgresql"],
+        reason: "Adminer needs a database to log in to",
+    },
+    Requirement {
+        service: "dbgate",
+        any_of: &["mysql", "postgresql", "mssql", "redis"],
+        reason: "DbGate connects to the project's databases",
+    },
+    Requirement {
+        service: "php",
+        any_of: &["nginx", "apache"],
+        reason: "PHP-FPM needs a web server in front of it",
+    },
+    Requirement {
+        service: "varnish",
+        any_of: &["nginx", "apache"],
+        reason: "Varnish caches the web server's pages",
+    },
+    Requirement {
+        service: "ssl",
+        any_of: &["nginx"],
+        reason: "HTTPS is served by Nginx, which can front Apache (see Settings)",
+    },
+];
+
+/// Requirements of `service` that no enabled service of the project meets,
+/// each with the companions the project has to choose from.
+pub fn missing_requirements(
+    project: &ProjectConfig,
+    service: &str,
+) -> Vec<(&'static Requirement, Vec<&'static str>)> {
+    let enabled = |name: &str| project.services.get(name).is_some_and(|s| s.enabled);
+    REQUIREMENTS
+        .iter()
+        .filter(|r| r.service == service && !r.any_of.iter().any(|s| enabled(s)))
+        .filter_map(|r| {
+            let options: Vec<&str> = r
+                .any_of
+                .iter()
+                .copied()
+                .filter(|s| project.services.contains_key(*s))
+                .collect();
+            (!options.is_empty()).then_some((r, options))
+        })
+        .collect()
+}
+
+/// Warnings about running `service` next to the project's other enabled
+/// services: host ports they already take. Nginx and Apache are kept apart
+/// by the project's web topology instead.
+pub fn conflict_hints(project: &ProjectConfig, service: &str) -> Vec<String> {
+    let Some(svc) = project.services.get(service) else {
+        return Vec::new();
+    };
+    let mut others: Vec<(&String, &ServiceConfig)> = project
+        .services
+        .iter()
+        .filter(|(name, s)| *name != service && s.enabled)
+        .collect();
+    others.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut hints = Vec::new();
+    // ssl shares nginx's container, so its port is no separate listener
+    if svc.port != 0 && !svc.internal && service != "ssl" {
+        for (name, other) in &others {
+            if other.port == svc.port && !other.internal && name.as_str() != "ssl" {
+                hints.push(format!("{} also uses host port {}", name, svc.port));
+            }
+        }
+    }
+    hints
+}
@@ -1,3 +1,4 @@
+use crate::error::{DockStackError, Result};
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -7,9 +8,10 @@ pub struct SslManager;
 #[allow(dead_code)]
 impl SslManager {
     /// Generate self-signed SSL certificate
-    pub fn generate_self_signed(project_dir: &str) -> Result<(String, String), String> {
+    pub fn generate_self_signed(project_dir: &str) -> Result<(String, String)> {
         let certs_dir = Path::new(project_dir).join("certs");
-        fs::create_dir_all(&certs_dir).map_err(|e| format!("Failed to create certs dir: {}", e))?;
+        fs::create_dir_all(&certs_dir)
+            .map_err(|e| DockStackError::io("Failed to create certs dir", &e))?;
 
         let cert_path = certs_dir.join("server.crt");
         let key_path = certs_dir.join("server.key");
@@ -27,12 +29,16 @@ impl SslManager {
         }
     }
 
-    fn generate_with_rcgen(cert_path: &Path, key_path: &Path) -> Result<(), String> {
+    fn generate_with_rcgen(cert_path: &Path, key_path: &Path) -> Result<()> {
         use rcgen::{CertificateParams, KeyPair};
 
+        let rcgen_error = |context: &str, e: rcgen::Error| DockStackError::Command {
+            context: context.to_string(),
+            detail: e.to_string(),
+        };
         let mut params =
             CertificateParams::new(vec!["localhost".to_string(), "127.0.0.1".to_string()])
-                .map_err(|e| format!("Failed to create cert params: {}", e))?;
+                .map_err(|e| rcgen_error("Failed to create cert params", e))?;
         params.distinguished_name.push(
             rcgen::DnType::CommonName,
             rcgen::DnValue::Utf8String("DockStack Dev Certificate".to_string()),
@@ -43,14 +49,15 @@ impl SslManager {
         );
 
         let key_pair =
-            KeyPair::generate().map_err(|e| format!("Failed to generate key pair: {}", e))?;
+            KeyPair::generate().map_err(|e| rcgen_error("Failed to generate key pair", e))?;
         let cert = params
             .self_signed(&key_pair)
-            .map_err(|e| format!("Failed to self-sign: {}", e))?;
+            .map_err(|e| rcgen_error("Failed to self-sign", e))?;
 
-        fs::write(cert_path, cert.pem()).map_err(|e| format!("Failed to write cert: {}", e))?;
+        fs::write(cert_path, cert.pem())
+            .map_err(|e| DockStackError::io("Failed to write cert", &e))?;
         fs::write(key_path, key_pair.serialize_pem())
-            .map_err(|e| format!("Failed to write key: {}", e))?;
+            .map_err(|e| DockStackError::io("Failed to write key", &e))?;
 
         #[cfg(unix)]
         {
@@ -65,10 +72,7 @@ impl SslManager {
         Ok(())
     }
 
-    fn generate_with_openssl(
-        cert_path: &Path,
-        key_path: &Path,
-    ) -> Result<(String, String), String> {
+    fn generate_with_openssl(cert_path: &Path, key_path: &Path) -> Result<(String, String)> {
         let output = Command::new("openssl")
             .args([
                 "req",
@@ -86,7 +90,7 @@ impl SslManager {
                 "/C=US/ST=Dev/L=Local/O=DockStack/CN=localhost",
             ])
             .output()
-            .map_err(|e| format!("Failed to run openssl: {}", e))?;
+            .map_err(|e| DockStackError::io("Failed to run openssl", &e))?;
 
         if output.status.success() {
             #[cfg(unix)]
@@ -103,8 +107,10 @@ impl SslManager {
                 key_path.to_string_lossy().to_string(),
             ))
         } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(format!("openssl failed: {}", stderr))
+            Err(DockStackError::Command {
+                context: "openssl failed".to_string(),
+                detail: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            })
         }
     }
 
@@ -114,11 +120,33 @@ impl SslManager {
         certs_dir.join("server.crt").exists() && certs_dir.join("server.key").exists()
     }
 
+    /// Expiry of the project's certificate, read with `openssl x509`.
+    /// `None` when there is no certificate or openssl is unavailable.
+    pub fn cert_expiry(project_dir: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let cert_path = Path::new(project_dir).join("certs").join("server.crt");
+        let output = Command::new("openssl")
+            .args(["x509", "-enddate", "-noout", "-in"])
+            .arg(&cert_path)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // notAfter=Jan  1 00:00:00 4096 GMT
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let date = stdout.trim().strip_prefix("notAfter=")?;
+        let date = date.split_whitespace().collect::<Vec<_>>().join(" ");
+        chrono::NaiveDateTime::parse_from_str(&date, "%b %d %H:%M:%S %Y GMT")
+            .ok()
+            .map(|naive| naive.and_utc())
+    }
+
     /// Remove SSL certificates
-    pub fn remove_certs(project_dir: &str) -> Result<(), String> {
+    pub fn remove_certs(project_dir: &str) -> Result<()> {
         let certs_dir = Path::new(project_dir).join("certs");
         if certs_dir.exists() {
-            fs::remove_dir_all(&certs_dir).map_err(|e| format!("Failed to remove certs: {}", e))?;
+            fs::remove_dir_all(&certs_dir)
+                .map_err(|e| DockStackError::io("Failed to remove certs", &e))?;
         }
         Ok(())
     }
@@ -0,0 +1,384 @@
+use crate::config::ProjectConfig;
+use crate::docker::compose;
+use crate::docker::manager::{ContainerInfo, ServiceStatus};
+use crate::runtime::{CancelToken, RuntimeHandle};
+use crate::services::{self, ServiceCategory};
+use serde::Serialize;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::AbortHandle;
+
+/// Longest a browser may take to send its request and read the answer.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Request headers past this size are not read; only the request line matters.
+const MAX_REQUEST: usize = 8 * 1024;
+/// Seconds between the page's own reloads.
+const REFRESH_SECS: u32 = 5;
+
+/// One service of the active project, as the status page lists it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceRow {
+    pub name: String,
+    pub version: String,
+    pub running: bool,
+    pub address: Option<String>, // `localhost:port`; none for internal services
+    pub url: Option<String>,     // For web servers and admin tools
+}
+
+/// What the status page shows; the app refreshes it while the page is served.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatusSnapshot {
+    pub project: Option<String>,
+    pub stack: String, // e.g. `running`
+    pub services: Vec<ServiceRow>,
+}
+
+impl StatusSnapshot {
+    pub fn new(
+        project: Option<&ProjectConfig>,
+        status: &ServiceStatus,
+        containers: &[ContainerInfo],
+    ) -> Self {
+        let Some(project) = project else {
+            return Self {
+                stack: "no project".to_string(),
+                ..Self::default()
+            };
+        };
+        let stack = match status {
+            ServiceStatus::Running => "running",
+            ServiceStatus::Starting => "starting",
+            ServiceStatus::Stopping => "stopping",
+            ServiceStatus::Stopped => "stopped",
+            ServiceStatus::Error(_) => "error",
+        };
+        let services = project
+            .services
+            .iter()
+            .filter(|(_, svc)| svc.enabled)
+            .map(|(name, svc)| {
+                let info = services::get_service_info(name);
+                let port = project.host_port(name);
+                let address = (!svc.internal && port != 0).then(|| format!("localhost:{}", port));
+                let web = info.as_ref().is_some_and(|i| {
+                    matches!(
+                        i.category,
+                        ServiceCategory::WebServer | ServiceCategory::Admin
+                    )
+                });
+                let url = match address.as_ref() {
+                    Some(_) if name == "nginx" && project.ssl_enabled => Some(format!(
+                        "https://localhost:{}",
+                        compose::https_port(project)
+                    )),
+                    Some(address) if web => Some(format!("http://{}", address)),
+                    _ => None,
+                };
+                ServiceRow {
+                    name: info.map_or_else(|| name.clone(), |i| i.display_name),
+                    version: svc.version.clone(),
+                    running: containers.iter().any(|c| c.is_running_service(name)),
+                    address,
+                    url,
+                }
+            })
+            .collect();
+        Self {
+            project: Some(project.name.clone()),
+            stack: stack.to_string(),
+            services,
+        }
+    }
+}
+
+/// Serves the status page on localhost from the latest snapshot.
+pub struct StatusPage {
+    snapshot: Arc<Mutex<StatusSnapshot>>,
+    task: Mutex<Option<AbortHandle>>,
+    runtime: RuntimeHandle,
+}
+
+impl StatusPage {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        Self {
+            snapshot: Arc::new(Mutex::new(StatusSnapshot::default())),
+            task: Mutex::new(None),
+            runtime,
+        }
+    }
+
+    /// Listen on `port`, replacing the page already served. The port is bound
+    /// before returning so a conflict is reported right away.
+    pub fn start(&self, port: u16) -> io::Result<()> {
+        self.stop();
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        let snapshot = self.snapshot.clone();
+        let cancel = self.runtime.cancel_token();
+        let task = self.runtime.spawn(async move {
+            match TcpListener::from_std(listener) {
+                Ok(listener) => serve(listener, snapshot, cancel).await,
+                Err(e) => log::error!("Status page stopped: {}", e),
+            }
+        });
+        *self.task.lock().unwrap_or_else(|e| e.into_inner()) = Some(task);
+        log::info!("Status page serving at http://localhost:{}", port);
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+            log::info!("Status page stopped");
+        }
+    }
+
+    pub fn is_serving(&self) -> bool {
+        self.task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn update(&self, snapshot: StatusSnapshot) {
+        *self.snapshot.lock().unwrap_or_else(|e| e.into_inner()) = snapshot;
+    }
+}
+
+async fn serve(
+    listener: TcpListener,
+    snapshot: Arc<Mutex<StatusSnapshot>>,
+    mut cancel: CancelToken,
+) {
+    loop {
+        let accepted = tokio::select! {
+            _ = cancel.cancelled() => return,
+            accepted = listener.accept() => accepted,
+        };
+        match accepted {
+            // One at a time: the page is tiny and only a few people look at it
+            Ok((stream, _)) => {
+                if tokio::time::timeout(REQUEST_TIMEOUT, answer(stream, &snapshot))
+                    .await
+                    .is_err()
+                {
+                    log::debug!("Status page request timed out");
+                }
+            }
+            Err(e) => log::warn!("Status page connection failed: {}", e),
+        }
+    }
+}
+
+async fn answer(mut stream: TcpStream, snapshot: &Mutex<StatusSnapshot>) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buffer[..n]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let request_line = request.lines().next().unwrap_or_default();
+    let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let (status, content_type, body) = respond(request_line, &snapshot);
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if !request_line.starts_with("HEAD ") {
+        response.push_str(&body);
+    }
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        log::debug!("Status page response not sent: {}", e);
+    }
+    stream.shutdown().await.ok();
+}
+
+/// Status, content type and body answering an HTTP request line such as
+/// `GET /status.json HTTP/1.1`.
+fn respond(request_line: &str, snapshot: &StatusSnapshot) -> (&'static str, &'static str, String) {
+    let mut words = request_line.split_whitespace();
+    let method = words.next().unwrap_or_default();
+    let path = words.next().unwrap_or_default();
+    if method != "GET" && method != "HEAD" {
+        return (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "The status page is read-only\n".to_string(),
+        );
+    }
+    match path.split('?').next().unwrap_or_default() {
+        "/" => ("200 OK", "text/html; charset=utf-8", render_html(snapshot)),
+        "/status.json" => (
+            "200 OK",
+            "application/json",
+            serde_json::to_string_pretty(snapshot).unwrap_or_default(),
+        ),
+        _ => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found\n".to_string(),
+        ),
+    }
+}
+
+fn render_html(snapshot: &StatusSnapshot) -> String {
+    let title = match &snapshot.project {
+        Some(project) => format!("{} - DockStack", escape(project)),
+        None => "DockStack".to_string(),
+    };
+    let mut rows = String::new();
+    for service in &snapshot.services {
+        let state = if service.running {
+            "running"
+        } else {
+            "stopped"
+        };
+        let reach = match (&service.url, &service.address) {
+            (Some(url), _) => format!("<a href=\"{0}\">{0}</a>", escape(url)),
+            (None, Some(address)) => escape(address),
+            (None, None) => "internal".to_string(),
+        };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>\n",
+            escape(&service.name),
+            escape(&service.version),
+            state,
+            state,
+            reach
+        ));
+    }
+    if rows.is_empty() {
+        rows.push_str("<tr><td colspan=\"4\">No services enabled</td></tr>\n");
+    }
+    format!(
+        "<!DOCTYPE html>
+<html><head><meta charset=\"utf-8\"><meta http-equiv=\"refresh\" content=\"{refresh}\">
+<title>{title}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; background: #111418; color: #e6e8eb; margin: 2rem; }}
+table {{ border-collapse: collapse; min-width: 40rem; }}
+th, td {{ text-align: left; padding: .5rem 1rem; border-bottom: 1px solid #2a2f36; }}
+th {{ color: #8b929b; font-weight: 600; }}
+a {{ color: #58a6ff; }}
+.running {{ color: #3fb950; }}
+.stopped, .error {{ color: #8b929b; }}
+footer {{ color: #8b929b; font-size: .8rem; margin-top: 1.5rem; }}
+</style></head>
+<body>
+<h1>{title}</h1>
+<p>Stack: <strong class=\"{stack}\">{stack}</strong></p>
+<table>
+<tr><th>Service</th><th>Version</th><th>State</th><th>Address</th></tr>
+{rows}</table>
+<footer>Updated {updated} · refreshes every {refresh}s · <a href=\"/status.json\">JSON</a></footer>
+</body></html>
+",
+        refresh = REFRESH_SECS,
+        title = title,
+        stack = escape(&snapshot.stack),
+        rows = rows,
+        updated = chrono::Local::now().format("%H:%M:%S"),
+    )
+}
+
+/// `text` safe to put in HTML content or a quoted attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> StatusSnapshot {
+        let mut project = ProjectConfig {
+            name: "<Shop & \"Co\">".to_string(),
+            ..ProjectConfig::default()
+        };
+        for (name, svc) in project.services.iter_mut() {
+            svc.enabled = name == "mysql" || name == "nginx";
+        }
+        let mysql = ContainerInfo {
+            id: "0123abcd".to_string(),
+            name: "dockstack_shop_mysql".to_string(),
+            image: "mysql:8.0".to_string(),
+            status: "Up 2 minutes".to_string(),
+            ports: String::new(),
+            state: "running".to_string(),
+            service: "mysql".to_string(),
+            restart_count: 0,
+            exit_code: 0,
+            crash_log: None,
+        };
+        StatusSnapshot::new(Some(&project), &ServiceStatus::Running, &[mysql])
+    }
+
+    #[test]
+    fn snapshot_lists_enabled_services_and_how_to_reach_them() {
+        let project = ProjectConfig::default();
+        let snapshot = snapshot();
+        assert_eq!(snapshot.stack, "running");
+        assert_eq!(snapshot.services.len(), 2);
+        let row = |name: &str| snapshot.services.iter().find(|s| s.name == name);
+        let mysql = row("MySQL");
+        assert!(mysql.is_some_and(|s| s.running && s.url.is_none()));
+        assert_eq!(
+            mysql.and_then(|s| s.address.as_deref()),
+            Some(format!("localhost:{}", project.host_port("mysql")).as_str())
+        );
+        let nginx = row("Nginx");
+        assert!(nginx.is_some_and(|s| !s.running));
+        assert_eq!(
+            nginx.and_then(|s| s.url.as_deref()),
+            Some(format!("http://localhost:{}", project.host_port("nginx")).as_str())
+        );
+
+        let none = StatusSnapshot::new(None, &ServiceStatus::Stopped, &[]);
+        assert_eq!(none.project, None);
+        assert_eq!(none.stack, "no project");
+    }
+
+    #[test]
+    fn only_reads_of_known_paths_are_answered() {
+        let snapshot = snapshot();
+        let status = |request: &str| respond(request, &snapshot).0;
+        assert_eq!(status("GET / HTTP/1.1"), "200 OK");
+        assert_eq!(status("HEAD /?refresh=1 HTTP/1.1"), "200 OK");
+        assert_eq!(status("POST / HTTP/1.1"), "405 Method Not Allowed");
+        assert_eq!(
+            status("DELETE /status.json HTTP/1.1"),
+            "405 Method Not Allowed"
+        );
+        assert_eq!(status("GET /admin HTTP/1.1"), "404 Not Found");
+        assert_eq!(status(""), "405 Method Not Allowed");
+
+        let (_, content_type, body) = respond("GET /status.json HTTP/1.1", &snapshot);
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        assert_eq!(json["stack"], "running");
+    }
+
+    #[test]
+    fn page_escapes_the_project_name() {
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+        let html = render_html(&snapshot());
+        assert!(html.contains("<title>&lt;Shop &amp; &quot;Co&quot;&gt; - DockStack</title>"));
+        assert!(!html.contains("<Shop"));
+    }
+}
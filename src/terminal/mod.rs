@@ -1,9 +1,15 @@
+use crate::config::{AppConfig, TerminalConfig};
+use crate::error::{DockStackError, Result as DsResult};
+use crate::runtime::RuntimeHandle;
+use crate::utils::push_capped;
 use crossbeam_channel::{Receiver, Sender};
 use portable_pty::{native_pty_system, Child, CommandBuilder, PtySize};
 use std::collections::VecDeque;
+use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -13,37 +19,140 @@ pub enum TerminalEvent {
     Exited(i32),
 }
 
+/// Session transcripts kept in the transcripts folder; older ones are deleted.
+pub const KEPT_TRANSCRIPTS: usize = 50;
+
+/// A line of shell output, with when it arrived.
+#[derive(Debug, Clone)]
+pub struct TerminalLine {
+    pub at: chrono::DateTime<chrono::Local>,
+    pub text: String,
+}
+
+impl TerminalLine {
+    pub fn new(text: String) -> Self {
+        Self {
+            at: chrono::Local::now(),
+            text,
+        }
+    }
+
+    /// The line as transcripts store it: `[2024-05-01 14:03:22] text`.
+    pub fn to_transcript(&self) -> String {
+        format!("[{}] {}", self.at.format("%Y-%m-%d %H:%M:%S"), self.text)
+    }
+}
+
+/// `lines` as a transcript, one timestamped line each.
+pub fn transcript(lines: &[TerminalLine]) -> String {
+    lines
+        .iter()
+        .map(|line| line.to_transcript() + "\n")
+        .collect()
+}
+
+pub fn transcripts_dir() -> PathBuf {
+    AppConfig::config_dir().join("transcripts")
+}
+
+/// A new transcript file for a session starting now, after deleting the oldest
+/// transcripts past `KEPT_TRANSCRIPTS`.
+fn open_transcript() -> std::io::Result<File> {
+    let dir = transcripts_dir();
+    fs::create_dir_all(&dir)?;
+    let mut old: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    old.sort(); // Named by start time
+    let excess = (old.len() + 1).saturating_sub(KEPT_TRANSCRIPTS);
+    for path in old.iter().take(excess) {
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!("Failed to delete transcript {}: {}", path.display(), e);
+        }
+    }
+    let name = format!(
+        "session-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    File::create(dir.join(name))
+}
+
+/// Enter as the PTY expects it: CR for ConPTY on Windows, LF elsewhere.
+const ENTER: &str = if cfg!(target_os = "windows") {
+    "\r"
+} else {
+    "\n"
+};
+
+/// Quoting rules of the shell running in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShellKind {
+    Posix, // bash, zsh, Git Bash
+    Fish,
+    PowerShell,
+    Cmd,
+}
+
+impl ShellKind {
+    /// Quote `arg` so the shell passes it through as a single word. Plain
+    /// words stay bare, so a quoted program name never becomes a PowerShell string.
+    pub fn quote(self, arg: &str) -> String {
+        let plain = |c: char| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c);
+        if !arg.is_empty() && arg.chars().all(plain) {
+            return arg.to_string();
+        }
+        match self {
+            ShellKind::Posix => format!("'{}'", arg.replace('\'', "'\\''")),
+            ShellKind::Fish => format!("'{}'", arg.replace('\\', "\\\\").replace('\'', "\\'")),
+            ShellKind::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+            ShellKind::Cmd => format!("\"{}\"", arg.replace('"', "\"\"")),
+        }
+    }
+}
+
 pub struct EmbeddedTerminal {
-    pub output_lines: Arc<Mutex<VecDeque<String>>>,
+    pub output_lines: Arc<Mutex<VecDeque<TerminalLine>>>,
+    pub capacity: Arc<AtomicUsize>, // Scrollback lines kept in `output_lines`
     pub event_tx: Sender<TerminalEvent>,
     pub event_rx: Receiver<TerminalEvent>,
     master_writer: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
     running: Arc<Mutex<bool>>,
-    main_thread: Mutex<Option<thread::JoinHandle<()>>>,
+    shell_kind: Arc<Mutex<ShellKind>>, // Of the current session, for quoting `dc` expansions
+    runtime: RuntimeHandle,
 }
 
 impl EmbeddedTerminal {
-    pub fn new() -> Self {
+    pub fn new(runtime: RuntimeHandle, scrollback: usize) -> Self {
         let (event_tx, event_rx) = crossbeam_channel::bounded(1000);
         Self {
             output_lines: Arc::new(Mutex::new(VecDeque::new())),
+            capacity: Arc::new(AtomicUsize::new(scrollback)),
             event_tx,
             event_rx,
             master_writer: Arc::new(Mutex::new(None)),
             running: Arc::new(Mutex::new(false)),
-            main_thread: Mutex::new(None),
+            shell_kind: Arc::new(Mutex::new(ShellKind::Posix)),
+            runtime,
         }
     }
 
-    pub fn start(&self) {
+    /// Launch the configured shell, in `cwd` when it is an existing directory.
+    pub fn start(&self, config: &TerminalConfig, cwd: Option<&Path>) {
+        let config = config.clone();
+        let cwd = cwd.filter(|dir| dir.is_dir()).map(shell_path);
         let tx = self.event_tx.clone();
         let output_lines = self.output_lines.clone();
+        let capacity = self.capacity.clone();
         let master_writer = self.master_writer.clone();
         let running = self.running.clone();
+        let shell_kind = self.shell_kind.clone();
 
         *running.lock().unwrap_or_else(|e| e.into_inner()) = true;
 
-        let handle = thread::spawn(move || {
+        let runtime = self.runtime.clone();
+        // Not supervised: a crashed shell session is not restarted behind the user's back
+        self.runtime.spawn_blocking(move || {
             let pty_system = native_pty_system();
 
             let pair = match pty_system.openpty(PtySize {
@@ -61,14 +170,25 @@ impl EmbeddedTerminal {
                 }
             };
 
-            let shell = if cfg!(target_os = "windows") {
-                "powershell.exe"
-            } else {
-                "bash"
+            let (mut cmd, kind) = match shell_command(&config) {
+                Ok(shell) => shell,
+                Err(e) => {
+                    *running.lock().unwrap_or_else(|e| e.into_inner()) = false;
+                    tx.send(TerminalEvent::Error(e.to_string())).ok();
+                    return;
+                }
             };
-            let mut cmd = CommandBuilder::new(shell);
-            if !cfg!(target_os = "windows") {
-                cmd.arg("-i"); // Interactive
+            apply_env(&mut cmd, &config);
+            *shell_kind.lock().unwrap_or_else(|e| e.into_inner()) = kind;
+            // cmd.exe cannot start in a UNC directory; `pushd` maps it to a drive letter
+            let mut pushd = None;
+            if let Some(dir) = cwd {
+                let dir_str = dir.to_string_lossy().to_string();
+                if kind == ShellKind::Cmd && dir_str.starts_with(r"\\") {
+                    pushd = Some(format!("pushd {}", kind.quote(&dir_str)));
+                } else {
+                    cmd.cwd(dir);
+                }
             }
 
             let mut child: Box<dyn Child + Send> = match pair.slave.spawn_command(cmd) {
@@ -88,16 +208,27 @@ impl EmbeddedTerminal {
             drop(pair.slave);
 
             // Set up writer
-            let writer = pair.master.take_writer().unwrap();
+            let mut writer = pair.master.take_writer().unwrap();
+            if let Some(pushd) = pushd {
+                write_line(&mut writer, &pushd);
+            }
             *master_writer.lock().unwrap_or_else(|e| e.into_inner()) = Some(writer);
 
+            let mut transcript = None;
+            if config.record_transcripts {
+                match open_transcript() {
+                    Ok(file) => transcript = Some(file),
+                    Err(e) => log::warn!("Not recording a terminal transcript: {}", e),
+                }
+            }
+
             // Reader thread
             let mut reader = pair.master.try_clone_reader().unwrap();
             let tx_out = tx.clone();
             let lines_out = output_lines.clone();
             let running_out = running.clone();
 
-            let reader_handle = thread::spawn(move || {
+            runtime.spawn_blocking(move || {
                 let mut buffer = [0u8; 4096];
                 let mut line_buffer = String::new();
                 loop {
@@ -119,16 +250,23 @@ impl EmbeddedTerminal {
                                 for line in lines {
                                     let cleaned = line.replace("\r", "");
                                     if !cleaned.trim().is_empty() || line.len() > 2 {
-                                        l.push_back(cleaned);
+                                        let line = TerminalLine::new(cleaned);
+                                        if let Some(file) = transcript.as_mut() {
+                                            if let Err(e) =
+                                                writeln!(file, "{}", line.to_transcript())
+                                            {
+                                                log::warn!("Stopped recording transcript: {}", e);
+                                                transcript = None;
+                                            }
+                                        }
+                                        push_capped(&mut l, line, &capacity);
                                     }
                                 }
-
-                                if l.len() > 1000 {
-                                    let drain = l.len() - 800;
-                                    l.drain(0..drain);
-                                }
                             }
-                            tx_out.send(TerminalEvent::Output(data.to_string())).ok();
+                            // Only a wake-up; the text is already in `output_lines`
+                            tx_out
+                                .try_send(TerminalEvent::Output(data.to_string()))
+                                .ok();
                         }
                         Err(_) => break,
                     }
@@ -150,21 +288,43 @@ impl EmbeddedTerminal {
                     .ok();
                 }
             }
-            
-            let _ = reader_handle.join();
         });
-        *self.main_thread.lock().unwrap() = Some(handle);
+    }
+
+    /// Send a line typed in the Terminal tab. A leading `dc` expands to
+    /// `compose`, the active project's compose invocation, so `dc ps` works
+    /// from any directory.
+    pub fn send_command(&self, input: &str, compose: Option<&[String]>) {
+        let Some(args) = dc_args(input) else {
+            return self.send_input(input);
+        };
+        match compose {
+            Some(words) => {
+                let kind = *self.shell_kind.lock().unwrap_or_else(|e| e.into_inner());
+                let program: Vec<String> = words.iter().map(|w| kind.quote(w)).collect();
+                self.send_input(&format!("{}{}", program.join(" "), args));
+            }
+            None => {
+                let mut output = self.output_lines.lock().unwrap_or_else(|e| e.into_inner());
+                let line = TerminalLine::new("dc: no active project".to_string());
+                push_capped(&mut output, line, &self.capacity);
+            }
+        }
     }
 
     pub fn send_input(&self, input: &str) {
         if let Some(ref mut writer) = *self.master_writer.lock().unwrap_or_else(|e| e.into_inner()) {
-            let data = if input.ends_with('\n') {
-                input.to_string()
-            } else {
-                format!("{}\n", input)
-            };
-            let _ = writer.write_all(data.as_bytes());
-            let _ = writer.flush();
+            write_line(writer, input);
+        }
+    }
+
+    /// Change the scrollback size, dropping the oldest lines if it shrank.
+    pub fn set_capacity(&self, lines: usize) {
+        let lines = lines.max(1);
+        self.capacity.store(lines, Ordering::Relaxed);
+        let mut output = self.output_lines.lock().unwrap_or_else(|e| e.into_inner());
+        while output.len() > lines {
+            output.pop_front();
         }
     }
 
@@ -172,11 +332,153 @@ impl EmbeddedTerminal {
         *self.running.lock().unwrap_or_else(|e| e.into_inner())
     }
 
+    /// Ask the shell to exit; runtime shutdown waits for it to do so.
     pub fn stop(&self) {
-        *self.running.lock().unwrap() = false;
+        *self.running.lock().unwrap_or_else(|e| e.into_inner()) = false;
         self.send_input("exit\n");
-        if let Some(h) = self.main_thread.lock().unwrap().take() {
-            let _ = h.join();
+    }
+}
+
+/// The arguments after a leading `dc` word, including their leading space.
+fn dc_args(input: &str) -> Option<&str> {
+    let args = input.trim_start().strip_prefix("dc")?;
+    (args.is_empty() || args.starts_with(char::is_whitespace)).then_some(args)
+}
+
+/// Send `input` followed by Enter; embedded newlines become Enter too.
+fn write_line(writer: &mut Box<dyn Write + Send>, input: &str) {
+    let line = input
+        .trim_end_matches(['\r', '\n'])
+        .replace("\r\n", "\n")
+        .replace('\n', ENTER);
+    let _ = writer.write_all(format!("{}{}", line, ENTER).as_bytes());
+    let _ = writer.flush();
+}
+
+/// The shell to launch for `config` on this platform.
+fn shell_command(config: &TerminalConfig) -> DsResult<(CommandBuilder, ShellKind)> {
+    if !cfg!(target_os = "windows") {
+        let shell = unix_shell(&config.unix_shell);
+        let kind = match Path::new(&shell).file_name() {
+            Some(name) if name == "fish" => ShellKind::Fish,
+            _ => ShellKind::Posix,
+        };
+        let mut cmd = CommandBuilder::new(&shell);
+        if config.login_shell {
+            cmd.arg("-l");
+        }
+        cmd.arg("-i"); // Interactive
+        return Ok((cmd, kind));
+    }
+    match config.windows_shell.as_str() {
+        "cmd" => Ok((CommandBuilder::new("cmd.exe"), ShellKind::Cmd)),
+        "git-bash" => {
+            let bash = git_bash().ok_or_else(|| {
+                DockStackError::Config(
+                    "Git Bash not found. Install Git for Windows or choose another shell in \
+                     Settings."
+                        .to_string(),
+                )
+            })?;
+            let mut cmd = CommandBuilder::new(bash);
+            cmd.args(["--login", "-i"]);
+            Ok((cmd, ShellKind::Posix))
+        }
+        _ => {
+            let mut cmd = CommandBuilder::new("powershell.exe");
+            cmd.arg("-NoLogo");
+            Ok((cmd, ShellKind::PowerShell))
         }
     }
 }
+
+/// `configured`, else the user's shell from `$SHELL`, else bash from PATH.
+pub fn unix_shell(configured: &str) -> String {
+    if !configured.trim().is_empty() {
+        return configured.trim().to_string();
+    }
+    default_unix_shell().to_string()
+}
+
+/// The user's shell from `$SHELL`, else bash from PATH; looked up once per run.
+pub fn default_unix_shell() -> &'static str {
+    static SHELL: OnceLock<String> = OnceLock::new();
+    SHELL.get_or_init(|| {
+        std::env::var("SHELL")
+            .ok()
+            .filter(|shell| Path::new(shell).is_file())
+            .unwrap_or_else(|| "bash".to_string())
+    })
+}
+
+/// Login shells listed in /etc/shells that exist on this machine.
+pub fn installed_shells() -> &'static [String] {
+    static SHELLS: OnceLock<Vec<String>> = OnceLock::new();
+    SHELLS.get_or_init(|| {
+        let mut shells: Vec<String> = std::fs::read_to_string("/etc/shells")
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|path| Path::new(path).is_file())
+            .map(str::to_string)
+            .collect();
+        shells.dedup();
+        shells
+    })
+}
+
+/// Prepend the configured PATH entries and set the configured variables.
+fn apply_env(cmd: &mut CommandBuilder, config: &TerminalConfig) {
+    let extra: Vec<PathBuf> = config
+        .extra_path
+        .lines()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| match dir.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(dir),
+        })
+        .collect();
+    if !extra.is_empty() {
+        let inherited = std::env::var_os("PATH").unwrap_or_default();
+        match std::env::join_paths(extra.into_iter().chain(std::env::split_paths(&inherited))) {
+            Ok(path) => cmd.env("PATH", path),
+            Err(e) => log::warn!("Ignoring terminal PATH additions: {}", e),
+        }
+    }
+    for line in config.env.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => cmd.env(key.trim(), value.trim()),
+            _ => log::warn!("Ignoring terminal variable that is not KEY=VALUE: {}", line),
+        }
+    }
+}
+
+/// Git for Windows' bash, from a machine-wide or per-user install.
+fn git_bash() -> Option<PathBuf> {
+    let program_dirs = ["ProgramFiles", "ProgramW6432", "ProgramFiles(x86)"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from);
+    let user_dir = std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("Programs"));
+    program_dirs
+        .chain(user_dir)
+        .map(|dir| dir.join("Git").join("bin").join("bash.exe"))
+        .find(|path| path.is_file())
+}
+
+/// `dir` without the `\\?\` prefix `canonicalize` adds on Windows, which shells reject.
+fn shell_path(dir: &Path) -> PathBuf {
+    let dir_str = dir.to_string_lossy();
+    if let Some(share) = dir_str.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(local) = dir_str.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        dir.to_path_buf()
+    }
+}
@@ -3,40 +3,70 @@
 // Note: tray-icon requires the event loop to run on the main thread.
 // We provide the setup functions and menu builders here.
 
+use crate::error::{DockStackError, Result};
+use crate::runtime::RuntimeHandle;
 use crossbeam_channel::{Receiver, Sender};
+use std::process::{Command, Stdio};
+use std::time::Duration;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     TrayIcon, TrayIconBuilder,
 };
 
+/// Balloon tip from a temporary tray icon; the script waits so the tip stays visible.
+const WINDOWS_BALLOON: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+    $n = New-Object System.Windows.Forms.NotifyIcon; \
+    $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+    $n.ShowBalloonTip(5000, $env:DOCKSTACK_TITLE, $env:DOCKSTACK_BODY, 'Info'); \
+    Start-Sleep -Seconds 6; $n.Dispose()";
+const MACOS_NOTIFICATION: &str = "display notification (system attribute \"DOCKSTACK_BODY\") \
+    with title (system attribute \"DOCKSTACK_TITLE\")";
+
+/// Menu ids of the Active Project entries: this prefix, then the project id.
+const PROJECT_ID_PREFIX: &str = "project:";
+
 #[derive(Debug, Clone)]
 pub enum TrayCommand {
     Start,
     Stop,
     Restart,
     OpenUI,
+    SelectProject(String),
     Quit,
 }
 
 pub struct SystemTray {
     pub command_tx: Sender<TrayCommand>,
     pub command_rx: Receiver<TrayCommand>,
+    runtime: RuntimeHandle,
     tray_icon: Option<TrayIcon>,
+    project_item: Option<MenuItem>, // Disabled header naming the project the actions apply to
+    projects_menu: Option<Submenu>,
+    project_items: Vec<CheckMenuItem>, // One per project; only the active one is checked
 }
 
 impl SystemTray {
-    pub fn new() -> Self {
+    pub fn new(runtime: RuntimeHandle) -> Self {
         let (command_tx, command_rx) = crossbeam_channel::bounded(100);
         Self {
             command_tx,
             command_rx,
+            runtime,
             tray_icon: None,
+            project_item: None,
+            projects_menu: None,
+            project_items: Vec::new(),
         }
     }
 
-    pub fn setup(&mut self) -> Result<(), String> {
+    /// Build the tray icon and menu. Menu clicks wake `ctx` so they are handled
+    /// even while the UI repaints slowly.
+    pub fn setup(&mut self, ctx: &egui::Context) -> Result<()> {
         let menu = Menu::new();
 
+        let project_item = MenuItem::new("No project", false, None);
+        let projects_menu = Submenu::new("Active Project", false);
+        let separator0 = PredefinedMenuItem::separator();
         let start_item = MenuItem::new("▶ Start Services", true, None);
         let stop_item = MenuItem::new("⏹ Stop Services", true, None);
         let restart_item = MenuItem::new("🔄 Restart Services", true, None);
@@ -45,13 +75,21 @@ impl SystemTray {
         let separator2 = PredefinedMenuItem::separator();
         let quit_item = MenuItem::new("❌ Quit", true, None);
 
-        menu.append(&start_item).map_err(|e| e.to_string())?;
-        menu.append(&stop_item).map_err(|e| e.to_string())?;
-        menu.append(&restart_item).map_err(|e| e.to_string())?;
-        menu.append(&separator).map_err(|e| e.to_string())?;
-        menu.append(&open_item).map_err(|e| e.to_string())?;
-        menu.append(&separator2).map_err(|e| e.to_string())?;
-        menu.append(&quit_item).map_err(|e| e.to_string())?;
+        let unavailable =
+            |e: &dyn std::fmt::Display| DockStackError::TrayUnavailable(e.to_string());
+        menu.append_items(&[
+            &project_item,
+            &projects_menu,
+            &separator0,
+            &start_item,
+            &stop_item,
+            &restart_item,
+            &separator,
+            &open_item,
+            &separator2,
+            &quit_item,
+        ])
+        .map_err(|e| unavailable(&e))?;
 
         let start_id = start_item.id().clone();
         let stop_id = stop_item.id().clone();
@@ -62,11 +100,10 @@ impl SystemTray {
         // Use the app icon if available, otherwise fallback to generated icon
         let icon = if let Some(icon_data) = crate::utils::load_icon() {
             tray_icon::Icon::from_rgba(icon_data.rgba, icon_data.width, icon_data.height)
-                .map_err(|e| format!("Failed to create tray icon: {}", e))?
+                .map_err(|e| unavailable(&e))?
         } else {
             let icon_rgba = create_tray_icon_data();
-            tray_icon::Icon::from_rgba(icon_rgba, 16, 16)
-                .map_err(|e| format!("Failed to create fallback icon: {}", e))?
+            tray_icon::Icon::from_rgba(icon_rgba, 16, 16).map_err(|e| unavailable(&e))?
         };
 
         let tray = TrayIconBuilder::new()
@@ -74,24 +111,40 @@ impl SystemTray {
             .with_tooltip("DockStack - DevStack Manager")
             .with_icon(icon)
             .build()
-            .map_err(|e| format!("Failed to build tray icon: {}", e))?;
+            .map_err(|e| unavailable(&e))?;
 
         self.tray_icon = Some(tray);
+        self.project_item = Some(project_item);
+        self.projects_menu = Some(projects_menu);
 
-        // Spawn menu event handler
+        // Menu event listener; polls so it notices shutdown
         let tx = self.command_tx.clone();
-        std::thread::spawn(move || loop {
-            if let Ok(event) = MenuEvent::receiver().recv() {
-                if event.id() == &start_id {
-                    tx.send(TrayCommand::Start).ok();
-                } else if event.id() == &stop_id {
-                    tx.send(TrayCommand::Stop).ok();
-                } else if event.id() == &restart_id {
-                    tx.send(TrayCommand::Restart).ok();
-                } else if event.id() == &open_id {
-                    tx.send(TrayCommand::OpenUI).ok();
-                } else if event.id() == &quit_id {
-                    tx.send(TrayCommand::Quit).ok();
+        let ctx = ctx.clone();
+        let cancel = self.runtime.cancel_token();
+        self.runtime.supervise_blocking("tray listener", move || {
+            let tx = tx.clone();
+            let ctx = ctx.clone();
+            let cancel = cancel.clone();
+            let ids = [
+                (start_id.clone(), TrayCommand::Start),
+                (stop_id.clone(), TrayCommand::Stop),
+                (restart_id.clone(), TrayCommand::Restart),
+                (open_id.clone(), TrayCommand::OpenUI),
+                (quit_id.clone(), TrayCommand::Quit),
+            ];
+            move || {
+                while !cancel.is_cancelled() {
+                    let Ok(event) = MenuEvent::receiver().recv_timeout(Duration::from_millis(250))
+                    else {
+                        continue;
+                    };
+                    ctx.request_repaint();
+                    if let Some(project) = event.id().as_ref().strip_prefix(PROJECT_ID_PREFIX) {
+                        tx.send(TrayCommand::SelectProject(project.to_string()))
+                            .ok();
+                    } else if let Some((_, command)) = ids.iter().find(|(id, _)| event.id() == id) {
+                        tx.send(command.clone()).ok();
+                    }
                 }
             }
         });
@@ -100,6 +153,74 @@ impl SystemTray {
     }
 }
 
+impl SystemTray {
+    /// Show which project Start/Stop/Restart act on, in the menu header and tooltip.
+    pub fn set_project(&self, label: Option<&str>) {
+        if let Some(item) = &self.project_item {
+            item.set_text(label.unwrap_or("No project"));
+        }
+        if let Some(tray) = &self.tray_icon {
+            let tooltip = match label {
+                Some(label) => format!("DockStack - {}", label),
+                None => "DockStack - DevStack Manager".to_string(),
+            };
+            if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+                log::warn!("Failed to update tray tooltip: {}", e);
+            }
+        }
+    }
+}
+
+impl SystemTray {
+    /// Show a desktop notification. Uses the platform's own tool, so it works
+    /// without extra libraries and fails silently where none is installed.
+    pub fn notify(&self, title: &str, body: &str) {
+        let (program, args): (&str, &[&str]) = if cfg!(target_os = "windows") {
+            (
+                "powershell.exe",
+                &["-NoProfile", "-NonInteractive", "-Command", WINDOWS_BALLOON],
+            )
+        } else if cfg!(target_os = "macos") {
+            ("osascript", &["-e", MACOS_NOTIFICATION])
+        } else {
+            ("notify-send", &["--app-name=DockStack", "--", title, body])
+        };
+        let mut cmd = Command::new(program);
+        // Passed through the environment so no quoting is needed in the scripts
+        cmd.args(args)
+            .env("DOCKSTACK_TITLE", title)
+            .env("DOCKSTACK_BODY", body)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        self.runtime.spawn_blocking(move || {
+            if let Err(e) = cmd.status() {
+                log::debug!("Desktop notification via {} failed: {}", program, e);
+            }
+        });
+    }
+
+    /// Rebuild the Active Project radio group from (id, label) pairs, in menu order.
+    pub fn set_projects(&mut self, projects: &[(String, String)], active: Option<&str>) {
+        let Some(menu) = &self.projects_menu else {
+            return;
+        };
+        for item in self.project_items.drain(..) {
+            menu.remove(&item).ok();
+        }
+        for (id, label) in projects {
+            let checked = active == Some(id.as_str());
+            let item_id = format!("{}{}", PROJECT_ID_PREFIX, id);
+            let item = CheckMenuItem::with_id(item_id, label, true, checked, None);
+            match menu.append(&item) {
+                Ok(()) => self.project_items.push(item),
+                Err(e) => log::warn!("Failed to add {} to the tray menu: {}", label, e),
+            }
+        }
+        menu.set_enabled(!self.project_items.is_empty());
+    }
+}
+
 fn create_tray_icon_data() -> Vec<u8> {
     let size = 16usize;
     let mut data = Vec::with_capacity(size * size * 4);
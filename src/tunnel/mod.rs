@@ -0,0 +1,133 @@
+use crate::config::ProjectConfig;
+use crate::runtime::RuntimeHandle;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TunnelStatus {
+    Off,
+    Connecting,
+    Online(String),
+    Failed(String),
+}
+
+/// Shares the project's web server under a public URL using the `ngrok` binary.
+pub struct TunnelManager {
+    pub status: Arc<Mutex<TunnelStatus>>,
+    pub ngrok_available: bool,
+    child: Arc<Mutex<Option<Child>>>,
+    generation: Arc<AtomicU64>, // Bumped on stop so old reader tasks go quiet
+    runtime: RuntimeHandle,
+}
+
+impl TunnelManager {
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        Self {
+            status: Arc::new(Mutex::new(TunnelStatus::Off)),
+            ngrok_available: which::which("ngrok").is_ok(),
+            child: Arc::new(Mutex::new(None)),
+            generation: Arc::new(AtomicU64::new(0)),
+            runtime,
+        }
+    }
+
+    pub fn start(&self, project: &ProjectConfig) {
+        self.stop();
+        let Some(port) = web_port(project) else {
+            *self.status.lock().unwrap_or_else(|e| e.into_inner()) =
+                TunnelStatus::Failed("Enable Nginx or Apache to share the project".to_string());
+            return;
+        };
+
+        let spawned = Command::new("ngrok")
+            .arg("http")
+            .arg(port.to_string())
+            .args(["--log", "stdout", "--log-format", "json"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(c) => c,
+            Err(e) => {
+                *self.status.lock().unwrap_or_else(|e| e.into_inner()) =
+                    TunnelStatus::Failed(format!("Failed to run ngrok: {}", e));
+                return;
+            }
+        };
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = TunnelStatus::Connecting;
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        *self.child.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+
+        let status = self.status.clone();
+        let generation = self.generation.clone();
+        let current = generation.load(Ordering::SeqCst);
+        self.runtime.spawn_blocking(move || {
+            let mut last_error = None;
+            if let Some(stdout) = stdout {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+                        continue;
+                    };
+                    if generation.load(Ordering::SeqCst) != current {
+                        return;
+                    }
+                    if let Some(url) = entry.get("url").and_then(|u| u.as_str()) {
+                        log::info!("Tunnel online at {}", url);
+                        *status.lock().unwrap_or_else(|e| e.into_inner()) =
+                            TunnelStatus::Online(url.to_string());
+                    } else if let Some(err) = entry.get("err").and_then(|e| e.as_str()) {
+                        last_error = Some(err.to_string());
+                    }
+                }
+            }
+
+            // stdout closed: ngrok exited or was stopped
+            let mut stderr_text = String::new();
+            if let Some(stderr) = stderr {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    stderr_text.push_str(&line);
+                    stderr_text.push('\n');
+                }
+            }
+            if generation.load(Ordering::SeqCst) == current {
+                let reason = stderr_text
+                    .lines()
+                    .find(|l| l.contains("ERROR"))
+                    .map(|l| l.to_string())
+                    .or(last_error)
+                    .unwrap_or_else(|| "ngrok exited unexpectedly".to_string());
+                log::warn!("Tunnel closed: {}", reason);
+                *status.lock().unwrap_or_else(|e| e.into_inner()) = TunnelStatus::Failed(reason);
+            }
+        });
+    }
+
+    /// Close the tunnel; the public URL stops working immediately.
+    pub fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(mut child) = self.child.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            child.kill().ok();
+            child.wait().ok();
+            log::info!("Tunnel closed");
+        }
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = TunnelStatus::Off;
+    }
+}
+
+/// Host port of the project's web entry point, if any.
+pub fn web_port(project: &ProjectConfig) -> Option<u16> {
+    ["nginx", "apache"]
+        .into_iter()
+        .find(|name| {
+            project
+                .services
+                .get(*name)
+                .is_some_and(|svc| svc.enabled && !svc.internal)
+        })
+        .map(|name| project.host_port(name))
+}
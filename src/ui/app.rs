@@ -1,52 +1,196 @@
 use eframe::egui::{self, RichText, ScrollArea, Vec2};
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::config::AppConfig;
-use crate::docker::manager::{DockerEvent, DockerManager, ServiceStatus};
+use crate::audit::AuditLog;
+use crate::config::{AppConfig, ComposeImport, ServiceRename, VolumeCopy};
+use crate::diagnostics;
+use crate::docker::ci;
+use crate::docker::compose::{self, ComposeValidation};
+use crate::docker::devcontainer;
+use crate::docker::image_lock::ImageLock;
+use crate::docker::kubernetes;
+use crate::docker::manager::{
+    BuildCacheUsage, DirectoryAction, DockerEvent, DockerManager, EngineInfo, ForeignContainer,
+    OrphanResource, ProjectContainers, ServiceStatus, StopMode,
+};
+use crate::doctor::{self, DoctorFix, DoctorReport};
+use crate::error::DockStackError;
+use crate::framework;
+use crate::git::{ClonedRepo, GitClient, GitEvent};
+use crate::hooks::{HookEvent, HookOutput, HookRunner};
+use crate::http_client::{HttpClient, HttpEvent};
+use crate::hub::{HubClient, HubEvent};
+use crate::image_scan::{ScanReport, Severity};
+use crate::log_watcher::LogWatcher;
+use crate::logging;
 use crate::monitor::{ContainerStats, MonitorEvent, ResourceMonitor, SystemStats};
+use crate::plugins;
 use crate::port_scanner::{PortInfo, PortScanner};
+use crate::runtime::AppRuntime;
+use crate::services::catalog::{self, CatalogEntry};
 use crate::ssl::SslManager;
-use crate::terminal::EmbeddedTerminal;
+use crate::status_page::{StatusPage, StatusSnapshot};
+use crate::terminal::{self, EmbeddedTerminal};
 use crate::tray::{SystemTray, TrayCommand};
+use crate::tunnel::{self, TunnelManager};
+use crate::ui::commands::{AppCommand, CommandBus, Password};
 use crate::ui::panels::{self, Tab};
 use crate::ui::theme;
+use crate::ui::widgets::{autofocus, sparkline, status_icon, StatusKind};
+
+/// While the daemon is down it is re-checked with exponential backoff between these bounds.
+const DOCKER_RETRY_MIN: Duration = Duration::from_secs(2);
+const DOCKER_RETRY_MAX: Duration = Duration::from_secs(60);
+/// Repaint intervals, from an operation in progress down to a minimized window.
+/// Input always repaints immediately; these only pace background updates.
+const REPAINT_BUSY: Duration = Duration::from_millis(250);
+const REPAINT_LIVE: Duration = Duration::from_secs(1); // Tabs showing 1 Hz metrics or output
+const REPAINT_IDLE: Duration = Duration::from_secs(3);
+const REPAINT_MINIMIZED: Duration = Duration::from_secs(30);
+/// Docker events handled per frame; the rest wait for an immediate next frame
+/// so a burst of output cannot stall input handling.
+const DOCKER_EVENTS_PER_FRAME: usize = 200;
+/// Docker events kept for diagnostics bundles.
+const RECENT_EVENTS: usize = 200;
+/// Re-check a healthy daemon this often so a stopped engine is noticed.
+const DOCKER_HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+/// Re-read the active project's git branch this often, so a checkout shows up.
+const BRANCH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A start, stop or pull whose outcome is sent as a desktop notification
+/// if the window is in the background when it ends.
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Start, // Also restart: both end with the stack running
+    Stop,
+    Pull,
+}
+
+impl Operation {
+    fn succeeded(self) -> &'static str {
+        match self {
+            Operation::Start => "Stack is running",
+            Operation::Stop => "Stack stopped",
+            Operation::Pull => "Images pulled",
+        }
+    }
+
+    fn failed(self) -> &'static str {
+        match self {
+            Operation::Start => "start failed",
+            Operation::Stop => "stop failed",
+            Operation::Pull => "pull failed",
+        }
+    }
+}
+
+/// What the tray's Active Project menu currently shows.
+#[derive(PartialEq)]
+struct TrayProjects {
+    projects: Vec<(String, String)>, // (id, label) in `projects_by_recency` order
+    active: Option<String>,
+}
 
 pub struct DockStackApp {
+    runtime: AppRuntime,
     config: AppConfig,
     docker: DockerManager,
     monitor: ResourceMonitor,
     terminal: EmbeddedTerminal,
     tray: SystemTray,
+    log_watcher: LogWatcher,
+    http: HttpClient,
+    hub: HubClient,
+    hooks: HookRunner,
+    tunnel: TunnelManager,
+    status_page: StatusPage,
+    commands: CommandBus,
+    audit: AuditLog,
 
     // UI State
     active_tab: Tab,
     terminal_input: String,
-    new_project_name: String,
+    new_project: panels::NewProjectState,
+    git: GitClient,
+    pending_directory_confirm: Option<(String, String, DirectoryAction)>,
+    compose_issue: Option<ComposeValidation>,
+    destroy_confirm: Option<String>, // Open dialog; what the user typed so far
+    doctor: Option<Option<DoctorReport>>, // Open dialog; inner `None` while checks run
+    image_scan: Option<Option<String>>, // Open dialog; project id shown, `None` while scanning
+    scan_severity: Severity,         // Least severe findings the dialog lists
+    import_preview: Option<panels::ImportPreview>, // Open dialog for a compose file being imported
+    log_view: panels::LogViewState,
+    detached_tabs: Vec<Tab>, // Shown in their own windows instead of the main one
+    history_view: panels::HistoryViewState,
+    request_view: panels::RequestViewState,
+    image_browser: panels::ImageBrowserState,
+    catalog_view: panels::CatalogViewState,
+    app_log: Vec<(String, log::Level)>, // Formatted records the App Log tab shows
+    app_log_shown: Option<(u64, Option<log::Level>)>, // Log generation and level `app_log` is of
 
     // Cached data
     port_infos: Vec<PortInfo>,
+    orphans: Option<Vec<OrphanResource>>,
+    foreign_containers: Option<Vec<ForeignContainer>>, // Last discovery; `None` until run
+    build_cache: Option<BuildCacheUsage>,
+    /// Last image scan of each project, by id; services with critical findings get a badge.
+    scan_reports: HashMap<String, ScanReport>,
+    project_facts: panels::ProjectFacts,
+    copied_report: Option<String>, // Container whose report is on the clipboard
+    catalog: Vec<CatalogEntry>,    // Reloaded each time the catalog dialog opens
+    fleet: HashMap<String, ProjectContainers>, // Containers of every project, by id
+    unhealthy: HashSet<String>,    // Containers last seen unhealthy, for the hook
+    last_error: Option<DockStackError>,
     sys_stats: SystemStats,
     container_stats: Vec<ContainerStats>,
     cpu_history: std::collections::VecDeque<f32>,
     mem_history: std::collections::VecDeque<f32>,
+    recent_events: std::collections::VecDeque<String>,
 
     // Flags
-    docker_available: bool,
+    engine: EngineInfo,
+    docker_retry: Duration, // Current backoff; zero until the daemon was first found down
+    next_docker_check: Instant,
+    next_branch_check: Instant,
     tray_initialized: bool,
+    minimized: bool, // Monitors are paused while the window is minimized
+    shown_project: Option<Option<String>>, // Label last put in the window title and tray
+    tray_projects: Option<TrayProjects>, // Last list given to the tray's Active Project menu
+    pending_operation: Option<(String, Operation)>, // Project label, awaiting an outcome
+    status_page_stale: bool, // Stack, containers or project changed since the last snapshot
+    facts_stale: bool, // Project, config or scans changed since `project_facts` and the tray menu
     _last_refresh: Instant,
     last_container_refresh: Instant,
 }
 
 impl DockStackApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> std::io::Result<Self> {
         egui_extras::install_image_loaders(&cc.egui_ctx);
-        theme::apply_theme(&cc.egui_ctx);
-
+        plugins::load(); // Before the config, so projects get the plugins' services
         let config = AppConfig::load();
-        let docker = DockerManager::new();
-        let monitor = ResourceMonitor::new();
-        let terminal = EmbeddedTerminal::new();
-        let tray = SystemTray::new();
+        theme::apply_theme(&cc.egui_ctx, config.theme.high_contrast);
+        diagnostics::set_crash_reports(config.crash_reports);
+        if compose::selinux_enforcing() {
+            log::info!("SELinux is enforcing; project mounts get a relabel flag by default");
+        }
+        let runtime = AppRuntime::new()?;
+        let docker = DockerManager::new(runtime.handle(), &config.buffers);
+        docker.set_proxy(&config.proxy);
+        docker.set_registries(&config.registries);
+        let monitor = ResourceMonitor::new(runtime.handle());
+        let terminal = EmbeddedTerminal::new(runtime.handle(), config.buffers.terminal_lines);
+        let tray = SystemTray::new(runtime.handle());
+        let log_watcher = LogWatcher::new(docker.logs.clone(), docker.log_capacity.clone());
+        let status_page = StatusPage::new(runtime.handle());
+        let audit = AuditLog::open(&runtime.handle());
+        if config.status_page.enabled {
+            if let Err(e) = status_page.start(config.status_page.port) {
+                log::warn!("Status page not started: {}", e);
+            }
+        }
 
         // Check Docker availability
         docker.check_docker();
@@ -55,114 +199,1595 @@ impl DockStackApp {
         monitor.start();
 
         // Initial port scan
-        let port_infos = if let Some(project) = config.active_project() {
+        let port_infos = if let Some(project) = config.active_project().filter(|p| !p.archived) {
             PortScanner::scan_project_ports(&project.services)
         } else {
             PortScanner::get_common_ports()
         };
 
-        Self {
+        let git = GitClient::new(runtime.handle());
+        Ok(Self {
+            http: HttpClient::new(runtime.handle()),
+            hub: HubClient::new(runtime.handle()),
+            hooks: HookRunner::new(runtime.handle()),
+            tunnel: TunnelManager::new(runtime.handle()),
+            status_page,
+            runtime,
+            commands: CommandBus::new(),
+            audit,
             config,
             docker,
             monitor,
             terminal,
             tray,
+            log_watcher,
             active_tab: Tab::Dashboard,
             terminal_input: String::new(),
-            new_project_name: String::new(),
+            new_project: panels::NewProjectState {
+                git_available: git.git_available,
+                ..panels::NewProjectState::default()
+            },
+            git,
+            pending_directory_confirm: None,
+            compose_issue: None,
+            destroy_confirm: None,
+            doctor: None,
+            image_scan: None,
+            import_preview: None,
+            scan_severity: Severity::High,
+            log_view: panels::LogViewState::default(),
+            app_log: Vec::new(),
+            app_log_shown: None,
+            detached_tabs: Vec::new(),
+            history_view: panels::HistoryViewState::default(),
+            request_view: panels::RequestViewState::default(),
+            image_browser: panels::ImageBrowserState::default(),
+            catalog_view: panels::CatalogViewState::default(),
             port_infos,
+            orphans: None,
+            foreign_containers: None,
+            build_cache: None,
+            scan_reports: HashMap::new(),
+            project_facts: panels::ProjectFacts::default(),
+            copied_report: None,
+            catalog: Vec::new(),
+            fleet: HashMap::new(),
+            unhealthy: HashSet::new(),
+            last_error: None,
             sys_stats: SystemStats::default(),
             container_stats: Vec::new(),
             cpu_history: std::collections::VecDeque::from(vec![0.0; 60]),
             mem_history: std::collections::VecDeque::from(vec![0.0; 60]),
-            docker_available: false,
+            recent_events: std::collections::VecDeque::new(),
+            engine: EngineInfo::default(),
+            docker_retry: Duration::ZERO,
+            next_docker_check: Instant::now() + DOCKER_HEALTH_INTERVAL,
+            next_branch_check: Instant::now() + BRANCH_CHECK_INTERVAL,
             tray_initialized: false,
+            minimized: false,
+            shown_project: None,
+            tray_projects: None,
+            pending_operation: None,
+            status_page_stale: true,
+            facts_stale: true,
             _last_refresh: Instant::now(),
             last_container_refresh: Instant::now(),
+        })
+    }
+
+    fn process_docker_events(&mut self, ctx: &egui::Context) {
+        for _ in 0..DOCKER_EVENTS_PER_FRAME {
+            let Ok(event) = self.docker.event_rx.try_recv() else {
+                return;
+            };
+            self.remember_event(&event);
+            self.track_operation(ctx, &event);
+            match event {
+                DockerEvent::DockerAvailable(engine) => {
+                    self.docker_retry = if engine.available {
+                        Duration::ZERO
+                    } else {
+                        (self.docker_retry * 2).clamp(DOCKER_RETRY_MIN, DOCKER_RETRY_MAX)
+                    };
+                    self.next_docker_check = Instant::now()
+                        + match engine.available {
+                            true => DOCKER_HEALTH_INTERVAL,
+                            false => self.docker_retry,
+                        };
+                    if engine.available && !self.engine.available {
+                        log::info!("Docker daemon is reachable");
+                        self.commands.send(AppCommand::RefreshContainers);
+                    } else if !engine.available && self.engine.available {
+                        log::warn!("Docker daemon is no longer reachable");
+                    }
+                    self.engine = engine;
+                }
+                DockerEvent::StatusChange(_, status) => {
+                    self.status_page_stale = true;
+                    // Revoke the public URL as soon as the stack goes down
+                    if matches!(status, ServiceStatus::Stopping | ServiceStatus::Stopped) {
+                        self.tunnel.stop();
+                        self.docker.stop_log_followers();
+                    }
+                    // A new attempt supersedes the previous failure
+                    if status == ServiceStatus::Starting {
+                        self.last_error = None;
+                    }
+                    match status {
+                        ServiceStatus::Running => self.fire_hook(HookEvent::StackStarted),
+                        ServiceStatus::Stopped => self.fire_hook(HookEvent::StackStopped),
+                        _ => {}
+                    }
+                }
+                DockerEvent::Log(_) | DockerEvent::ImagesPulled => {}
+                DockerEvent::ContainerList(list) => {
+                    self.status_page_stale = true;
+                    // The main container list is already updated via Mutex in DockerManager;
+                    // here only containers that just turned unhealthy are picked out
+                    let unhealthy: HashSet<String> = list
+                        .iter()
+                        .filter(|c| c.status.contains("(unhealthy)"))
+                        .map(|c| c.name.clone())
+                        .collect();
+                    for c in list.iter().filter(|c| {
+                        unhealthy.contains(&c.name) && !self.unhealthy.contains(&c.name)
+                    }) {
+                        let event =
+                            HookEvent::ContainerUnhealthy(c.name.clone(), c.service.clone());
+                        self.fire_hook(event);
+                    }
+                    self.unhealthy = unhealthy;
+                }
+                DockerEvent::Error(e) => {
+                    log::error!("Docker error: {}", e);
+                    self.last_error = Some(e);
+                }
+                DockerEvent::ConfirmDirectory(project_id, reason, action) => {
+                    self.pending_directory_confirm = Some((project_id, reason, action));
+                }
+                DockerEvent::ComposeInvalid(issue) => {
+                    log::error!("Invalid compose file: {}", issue.message);
+                    self.compose_issue = Some(issue);
+                }
+                DockerEvent::BuildCacheUsage(usage) => {
+                    self.build_cache = Some(usage);
+                }
+                DockerEvent::ContainerReport(container, report) => {
+                    ctx.copy_text(report);
+                    self.copied_report = Some(container);
+                }
+                DockerEvent::Fleet(fleet) => {
+                    self.fleet = fleet;
+                }
+                DockerEvent::DoctorReport(report) => {
+                    // Dropped if the dialog was closed while the checks ran
+                    if self.doctor.is_some() {
+                        self.doctor = Some(Some(*report));
+                    }
+                }
+                DockerEvent::ImageScan(Ok(report)) => {
+                    if self.image_scan.is_some() {
+                        self.image_scan = Some(Some(report.project_id.clone()));
+                    }
+                    self.scan_reports.insert(report.project_id.clone(), *report);
+                    self.facts_stale = true;
+                }
+                DockerEvent::ImageScan(Err(e)) => {
+                    self.image_scan = None;
+                    self.last_error = Some(e);
+                }
+                DockerEvent::OrphanedResources(list) => {
+                    self.orphans = Some(list);
+                }
+                DockerEvent::ForeignContainers(list) => {
+                    self.foreign_containers = Some(list);
+                }
+                DockerEvent::ContainersCaptured(services) if !services.is_empty() => {
+                    let names: Vec<_> = services.iter().map(|(name, _)| name.clone()).collect();
+                    let name = format!("Captured: {}", names.join(", "));
+                    self.config.add_project_with_services(name, services);
+                    self.audit_active(format!("Captured containers {}", names.join(", ")));
+                }
+                DockerEvent::ContainersCaptured(_) => {}
+                DockerEvent::SnapshotCreated(path) => {
+                    self.fire_hook(HookEvent::BackupFinished(path));
+                }
+                DockerEvent::SnapshotRestored(restored) => {
+                    if let Some(project) = self
+                        .config
+                        .projects
+                        .iter_mut()
+                        .find(|p| p.id == restored.id)
+                    {
+                        *project = *restored;
+                        self.config.save();
+                        self.facts_stale = true;
+                    }
+                }
+                DockerEvent::PlatformFallback(project_id, fallbacks) => {
+                    if let Some(project) =
+                        self.config.projects.iter_mut().find(|p| p.id == project_id)
+                    {
+                        for (name, platform) in &fallbacks {
+                            if let Some(svc) = project.services.get_mut(name) {
+                                svc.platform = platform.clone();
+                            }
+                            let action = format!(
+                                "Runs {} as {}: its image has no arm64 build",
+                                name, platform
+                            );
+                            self.audit.record(&project.name, action);
+                        }
+                        self.config.save();
+                    }
+                }
+                DockerEvent::RegistryLogin(host, result) => {
+                    if let Err(e) = &result {
+                        self.last_error = Some(e.clone());
+                    }
+                    if let Some(registry) =
+                        self.config.registries.iter_mut().find(|r| r.host == host)
+                    {
+                        registry.verified_at = match result {
+                            Ok(()) => chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+                            Err(_) => String::new(),
+                        };
+                        self.config.save();
+                    }
+                }
+                DockerEvent::MaintenanceDone(report) => {
+                    let format_bytes = crate::utils::format_bytes;
+                    let mut summary = format!("Reclaimed {}", format_bytes(report.reclaimed()));
+                    let steps: Vec<String> = report
+                        .steps
+                        .iter()
+                        .filter(|(_, bytes)| *bytes > 0)
+                        .map(|(label, bytes)| {
+                            format!("{} {}", label.to_lowercase(), format_bytes(*bytes))
+                        })
+                        .collect();
+                    if !steps.is_empty() {
+                        summary.push_str(&format!(": {}", steps.join(", ")));
+                    }
+                    if !report.errors.is_empty() {
+                        summary.push_str(&format!(
+                            " ({} steps failed, see Logs)",
+                            report.errors.len()
+                        ));
+                    }
+                    log::info!("Maintenance finished: {}", summary);
+                    self.audit
+                        .record("", format!("Ran maintenance: {}", summary));
+                    if self.config.notifications {
+                        self.tray.notify("DockStack maintenance", &summary);
+                    }
+                    self.config.maintenance.last_summary = summary;
+                    self.config.save();
+                }
+                DockerEvent::ComposeImportRead(Ok(import), remaps) => {
+                    self.import_preview = Some(panels::ImportPreview::new(*import, remaps));
+                }
+                DockerEvent::ComposeImportRead(Err(e), _) => {
+                    self.last_error = Some(e);
+                }
+                DockerEvent::RenamesMigrated(project_id, migrated) => {
+                    if let Some(project) =
+                        self.config.projects.iter_mut().find(|p| p.id == project_id)
+                    {
+                        // Renames made while the stack started wait for the next start
+                        project.pending_renames.retain(|r| !migrated.contains(r));
+                        self.config.save();
+                    }
+                }
+                DockerEvent::ImageLockWritten(project_id) => {
+                    if self.config.active_project_id.as_deref() == Some(project_id.as_str()) {
+                        self.facts_stale = true;
+                    }
+                }
+            }
+        }
+        if !self.docker.event_rx.is_empty() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Notify about the end of the pending operation if `event` ends it and
+    /// the window is in the background.
+    fn track_operation(&mut self, ctx: &egui::Context, event: &DockerEvent) {
+        let Some((_, operation)) = self.pending_operation else {
+            return;
+        };
+        let outcome = match event {
+            DockerEvent::StatusChange(_, ServiceStatus::Running)
+                if operation == Operation::Start =>
+            {
+                Ok(())
+            }
+            DockerEvent::StatusChange(_, ServiceStatus::Stopped)
+                if operation == Operation::Stop =>
+            {
+                Ok(())
+            }
+            DockerEvent::ImagesPulled if operation == Operation::Pull => Ok(()),
+            DockerEvent::Error(e) => Err(e.to_string()),
+            DockerEvent::ComposeInvalid(issue) => Err(issue.message.clone()),
+            DockerEvent::ConfirmDirectory(..) => {
+                // Needs an answer in the window anyway
+                self.pending_operation = None;
+                return;
+            }
+            _ => return,
+        };
+        let Some((project, operation)) = self.pending_operation.take() else {
+            return;
+        };
+        let background = ctx.input(|i| {
+            let viewport = i.viewport();
+            viewport.minimized.unwrap_or(false) || !viewport.focused.unwrap_or(true)
+        });
+        if !self.config.notifications || !background {
+            return;
+        }
+        match outcome {
+            Ok(()) => self.tray.notify(&project, operation.succeeded()),
+            Err(e) => {
+                let summary = e.lines().next().unwrap_or_default();
+                let title = format!("{}: {}", project, operation.failed());
+                self.tray
+                    .notify(&title, &crate::utils::truncate_string(summary, 200));
+            }
+        }
+    }
+
+    fn process_monitor_events(&mut self) {
+        while let Ok(event) = self.monitor.event_rx.try_recv() {
+            match event {
+                MonitorEvent::SystemUpdate(stats) => {
+                    self.sys_stats = stats;
+                    self.cpu_history = self.monitor.cpu_history.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                    self.mem_history = self.monitor.mem_history.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                }
+                MonitorEvent::ContainerUpdate(stats) => {
+                    self.container_stats = stats;
+                }
+                MonitorEvent::HistoryExported(path, Ok(samples)) => {
+                    log::info!("Exported {} metrics samples to {}", samples, path.display());
+                }
+                MonitorEvent::HistoryExported(_, Err(e)) => {
+                    self.last_error = Some(e);
+                }
+            }
+        }
+    }
+
+    fn process_terminal_events(&mut self) {
+        while let Ok(_event) = self.terminal.event_rx.try_recv() {
+            // Events are already stored in terminal.output_lines
+        }
+    }
+
+    fn process_http_events(&mut self) {
+        while let Ok(event) = self.http.event_rx.try_recv() {
+            match event {
+                HttpEvent::Response(result) => {
+                    self.request_view.pending = false;
+                    self.request_view.response = Some(result);
+                }
+            }
+        }
+    }
+
+    fn process_hub_events(&mut self) {
+        while let Ok(event) = self.hub.event_rx.try_recv() {
+            match event {
+                // Answers to an older query arrive late; keep the latest
+                HubEvent::Results(query, result) if query == self.image_browser.searched => {
+                    self.image_browser.pending = false;
+                    self.image_browser.results = Some(result);
+                }
+                HubEvent::Results(..) => {}
+            }
+        }
+    }
+
+    fn process_hook_events(&mut self) {
+        while let Ok(output) = self.hooks.event_rx.try_recv() {
+            match output {
+                HookOutput::Notify(title, body) => self.tray.notify(&title, &body),
+                HookOutput::Failed(err) => self.last_error = Some(err),
+            }
+        }
+    }
+
+    /// Hand `event` about the active project to the hook scripts, when enabled.
+    fn fire_hook(&self, event: HookEvent) {
+        if !self.config.hooks_enabled {
+            return;
+        }
+        if let Some(project) = self.config.active_project() {
+            self.hooks.fire(event, project);
+        }
+    }
+
+    fn process_git_events(&mut self) {
+        while let Ok(event) = self.git.event_rx.try_recv() {
+            match event {
+                GitEvent::Progress(line) => self.new_project.clone_progress = Some(line),
+                GitEvent::Cloned(result) => {
+                    self.new_project.clone_progress = None;
+                    match result {
+                        Ok(repo) => self.open_cloned_project(repo),
+                        Err(e) => self.last_error = Some(e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Make a fresh clone a project: import its own compose file, or give it the
+    /// stack its framework needs (DockStack's default one serving `www/` if none
+    /// is recognised). Then start it.
+    fn open_cloned_project(&mut self, repo: ClonedRepo) {
+        if let Some(compose_file) = &repo.compose_file {
+            if let Err(e) = self.config.import_from_compose(compose_file) {
+                self.last_error = Some(e);
+                return;
+            }
+        } else {
+            self.config
+                .add_project_in(repo.name.clone(), &repo.directory);
+            if let Some(project) = self.config.active_project_mut() {
+                framework::configure(project);
+            }
+            self.config.save();
+        }
+        self.new_project.git_url.clear();
+        self.audit_active(format!("Cloned {}", repo.url));
+        self.commands.send(AppCommand::StartStack);
+    }
+
+    fn process_tray_events(&mut self) {
+        while let Ok(cmd) = self.tray.command_rx.try_recv() {
+            self.commands.send(match cmd {
+                TrayCommand::Start => AppCommand::StartStack,
+                TrayCommand::Stop => AppCommand::StopStack(StopMode::Stop),
+                TrayCommand::Restart => AppCommand::RestartStack,
+                TrayCommand::OpenUI => AppCommand::ShowWindow,
+                TrayCommand::SelectProject(id) => AppCommand::SelectProject(id),
+                TrayCommand::Quit => AppCommand::Quit,
+            });
+        }
+    }
+
+    /// Apply queued commands. The only place user actions reach the backends.
+    fn process_commands(&mut self, ctx: &egui::Context) {
+        for command in self.commands.drain() {
+            self.dispatch(ctx, command);
+        }
+    }
+
+    fn dispatch(&mut self, ctx: &egui::Context, command: AppCommand) {
+        match command {
+            AppCommand::StartStack => {
+                if let Some(project) = self.config.active_project() {
+                    if self.docker.start_services(project) {
+                        self.pending_operation = Some((project.display_name(), Operation::Start));
+                        self.audit.record(&project.name, "Started stack");
+                    }
+                }
+            }
+            AppCommand::StopStack(mode) => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.stop_services(project, mode);
+                    self.pending_operation = Some((project.display_name(), Operation::Stop));
+                    self.audit.record(&project.name, mode.audit_action());
+                }
+            }
+            AppCommand::RestartStack => {
+                if let Some(project) = self.config.active_project() {
+                    if self.docker.restart_services(project) {
+                        self.pending_operation = Some((project.display_name(), Operation::Start));
+                        self.audit.record(&project.name, "Restarted stack");
+                    }
+                }
+            }
+            AppCommand::PullImages => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.pull_images(project);
+                    self.pending_operation = Some((project.display_name(), Operation::Pull));
+                    self.audit.record(&project.name, "Pulled images");
+                }
+            }
+            AppCommand::UpdateImageLock => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.update_image_lock(project);
+                    self.audit.record(&project.name, "Updated the image lock");
+                }
+            }
+            AppCommand::RebuildImage(service) => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.rebuild_image(project, &service);
+                    let action = format!("Rebuilt image of {} without cache", service);
+                    self.audit.record(&project.name, action);
+                }
+            }
+            AppCommand::CheckBuildCache => self.docker.check_build_cache(),
+            AppCommand::PruneBuildCache => {
+                self.docker.prune_build_cache();
+                self.audit.record("", "Pruned the Docker build cache");
+            }
+            AppCommand::RunMaintenance => {
+                // Counted from the start, so a slow run is not started twice
+                self.config.maintenance.last_run = chrono::Utc::now().timestamp();
+                self.config.save();
+                self.docker.run_maintenance(&self.config.maintenance);
+            }
+            AppCommand::ApplyBuffers => {
+                let buffers = &self.config.buffers;
+                self.docker.set_log_capacity(buffers.log_lines);
+                self.terminal.set_capacity(buffers.terminal_lines);
+            }
+            AppCommand::ApplyProxy => self.docker.set_proxy(&self.config.proxy),
+            AppCommand::SetRegistries => self.docker.set_registries(&self.config.registries),
+            AppCommand::RemoveRegistry(registry) => self.docker.remove_registry(registry),
+            AppCommand::LoginRegistry(registry, Password(password)) => {
+                self.docker.login_registry(registry, password)
+            }
+            AppCommand::ApplyStatusPage => {
+                let page = &self.config.status_page;
+                if !page.enabled {
+                    self.status_page.stop();
+                } else if let Err(e) = self.status_page.start(page.port) {
+                    let context = "Failed to start the status page";
+                    self.last_error = Some(match e.kind() {
+                        io::ErrorKind::AddrInUse => DockStackError::PortInUse {
+                            context: context.to_string(),
+                            port: Some(page.port),
+                        },
+                        _ => DockStackError::io(context, &e),
+                    });
+                    self.config.status_page.enabled = false;
+                    self.config.save();
+                }
+                self.status_page_stale = true;
+            }
+            AppCommand::ConfirmDirectory(project_id, action) => {
+                if let Some(project) = self.config.projects.iter_mut().find(|p| p.id == project_id)
+                {
+                    project.directory_confirmed = true;
+                    self.config.save();
+                }
+                if let Some(project) = self.config.projects.iter().find(|p| p.id == project_id) {
+                    let (accepted, verb) = match action {
+                        DirectoryAction::Start => (self.docker.start_services(project), "started"),
+                        DirectoryAction::Restart => {
+                            (self.docker.restart_services(project), "restarted")
+                        }
+                    };
+                    if !accepted {
+                        return;
+                    }
+                    self.pending_operation = Some((project.display_name(), Operation::Start));
+                    let entry = format!(
+                        "Confirmed directory {} and {} stack",
+                        project.directory, verb
+                    );
+                    self.audit.record(&project.name, entry);
+                }
+            }
+            AppCommand::SelectProject(id) => {
+                self.config.set_active_project(&id);
+                // Clicking the active entry unchecks it natively; rebuild to restore the mark
+                self.tray_projects = None;
+                self.sync_tray_projects();
+            }
+            AppCommand::CheckDocker => self.docker.check_docker(),
+            AppCommand::StartDockerDaemon => {
+                self.docker.start_daemon();
+                // Poll quickly while the engine boots
+                self.docker_retry = Duration::ZERO;
+                self.next_docker_check = Instant::now() + DOCKER_RETRY_MIN;
+            }
+            AppCommand::RefreshContainers => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.refresh_containers(project);
+                }
+                self.docker.refresh_fleet();
+            }
+            AppCommand::SetProjectRunning(id, run) => {
+                if self.config.active_project_id.as_deref() == Some(id.as_str()) {
+                    self.commands.send(if run {
+                        AppCommand::StartStack
+                    } else {
+                        AppCommand::StopStack(StopMode::Stop)
+                    });
+                } else if let Some(project) = self.config.projects.iter().find(|p| p.id == id) {
+                    if !self.docker.set_background_project(project, run) {
+                        return;
+                    }
+                    let action = if run {
+                        "Started stack"
+                    } else {
+                        "Stopped stack"
+                    };
+                    self.audit.record(&project.name, action);
+                }
+            }
+            AppCommand::FollowServiceLogs(service) => {
+                if let Some(project) = self.config.active_project() {
+                    let container = compose::container_name(project, &service);
+                    self.docker.follow_container_logs(&container, &service);
+                    self.show_tab(ctx, Tab::Logs);
+                }
+            }
+            AppCommand::AttachContainer { container, label } => {
+                self.docker.attach_container(&container, &label);
+                self.log_view.app_log = false;
+                self.log_view.attached = Some(container);
+                self.show_tab(ctx, Tab::Logs);
+            }
+            AppCommand::CopyContainerReport(container) => {
+                self.copied_report = None;
+                self.docker.container_report(&container);
+            }
+            AppCommand::DetachContainer(container) => {
+                self.docker.detach_container(&container);
+                self.log_view.paused.remove(&container);
+                if self.log_view.attached.as_deref() == Some(container.as_str()) {
+                    self.log_view.attached = None;
+                }
+            }
+            AppCommand::StartTunnel => {
+                if let Some(project) = self.config.active_project() {
+                    self.tunnel.start(project);
+                    self.audit
+                        .record(&project.name, "Shared the stack on a public tunnel");
+                }
+            }
+            AppCommand::StopTunnel => {
+                self.tunnel.stop();
+                self.audit_active("Stopped the public tunnel");
+            }
+            AppCommand::ScanPorts => {
+                self.port_infos = match self.config.active_project().filter(|p| !p.archived) {
+                    Some(project) => PortScanner::scan_project_ports(&project.services),
+                    None => PortScanner::get_common_ports(),
+                };
+            }
+            AppCommand::SendRequest(request) => {
+                self.request_view.pending = true;
+                self.http.send(request);
+            }
+            AppCommand::CreateSnapshot => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.create_snapshot(project);
+                    self.audit.record(&project.name, "Created snapshot");
+                }
+            }
+            AppCommand::RestoreSnapshot(archive) => {
+                if let Some(project) = self.config.active_project() {
+                    let action = format!("Restored snapshot {}", archive.display());
+                    self.docker.restore_snapshot(project, archive);
+                    self.audit.record(&project.name, action);
+                }
+            }
+            AppCommand::ScanOrphans => {
+                self.orphans = None;
+                self.docker.scan_orphans(self.known_project_ids());
+            }
+            AppCommand::RemoveOrphans(orphans) => {
+                self.orphans = None;
+                let action = format!("Removed {} orphaned resource(s)", orphans.len());
+                self.audit.record("", action);
+                self.docker
+                    .remove_orphans(orphans, self.known_project_ids());
+            }
+            AppCommand::DiscoverContainers => {
+                self.foreign_containers = None;
+                self.docker.discover_containers();
+            }
+            AppCommand::CaptureContainers(names) => self.docker.capture_containers(names),
+            AppCommand::CloneProject(url) => {
+                self.new_project.clone_progress = Some(format!("Cloning {}…", url));
+                self.git.clone_project(&url);
+            }
+            AppCommand::OpenCodebase(path) => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "New Project".to_string());
+                let id = self.config.add_project_in(name, &path);
+                if let Some(project) = self.config.projects.iter_mut().find(|p| p.id == id) {
+                    // The folder is the user's own; ask before writing into it
+                    project.directory_confirmed = false;
+                    framework::configure(project);
+                }
+                self.commands.send(AppCommand::SaveConfig);
+            }
+            AppCommand::ReadComposeImport(path) => {
+                let tx = self.docker.event_tx.clone();
+                self.runtime.handle().spawn_blocking(move || {
+                    let read = ComposeImport::read(&path).map(Box::new);
+                    let remaps = match &read {
+                        Ok(import) => PortScanner::suggest_remaps(&import.host_ports()),
+                        Err(_) => Vec::new(),
+                    };
+                    tx.send(DockerEvent::ComposeImportRead(read, remaps)).ok();
+                });
+            }
+            AppCommand::AdoptComposeProject(compose_file) => {
+                match self.config.import_from_compose(&compose_file) {
+                    Ok(_) => {
+                        let action = format!("Adopted compose project {}", compose_file.display());
+                        self.audit_active(action);
+                        self.commands.send(AppCommand::DiscoverContainers);
+                    }
+                    Err(e) => self.last_error = Some(e),
+                }
+            }
+            AppCommand::RenameService {
+                from,
+                to,
+                copy_data,
+            } => {
+                self.rename_service(&from, &to, copy_data);
+            }
+            AppCommand::UpdateGitignore => {
+                let Some(project) = self.config.active_project() else {
+                    return;
+                };
+                match compose::update_gitignore(project) {
+                    Ok(added) if added.is_empty() => {}
+                    Ok(added) => {
+                        log::info!("Added to .gitignore: {}", added.join(", "));
+                        self.audit_active("Updated .gitignore");
+                        self.facts_stale = true;
+                    }
+                    Err(e) => {
+                        let context = "Failed to update .gitignore";
+                        self.last_error = Some(DockStackError::io(context, &e));
+                    }
+                }
+            }
+            AppCommand::RunDoctor => {
+                if let Some(project) = self.config.active_project() {
+                    self.doctor = Some(None);
+                    self.docker.run_doctor(project, self.known_project_ids());
+                }
+            }
+            AppCommand::ScanImages => {
+                if let Some(project) = self.config.active_project() {
+                    self.image_scan = Some(None);
+                    self.docker.scan_images(project);
+                }
+            }
+            AppCommand::ApplyDoctorFix(fix) => {
+                // These finish in the background and log their outcome instead
+                let rerun = !matches!(
+                    fix,
+                    DoctorFix::RemoveOrphans(_) | DoctorFix::AllowPortsFrom(_)
+                );
+                self.audit_active(format!("Applied doctor fix: {}", fix.label()));
+                self.apply_doctor_fix(fix);
+                if rerun {
+                    self.commands.send(AppCommand::RunDoctor);
+                }
+            }
+            AppCommand::GenerateSsl => {
+                if let Some(project) = self.config.active_project() {
+                    match SslManager::generate_self_signed(&project.directory) {
+                        Ok((cert, key)) => {
+                            log::info!("SSL cert generated: {}, {}", cert, key);
+                            self.audit
+                                .record(&project.name, "Generated SSL certificate");
+                        }
+                        Err(e) => {
+                            log::error!("SSL generation failed: {}", e);
+                            self.last_error = Some(e);
+                        }
+                    }
+                }
+            }
+            AppCommand::RemoveSsl => {
+                if let Some(project) = self.config.active_project() {
+                    match SslManager::remove_certs(&project.directory) {
+                        Ok(()) => self.audit.record(&project.name, "Removed SSL certificate"),
+                        Err(e) => {
+                            log::error!("SSL removal failed: {}", e);
+                            self.last_error = Some(e);
+                        }
+                    }
+                }
+            }
+            AppCommand::ExportDiagnostics(dest) => {
+                let files = self.diagnostics_files();
+                self.docker
+                    .export_diagnostics(self.config.active_project(), dest, files);
+            }
+            AppCommand::ExportMetrics(dest) => self.monitor.export_history(dest),
+            AppCommand::ExportKubernetes(dest) => {
+                if let Some(project) = self.config.active_project() {
+                    match kubernetes::export(project, &dest) {
+                        Ok(()) => self.audit.record(
+                            &project.name,
+                            format!("Exported Kubernetes manifests to {}", dest.display()),
+                        ),
+                        Err(e) => {
+                            let context = format!("Failed to write {}", dest.display());
+                            self.last_error = Some(DockStackError::io(context, &e));
+                        }
+                    }
+                }
+            }
+            AppCommand::ExportCi(format, dest) => {
+                if let Some(project) = self.config.active_project() {
+                    match ci::export(project, format, &dest) {
+                        Ok(()) => self.audit.record(
+                            &project.name,
+                            format!("Exported a {} job to {}", format.label(), dest.display()),
+                        ),
+                        Err(e) => {
+                            let context = format!("Failed to write {}", dest.display());
+                            self.last_error = Some(DockStackError::io(context, &e));
+                        }
+                    }
+                }
+            }
+            AppCommand::SaveDoctorReport(dest) => {
+                if let Some(Some(report)) = &self.doctor {
+                    if let Err(e) = std::fs::write(&dest, report.to_text()) {
+                        let context = format!("Failed to save the report to {}", dest.display());
+                        self.last_error = Some(DockStackError::io(context, &e));
+                    }
+                }
+            }
+            AppCommand::SaveTranscript(dest, transcript) => {
+                let tx = self.docker.event_tx.clone();
+                self.runtime.handle().spawn_blocking(move || {
+                    if let Err(e) = std::fs::write(&dest, transcript) {
+                        let context =
+                            format!("Failed to save the transcript to {}", dest.display());
+                        tx.send(DockerEvent::Error(DockStackError::io(context, &e)))
+                            .ok();
+                    }
+                });
+            }
+            AppCommand::WriteDevcontainer => {
+                if let Some(project) = self.config.active_project() {
+                    match devcontainer::write(project) {
+                        Ok(service) => self.audit.record(
+                            &project.name,
+                            format!("Wrote .devcontainer/devcontainer.json for {}", service),
+                        ),
+                        Err(e) => {
+                            let path = devcontainer::config_path(project);
+                            let context = format!("Failed to write {}", path.display());
+                            self.last_error = Some(DockStackError::io(context, &e));
+                        }
+                    }
+                }
+            }
+            AppCommand::StartTerminal => {
+                if !self.terminal.is_running() {
+                    let cwd = self
+                        .config
+                        .active_project()
+                        .map(|p| Path::new(&p.directory));
+                    self.terminal.start(&self.config.terminal, cwd);
+                }
+            }
+            AppCommand::SendTerminalInput(input) => {
+                let use_plugin = *self
+                    .docker
+                    .use_compose_plugin
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner());
+                let compose = self
+                    .config
+                    .active_project()
+                    .map(|p| compose::compose_invocation(p, use_plugin));
+                self.terminal.send_command(&input, compose.as_deref());
+            }
+            AppCommand::SaveConfig => {
+                self.config.save();
+                self.facts_stale = true;
+            }
+            AppCommand::ShowWindow => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            AppCommand::Quit => {
+                log::info!("Quit requested, initiating graceful shutdown...");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
         }
     }
 
-    fn process_docker_events(&mut self) {
-        while let Ok(event) = self.docker.event_rx.try_recv() {
-            match event {
-                DockerEvent::DockerAvailable(available) => {
-                    self.docker_available = available;
-                }
-                DockerEvent::StatusChange(_, _status) => {}
-                DockerEvent::Log(_) => {}
-                DockerEvent::ContainerList(_list) => {
-                    // Update our monitor stats and analytic history
-                    // The main container list is already updated via Mutex in DockerManager,
-                    // but we sync it here to trigger UI updates if necessary.
+    /// Repaint only as often as what is on screen can change, and stop sampling
+    /// metrics nobody can see while the window is minimized.
+    fn schedule_repaint(&mut self, ctx: &egui::Context) {
+        let (minimized, focused) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (
+                viewport.minimized.unwrap_or(false),
+                viewport.focused.unwrap_or(true),
+            )
+        });
+        if minimized != self.minimized {
+            self.minimized = minimized;
+            if minimized {
+                log::debug!("Window minimized; pausing resource monitors");
+                self.monitor.stop();
+            } else {
+                self.monitor.start();
+            }
+        }
+
+        let status = self
+            .docker
+            .status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let starting = self
+            .docker
+            .start_progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|p| p.finished_at.is_none());
+        let busy = starting
+            || matches!(status, ServiceStatus::Starting | ServiceStatus::Stopping)
+            || matches!(self.doctor, Some(None))
+            || matches!(self.image_scan, Some(None))
+            || self.request_view.pending;
+        let live_tab = matches!(
+            self.active_tab,
+            Tab::Dashboard | Tab::Monitor | Tab::Logs | Tab::Terminal
+        ) || !self.detached_tabs.is_empty();
+
+        let interval = if minimized {
+            REPAINT_MINIMIZED
+        } else if busy {
+            REPAINT_BUSY
+        } else if live_tab && focused {
+            REPAINT_LIVE
+        } else {
+            REPAINT_IDLE
+        };
+        ctx.request_repaint_after(interval);
+    }
+
+    fn remember_event(&mut self, event: &DockerEvent) {
+        let summary = match event {
+            DockerEvent::Log(_) => return, // Already in the log buffer
+            DockerEvent::ContainerList(list) => format!("ContainerList({} containers)", list.len()),
+            DockerEvent::ContainerReport(container, _) => format!("ContainerReport({})", container),
+            other => {
+                let mut text = format!("{:?}", other);
+                if let Some((cut, _)) = text.char_indices().nth(500) {
+                    text.truncate(cut);
+                    text.push('…');
+                }
+                text
+            }
+        };
+        if self.recent_events.len() == RECENT_EVENTS {
+            self.recent_events.pop_front();
+        }
+        let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        self.recent_events
+            .push_back(format!("{} {}", time, summary));
+    }
+
+    /// Files only the UI knows about; the Docker side is gathered by the manager.
+    fn diagnostics_files(&mut self) -> Vec<(String, String)> {
+        let system = format!(
+            "DockStack {}\nPlatform: {} {}\nDocker available: {}\nClient: {}\nServer: {}\n\
+             API: {}\nEngine platform: {}\nRootless: {}\nStatus: {:?}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.engine.available,
+            self.engine.client_version,
+            self.engine.server_version,
+            self.engine.api_version,
+            self.engine.platform,
+            self.engine.rootless,
+            self.docker.status.lock().unwrap_or_else(|e| e.into_inner()),
+        );
+        let events: Vec<_> = self.recent_events.iter().cloned().collect();
+        let history: Vec<_> = self
+            .audit
+            .entries()
+            .iter()
+            .map(|e| {
+                let time = e.time().map(|t| t.to_rfc3339()).unwrap_or_default();
+                format!("{} [{}] {}: {}", time, e.user, e.project, e.action)
+            })
+            .collect();
+        let app_log: Vec<_> = logging::snapshot(None)
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        vec![
+            ("system.txt".to_string(), system),
+            ("app-log.txt".to_string(), app_log.join("\n")),
+            ("recent-events.txt".to_string(), events.join("\n")),
+            ("history.txt".to_string(), history.join("\n")),
+        ]
+    }
+
+    /// Rename a service of the active project and queue its old container, and
+    /// its data when `copy_data` is set, for migration on the next start.
+    fn rename_service(&mut self, from: &str, to: &str, copy_data: bool) {
+        let Some(project) = self.config.active_project_mut() else {
+            return;
+        };
+        let container = compose::container_name(project, from);
+        let before = compose::service_volumes(project, from);
+        let Some(to) = project.rename_service(from, to) else {
+            self.last_error = Some(DockStackError::Config(format!(
+                "Cannot rename {} to \"{}\": the name is empty or already taken.",
+                from, to
+            )));
+            return;
+        };
+        let volumes = if copy_data {
+            before
+                .into_iter()
+                .zip(compose::service_volumes(project, &to))
+                .filter(|(from, to)| from != to)
+                .map(|(from, to)| VolumeCopy { from, to })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        project
+            .pending_renames
+            .push(ServiceRename { container, volumes });
+        self.docker.regenerate_compose(project, false);
+        self.config.save();
+        self.audit_active(format!("Renamed service {} to {}", from, to));
+    }
+
+    /// Record an action against the active project in the History log.
+    fn audit_active(&mut self, action: impl Into<String>) {
+        let project = self.config.active_project().map(|p| p.name.as_str());
+        self.audit.record(project.unwrap_or_default(), action);
+    }
+
+    fn apply_doctor_fix(&mut self, fix: DoctorFix) {
+        match fix {
+            DoctorFix::CreateDirectory(dir) => {
+                let tx = self.docker.event_tx.clone();
+                self.runtime.handle().spawn_blocking(move || {
+                    if let Err(e) = std::fs::create_dir_all(&dir) {
+                        let context = format!("Failed to create {}", dir);
+                        tx.send(DockerEvent::Error(DockStackError::io(context, &e)))
+                            .ok();
+                    }
+                });
+            }
+            DoctorFix::RegenerateFiles => {
+                if let Some(project) = self.config.active_project() {
+                    self.docker.regenerate_compose(project, true);
+                }
+            }
+            DoctorFix::UsePort { service, port } => {
+                if let Some(project) = self.config.active_project_mut() {
+                    project.set_host_port(&service, port);
+                    self.config.save();
+                }
+            }
+            DoctorFix::UsePorts(ports) => {
+                if let Some(project) = self.config.active_project_mut() {
+                    for (service, port) in ports {
+                        project.set_host_port(&service, port);
+                    }
+                    self.config.save();
+                }
+            }
+            DoctorFix::AllowPortsFrom(port) => {
+                let tx = self.docker.event_tx.clone();
+                self.runtime.handle().spawn_blocking(move || {
+                    match doctor::allow_ports_from(port) {
+                        Ok(()) => log::info!(
+                            "Unprivileged users may now bind ports from {}; run the doctor again",
+                            port
+                        ),
+                        Err(e) => {
+                            let context = format!("Could not allow ports from {}", port);
+                            tx.send(DockerEvent::Error(DockStackError::io(context, &e)))
+                                .ok();
+                        }
+                    }
+                });
+            }
+            DoctorFix::UpdateGitignore => self.commands.send(AppCommand::UpdateGitignore),
+            DoctorFix::GenerateCertificate => self.commands.send(AppCommand::GenerateSsl),
+            DoctorFix::RemoveOrphans(orphans) => {
+                self.commands.send(AppCommand::RemoveOrphans(orphans))
+            }
+        }
+    }
+
+    /// Give the tray's Active Project menu the current projects, if they changed.
+    fn sync_tray_projects(&mut self) {
+        let tray_projects = TrayProjects {
+            projects: self
+                .config
+                .projects_by_recency()
+                .iter()
+                .map(|p| (p.id.clone(), p.display_name()))
+                .collect(),
+            active: self.config.active_project_id.clone(),
+        };
+        if self.tray_projects.as_ref() != Some(&tray_projects) {
+            self.tray
+                .set_projects(&tray_projects.projects, tray_projects.active.as_deref());
+            self.tray_projects = Some(tray_projects);
+        }
+    }
+
+    fn known_project_ids(&self) -> Vec<String> {
+        self.config.projects.iter().map(|p| p.id.clone()).collect()
+    }
+
+    fn project_facts(&self) -> panels::ProjectFacts {
+        let Some(project) = self.config.active_project() else {
+            return panels::ProjectFacts::default();
+        };
+        panels::ProjectFacts {
+            critical: self
+                .scan_reports
+                .get(&project.id)
+                .map(ScanReport::critical_services)
+                .unwrap_or_default(),
+            code_mounts: compose::code_mount_services(project),
+            git_repo: compose::is_git_repo(project),
+            missing_gitignore: compose::missing_gitignore_entries(project),
+            branch: project.git_branch(),
+            image_lock: project.lock_images.then(|| ImageLock::load(project)),
+        }
+    }
+
+    fn render_directory_confirm(&mut self, ctx: &egui::Context) {
+        let Some((project_id, reason, action)) = &self.pending_directory_confirm else {
+            return;
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(egui::Id::new("confirm_project_directory")).show(ctx, |ui| {
+            ui.set_max_width(460.0);
+            ui.label(
+                RichText::new("⚠ Confirm Project Directory")
+                    .size(18.0)
+                    .strong()
+                    .color(theme::COLOR_WARNING),
+            );
+            ui.add_space(8.0);
+            ui.label(RichText::new(reason).color(theme::COLOR_TEXT_DIM));
+            ui.add_space(16.0);
+            ui.horizontal(|ui| {
+                let cancel = ui.button("Cancel");
+                autofocus(ui, &cancel);
+                if cancel.clicked() {
+                    cancelled = true;
+                }
+                if ui
+                    .button(RichText::new("Continue").color(theme::COLOR_WARNING))
+                    .clicked()
+                {
+                    confirmed = true;
+                }
+            });
+        });
+
+        if confirmed {
+            self.commands
+                .send(AppCommand::ConfirmDirectory(project_id.clone(), *action));
+            self.pending_directory_confirm = None;
+        } else if cancelled || modal.should_close() {
+            self.pending_directory_confirm = None;
+        }
+    }
+
+    /// `down -v` deletes databases and uploads, so the project name must be typed first.
+    fn render_destroy_confirm(&mut self, ctx: &egui::Context) {
+        let Some(typed) = &mut self.destroy_confirm else {
+            return;
+        };
+        let Some(project) = self.config.active_project() else {
+            self.destroy_confirm = None;
+            return;
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(egui::Id::new("confirm_destroy_data")).show(ctx, |ui| {
+            ui.set_max_width(460.0);
+            ui.label(
+                RichText::new("⚠ Destroy Project Data")
+                    .size(18.0)
+                    .strong()
+                    .color(theme::COLOR_ERROR),
+            );
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new(
+                    "This removes the containers, networks and every volume of the stack. \
+                     Database contents and other stored data are deleted permanently.",
+                )
+                .color(theme::COLOR_TEXT_DIM),
+            );
+            ui.add_space(12.0);
+            ui.label(format!("Type {} to confirm:", project.name));
+            let input = ui.add(egui::TextEdit::singleline(typed).hint_text(&project.name));
+            autofocus(ui, &input);
+            ui.add_space(16.0);
+            ui.horizontal(|ui| {
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+                let matches = typed.trim() == project.name;
+                if ui
+                    .add_enabled(
+                        matches,
+                        egui::Button::new(RichText::new("💣 Destroy").color(theme::COLOR_ERROR)),
+                    )
+                    .clicked()
+                {
+                    confirmed = true;
+                }
+            });
+        });
+
+        if confirmed {
+            self.commands.send(AppCommand::StopStack(StopMode::Destroy));
+            self.destroy_confirm = None;
+        } else if cancelled || modal.should_close() {
+            self.destroy_confirm = None;
+        }
+    }
+
+    fn render_compose_issue(&mut self, ctx: &egui::Context) {
+        let Some(issue) = &self.compose_issue else {
+            return;
+        };
+
+        let mut close = false;
+        let modal = egui::Modal::new(egui::Id::new("compose_validation")).show(ctx, |ui| {
+            ui.set_max_width(640.0);
+            ui.label(
+                RichText::new("✘ Compose File Is Invalid")
+                    .size(18.0)
+                    .strong()
+                    .color(theme::COLOR_ERROR),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                RichText::new("docker compose config rejected the generated file, so no containers were started.")
+                    .color(theme::COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            ui.label(RichText::new(&issue.message).monospace().color(theme::COLOR_TEXT));
+
+            if !issue.excerpt.is_empty() {
+                ui.add_space(8.0);
+                egui::Frame::new()
+                    .fill(theme::COLOR_BG_PANEL)
+                    .corner_radius(egui::CornerRadius::same(6))
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        for (number, text) in &issue.excerpt {
+                            let color = if Some(*number) == issue.line {
+                                theme::COLOR_ERROR
+                            } else {
+                                theme::COLOR_TEXT_DIM
+                            };
+                            ui.label(
+                                RichText::new(format!("{:>4} │ {}", number, text))
+                                    .monospace()
+                                    .size(12.0)
+                                    .color(color),
+                            );
+                        }
+                    });
+            }
+
+            ui.add_space(4.0);
+            ui.label(
+                RichText::new(&issue.path)
+                    .size(11.0)
+                    .color(theme::COLOR_TEXT_MUTED),
+            );
+            ui.add_space(12.0);
+            ui.horizontal(|ui| {
+                if ui.button("📄 Open Compose File").clicked() {
+                    crate::utils::open_url(&issue.path);
                 }
-                DockerEvent::Error(e) => {
-                    log::error!("Docker error: {}", e);
+                let close_button = ui.button("Close");
+                autofocus(ui, &close_button);
+                if close_button.clicked() {
+                    close = true;
                 }
+            });
+        });
+
+        if close || modal.should_close() {
+            self.compose_issue = None;
+        }
+    }
+
+    fn render_image_browser(&mut self, ctx: &egui::Context) {
+        if !self.image_browser.open {
+            return;
+        }
+        let mut actions = panels::ImageBrowserActions::default();
+        panels::render_image_browser(ctx, &mut self.image_browser, &mut actions);
+        if let Some(query) = actions.search {
+            self.hub.search(&query);
+        }
+        let Some((name, svc)) = actions.create else {
+            return;
+        };
+        let Some(project) = self.config.active_project_mut() else {
+            return;
+        };
+        let image = svc.image.clone().unwrap_or_default();
+        let unique = project.add_custom_service(&name, svc);
+        self.config.save();
+        self.audit_active(format!("Added {} from Docker Hub image {}", unique, image));
+    }
+
+    fn render_service_catalog(&mut self, ctx: &egui::Context) {
+        if !self.catalog_view.open {
+            return;
+        }
+        let mut actions = panels::CatalogActions::default();
+        panels::render_service_catalog(
+            ctx,
+            &self.config,
+            &self.catalog,
+            &mut self.catalog_view,
+            &mut actions,
+        );
+        let Some((id, entry)) = actions.add else {
+            return;
+        };
+        let Some(project) = self.config.projects.iter_mut().find(|p| p.id == id) else {
+            return;
+        };
+        let svc = entry.service(PortScanner::find_available_port(entry.port));
+        let name = project.add_custom_service(&entry.id, svc);
+        for required in &entry.requires {
+            if let Some(svc) = project.services.get_mut(required) {
+                svc.enabled = true;
             }
         }
+        let project_name = project.name.clone();
+        self.config.save();
+        self.audit.record(
+            &project_name,
+            format!("Added {} from the service catalog", name),
+        );
     }
 
-    fn process_monitor_events(&mut self) {
-        while let Ok(event) = self.monitor.event_rx.try_recv() {
-            match event {
-                MonitorEvent::SystemUpdate(stats) => {
-                    self.sys_stats = stats;
-                    self.cpu_history = self.monitor.cpu_history.lock().unwrap_or_else(|e| e.into_inner()).clone();
-                    self.mem_history = self.monitor.mem_history.lock().unwrap_or_else(|e| e.into_inner()).clone();
-                }
-                MonitorEvent::ContainerUpdate(stats) => {
-                    self.container_stats = stats;
+    fn render_doctor(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.doctor else {
+            return;
+        };
+
+        let mut actions = panels::DoctorActions::default();
+        let mut close = false;
+        let modal = egui::Modal::new(egui::Id::new("doctor_report")).show(ctx, |ui| {
+            ui.set_max_width(720.0);
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new("🩺 Doctor")
+                        .size(18.0)
+                        .strong()
+                        .color(theme::COLOR_ACCENT),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let close_button = ui.button("Close");
+                    autofocus(ui, &close_button);
+                    if close_button.clicked() {
+                        close = true;
+                    }
+                });
+            });
+            ui.add_space(8.0);
+            match report {
+                Some(report) => panels::render_doctor_report(ui, report, &mut actions),
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(RichText::new("Running checks…").color(theme::COLOR_TEXT_DIM));
+                    });
                 }
             }
+        });
+
+        if let Some(fix) = actions.fix {
+            self.commands.send(AppCommand::ApplyDoctorFix(fix));
+        }
+        if actions.rerun {
+            self.commands.send(AppCommand::RunDoctor);
+        }
+        if let Some(dest) = actions.save_report {
+            self.commands.send(AppCommand::SaveDoctorReport(dest));
+        }
+        if close || modal.should_close() {
+            self.doctor = None;
         }
     }
 
-    fn process_terminal_events(&mut self) {
-        while let Ok(_event) = self.terminal.event_rx.try_recv() {
-            // Events are already stored in terminal.output_lines
+    fn render_import_preview(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &mut self.import_preview else {
+            return;
+        };
+        let open = panels::render_import_preview(ctx, preview);
+        if preview.confirmed {
+            if let Some(preview) = self.import_preview.take() {
+                let custom_ports = preview.custom_ports();
+                self.config
+                    .add_imported_project(preview.import, custom_ports);
+                self.commands.send(AppCommand::SaveConfig);
+            }
+        } else if !open {
+            self.import_preview = None;
         }
     }
 
-    fn process_tray_events(&mut self, ctx: &egui::Context) {
-        while let Ok(cmd) = self.tray.command_rx.try_recv() {
-            match cmd {
-                TrayCommand::Start => {
-                    if let Some(project) = self.config.active_project() {
-                        self.docker.start_services(project);
-                    }
-                }
-                TrayCommand::Stop => {
-                    if let Some(project) = self.config.active_project() {
-                        self.docker.stop_services(project);
+    fn render_image_scan(&mut self, ctx: &egui::Context) {
+        let Some(shown) = &self.image_scan else {
+            return;
+        };
+        let report = shown.as_ref().and_then(|id| self.scan_reports.get(id));
+
+        let mut rescan = false;
+        let mut close = false;
+        let modal = egui::Modal::new(egui::Id::new("image_scan")).show(ctx, |ui| {
+            ui.set_max_width(860.0);
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new("🛡 Image Scan")
+                        .size(18.0)
+                        .strong()
+                        .color(theme::COLOR_ACCENT),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let close_button = ui.button("Close");
+                    autofocus(ui, &close_button);
+                    if close_button.clicked() {
+                        close = true;
                     }
-                }
-                TrayCommand::Restart => {
-                    if let Some(project) = self.config.active_project() {
-                        self.docker.restart_services(project);
+                    if report.is_some() && ui.button("🔄 Scan Again").clicked() {
+                        rescan = true;
                     }
+                });
+            });
+            ui.add_space(8.0);
+            match report {
+                Some(report) => {
+                    panels::render_scan_report(ui, report, &mut self.scan_severity);
                 }
-                TrayCommand::OpenUI => {
-                    // Window focus is handled by the framework
-                }
-                TrayCommand::Quit => {
-                    log::info!("Quit requested from system tray, initiating graceful shutdown...");
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(
+                            RichText::new(
+                                "Scanning images… the first scan downloads the vulnerability \
+                                 database.",
+                            )
+                            .color(theme::COLOR_TEXT_DIM),
+                        );
+                    });
                 }
             }
+        });
+
+        if rescan {
+            self.commands.send(AppCommand::ScanImages);
+        }
+        if close || modal.should_close() {
+            self.image_scan = None;
         }
     }
 
+    fn render_status_bar(&mut self, ui: &mut egui::Ui) {
+        let small = |text: String, color| egui::RichText::new(text).size(11.0).color(color);
+        ui.horizontal(|ui| {
+            let (engine, color, kind) = if self.engine.available {
+                (
+                    "Docker Engine: Online",
+                    theme::COLOR_SUCCESS,
+                    StatusKind::Ok,
+                )
+            } else {
+                (
+                    "Docker Engine: Offline",
+                    theme::COLOR_ERROR,
+                    StatusKind::Error,
+                )
+            };
+            status_icon(ui, kind, engine);
+            ui.label(small(engine.to_string(), color));
+            ui.add_space(12.0);
+            ui.separator();
+            ui.add_space(12.0);
+
+            let project = self.config.active_project().map(|p| p.display_name());
+            ui.label(small(
+                project.unwrap_or("No project".to_string()),
+                theme::COLOR_TEXT_DIM,
+            ));
+            let running = self
+                .docker
+                .containers
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .filter(|c| c.state == "running")
+                .count();
+            ui.label(small(
+                format!("{} running", running),
+                if running > 0 {
+                    theme::COLOR_SUCCESS
+                } else {
+                    theme::COLOR_TEXT_MUTED
+                },
+            ));
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let spark = Vec2::new(60.0, 14.0);
+                ui.label(small(
+                    format!(
+                        "MEM: {:.1} GB",
+                        self.sys_stats.memory_used as f32 / 1024.0 / 1024.0 / 1024.0
+                    ),
+                    theme::COLOR_TEXT_DIM,
+                ));
+                sparkline(
+                    ui,
+                    self.mem_history.make_contiguous(),
+                    100.0,
+                    theme::COLOR_SECONDARY,
+                    spark,
+                );
+                ui.add_space(16.0);
+                ui.label(small(
+                    format!("CPU: {:.1}%", self.sys_stats.cpu_usage),
+                    theme::COLOR_TEXT_DIM,
+                ));
+                sparkline(
+                    ui,
+                    self.cpu_history.make_contiguous(),
+                    100.0,
+                    theme::COLOR_PRIMARY,
+                    spark,
+                );
+            });
+        });
+    }
+
     fn render_header(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             // Title based on active tab
             let (icon, title) = match self.active_tab {
                 Tab::Dashboard => ("🏠", "System Overview"),
+                Tab::Projects => ("🗂", "All Projects"),
                 Tab::Services => ("📦", "Service Stack"),
                 Tab::Containers => ("🐳", "Docker Containers"),
                 Tab::Logs => ("📋", "System Logs"),
+                Tab::History => ("🕘", "Activity History"),
                 Tab::Terminal => ("💻", "Interactive Console"),
                 Tab::Ports => ("🔌", "Port Checker"),
                 Tab::Monitor => ("📊", "Live Analytics"),
+                Tab::Requests => ("🌐", "API Client"),
                 Tab::Settings => ("⚙️", "Settings"),
             };
             ui.horizontal(|ui| {
@@ -193,8 +1818,14 @@ impl DockStackApp {
             // Global Actions (Right aligned)
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 let status = self.docker.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
-                let can_start = matches!(status, ServiceStatus::Stopped | ServiceStatus::Error(_));
-                let can_stop = matches!(status, ServiceStatus::Running);
+                let can_start = self.engine.available
+                    && matches!(status, ServiceStatus::Stopped | ServiceStatus::Error(_));
+                let can_stop = self.engine.available && matches!(status, ServiceStatus::Running);
+                let can_down = self.engine.available
+                    && matches!(
+                        status,
+                        ServiceStatus::Running | ServiceStatus::Stopped | ServiceStatus::Error(_)
+                    );
 
                 // Start All Button - More Prominent
                 ui.add_enabled_ui(can_start, |ui| {
@@ -208,9 +1839,7 @@ impl DockStackApp {
                     .min_size(Vec2::new(140.0, 42.0));
 
                     if ui.add(btn).clicked() {
-                        if let Some(project) = self.config.active_project() {
-                            self.docker.start_services(project);
-                        }
+                        self.commands.send(AppCommand::StartStack);
                     }
                 });
 
@@ -229,9 +1858,7 @@ impl DockStackApp {
                         )
                         .clicked()
                     {
-                        if let Some(project) = self.config.active_project() {
-                            self.docker.restart_services(project);
-                        }
+                        self.commands.send(AppCommand::RestartStack);
                     }
                 });
 
@@ -245,48 +1872,316 @@ impl DockStackApp {
                                 .stroke(egui::Stroke::new(1.0, theme::COLOR_BORDER))
                                 .min_size(Vec2::new(80.0, 42.0)),
                         )
+                        .on_hover_text("Stop the containers; they are kept for a quick restart")
                         .clicked()
                     {
-                        if let Some(project) = self.config.active_project() {
-                            self.docker.stop_services(project);
-                        }
+                        self.commands.send(AppCommand::StopStack(StopMode::Stop));
                     }
                 });
+
+                ui.add_space(8.0);
+
+                ui.add_enabled_ui(can_down, |ui| {
+                    ui.menu_button(RichText::new("⏏").size(18.0), |ui| {
+                        if ui
+                            .button("⏏ Down")
+                            .on_hover_text("Remove containers and networks; volumes are kept")
+                            .clicked()
+                        {
+                            self.commands.send(AppCommand::StopStack(StopMode::Down));
+                            ui.close_menu();
+                        }
+                        if ui
+                            .button(RichText::new("💣 Destroy Data…").color(theme::COLOR_ERROR))
+                            .on_hover_text("Remove containers, networks and volumes (down -v)")
+                            .clicked()
+                        {
+                            self.destroy_confirm = Some(String::new());
+                            ui.close_menu();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Remove containers");
+                });
             });
         });
         ui.add_space(20.0);
         ui.separator();
         ui.add_space(20.0);
     }
+
+    /// Logs or Monitor in the main window, or a note while it has its own window.
+    fn render_detachable_tab(&mut self, ui: &mut egui::Ui, tab: Tab) {
+        if self.detached_tabs.contains(&tab) {
+            let mut bring_back = false;
+            panels::render_detached_notice(ui, detached_title(tab), &mut bring_back);
+            if bring_back {
+                self.detached_tabs.retain(|t| *t != tab);
+            }
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .button("⧉ Open in Window")
+                    .on_hover_text("Move this tab to its own window, e.g. on a second monitor")
+                    .clicked()
+                {
+                    self.detached_tabs.push(tab);
+                    self.active_tab = Tab::Services;
+                }
+            });
+        });
+        self.render_tab_content(ui, tab);
+    }
+
+    /// Switch to `tab`, or raise its window when it has one.
+    fn show_tab(&mut self, ctx: &egui::Context, tab: Tab) {
+        if self.detached_tabs.contains(&tab) {
+            ctx.send_viewport_cmd_to(detached_viewport(tab), egui::ViewportCommand::Focus);
+        } else {
+            self.active_tab = tab;
+        }
+    }
+
+    fn render_tab_content(&mut self, ui: &mut egui::Ui, tab: Tab) {
+        match tab {
+            Tab::Logs => self.render_logs_tab(ui),
+            _ => self.render_monitor_tab(ui),
+        }
+    }
+
+    /// Tabs moved to their own windows, each in a viewport of its own. Closing
+    /// the window puts the tab back in the main window.
+    fn render_detached_tabs(&mut self, ctx: &egui::Context) {
+        let mut i = 0;
+        while let Some(&tab) = self.detached_tabs.get(i) {
+            let title = detached_window_title(tab);
+            let builder = egui::ViewportBuilder::default()
+                .with_title(title)
+                .with_inner_size([960.0, 720.0])
+                .with_min_inner_size([480.0, 360.0]);
+            let closed =
+                ctx.show_viewport_immediate(detached_viewport(tab), builder, |ctx, class| {
+                    if class == egui::ViewportClass::Embedded {
+                        // The backend cannot open windows: float it in the main one
+                        let mut open = true;
+                        egui::Window::new(title)
+                            .open(&mut open)
+                            .default_size([720.0, 540.0])
+                            .show(ctx, |ui| {
+                                ScrollArea::vertical()
+                                    .show(ui, |ui| self.render_tab_content(ui, tab))
+                            });
+                        return !open;
+                    }
+                    egui::CentralPanel::default()
+                        .frame(
+                            egui::Frame::new()
+                                .fill(theme::COLOR_BG_APP)
+                                .inner_margin(egui::Margin::symmetric(24, 16)),
+                        )
+                        .show(ctx, |ui| {
+                            ScrollArea::vertical()
+                                .auto_shrink([false; 2])
+                                .show(ui, |ui| self.render_tab_content(ui, tab));
+                        });
+                    ctx.input(|i| i.viewport().close_requested())
+                });
+            if closed {
+                self.detached_tabs.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn render_logs_tab(&mut self, ui: &mut egui::Ui) {
+        self.render_log_tabs(ui);
+        if self.log_view.app_log {
+            let shown = (logging::generation(), self.log_view.app_log_level);
+            if self.app_log_shown != Some(shown) {
+                self.app_log = logging::snapshot(self.log_view.app_log_level)
+                    .iter()
+                    .map(|record| (record.to_string(), record.level))
+                    .collect();
+                self.app_log_shown = Some(shown);
+            }
+            let mut clear = false;
+            panels::render_app_log(ui, &self.app_log, &mut self.log_view, &mut clear);
+            if clear {
+                logging::clear();
+            }
+        } else if self.log_view.attached.is_some() {
+            let mut attached = self
+                .docker
+                .attached
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let output = attached
+                .iter_mut()
+                .find(|a| self.log_view.attached.as_ref() == Some(&a.container));
+            match output {
+                Some(output) => {
+                    let mut clear = false;
+                    panels::render_attached_output(ui, output, &mut self.log_view, &mut clear);
+                    if clear {
+                        output.lines.clear();
+                    }
+                }
+                None => self.log_view.attached = None,
+            }
+        } else {
+            let mut clear = false;
+            let mut sources_changed = false;
+            let mut logs_guard = self.docker.logs.lock().unwrap_or_else(|e| e.into_inner());
+            panels::render_logs(
+                ui,
+                logs_guard.make_contiguous(),
+                self.config.active_project_mut().map(|p| &mut p.log_files),
+                &mut self.log_view,
+                &mut clear,
+                &mut sources_changed,
+            );
+            if clear {
+                logs_guard.clear();
+            }
+            if sources_changed {
+                self.commands.send(AppCommand::SaveConfig);
+            }
+        }
+    }
+
+    fn render_monitor_tab(&mut self, ui: &mut egui::Ui) {
+        let mut export = None;
+        panels::render_monitor(
+            ui,
+            &self.sys_stats,
+            &self.container_stats,
+            self.cpu_history.make_contiguous(),
+            self.mem_history.make_contiguous(),
+            &mut export,
+        );
+        if let Some(path) = export {
+            self.commands.send(AppCommand::ExportMetrics(path));
+        }
+    }
+
+    /// The Logs tab's sub-tabs, including one per attached container.
+    fn render_log_tabs(&mut self, ui: &mut egui::Ui) {
+        let mut detach = None;
+        {
+            let attached = self
+                .docker
+                .attached
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            panels::render_log_tabs(ui, &mut self.log_view, &attached, &mut detach);
+        }
+        if let Some(container) = detach {
+            self.commands.send(AppCommand::DetachContainer(container));
+        }
+    }
 }
 
 impl eframe::App for DockStackApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Request continuous repaint for animations and monitoring
-        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        self.schedule_repaint(ctx);
 
         // Process events
-        self.process_docker_events();
+        self.process_docker_events(ctx);
         self.process_monitor_events();
         self.process_terminal_events();
-        self.process_tray_events(ctx);
+        self.process_http_events();
+        self.process_hub_events();
+        self.process_hook_events();
+        self.process_git_events();
+        self.process_tray_events();
+
+        self.render_directory_confirm(ctx);
+        self.render_compose_issue(ctx);
+        self.render_destroy_confirm(ctx);
+        self.render_doctor(ctx);
+        self.render_image_scan(ctx);
+        self.render_import_preview(ctx);
+        self.render_image_browser(ctx);
+        self.render_service_catalog(ctx);
+        self.render_detached_tabs(ctx);
 
         // Init tray (only once)
         if !self.tray_initialized {
-            if let Err(e) = self.tray.setup() {
+            if let Err(e) = self.tray.setup(ctx) {
                 log::error!("Failed to initialize system tray: {}", e);
+                if let Some(hint) = e.hint() {
+                    log::info!("{}", hint);
+                }
             }
             self.tray_initialized = true;
         }
 
+        // Keep the window title and tray naming the active project
+        let label = self.config.active_project().map(|p| p.display_name());
+        if self.shown_project.as_ref() != Some(&label) {
+            let title = match &label {
+                Some(label) => format!("{} - DockStack", label),
+                None => "DockStack - DevStack Manager".to_string(),
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+            self.tray.set_project(label.as_deref());
+            self.shown_project = Some(label);
+            self.status_page_stale = true;
+            self.facts_stale = true;
+        }
+        if self.facts_stale {
+            self.facts_stale = false;
+            self.project_facts = self.project_facts();
+            self.sync_tray_projects();
+        } else if Instant::now() >= self.next_branch_check {
+            self.next_branch_check = Instant::now() + BRANCH_CHECK_INTERVAL;
+            self.project_facts.branch = self.config.active_project().and_then(|p| p.git_branch());
+        }
+        // Notice the daemon starting or stopping
+        if Instant::now() >= self.next_docker_check {
+            // Pushed out again once the check reports back
+            self.next_docker_check = Instant::now() + DOCKER_HEALTH_INTERVAL;
+            self.commands.send(AppCommand::CheckDocker);
+        }
+
+        // Opt-in cleanup, only while the engine is reachable
+        let now = chrono::Utc::now().timestamp();
+        if self.engine.available && self.config.maintenance.is_due(now) {
+            self.commands.send(AppCommand::RunMaintenance);
+        }
+
         // Periodic container refresh
         if self.last_container_refresh.elapsed().as_secs() >= 3 {
-            if let Some(project) = self.config.active_project() {
-                self.docker.refresh_containers(project);
-            }
+            self.commands.send(AppCommand::RefreshContainers);
             self.last_container_refresh = Instant::now();
         }
 
+        if let Some(project) = self.config.active_project() {
+            self.log_watcher.sync(project);
+        }
+
+        // Rebuilt only when something it shows changed; the periodic container
+        // refresh picks up edited services within a few seconds
+        if self.status_page_stale && self.status_page.is_serving() {
+            self.status_page_stale = false;
+            let status = self
+                .docker
+                .status
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            let containers = self
+                .docker
+                .containers
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let snapshot = StatusSnapshot::new(self.config.active_project(), &status, &containers);
+            self.status_page.update(snapshot);
+        }
+
         // Bottom status bar (integrated with background)
         egui::TopBottomPanel::bottom("status_bar")
             .max_height(32.0)
@@ -295,40 +2190,7 @@ impl eframe::App for DockStackApp {
                     .fill(theme::COLOR_BG_APP)
                     .inner_margin(egui::Margin::symmetric(16, 4)),
             )
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label(
-                        egui::RichText::new("DockStack Native")
-                            .size(11.0)
-                            .color(theme::COLOR_TEXT_MUTED),
-                    );
-                    ui.add_space(12.0);
-                    ui.separator();
-                    ui.add_space(12.0);
-                    ui.label(
-                        egui::RichText::new("Docker Engine: Online")
-                            .size(11.0)
-                            .color(theme::COLOR_SUCCESS),
-                    );
-
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        ui.label(
-                            egui::RichText::new(format!(
-                                "MEM: {:.1} GB",
-                                self.sys_stats.memory_used as f32 / 1024.0 / 1024.0 / 1024.0
-                            ))
-                            .size(11.0)
-                            .color(theme::COLOR_TEXT_DIM),
-                        );
-                        ui.add_space(16.0);
-                        ui.label(
-                            egui::RichText::new(format!("CPU: {:.1}%", self.sys_stats.cpu_usage))
-                                .size(11.0)
-                                .color(theme::COLOR_TEXT_DIM),
-                        );
-                    });
-                });
-            });
+            .show(ctx, |ui| self.render_status_bar(ui));
 
         // Permanent Slim Sidebar
         egui::SidePanel::left("sidebar")
@@ -343,7 +2205,23 @@ impl eframe::App for DockStackApp {
             )
             .show(ctx, |ui| {
                 let status = self.docker.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
-                panels::render_sidebar(ui, &mut self.active_tab, &mut self.config, &status);
+                let mut actions = panels::SidebarActions::default();
+                panels::render_sidebar(
+                    ui,
+                    &mut self.active_tab,
+                    &mut self.config,
+                    &status,
+                    &mut actions,
+                );
+                if actions.save_config {
+                    self.commands.send(AppCommand::SaveConfig);
+                }
+                if let Some(path) = actions.open_codebase {
+                    self.commands.send(AppCommand::OpenCodebase(path));
+                }
+                if let Some(path) = actions.import_compose {
+                    self.commands.send(AppCommand::ReadComposeImport(path));
+                }
             });
 
         // Modern Central Panel
@@ -360,72 +2238,226 @@ impl eframe::App for DockStackApp {
                                 // Integrated Header
                                 self.render_header(ui);
 
+                                if let Some(progress) = self
+                                    .docker
+                                    .start_progress
+                                    .lock()
+                                    .unwrap_or_else(|e| e.into_inner())
+                                    .as_ref()
+                                    .filter(|p| {
+                                        p.finished_at.is_none_or(|t| t.elapsed().as_secs() < 8)
+                                    })
+                                {
+                                    panels::render_start_progress(ui, progress);
+                                    ui.add_space(20.0);
+                                }
+
+                                if let Some(err) = &self.last_error {
+                                    let mut dismiss = false;
+                                    panels::render_error_banner(ui, err, &mut dismiss);
+                                    if dismiss {
+                                        self.last_error = None;
+                                    }
+                                }
+
                                 match self.active_tab {
                                     Tab::Dashboard => {
                                         let status = self.docker.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                                        let mut actions = panels::DashboardActions::default();
                                         panels::render_dashboard(
                                             ui,
                                             &mut self.config,
-                                            &status,
-                                            &self.sys_stats,
+                                            panels::Host {
+                                                stats: &self.sys_stats,
+                                                engine: &self.engine,
+                                            },
                                             &self.docker.containers.lock().unwrap_or_else(|e| e.into_inner()),
-                                            self.docker_available,
+                                            &self.container_stats,
+                                            &self.project_facts,
+                                            &mut actions,
                                         );
+                                        if actions.check_docker {
+                                            self.commands.send(AppCommand::CheckDocker);
+                                        }
+                                        if actions.start_docker {
+                                            self.commands.send(AppCommand::StartDockerDaemon);
+                                        }
+                                        if actions.save_config {
+                                            self.commands.send(AppCommand::SaveConfig);
+                                        }
+
+                                        let has_web = self
+                                            .config
+                                            .active_project()
+                                            .and_then(tunnel::web_port)
+                                            .is_some();
+                                        if self.engine.available && has_web {
+                                            let tunnel_status = self
+                                                .tunnel
+                                                .status
+                                                .lock()
+                                                .unwrap_or_else(|e| e.into_inner())
+                                                .clone();
+                                            let mut share = None;
+                                            panels::render_tunnel(
+                                                ui,
+                                                &tunnel_status,
+                                                self.tunnel.ngrok_available,
+                                                status == ServiceStatus::Running,
+                                                &mut share,
+                                            );
+                                            match share {
+                                                Some(true) => {
+                                                    self.commands.send(AppCommand::StartTunnel)
+                                                }
+                                                Some(false) => {
+                                                    self.commands.send(AppCommand::StopTunnel)
+                                                }
+                                                None => {}
+                                            }
+                                        }
                                     }
 
+                                    Tab::Projects => {
+                                        let status = self
+                                            .docker
+                                            .status
+                                            .lock()
+                                            .unwrap_or_else(|e| e.into_inner())
+                                            .clone();
+                                        let mut actions = panels::ProjectsActions::default();
+                                        panels::render_projects_overview(
+                                            ui,
+                                            &self.config,
+                                            &self.fleet,
+                                            &self.container_stats,
+                                            &status,
+                                            &mut actions,
+                                        );
+                                        if let Some(id) = actions.open {
+                                            self.commands.send(AppCommand::SelectProject(id));
+                                            self.active_tab = Tab::Dashboard;
+                                        }
+                                        if let Some(id) = actions.start {
+                                            self.commands
+                                                .send(AppCommand::SetProjectRunning(id, true));
+                                        }
+                                        if let Some(id) = actions.stop {
+                                            self.commands
+                                                .send(AppCommand::SetProjectRunning(id, false));
+                                        }
+                                    }
                                     Tab::Services => {
+                                        let mut actions = panels::ServicesActions::default();
                                         panels::render_services(
                                             ui,
                                             &mut self.config,
                                             &self.docker.containers.lock().unwrap_or_else(|e| e.into_inner()),
+                                            &self.container_stats,
+                                            self.build_cache.as_ref(),
+                                            &self.project_facts,
+                                            &mut actions,
                                         );
+                                        if actions.scan_images {
+                                            self.commands.send(AppCommand::ScanImages);
+                                        }
+                                        if let Some(service) = actions.follow_logs {
+                                            self.commands
+                                                .send(AppCommand::FollowServiceLogs(service));
+                                        }
+                                        if actions.browse_images {
+                                            self.image_browser.open = true;
+                                        }
+                                        if actions.open_catalog {
+                                            self.catalog = catalog::load();
+                                            self.catalog_view.open = true;
+                                        }
+                                        if let Some(service) = actions.rebuild {
+                                            self.commands.send(AppCommand::RebuildImage(service));
+                                        }
+                                        if let Some((from, to, copy_data)) = actions.rename {
+                                            self.commands.send(AppCommand::RenameService {
+                                                from,
+                                                to,
+                                                copy_data,
+                                            });
+                                        }
+                                        if actions.check_build_cache {
+                                            self.commands.send(AppCommand::CheckBuildCache);
+                                        }
+                                        if actions.prune_build_cache {
+                                            self.commands.send(AppCommand::PruneBuildCache);
+                                        }
+                                        if actions.save_config {
+                                            self.commands.send(AppCommand::SaveConfig);
+                                        }
+                                        for (project, action) in actions.audit {
+                                            self.audit.record(&project, action);
+                                        }
                                     }
                                     Tab::Containers => {
+                                        let mut actions = panels::ContainersActions::default();
                                         panels::render_containers(
                                             ui,
                                             &self.docker.containers.lock().unwrap_or_else(|e| e.into_inner()),
+                                            self.copied_report.as_deref(),
+                                            &mut actions,
                                         );
+                                        if let Some(container) = actions.copy_report {
+                                            self.commands
+                                                .send(AppCommand::CopyContainerReport(container));
+                                        }
+                                        if let Some((container, label)) = actions.attach {
+                                            self.commands.send(AppCommand::AttachContainer {
+                                                container,
+                                                label,
+                                            });
+                                        }
                                     }
-                                    Tab::Logs => {
-                                        let mut clear = false;
-                                        let mut logs_guard = self.docker.logs.lock().unwrap_or_else(|e| e.into_inner());
-                                        panels::render_logs(
+                                    Tab::Logs | Tab::Monitor => {
+                                        self.render_detachable_tab(ui, self.active_tab);
+                                    }
+                                    Tab::History => {
+                                        let project =
+                                            self.config.active_project().map(|p| p.name.as_str());
+                                        let path = self.audit.path().clone();
+                                        panels::render_history(
                                             ui,
-                                            logs_guard.make_contiguous(),
-                                            &mut clear,
+                                            self.audit.entries(),
+                                            project,
+                                            &mut self.history_view,
+                                            &path,
                                         );
-                                        if clear {
-                                            logs_guard.clear();
-                                        }
                                     }
                                     Tab::Terminal => {
                                         let mut term_lines_guard =
                                             self.terminal.output_lines.lock().unwrap_or_else(|e| e.into_inner());
                                         let term_lines = term_lines_guard.make_contiguous();
-                                        let mut send = false;
-                                        let mut clear = false;
-                                        let mut start = false;
+                                        let mut actions = panels::TerminalActions::default();
                                         let term_running = self.terminal.is_running();
 
                                         panels::render_terminal(
                                             ui,
                                             term_lines,
                                             &mut self.terminal_input,
-                                            &mut send,
-                                            &mut clear,
-                                            &mut start,
                                             term_running,
+                                            &mut actions,
                                         );
 
-                                        if start && !term_running {
-                                            self.terminal.start();
+                                        if actions.start && !term_running {
+                                            self.commands.send(AppCommand::StartTerminal);
                                         }
-                                        if send && !self.terminal_input.is_empty() {
-                                            let input = self.terminal_input.clone();
-                                            self.terminal.send_input(&input);
-                                            self.terminal_input.clear();
+                                        if actions.send_input && !self.terminal_input.is_empty() {
+                                            let input = std::mem::take(&mut self.terminal_input);
+                                            self.commands
+                                                .send(AppCommand::SendTerminalInput(input));
                                         }
-                                        if clear {
+                                        if let Some(dest) = actions.save_transcript {
+                                            let transcript = terminal::transcript(term_lines);
+                                            self.commands
+                                                .send(AppCommand::SaveTranscript(dest, transcript));
+                                        }
+                                        if actions.clear {
                                             term_lines_guard.clear();
                                         }
                                     }
@@ -433,67 +2465,150 @@ impl eframe::App for DockStackApp {
                                         let mut scan = false;
                                         panels::render_ports(ui, &self.port_infos, &mut scan);
                                         if scan {
-                                            if let Some(project) = self.config.active_project() {
-                                                self.port_infos = PortScanner::scan_project_ports(
-                                                    &project.services,
-                                                );
-                                            } else {
-                                                self.port_infos = PortScanner::get_common_ports();
-                                            }
+                                            self.commands.send(AppCommand::ScanPorts);
                                         }
                                     }
-                                    Tab::Monitor => {
-                                        panels::render_monitor(
+                                    Tab::Requests => {
+                                        let mut send = false;
+                                        let mut collection_changed = false;
+                                        panels::render_requests(
                                             ui,
-                                            &self.sys_stats,
-                                            &self.container_stats,
-                                            self.cpu_history.make_contiguous(),
-                                            self.mem_history.make_contiguous(),
+                                            self.config.active_project_mut(),
+                                            &mut self.request_view,
+                                            &mut send,
+                                            &mut collection_changed,
                                         );
+                                        if send {
+                                            let request = self.request_view.draft.clone();
+                                            self.commands.send(AppCommand::SendRequest(request));
+                                        }
+                                        if collection_changed {
+                                            self.commands.send(AppCommand::SaveConfig);
+                                        }
                                     }
                                     Tab::Settings => {
-                                        let mut gen_ssl = false;
-                                        let mut rem_ssl = false;
+                                        let mut actions = panels::SettingsActions::default();
+                                        let stack_stopped = matches!(
+                                            *self
+                                                .docker
+                                                .status
+                                                .lock()
+                                                .unwrap_or_else(|e| e.into_inner()),
+                                            ServiceStatus::Stopped | ServiceStatus::Error(_)
+                                        );
                                         panels::render_settings(
                                             ui,
                                             &mut self.config,
-                                            &mut self.new_project_name,
-                                            &mut gen_ssl,
-                                            &mut rem_ssl,
+                                            &mut self.new_project,
+                                            &self.project_facts,
+                                            panels::CleanupScans {
+                                                orphans: self.orphans.as_deref(),
+                                                foreign: self.foreign_containers.as_deref(),
+                                            },
+                                            stack_stopped,
+                                            &mut actions,
                                         );
 
-                                        if gen_ssl {
-                                            if let Some(project) = self.config.active_project() {
-                                                match SslManager::generate_self_signed(
-                                                    &project.directory,
-                                                ) {
-                                                    Ok((cert, key)) => {
-                                                        log::info!(
-                                                            "SSL cert generated: {}, {}",
-                                                            cert,
-                                                            key
-                                                        );
-                                                    }
-                                                    Err(e) => {
-                                                        log::error!("SSL generation failed: {}", e);
-                                                    }
-                                                }
-                                            }
+                                        if actions.create_snapshot {
+                                            self.commands.send(AppCommand::CreateSnapshot);
                                         }
-                                        if rem_ssl {
-                                            if let Some(project) = self.config.active_project() {
-                                                if let Err(e) =
-                                                    SslManager::remove_certs(&project.directory)
-                                                {
-                                                    log::error!("SSL removal failed: {}", e);
+                                        if let Some(archive) = actions.restore_snapshot.take() {
+                                            self.commands
+                                                .send(AppCommand::RestoreSnapshot(archive));
+                                        }
+                                        if actions.scan_orphans || actions.remove_orphans {
+                                            self.commands.send(match self.orphans.clone() {
+                                                Some(orphans) if actions.remove_orphans => {
+                                                    AppCommand::RemoveOrphans(orphans)
                                                 }
-                                            }
+                                                _ => AppCommand::ScanOrphans,
+                                            });
+                                        }
+                                        if actions.discover_containers {
+                                            self.commands.send(AppCommand::DiscoverContainers);
+                                        }
+                                        if let Some(url) = actions.clone_repo.take() {
+                                            self.commands.send(AppCommand::CloneProject(url));
+                                        }
+                                        if let Some(names) = actions.capture.take() {
+                                            self.commands
+                                                .send(AppCommand::CaptureContainers(names));
+                                        }
+                                        if let Some(compose_file) = actions.adopt.take() {
+                                            self.commands.send(AppCommand::AdoptComposeProject(
+                                                compose_file,
+                                            ));
+                                        }
+                                        if actions.update_gitignore {
+                                            self.commands.send(AppCommand::UpdateGitignore);
+                                        }
+                                        if actions.run_doctor {
+                                            self.commands.send(AppCommand::RunDoctor);
+                                        }
+                                        if actions.pull_images {
+                                            self.commands.send(AppCommand::PullImages);
+                                        }
+                                        if let Some(dest) = actions.export_diagnostics.take() {
+                                            self.commands.send(AppCommand::ExportDiagnostics(dest));
+                                        }
+                                        if let Some(dest) = actions.export_kubernetes.take() {
+                                            self.commands.send(AppCommand::ExportKubernetes(dest));
+                                        }
+                                        if let Some((format, dest)) = actions.export_ci.take() {
+                                            self.commands.send(AppCommand::ExportCi(format, dest));
+                                        }
+                                        if actions.update_image_lock {
+                                            self.commands.send(AppCommand::UpdateImageLock);
+                                        }
+                                        if actions.write_devcontainer {
+                                            self.commands.send(AppCommand::WriteDevcontainer);
+                                        }
+                                        if actions.buffers_changed {
+                                            self.commands.send(AppCommand::ApplyBuffers);
+                                        }
+                                        if actions.proxy_changed {
+                                            self.commands.send(AppCommand::ApplyProxy);
+                                        }
+                                        if let Some(removed) = actions.remove_registry.take() {
+                                            self.commands.send(AppCommand::RemoveRegistry(removed));
+                                        }
+                                        if actions.run_maintenance {
+                                            self.commands.send(AppCommand::RunMaintenance);
+                                        }
+                                        if actions.status_page_changed {
+                                            self.commands.send(AppCommand::ApplyStatusPage);
+                                        }
+                                        if actions.registries_changed {
+                                            self.commands.send(AppCommand::SetRegistries);
+                                        }
+                                        if let Some((registry, password)) =
+                                            actions.login_registry.take()
+                                        {
+                                            self.commands.send(AppCommand::LoginRegistry(
+                                                registry,
+                                                Password(password),
+                                            ));
+                                        }
+                                        for (project, action) in actions.audit {
+                                            self.audit.record(&project, action);
+                                        }
+                                        if actions.save_config {
+                                            self.commands.send(AppCommand::SaveConfig);
+                                        }
+                                        if actions.generate_ssl {
+                                            self.commands.send(AppCommand::GenerateSsl);
+                                        }
+                                        if actions.remove_ssl {
+                                            self.commands.send(AppCommand::RemoveSsl);
                                         }
                                     }
                                 }
                             });
                     });
             });
+
+        // Actions queued by this frame's widgets, the tray, etc.
+        self.process_commands(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -502,7 +2617,10 @@ impl eframe::App for DockStackApp {
         // Stop polling system and docker stats early
         self.monitor.stop();
         self.terminal.stop();
-        self.docker.wait_all();
+        self.tunnel.stop();
+        self.status_page.stop();
+        self.docker.stop_log_followers();
+        self.runtime.shutdown(Duration::from_secs(10));
 
         // Save current configuration to disk
         log::info!("Saving configuration...");
@@ -520,3 +2638,22 @@ impl eframe::App for DockStackApp {
         log::info!("DockStack shutdown complete.");
     }
 }
+
+/// Name of a tab that can be moved to its own window.
+fn detached_title(tab: Tab) -> &'static str {
+    match tab {
+        Tab::Logs => "Logs",
+        _ => "Monitor",
+    }
+}
+
+fn detached_window_title(tab: Tab) -> &'static str {
+    match tab {
+        Tab::Logs => "Logs - DockStack",
+        _ => "Monitor - DockStack",
+    }
+}
+
+fn detached_viewport(tab: Tab) -> egui::ViewportId {
+    egui::ViewportId::from_hash_of(("detached_tab", detached_title(tab)))
+}
@@ -0,0 +1,152 @@
+use crate::config::{RegistryConfig, SavedRequest};
+use crate::docker::ci::CiFormat;
+use crate::docker::manager::{DirectoryAction, OrphanResource, StopMode};
+use crate::doctor::DoctorFix;
+use crossbeam_channel::{Receiver, Sender};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Every user action, whichever surface it comes from (panels, tray, and later
+/// the command palette, CLI or control API). Sent over `CommandBus` and applied
+/// in one place by `DockStackApp::dispatch`.
+#[derive(Debug, Clone)]
+pub enum AppCommand {
+    StartStack,
+    StopStack(StopMode),
+    PullImages,
+    /// Resolve the active project's image tags again and rewrite its `dockstack.lock`.
+    UpdateImageLock,
+    /// Build a custom service's image again, ignoring the build cache.
+    RebuildImage(String),
+    CheckBuildCache,
+    PruneBuildCache,
+    /// Prune images, containers and build cache as set up under Maintenance.
+    RunMaintenance,
+    /// Serve or stop the status page as set up in Settings.
+    ApplyStatusPage,
+    /// Resize the log and terminal buffers to the line counts set in Settings.
+    ApplyBuffers,
+    /// Use the proxy set in Settings for the compose commands started from now on.
+    ApplyProxy,
+    /// Log in to the registries set in Settings before the pulls started from now on.
+    SetRegistries,
+    /// Forget a removed registry's keyring password.
+    RemoveRegistry(RegistryConfig),
+    /// Save the password to the keyring, unless empty, and `docker login` to the registry.
+    LoginRegistry(RegistryConfig, Password),
+    RestartStack,
+    /// Run the start or restart that waited on the user confirming the project's directory.
+    ConfirmDirectory(String, DirectoryAction),
+    /// Make a project the active one, e.g. from the tray's Active Project menu.
+    SelectProject(String),
+    /// Start (`true`) or stop a project's stack, whether or not it is the active one.
+    SetProjectRunning(String, bool),
+    RefreshContainers,
+    /// Re-detect the Docker engine, its versions and the compose plugin.
+    CheckDocker,
+    /// Launch Docker Desktop, or the docker service on Linux.
+    StartDockerDaemon,
+    /// Stream a compose service's container logs into the Logs tab.
+    FollowServiceLogs(String),
+    /// Follow one container's output in its own Logs sub-tab, under the given label.
+    AttachContainer {
+        container: String,
+        label: String,
+    },
+    /// Close an attached container's sub-tab and stop following it.
+    DetachContainer(String),
+    /// Copy a Markdown report of a container to the clipboard.
+    CopyContainerReport(String),
+    StartTunnel,
+    StopTunnel,
+    ScanPorts,
+    SendRequest(SavedRequest),
+    CreateSnapshot,
+    RestoreSnapshot(PathBuf),
+    ScanOrphans,
+    /// List running containers that no DockStack project owns.
+    DiscoverContainers,
+    /// Create a project from a compose file found through a discovered container's labels.
+    AdoptComposeProject(PathBuf),
+    /// Read a compose file and check its host ports, then open the import preview.
+    ReadComposeImport(PathBuf),
+    /// Add the folder as a project, set up for the framework found in it.
+    OpenCodebase(PathBuf),
+    /// Create a project from `docker inspect` of the named hand-started containers.
+    CaptureContainers(Vec<String>),
+    /// Clone a git repository into the projects folder and start it as a new project.
+    CloneProject(String),
+    RemoveOrphans(Vec<OrphanResource>),
+    RunDoctor,
+    /// Scan the active project's images for known vulnerabilities.
+    ScanImages,
+    /// Rename a custom service or instance; its old container is removed, and
+    /// its volumes copied when asked, on the next start.
+    RenameService {
+        from: String,
+        to: String,
+        copy_data: bool,
+    },
+    /// Append DockStack's generated and private paths to the project's `.gitignore`.
+    UpdateGitignore,
+    ApplyDoctorFix(DoctorFix),
+    /// Write the open doctor report as text to the given path.
+    SaveDoctorReport(PathBuf),
+    GenerateSsl,
+    RemoveSsl,
+    /// Zip logs, Docker info and the compose file, redacted, into the given path.
+    ExportDiagnostics(PathBuf),
+    /// Write the retained metrics samples to the given path, as JSON or CSV by extension.
+    ExportMetrics(PathBuf),
+    /// Write the active project's services as Kubernetes manifests to the given path.
+    ExportKubernetes(PathBuf),
+    /// Write a CI job running the active project's services to the given path.
+    ExportCi(CiFormat, PathBuf),
+    /// Write `.devcontainer/devcontainer.json` for the active project.
+    WriteDevcontainer,
+    StartTerminal,
+    SendTerminalInput(String),
+    /// Write a terminal transcript to the given path.
+    SaveTranscript(PathBuf, String),
+    SaveConfig,
+    ShowWindow,
+    Quit,
+}
+
+/// A password typed into the UI. Its `Debug` output is redacted, so logging a
+/// command never shows it.
+#[derive(Clone)]
+pub struct Password(pub String);
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
+
+/// Commands queued beyond this are dropped; the UI drains the bus every frame,
+/// so only a stuck frame loop can fill it.
+const QUEUE_CAPACITY: usize = 256;
+
+/// Multi-producer queue of `AppCommand`s drained once per frame.
+pub struct CommandBus {
+    tx: Sender<AppCommand>,
+    rx: Receiver<AppCommand>,
+}
+
+impl CommandBus {
+    pub fn new() -> Self {
+        let (tx, rx) = crossbeam_channel::bounded(QUEUE_CAPACITY);
+        Self { tx, rx }
+    }
+
+    pub fn send(&self, command: AppCommand) {
+        if let Err(e) = self.tx.try_send(command) {
+            log::warn!("Command queue full, dropping {:?}", e.into_inner());
+        }
+    }
+
+    pub fn drain(&self) -> Vec<AppCommand> {
+        self.rx.try_iter().collect()
+    }
+}
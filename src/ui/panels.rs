@@ -1,32 +1,116 @@
-use crate::config::{AppConfig, ServiceConfig};
-use crate::docker::manager::{ContainerInfo, ServiceStatus};
+use crate::audit::AuditEntry;
+use crate::config::{
+    AppConfig, BranchEnv, ComposeImport, EnvProfile, LogSource, NetworkConfig, PhpRoute,
+    ProfileOverride, ProjectConfig, ProjectTag, RegistryConfig, SavedRequest, ServiceConfig,
+    FILE_SYNC_MODES, INSTANCE_KINDS, MOUNT_CONSISTENCIES, PLATFORMS, PULL_POLICIES,
+    RESTART_POLICIES, SELINUX_RELABEL_MODES, WEB_TOPOLOGIES, WINDOWS_SHELLS,
+};
+use crate::docker::ci::CiFormat;
+use crate::docker::compose::{
+    self, build_services, is_valid_cidr, DEFAULT_QUEUE_COMMAND, DEFAULT_SEARCH_HEAP,
+    DEFAULT_SOLR_CORE, MSSQL_EDITIONS, PHP_WORKER,
+};
+use crate::docker::devcontainer;
+use crate::docker::image_lock::{ImageLock, LOCK_FILE};
+use crate::docker::manager::{
+    AttachedOutput, BuildCacheUsage, ContainerInfo, EngineInfo, ForeignContainer, OrphanResource,
+    ProjectContainers, ServiceStatus, StartProgress, StartStage,
+};
+use crate::doctor::{CheckStatus, DoctorFix, DoctorReport};
+use crate::error::DockStackError;
+use crate::hooks;
+use crate::http_client::{HttpResponse, METHODS};
+use crate::hub::{self, HubImage};
+use crate::image_scan::{ScanReport, Severity};
 use crate::monitor::{ContainerStats, SystemStats};
-use crate::port_scanner::PortInfo;
-use crate::services::{get_service_registry, ServiceCategory};
+use crate::plugins::{self, ServicePlugin};
+use crate::port_scanner::{PortInfo, PortScanner};
+use crate::services::catalog::{self, CatalogEntry};
+use crate::services::{self, get_service_registry, ServiceCategory};
+use crate::terminal::{self, TerminalLine};
+use crate::tunnel::TunnelStatus;
 use crate::ui::theme::*;
 use crate::ui::widgets::*;
 use crate::utils;
 use egui::{self, Color32, Rect, RichText, ScrollArea, Stroke, StrokeKind, Vec2};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Tab {
     Dashboard,
+    Projects,
     Services,
     Containers,
     Logs,
+    History,
     Terminal,
     Ports,
     Monitor,
+    Requests,
     Settings,
 }
 
+/// Unpinned projects listed under "Recent" before the rest.
+const RECENT_PROJECTS: usize = 5;
+
+/// Workspace menu sections: pinned, recently opened, then everything else by name.
+/// With a `tag` filter only projects carrying that tag are listed.
+fn workspace_menu_groups<'a>(
+    config: &'a AppConfig,
+    tag: Option<&str>,
+) -> Vec<(&'static str, Vec<&'a ProjectConfig>)> {
+    let (pinned, rest): (Vec<_>, Vec<_>) = config
+        .projects_by_recency()
+        .into_iter()
+        .filter(|p| tag.is_none_or(|tag| p.tags.iter().any(|t| t.name.eq_ignore_ascii_case(tag))))
+        .partition(|p| p.pinned);
+    let recent_count = rest
+        .iter()
+        .take(RECENT_PROJECTS)
+        .filter(|p| p.last_opened > 0)
+        .count();
+    let (recent, mut others) = (rest[..recent_count].to_vec(), rest[recent_count..].to_vec());
+    others.sort_by_key(|p| p.name.to_lowercase());
+
+    [
+        ("PINNED", pinned),
+        ("RECENT", recent),
+        ("ALL PROJECTS", others),
+    ]
+    .into_iter()
+    .filter(|(_, group)| !group.is_empty())
+    .collect()
+}
+
+/// One colored dot per tag, used where a full chip would not fit.
+fn tag_dots(ui: &mut egui::Ui, tags: &[ProjectTag]) {
+    for tag in tags {
+        let (rect, response) = ui.allocate_exact_size(Vec2::splat(8.0), egui::Sense::hover());
+        let [r, g, b] = tag.color;
+        ui.painter()
+            .circle_filled(rect.center(), 3.5, Color32::from_rgb(r, g, b));
+        response.on_hover_text(&tag.name);
+    }
+}
+
+/// Actions requested from the sidebar, handled by the app after rendering.
+#[derive(Default)]
+pub struct SidebarActions {
+    /// Projects were edited in place; write the config to disk.
+    pub save_config: bool,
+    /// Compose file picked for import; the app reads it and opens the preview.
+    pub import_compose: Option<std::path::PathBuf>,
+    /// Folder picked to become a project, set up for the framework found in it.
+    pub open_codebase: Option<std::path::PathBuf>,
+}
+
 /// Render the sidebar
 pub fn render_sidebar(
     ui: &mut egui::Ui,
     active_tab: &mut Tab,
     config: &mut AppConfig,
     status: &ServiceStatus,
+    actions: &mut SidebarActions,
 ) {
     let width = ui.available_width();
 
@@ -70,52 +154,120 @@ pub fn render_sidebar(
     );
     ui.add_space(8.0);
 
+    let accent = config
+        .active_project()
+        .and_then(|p| p.accent)
+        .map(|[r, g, b]| Color32::from_rgb(r, g, b));
     egui::Frame::new()
-        .fill(COLOR_BG_CARD.gamma_multiply(0.5))
+        .fill(match accent {
+            Some(accent) => accent.gamma_multiply(0.12),
+            None => COLOR_BG_CARD.gamma_multiply(0.5),
+        })
         .corner_radius(egui::CornerRadius::same(10))
-        .stroke(Stroke::new(1.0, COLOR_BORDER))
+        .stroke(Stroke::new(1.0, accent.unwrap_or(COLOR_BORDER)))
         .inner_margin(egui::Margin::symmetric(12, 10))
         .show(ui, |ui| {
             ui.set_width(width);
             let project_name = config
                 .active_project()
-                .map(|p| p.name.clone())
-                .unwrap_or("Select Project".to_string());
+                .map(|p| p.display_name())
+                .unwrap_or("📂 Select Project".to_string());
 
             ui.menu_button(
-                RichText::new(format!("📂 {}", project_name))
-                    .strong()
-                    .color(COLOR_TEXT),
+                RichText::new(project_name).strong().color(COLOR_TEXT),
                 |ui| {
-                    for project in &config.projects {
-                        if ui
-                            .selectable_label(
-                                config.active_project_id.as_ref() == Some(&project.id),
-                                &project.name,
-                            )
-                            .clicked()
-                        {
-                            config.active_project_id = Some(project.id.clone());
-                            config.save();
-                            ui.close_menu();
+                    let mut switch_to = None;
+                    let mut toggle_pin = None;
+
+                    // Tag filter chips, remembered while the app runs
+                    let filter_id = egui::Id::new("workspace_tag_filter");
+                    let mut filter: Option<String> = ui.data(|d| d.get_temp(filter_id)).flatten();
+                    let all_tags = config.all_tags();
+                    filter = filter.filter(|name| all_tags.iter().any(|t| &t.name == name));
+                    if !all_tags.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            for tag in &all_tags {
+                                let selected = filter.as_ref() == Some(&tag.name);
+                                if tag_chip(ui, &tag.name, tag.color, selected).clicked() {
+                                    filter = (!selected).then(|| tag.name.clone());
+                                }
+                            }
+                        });
+                        ui.data_mut(|d| d.insert_temp(filter_id, filter.clone()));
+                        ui.separator();
+                    }
+
+                    for (heading, group) in workspace_menu_groups(config, filter.as_deref()) {
+                        ui.label(
+                            RichText::new(heading)
+                                .size(10.0)
+                                .color(COLOR_TEXT_MUTED)
+                                .strong(),
+                        );
+                        for project in group {
+                            ui.horizontal(|ui| {
+                                let (pin_color, pin_hint) = if project.pinned {
+                                    (COLOR_ACCENT, "Unpin")
+                                } else {
+                                    (COLOR_TEXT_MUTED, "Pin to top")
+                                };
+                                if ui
+                                    .small_button(RichText::new("📌").color(pin_color))
+                                    .on_hover_text(pin_hint)
+                                    .clicked()
+                                {
+                                    toggle_pin = Some(project.id.clone());
+                                }
+                                let active = config.active_project_id.as_ref() == Some(&project.id);
+                                if ui
+                                    .selectable_label(active, project.display_name())
+                                    .clicked()
+                                {
+                                    switch_to = Some(project.id.clone());
+                                    ui.close_menu();
+                                }
+                                tag_dots(ui, &project.tags);
+                            });
+                        }
+                        ui.add_space(4.0);
+                    }
+                    if let Some(id) = toggle_pin {
+                        if let Some(project) = config.projects.iter_mut().find(|p| p.id == id) {
+                            project.pinned = !project.pinned;
+                            actions.save_config = true;
                         }
                     }
+                    if let Some(id) = switch_to {
+                        config.set_active_project(&id);
+                    }
                     ui.separator();
                     if ui.button("➕ Create New Project").clicked() {
                         config.add_project("New Project".to_string());
                         ui.close_menu();
                     }
+                    if ui.button("📂 Open Existing Codebase").clicked() {
+                        actions.open_codebase = rfd::FileDialog::new().pick_folder();
+                        ui.close_menu();
+                    }
                     if ui.button("📥 Import docker-compose.yml").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
+                        actions.import_compose = rfd::FileDialog::new()
                             .add_filter("Docker Compose", &["yml", "yaml"])
-                            .pick_file()
-                        {
-                            let _ = config.import_from_compose(&path);
-                        }
+                            .pick_file();
                         ui.close_menu();
                     }
                 },
             );
+
+            if let Some(project) = config.active_project() {
+                if !project.tags.is_empty() {
+                    ui.add_space(6.0);
+                    ui.horizontal_wrapped(|ui| {
+                        for tag in &project.tags {
+                            tag_chip(ui, &tag.name, tag.color, false);
+                        }
+                    });
+                }
+            }
         });
 
     ui.add_space(32.0);
@@ -131,12 +283,15 @@ pub fn render_sidebar(
 
     let tabs = vec![
         (Tab::Dashboard, "🏠", "Overview"),
+        (Tab::Projects, "🗂", "All Projects"),
         (Tab::Services, "📦", "Service Stack"),
         (Tab::Containers, "🐳", "Containers"),
         (Tab::Logs, "📋", "System Logs"),
+        (Tab::History, "🕘", "History"),
         (Tab::Terminal, "💻", "Terminal"),
         (Tab::Ports, "🔌", "Port Checker"),
         (Tab::Monitor, "📊", "Real-time Metrics"),
+        (Tab::Requests, "🌐", "API Client"),
         (Tab::Settings, "⚙", "Preferences"),
     ];
 
@@ -148,6 +303,9 @@ pub fn render_sidebar(
         if response.clicked() {
             *active_tab = tab;
         }
+        response.widget_info(|| {
+            egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, true, is_active, label)
+        });
 
         if ui.is_rect_visible(rect) {
             let (bg, text_col) = if is_active {
@@ -189,6 +347,7 @@ pub fn render_sidebar(
                 egui::FontId::proportional(13.0),
                 text_col,
             );
+            focus_ring(ui, &response, 8);
         }
         ui.add_space(4.0);
     }
@@ -197,14 +356,16 @@ pub fn render_sidebar(
     ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
         ui.add_space(16.0);
 
-        let (status_text, status_col) = match status {
-            ServiceStatus::Running => ("STABLE", COLOR_SUCCESS),
-            _ => ("OFFLINE", COLOR_TEXT_MUTED),
+        let (status_text, kind) = match status {
+            ServiceStatus::Running => ("STABLE", StatusKind::Ok),
+            ServiceStatus::Starting => ("STARTING", StatusKind::Busy),
+            ServiceStatus::Stopping => ("STOPPING", StatusKind::Busy),
+            ServiceStatus::Error(_) => ("ERROR", StatusKind::Error),
+            ServiceStatus::Stopped => ("OFFLINE", StatusKind::Off),
         };
 
         ui.horizontal_centered(|ui| {
-            let (rect, _) = ui.allocate_exact_size(Vec2::new(12.0, 12.0), egui::Sense::hover());
-            ui.painter().circle_filled(rect.center(), 3.5, status_col);
+            status_icon(ui, kind, &format!("System status: {}", status_text));
             ui.add_space(8.0);
             ui.label(
                 RichText::new(format!("SYSTEM STATUS: {}", status_text))
@@ -216,15 +377,185 @@ pub fn render_sidebar(
     });
 }
 
+/// A compose file about to be imported, with new host ports for the ones taken.
+#[derive(Clone)]
+pub struct ImportPreview {
+    pub import: ComposeImport,
+    remaps: Vec<(String, u16, u16, bool)>, // Service, its port, the new one, whether to use it
+    pub confirmed: bool,
+}
+
+impl ImportPreview {
+    /// `suggestions` are `PortScanner::suggest_remaps` of the import's host ports.
+    pub fn new(import: ComposeImport, suggestions: Vec<(String, u16, u16)>) -> Self {
+        let remaps = suggestions
+            .into_iter()
+            .map(|(service, port, suggestion)| (service, port, suggestion, true))
+            .collect();
+        Self {
+            import,
+            remaps,
+            confirmed: false,
+        }
+    }
+
+    /// The chosen new host ports, for the project's `custom_ports`.
+    pub fn custom_ports(&self) -> HashMap<String, u16> {
+        self.remaps
+            .iter()
+            .filter(|(_, _, _, remap)| *remap)
+            .map(|(service, _, port, _)| (service.clone(), *port))
+            .collect()
+    }
+}
+
+/// List the services of a compose file about to be imported and let the user
+/// move the ones whose host port is taken. Returns whether the preview stays open.
+pub fn render_import_preview(ctx: &egui::Context, preview: &mut ImportPreview) -> bool {
+    let mut open = true;
+    let mut cancelled = false;
+    egui::Window::new(format!("Import {}", preview.import.name))
+        .id(egui::Id::new("compose_import_window"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(preview.import.directory.display().to_string()).color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            egui::Grid::new("compose_import_services")
+                .num_columns(3)
+                .spacing([16.0, 6.0])
+                .show(ui, |ui| {
+                    for (name, svc) in &preview.import.services {
+                        ui.label(RichText::new(name).color(COLOR_TEXT));
+                        ui.label(
+                            RichText::new(svc.image.as_deref().unwrap_or("build"))
+                                .color(COLOR_TEXT_DIM),
+                        );
+                        let remap = preview.remaps.iter_mut().find(|r| &r.0 == name);
+                        match remap {
+                            Some((_, port, new_port, use_it)) => {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(format!("⚠ {} in use", port))
+                                            .color(COLOR_WARNING),
+                                    );
+                                    ui.checkbox(use_it, "Use");
+                                    ui.add_enabled(
+                                        *use_it,
+                                        egui::DragValue::new(new_port).range(1..=65535),
+                                    );
+                                });
+                            }
+                            None if svc.port != 0 => {
+                                ui.label(format!("Port {}", svc.port));
+                            }
+                            None => {
+                                ui.label(RichText::new("No host port").color(COLOR_TEXT_DIM));
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            if !preview.remaps.is_empty() {
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new("Taken ports are published on the new port instead.")
+                        .color(COLOR_TEXT_DIM),
+                );
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("📥 Import").clicked() {
+                    preview.confirmed = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+    open && !cancelled
+}
+
+/// Render the per-service checklist shown while the stack is starting
+pub fn render_start_progress(ui: &mut egui::Ui, progress: &StartProgress) {
+    card_frame(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.horizontal(|ui| {
+            let title = match (progress.pull_only, progress.finished_at.is_some()) {
+                (true, true) => "Pull Finished",
+                (true, false) => "Pulling Images",
+                (false, true) => "Startup Finished",
+                (false, false) => "Starting Stack",
+            };
+            ui.label(RichText::new(title).size(16.0).strong().color(COLOR_TEXT));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    RichText::new(format!("⏱ {:.1}s", progress.elapsed().as_secs_f32()))
+                        .monospace()
+                        .color(COLOR_TEXT_DIM),
+                );
+            });
+        });
+        ui.add_space(8.0);
+
+        for (service, stage) in &progress.services {
+            ui.horizontal(|ui| {
+                match stage {
+                    StartStage::Started | StartStage::Healthy | StartStage::Pulled => {
+                        ui.label(RichText::new("✔").color(COLOR_SUCCESS));
+                    }
+                    StartStage::Failed => {
+                        ui.label(RichText::new("✘").color(COLOR_ERROR));
+                    }
+                    StartStage::Pending => {
+                        ui.label(RichText::new("○").color(COLOR_TEXT_MUTED));
+                    }
+                    _ => {
+                        ui.add(egui::Spinner::new().size(12.0).color(COLOR_PRIMARY));
+                    }
+                }
+                let display_name = crate::services::get_service_info(service)
+                    .map(|i| i.display_name)
+                    .unwrap_or_else(|| service.clone());
+                ui.label(RichText::new(display_name).strong().color(COLOR_TEXT));
+                ui.label(RichText::new(stage.label()).size(12.0).color(match stage {
+                    StartStage::Failed => COLOR_ERROR,
+                    StartStage::Started | StartStage::Healthy | StartStage::Pulled => COLOR_SUCCESS,
+                    _ => COLOR_TEXT_DIM,
+                }));
+            });
+        }
+    });
+}
+
+/// Actions requested from the dashboard, handled by the app after rendering.
+#[derive(Default)]
+pub struct DashboardActions {
+    pub check_docker: bool,
+    pub start_docker: bool,
+    /// Services were toggled in place; write the config to disk.
+    pub save_config: bool,
+}
+
 /// Render the dashboard panel
 pub fn render_dashboard(
     ui: &mut egui::Ui,
     config: &mut AppConfig,
-    _status: &ServiceStatus,
-    sys_stats: &SystemStats,
+    host: Host,
     containers: &[ContainerInfo],
-    docker_available: bool,
+    container_stats: &[ContainerStats],
+    facts: &ProjectFacts,
+    actions: &mut DashboardActions,
 ) {
+    let Host {
+        stats: sys_stats,
+        engine,
+    } = host;
+    let docker_available = engine.available;
     let mut something_changed = false;
     if !docker_available {
         ui.add_space(20.0);
@@ -235,6 +566,25 @@ pub fn render_dashboard(
                 ui.vertical(|ui| {
                     ui.heading(RichText::new("Docker Daemon Unreachable").color(COLOR_ERROR));
                     ui.label("DockStack requires Docker to manage your services. Please ensure Docker is running.");
+                    ui.label(
+                        RichText::new("DockStack keeps checking and reconnects as soon as it is up.")
+                            .size(11.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        let label = if cfg!(target_os = "linux") {
+                            "▶ Start Docker Daemon"
+                        } else {
+                            "▶ Start Docker Desktop"
+                        };
+                        if ui.button(label).clicked() {
+                            actions.start_docker = true;
+                        }
+                        if ui.button("🔄 Check Again").clicked() {
+                            actions.check_docker = true;
+                        }
+                    });
                 });
             });
         });
@@ -285,6 +635,11 @@ pub fn render_dashboard(
             ui.end_row();
         });
 
+    if containers.iter().any(|c| c.is_crash_looping()) {
+        ui.add_space(16.0);
+        render_crash_loops(ui, containers);
+    }
+
     ui.add_space(32.0);
 
     // Workspace and Domain Configuration
@@ -317,11 +672,26 @@ pub fn render_dashboard(
                                     .strong()
                                     .color(COLOR_TEXT),
                             );
-                            ui.label(
-                                RichText::new(&project.directory)
-                                    .size(11.0)
-                                    .color(COLOR_TEXT_DIM),
-                            );
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(&project.directory)
+                                        .size(11.0)
+                                        .color(COLOR_TEXT_DIM),
+                                );
+                                let Some(branch) = &facts.branch else {
+                                    return;
+                                };
+                                let overlays = project
+                                    .branch_env
+                                    .iter()
+                                    .filter(|o| o.matches(branch))
+                                    .count();
+                                let mut text = format!("⎇ {}", branch);
+                                if overlays > 0 {
+                                    text.push_str(&format!(" · {} env overlay(s)", overlays));
+                                }
+                                ui.label(RichText::new(text).size(11.0).color(COLOR_ACCENT));
+                            });
                         }
                     });
                 });
@@ -342,6 +712,39 @@ pub fn render_dashboard(
                         if ui.button("📋").clicked() {
                             ui.ctx().copy_text(format!("127.0.0.1  {}", project.domain));
                         }
+                        if project.profiles.is_empty() {
+                            return;
+                        }
+                        ui.add_space(12.0);
+                        ui.label(
+                            RichText::new("🧪 Profile:")
+                                .size(11.0)
+                                .color(COLOR_TEXT_DIM),
+                        );
+                        let running = containers.iter().any(|c| c.state == "running");
+                        let selected = match project.current_profile() {
+                            Some(profile) => profile.name.clone(),
+                            None => "Base".to_string(),
+                        };
+                        ui.add_enabled_ui(!running, |ui| {
+                            egui::ComboBox::from_id_salt("dashboard_profile")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    let names = project.profiles.iter().map(|p| p.name.clone());
+                                    for name in std::iter::once(String::new()).chain(names) {
+                                        let label = if name.is_empty() { "Base" } else { &name };
+                                        something_changed |= ui
+                                            .selectable_value(
+                                                &mut project.active_profile,
+                                                name.clone(),
+                                                label,
+                                            )
+                                            .changed();
+                                    }
+                                });
+                        })
+                        .response
+                        .on_disabled_hover_text("Stop the stack to switch profiles");
                     });
 
                     ui.add_space(8.0);
@@ -353,13 +756,18 @@ pub fn render_dashboard(
                             )
                             .clicked()
                         {
-                            let port = project
-                                .services
-                                .get("nginx")
-                                .map(|s| s.port)
-                                .or_else(|| project.services.get("apache").map(|s| s.port))
-                                .unwrap_or(80);
-                            utils::open_url(&format!("http://localhost:{}", port));
+                            let port = ["nginx", "apache"]
+                                .into_iter()
+                                .find(|name| project.services.contains_key(*name))
+                                .map_or(80, |name| project.host_port(name));
+                            let nginx_https = project.ssl_enabled
+                                && project.services.get("nginx").is_some_and(|s| s.enabled);
+                            if nginx_https {
+                                let port = compose::https_port(project);
+                                utils::open_url(&format!("https://localhost:{}", port));
+                            } else {
+                                utils::open_url(&format!("http://localhost:{}", port));
+                            }
                         }
                         ui.add_space(8.0);
                         if ui
@@ -371,6 +779,19 @@ pub fn render_dashboard(
                         {
                             utils::open_directory(&project.directory);
                         }
+                        if let Some(url) = services::adminer_login_url(project) {
+                            ui.add_space(8.0);
+                            if ui
+                                .add(
+                                    egui::Button::new(RichText::new("🗄  Adminer").strong())
+                                        .fill(COLOR_BG_HOVER),
+                                )
+                                .on_hover_text("Open Adminer (auto-login) for the project database")
+                                .clicked()
+                            {
+                                utils::open_url(&url);
+                            }
+                        }
                     });
                 }
             });
@@ -389,10 +810,25 @@ pub fn render_dashboard(
             card_frame(ui, |ui| {
                 ui.set_width(ui.available_width());
                 ui.set_height(140.0);
-                ui.label(RichText::new("Runtime Connectivity").strong());
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Runtime Connectivity").strong());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("🔄").on_hover_text("Check again").clicked() {
+                            actions.check_docker = true;
+                        }
+                    });
+                });
                 ui.add_space(12.0);
                 ui.horizontal_centered(|ui| {
-                    status_dot(ui, docker_available);
+                    status_icon(
+                        ui,
+                        if docker_available {
+                            StatusKind::Ok
+                        } else {
+                            StatusKind::Error
+                        },
+                        "Docker daemon",
+                    );
                     ui.add_space(8.0);
                     ui.label(
                         RichText::new(if docker_available {
@@ -405,17 +841,33 @@ pub fn render_dashboard(
                     );
                 });
                 ui.add_space(10.0);
+                let mut server = vec![format!("Server v{}", engine.server_version)];
+                if !engine.api_version.is_empty() {
+                    server.push(format!("API {}", engine.api_version));
+                }
+                if engine.rootless {
+                    server.push("rootless".to_string());
+                }
                 ui.label(
-                    RichText::new("API: 1.44  •  v25.0.3")
+                    RichText::new(server.join("  •  "))
                         .size(11.0)
                         .color(COLOR_TEXT_DIM),
                 );
+                let mut client = vec![format!("Client v{}", engine.client_version)];
+                if !engine.platform.is_empty() {
+                    client.push(engine.platform.clone());
+                }
+                ui.label(
+                    RichText::new(client.join("  •  "))
+                        .size(11.0)
+                        .color(COLOR_TEXT_MUTED),
+                );
             });
         });
     });
 
     if something_changed {
-        config.save();
+        actions.save_config = true;
     }
 
     ui.add_space(40.0);
@@ -461,18 +913,20 @@ pub fn render_dashboard(
                             .as_ref()
                             .map(|i| i.display_name.clone())
                             .unwrap_or(name.to_string());
-                        let icon = info.as_ref().map(|i| i.icon).unwrap_or("❓");
-                        let is_running = containers
-                            .iter()
-                            .any(|c| c.name.contains(name.as_str()) && c.state.contains("running"));
+                        let icon = info.as_ref().map_or("❓", |i| i.icon.as_str());
+                        let running = containers.iter().find(|c| c.is_running_service(name));
+                        let is_running = running.is_some();
+                        let stats =
+                            running.and_then(|c| container_stats.iter().find(|s| s.name == c.name));
 
                         service_card_compact(
                             ui,
                             &display_name,
                             icon,
                             &svc.version,
-                            svc.port,
+                            (!svc.internal).then_some(project.host_port(name)),
                             is_running,
+                            stats,
                         );
 
                         if (i + 1) % 2 == 0 {
@@ -484,6 +938,119 @@ pub fn render_dashboard(
     }
 }
 
+/// The most recent backend error, with its remediation hint when one is known.
+pub fn render_error_banner(ui: &mut egui::Ui, err: &DockStackError, dismiss: &mut bool) {
+    egui::Frame::new()
+        .fill(COLOR_ERROR.gamma_multiply(0.12))
+        .corner_radius(egui::CornerRadius::same(10))
+        .stroke(Stroke::new(1.0, COLOR_ERROR.gamma_multiply(0.5)))
+        .inner_margin(12.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("⚠").size(18.0).color(COLOR_ERROR));
+                ui.add_space(8.0);
+                ui.vertical(|ui| {
+                    ui.label(RichText::new(err.title()).strong().color(COLOR_ERROR));
+                    ui.label(RichText::new(err.to_string()).size(12.0).color(COLOR_TEXT));
+                    if let Some(hint) = err.hint() {
+                        ui.label(
+                            RichText::new(format!("→ {}", hint))
+                                .size(12.0)
+                                .color(COLOR_TEXT_DIM),
+                        );
+                    }
+                });
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if ui.small_button("✕").clicked() {
+                        *dismiss = true;
+                    }
+                });
+            });
+        });
+    ui.add_space(16.0);
+}
+
+/// Public URL sharing card shown under the Dashboard.
+pub fn render_tunnel(
+    ui: &mut egui::Ui,
+    status: &TunnelStatus,
+    ngrok_available: bool,
+    stack_running: bool,
+    share: &mut Option<bool>,
+) {
+    ui.add_space(24.0);
+    ui.label(
+        RichText::new("PUBLIC SHARING")
+            .size(9.0)
+            .color(COLOR_TEXT_MUTED)
+            .strong()
+            .extra_letter_spacing(1.2),
+    );
+    ui.add_space(10.0);
+
+    card_frame(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("🌍").size(22.0));
+            ui.add_space(8.0);
+            ui.vertical(|ui| match status {
+                TunnelStatus::Off => {
+                    ui.label(RichText::new("Not shared").strong().color(COLOR_TEXT));
+                    ui.label(
+                        RichText::new(
+                            "Expose the web server under a temporary public URL for demos.",
+                        )
+                        .size(12.0)
+                        .color(COLOR_TEXT_DIM),
+                    );
+                }
+                TunnelStatus::Connecting => {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Spinner::new());
+                        ui.label(RichText::new("Opening tunnel...").color(COLOR_TEXT_DIM));
+                    });
+                }
+                TunnelStatus::Online(url) => {
+                    ui.horizontal(|ui| {
+                        ui.hyperlink_to(RichText::new(url).strong().color(COLOR_PRIMARY), url);
+                        if ui.small_button("📋").on_hover_text("Copy URL").clicked() {
+                            ui.ctx().copy_text(url.clone());
+                        }
+                    });
+                    ui.label(
+                        RichText::new(
+                            "Anyone with this link can reach your site until you stop sharing.",
+                        )
+                        .size(12.0)
+                        .color(COLOR_WARNING),
+                    );
+                }
+                TunnelStatus::Failed(e) => {
+                    ui.label(RichText::new("Tunnel failed").strong().color(COLOR_ERROR));
+                    ui.label(RichText::new(e).size(12.0).color(COLOR_TEXT_DIM));
+                }
+            });
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if !ngrok_available {
+                    ui.hyperlink_to("Install ngrok", "https://ngrok.com/download");
+                } else if matches!(status, TunnelStatus::Online(_) | TunnelStatus::Connecting) {
+                    if danger_button(ui, "Stop Sharing").clicked() {
+                        *share = Some(false);
+                    }
+                } else if ui
+                    .add_enabled(stack_running, egui::Button::new("Share Publicly"))
+                    .on_disabled_hover_text("Start the stack first")
+                    .clicked()
+                {
+                    *share = Some(true);
+                }
+            });
+        });
+    });
+}
+
 fn stat_card(ui: &mut egui::Ui, title: &str, value: &str, icon: &str, accent: Color32) {
     egui::Frame::new()
         .fill(COLOR_BG_CARD)
@@ -547,8 +1114,9 @@ fn service_card_compact(
     name: &str,
     icon: &str,
     version: &str,
-    port: u16,
+    port: Option<u16>, // None for internal-only services
     running: bool,
+    stats: Option<&ContainerStats>,
 ) {
     egui::Frame::new()
         .fill(COLOR_BG_CARD)
@@ -593,26 +1161,34 @@ fn service_card_compact(
                         ui.label(RichText::new(name).size(16.0).strong().color(COLOR_TEXT));
                         if running {
                             ui.add_space(8.0);
-                            ui.label(RichText::new("●").size(10.0).color(COLOR_SUCCESS));
+                            status_icon(ui, StatusKind::Ok, "Running");
                         }
                     });
                     ui.add_space(1.0);
                     ui.label(
-                        RichText::new(format!("v{} ● Port: {}", version, port))
-                            .size(11.0)
-                            .color(COLOR_TEXT_DIM),
+                        RichText::new(match port {
+                            Some(port) => format!("v{} ● Port: {}", version, port),
+                            None => format!("v{} ● Internal", version),
+                        })
+                        .size(11.0)
+                        .color(COLOR_TEXT_DIM),
                     );
                 });
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if running {
-                        ui.label(
-                            RichText::new("ONLINE")
-                                .size(9.0)
-                                .strong()
-                                .color(COLOR_SUCCESS)
-                                .extra_letter_spacing(1.0),
-                        );
+                        ui.vertical(|ui| {
+                            ui.label(
+                                RichText::new("ONLINE")
+                                    .size(9.0)
+                                    .strong()
+                                    .color(COLOR_SUCCESS)
+                                    .extra_letter_spacing(1.0),
+                            );
+                            if let Some(stats) = stats {
+                                inline_stats(ui, stats);
+                            }
+                        });
                     } else {
                         ui.label(
                             RichText::new("OFFLINE")
@@ -627,8 +1203,83 @@ fn service_card_compact(
         });
 }
 
-pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[ContainerInfo]) {
+/// Live CPU and memory figures shown next to a service's status badge.
+fn inline_stats(ui: &mut egui::Ui, stats: &ContainerStats) {
+    // MemUsage reads "used / limit"; only the used part fits on a card
+    let mem = stats.mem_usage.split(" / ").next().unwrap_or_default();
+    ui.label(
+        RichText::new(format!("CPU {} · {}", stats.cpu_percent, mem))
+            .size(10.0)
+            .color(COLOR_TEXT_DIM),
+    );
+}
+
+/// Actions requested from the Services panel, handled by the app after rendering.
+#[derive(Default)]
+pub struct ServicesActions {
+    pub browse_images: bool,
+    pub open_catalog: bool,
+    pub scan_images: bool,
+    pub follow_logs: Option<String>,
+    pub rebuild: Option<String>,
+    pub check_build_cache: bool,
+    pub prune_build_cache: bool,
+    /// Service to rename: (old name, new name, copy its data).
+    pub rename: Option<(String, String, bool)>,
+    /// Changes made in place, for the History tab: (project name, action).
+    pub audit: Vec<(String, String)>,
+    /// Services were edited in place; write the config to disk.
+    pub save_config: bool,
+}
+
+/// What the panels show about the active project that is too costly to work
+/// out every frame; the app rebuilds it when the project, its config or a scan changes.
+#[derive(Default)]
+pub struct ProjectFacts {
+    /// Critical vulnerabilities per service, from the project's last image scan.
+    pub critical: BTreeMap<String, usize>,
+    /// Services that mount the project's code.
+    pub code_mounts: Vec<String>,
+    /// Whether the project directory is a git repository.
+    pub git_repo: bool,
+    /// DockStack's files the project's `.gitignore` does not list yet.
+    pub missing_gitignore: Vec<String>,
+    /// Branch checked out in the project; the app re-reads it periodically.
+    pub branch: Option<String>,
+    /// The project's `dockstack.lock`, while it locks images.
+    pub image_lock: Option<ImageLock>,
+}
+
+/// The machine and Docker engine the dashboard reports on.
+#[derive(Clone, Copy)]
+pub struct Host<'a> {
+    /// CPU and memory use, from the system monitor.
+    pub stats: &'a SystemStats,
+    /// Versions and reachability of the Docker engine.
+    pub engine: &'a EngineInfo,
+}
+
+/// Results of the Settings cleanup scans; each is `None` until it has run.
+#[derive(Clone, Copy)]
+pub struct CleanupScans<'a> {
+    /// Resources DockStack created for projects that are no longer configured.
+    pub orphans: Option<&'a [OrphanResource]>,
+    /// Running containers DockStack did not start.
+    pub foreign: Option<&'a [ForeignContainer]>,
+}
+
+pub fn render_services(
+    ui: &mut egui::Ui,
+    config: &mut AppConfig,
+    containers: &[ContainerInfo],
+    container_stats: &[ContainerStats],
+    build_cache: Option<&BuildCacheUsage>,
+    facts: &ProjectFacts,
+    actions: &mut ServicesActions,
+) {
     let mut something_changed = false;
+    let critical = &facts.critical;
+    let registries: Vec<String> = config.registries.iter().map(|r| r.host.clone()).collect();
 
     ui.horizontal(|ui| {
         ui.add(
@@ -654,6 +1305,27 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
         }
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button("🔎 Browse Images")
+                .on_hover_text("Search Docker Hub and add an image as a service")
+                .clicked()
+            {
+                actions.browse_images = true;
+            }
+            if ui
+                .button("📚 Catalog")
+                .on_hover_text("Add a ready-made community service such as n8n or Grafana")
+                .clicked()
+            {
+                actions.open_catalog = true;
+            }
+            if ui
+                .button("🛡 Scan Images")
+                .on_hover_text("Look for known vulnerabilities with Trivy or Docker Scout")
+                .clicked()
+            {
+                actions.scan_images = true;
+            }
             if ui
                 .add(
                     egui::Button::new(
@@ -680,6 +1352,7 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                             version: "latest".to_string(),
                             env_vars: HashMap::new(),
                             settings: HashMap::new(),
+                            ..ServiceConfig::default()
                         },
                     );
                     something_changed = true;
@@ -690,12 +1363,33 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
     ui.add_space(16.0);
 
     let mut service_to_remove = None;
+    let mut just_enabled = None;
+    let mut web_server_toggled = None;
+    let mut add_php_version = None;
+    let mut add_instance: Option<(String, String)> = None;
+    let mut php_services: Vec<String> = config
+        .active_project()
+        .map(|p| {
+            p.services
+                .iter()
+                .filter(|(name, svc)| svc.kind(name) == "php")
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    php_services.sort();
+    let buildable = config
+        .active_project()
+        .map(build_services)
+        .unwrap_or_default();
     let registry = get_service_registry();
+    let installed_plugins = plugins::installed();
     let categories = vec![
         ServiceCategory::WebServer,
         ServiceCategory::Database,
         ServiceCategory::Runtime,
         ServiceCategory::Cache,
+        ServiceCategory::Search,
         ServiceCategory::Admin,
         ServiceCategory::Security,
         ServiceCategory::Custom,
@@ -725,11 +1419,26 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                             svc_info.name.clone(),
                             svc_info.display_name.clone(),
                             svc_info.description.clone(),
-                            svc_info.icon.to_string(),
+                            svc_info.icon.clone(),
                         ));
                     }
-                }
-            }
+                    let mut instances: Vec<&String> = project
+                        .services
+                        .iter()
+                        .filter(|(_, s)| s.instance_of.as_deref() == Some(&svc_info.name))
+                        .map(|(name, _)| name)
+                        .collect();
+                    instances.sort();
+                    for name in instances {
+                        services_to_render.push((
+                            name.clone(),
+                            format!("{} ({})", svc_info.display_name, name),
+                            svc_info.description.clone(),
+                            svc_info.icon.clone(),
+                        ));
+                    }
+                }
+            }
         }
 
         if services_to_render.is_empty() {
@@ -748,7 +1457,9 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
             if let Some(project) = config.active_project_mut() {
                 if let Some(svc) = project.services.get_mut(&id) {
                     ui.push_id(&id, |ui| {
-                             let is_running = containers.iter().any(|c| c.name.contains(&id) && c.state.contains("running"));
+                             let running = containers.iter().find(|c| c.is_running_service(&id));
+                             let is_running = running.is_some();
+                             let stats = running.and_then(|c| container_stats.iter().find(|s| s.name == c.name));
 
                              egui::Frame::new()
                                 .fill(COLOR_BG_CARD)
@@ -779,7 +1490,16 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                             ui.label(RichText::new(&display_name).size(18.0).strong().color(COLOR_TEXT));
                                             if is_running {
                                                 ui.add_space(8.0);
-                                                ui.label(RichText::new("● RUNNING").size(10.0).color(COLOR_SUCCESS).strong());
+                                                ui.label(RichText::new("✔ RUNNING").size(10.0).color(COLOR_SUCCESS).strong());
+                                            }
+                                            if let Some(stats) = stats {
+                                                ui.add_space(8.0);
+                                                inline_stats(ui, stats);
+                                            }
+                                            if let Some(count) = critical.get(&id) {
+                                                ui.add_space(8.0);
+                                                ui.label(RichText::new("⚠ CRITICAL CVE").size(10.0).color(COLOR_ERROR).strong())
+                                                    .on_hover_text(format!("The last image scan found {} critical vulnerabilities", count));
                                             }
                                         });
                                         ui.add_space(4.0);
@@ -790,25 +1510,83 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                          // Toggle
                                         let mut enabled = svc.enabled;
-                                        if toggle_switch(ui, &mut enabled).changed() {
+                                        if toggle_switch(ui, &mut enabled, &format!("Enable {}", display_name)).changed() {
                                             svc.enabled = enabled;
                                             if id == "ssl" { project.ssl_enabled = enabled; }
+                                            if id == "sftp" && enabled && svc.settings.get("password").is_none_or(|p| p.is_empty()) {
+                                                svc.settings.insert("password".to_string(), generate_password());
+                                            }
+                                            let verb = if enabled { "Enabled" } else { "Disabled" };
+                                            actions.audit.push((project.name.clone(), format!("{} service {}", verb, display_name)));
+                                            if enabled {
+                                                just_enabled = Some(id.clone());
+                                            }
+                                            if id == "nginx" || id == "apache" {
+                                                web_server_toggled = Some(id.clone());
+                                            }
                                         }
 
                                         ui.add_space(24.0);
 
                                         // Config actions
                                         ui.menu_button(RichText::new("⚙ Config").size(13.0).color(COLOR_TEXT), |ui| {
+                                             if svc.is_custom || svc.instance_of.is_some() {
+                                                 if let Some(rename) = render_rename(ui, &id) {
+                                                     actions.rename = Some(rename);
+                                                     ui.close_menu();
+                                                 }
+                                                 ui.separator();
+                                             }
                                              if svc.is_custom {
+                                                 if buildable.contains(&id) {
+                                                     if ui.button("🔨 Rebuild (No Cache)").on_hover_text("docker compose build --no-cache").clicked() {
+                                                         actions.rebuild = Some(id.clone());
+                                                         ui.close_menu();
+                                                     }
+                                                     match build_cache {
+                                                         Some(usage) => {
+                                                             ui.label(RichText::new(format!("Build cache: {} ({} reclaimable)", usage.size, usage.reclaimable)).size(12.0).color(COLOR_TEXT_DIM));
+                                                         }
+                                                         None => {
+                                                             if ui.button("📊 Show Build Cache Usage").clicked() {
+                                                                 actions.check_build_cache = true;
+                                                             }
+                                                         }
+                                                     }
+                                                     if ui.button("🧹 Prune Build Cache").on_hover_text("Remove unused build cache. The cache is shared by all projects on this Docker engine.").clicked() {
+                                                         actions.prune_build_cache = true;
+                                                         ui.close_menu();
+                                                     }
+                                                     ui.separator();
+                                                 }
                                                  if ui.button(RichText::new("🗑 Remove Service").color(COLOR_ERROR)).clicked() {
                                                      service_to_remove = Some(id.clone());
                                                      ui.close_menu();
                                                  }
                                              } else {
-                                                 let config_path = match id.as_str() {
+                                                 if svc.instance_of.is_some() && ui.button(RichText::new("🗑 Remove Instance").color(COLOR_ERROR)).clicked() {
+                                                     service_to_remove = Some(id.clone());
+                                                     ui.close_menu();
+                                                 }
+                                                 if svc.instance_of.is_none() && INSTANCE_KINDS.contains(&id.as_str()) {
+                                                     ui.horizontal(|ui| {
+                                                         let name_id = ui.id().with("new_instance_name");
+                                                         let mut name: String = ui.data(|d| d.get_temp(name_id)).unwrap_or_default();
+                                                         ui.add(egui::TextEdit::singleline(&mut name).desired_width(140.0).hint_text(format!("{}-analytics", id)));
+                                                         if ui.add_enabled(!name.trim().is_empty(), egui::Button::new("➕ Add Instance")).on_hover_text("Another copy of this service with its own port, variables and data").clicked() {
+                                                             add_instance = Some((id.clone(), name.clone()));
+                                                             name.clear();
+                                                             ui.close_menu();
+                                                         }
+                                                         ui.data_mut(|d| d.insert_temp(name_id, name));
+                                                     });
+                                                 }
+                                                 let config_path = match svc.kind(&id) {
                                                     "nginx" => Some(std::path::Path::new(&project.directory).join("nginx/default.conf")),
                                                     "apache" => Some(std::path::Path::new(&project.directory).join("apache/httpd.conf")),
                                                     "php" => Some(std::path::Path::new(&project.directory).join("php/php.ini")),
+                                                    "varnish" => Some(std::path::Path::new(&project.directory).join("varnish/default.vcl")),
+                                                    "pgadmin" => Some(std::path::Path::new(&project.directory).join("pgadmin/servers.json")),
                                                     "mysql" => Some(std::path::Path::new(&project.directory).join("mysql/my.cnf")),
                                                     "postgresql" => Some(std::path::Path::new(&project.directory).join("postgresql/postgresql.conf")),
                                                     _ => None,
@@ -826,7 +1604,15 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                              }
                                         });
 
-                                        ui.label(RichText::new(format!("Port: {}", svc.port)).size(13.0).color(COLOR_TEXT_MUTED).monospace());
+                                        if svc.internal {
+                                            ui.label(RichText::new("Internal").size(13.0).color(COLOR_TEXT_MUTED).monospace());
+                                        } else if let Some(port) = project.custom_ports.get(&id).filter(|p| **p != 0) {
+                                            ui.label(RichText::new(format!("Port: {}", port)).size(13.0).color(COLOR_TEXT_MUTED).monospace());
+                                            ui.label(RichText::new("OVERRIDDEN").size(10.0).color(COLOR_WARNING).strong())
+                                                .on_hover_text(format!("Published on {} instead of the service's port {}", port, svc.port));
+                                        } else {
+                                            ui.label(RichText::new(format!("Port: {}", svc.port)).size(13.0).color(COLOR_TEXT_MUTED).monospace());
+                                        }
                                     });
                                 });
 
@@ -881,6 +1667,23 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                                                               svc.image = Some(img);
                                                                               something_changed = true;
                                                                           }
+                                                                          if !registries.is_empty() || !svc.registry.is_empty() {
+                                                                              let selected = if svc.registry.is_empty() { "As written" } else { svc.registry.as_str() };
+                                                                              egui::ComboBox::from_id_salt(format!("registry_{}", id))
+                                                                                  .selected_text(selected)
+                                                                                  .show_ui(ui, |ui| {
+                                                                                      if ui.selectable_value(&mut svc.registry, String::new(), "As written").changed() {
+                                                                                          something_changed = true;
+                                                                                      }
+                                                                                      for host in &registries {
+                                                                                          if ui.selectable_value(&mut svc.registry, host.clone(), host).changed() {
+                                                                                              something_changed = true;
+                                                                                          }
+                                                                                      }
+                                                                                  })
+                                                                                  .response
+                                                                                  .on_hover_text("Registry the image is pulled from, logged in to with its saved credentials");
+                                                                          }
                                                                       } else {
                                                                           ui.label(RichText::new(svc.image.as_ref().unwrap_or(&id)).strong().color(COLOR_ACCENT));
                                                                       }
@@ -897,14 +1700,28 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
 
                                                                   // Column 4: Port
                                                                   ui.vertical(|ui| {
-                                                                      let is_available = crate::port_scanner::PortScanner::is_port_available(svc.port);
                                                                       ui.horizontal(|ui| {
                                                                           ui.label(RichText::new("Host Port").size(11.0).color(COLOR_TEXT_DIM));
-                                                                          ui.add_space(4.0);
-                                                                          ui.label(RichText::new(if is_available { "✔" } else { "✘" }).size(10.0).color(if is_available { COLOR_SUCCESS } else { COLOR_ERROR }));
+                                                                          if !svc.internal {
+                                                                              let port = project.custom_ports.get(&id).copied().filter(|p| *p != 0).unwrap_or(svc.port);
+                                                                              let is_available = crate::port_scanner::PortScanner::is_port_available(port);
+                                                                              ui.add_space(4.0);
+                                                                              ui.label(RichText::new(if is_available { "✔" } else { "✘" }).size(10.0).color(if is_available { COLOR_SUCCESS } else { COLOR_ERROR }));
+                                                                          }
                                                                       });
                                                                       ui.add_space(4.0);
-                                                                      if ui.add(egui::DragValue::new(&mut svc.port).range(1..=65535)).changed() {
+                                                                      if let Some(custom) = project.custom_ports.get_mut(&id).filter(|p| **p != 0) {
+                                                                          if ui.add_enabled(!svc.internal, egui::DragValue::new(custom).range(1..=65535)).changed() {
+                                                                              something_changed = true;
+                                                                          }
+                                                                          if ui.small_button(format!("↺ Use {}", svc.port)).on_hover_text("Drop the override and publish the service's own port").clicked() {
+                                                                              project.custom_ports.remove(&id);
+                                                                              something_changed = true;
+                                                                          }
+                                                                      } else if ui.add_enabled(!svc.internal, egui::DragValue::new(&mut svc.port).range(1..=65535)).changed() {
+                                                                          something_changed = true;
+                                                                      }
+                                                                      if ui.checkbox(&mut svc.internal, "Internal only").on_hover_text("Don't publish any port; reachable only from other containers in this project").changed() {
                                                                           something_changed = true;
                                                                       }
                                                                   });
@@ -917,6 +1734,69 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                                                           something_changed = true;
                                                                       }
                                                                   });
+
+                                                                  // Column 6: Interface binding
+                                                                  ui.vertical(|ui| {
+                                                                      ui.label(RichText::new("All Interfaces").size(11.0).color(COLOR_TEXT_DIM));
+                                                                      ui.add_space(8.0);
+                                                                      if ui.checkbox(&mut svc.bind_all_interfaces, "").on_hover_text("Publish on 0.0.0.0 even when the project binds to localhost").changed() {
+                                                                          something_changed = true;
+                                                                      }
+                                                                  });
+
+                                                                  // Column 7: GPU
+                                                                  ui.vertical(|ui| {
+                                                                      ui.label(RichText::new("GPU").size(11.0).color(COLOR_TEXT_DIM));
+                                                                      ui.add_space(8.0);
+                                                                      if ui.checkbox(&mut svc.gpu, "").on_hover_text("Give the container access to the host's NVIDIA GPUs (requires the NVIDIA Container Toolkit)").changed() {
+                                                                          something_changed = true;
+                                                                      }
+                                                                  });
+
+                                                                  // Column 8: Host user
+                                                                  ui.vertical(|ui| {
+                                                                      ui.label(RichText::new("Host User").size(11.0).color(COLOR_TEXT_DIM));
+                                                                      ui.add_space(8.0);
+                                                                      if ui.checkbox(&mut svc.run_as_host_user, "").on_hover_text("Run as the owner of the project directory so files it creates in www/ stay editable (images that need root to start, like nginx, may fail)").changed() {
+                                                                          something_changed = true;
+                                                                      }
+                                                                  });
+
+                                                                  // Column 9: Restart Policy
+                                                                  ui.vertical(|ui| {
+                                                                      ui.label(RichText::new("Restart Policy").size(11.0).color(COLOR_TEXT_DIM));
+                                                                      ui.add_space(4.0);
+                                                                      egui::ComboBox::from_id_salt(format!("restart_{}", id))
+                                                                          .selected_text(svc.restart_policy.as_str())
+                                                                          .show_ui(ui, |ui| {
+                                                                              for policy in RESTART_POLICIES {
+                                                                                  if ui.selectable_value(&mut svc.restart_policy, policy.to_string(), *policy).changed() {
+                                                                                      something_changed = true;
+                                                                                  }
+                                                                              }
+                                                                          });
+                                                                  });
+
+                                                                  // Column 10: Platform
+                                                                  ui.vertical(|ui| {
+                                                                      ui.label(RichText::new("Platform").size(11.0).color(COLOR_TEXT_DIM));
+                                                                      ui.add_space(4.0);
+                                                                      let selected = if svc.platform.is_empty() { "Auto" } else { svc.platform.as_str() };
+                                                                      egui::ComboBox::from_id_salt(format!("platform_{}", id))
+                                                                          .selected_text(selected)
+                                                                          .show_ui(ui, |ui| {
+                                                                              if ui.selectable_value(&mut svc.platform, String::new(), "Auto").changed() {
+                                                                                  something_changed = true;
+                                                                              }
+                                                                              for platform in PLATFORMS {
+                                                                                  if ui.selectable_value(&mut svc.platform, platform.to_string(), *platform).changed() {
+                                                                                      something_changed = true;
+                                                                                  }
+                                                                              }
+                                                                          })
+                                                                          .response
+                                                                          .on_hover_text("Auto runs the engine's own platform. linux/amd64 runs under emulation on Apple Silicon, slower but available for every image");
+                                                                  });
                                                                   ui.end_row();
                                                               });
                                                       });
@@ -925,8 +1805,38 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                           ui.separator();
                                           ui.add_space(8.0);
 
+                                         // Code mount tuning
+                                         if facts.code_mounts.contains(&id) {
+                                             ui.label(RichText::new("Code Mount").strong().color(COLOR_ACCENT));
+                                             ui.horizontal(|ui| {
+                                                 ui.label("Consistency:");
+                                                 egui::ComboBox::from_id_salt(format!("consistency_{}", id))
+                                                     .selected_text(svc.mount_consistency.as_str())
+                                                     .show_ui(ui, |ui| {
+                                                         for mode in MOUNT_CONSISTENCIES {
+                                                             if ui.selectable_value(&mut svc.mount_consistency, mode.to_string(), *mode).changed() {
+                                                                 something_changed = true;
+                                                             }
+                                                         }
+                                                     });
+                                                 ui.label(RichText::new("cached/delegated speed up Docker Desktop on macOS").size(12.0).color(COLOR_TEXT_DIM));
+                                             });
+                                             ui.horizontal(|ui| {
+                                                 ui.label("Volume overlays:");
+                                                 let mut overlays = svc.volume_overlays.join(", ");
+                                                 if ui.add(egui::TextEdit::singleline(&mut overlays).desired_width(240.0).hint_text("node_modules, vendor")).changed() {
+                                                     svc.volume_overlays = overlays.split(',').map(|d| d.trim().to_string()).filter(|d| !d.is_empty()).collect();
+                                                     something_changed = true;
+                                                 }
+                                                 ui.label(RichText::new("Kept in named volumes instead of the host directory").size(12.0).color(COLOR_TEXT_DIM));
+                                             });
+                                             ui.add_space(8.0);
+                                             ui.separator();
+                                             ui.add_space(8.0);
+                                         }
+
                                          // PHP Configuration
-                                         if id == "php" {
+                                         if svc.kind(&id) == "php" {
                                              ui.label(RichText::new("PHP Version & Extensions").strong().color(COLOR_ACCENT));
                                              ui.horizontal(|ui| {
                                                  ui.label("Memory Limit:");
@@ -952,15 +1862,65 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                                      }
                                                  }
                                              });
+
+                                             let mut worker = svc.settings.get("queue_worker").is_some_and(|v| v == "true");
+                                             if id == "php" {
+                                                 ui.add_space(8.0);
+                                                 something_changed |= render_php_routes(ui, &mut project.php_routes, &php_services, &mut add_php_version);
+
+                                                 ui.add_space(8.0);
+                                                 ui.label(RichText::new("Queue Worker").strong().color(COLOR_ACCENT));
+                                                 if ui.checkbox(&mut worker, "Run a queue worker alongside PHP").changed() {
+                                                     svc.settings.insert("queue_worker".to_string(), worker.to_string());
+                                                     something_changed = true;
+                                                 }
+                                             }
+                                             if worker {
+                                                 egui::Grid::new("queue_worker_settings").show(ui, |ui| {
+                                                     ui.label("Build Context:");
+                                                  let mut build = svc.settings.get("build").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut build).desired_width(320.0).hint_text("./app (directory with a Dockerfile)")).changed() {
+                                                      svc.settings.insert("build".to_string(), build);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+
+                                                  ui.label("Command:");
+                                                     let mut cmd = svc.settings.get("queue_command").cloned().unwrap_or_else(|| DEFAULT_QUEUE_COMMAND.to_string());
+                                                     if ui.add(egui::TextEdit::singleline(&mut cmd).desired_width(360.0)).changed() {
+                                                         svc.settings.insert("queue_command".to_string(), cmd);
+                                                         something_changed = true;
+                                                     }
+                                                     ui.end_row();
+
+                                                     ui.label("Restart:");
+                                                     let mut restart = svc.settings.get("queue_restart").cloned().unwrap_or_else(|| svc.restart_policy.clone());
+                                                     egui::ComboBox::from_id_salt("queue_restart")
+                                                         .selected_text(restart.as_str())
+                                                         .show_ui(ui, |ui| {
+                                                             for policy in RESTART_POLICIES {
+                                                                 if ui.selectable_value(&mut restart, policy.to_string(), *policy).changed() {
+                                                                     svc.settings.insert("queue_restart".to_string(), restart.clone());
+                                                                     something_changed = true;
+                                                                 }
+                                                             }
+                                                         });
+                                                     ui.end_row();
+                                                 });
+                                                 if ui.button("📋 Stream Worker Logs").clicked() {
+                                                     actions.follow_logs = Some(PHP_WORKER.to_string());
+                                                 }
+                                             }
                                              ui.add_space(8.0);
                                              ui.separator();
                                              ui.add_space(8.0);
                                          }
 
                                          // Database Configuration
-                                         if id == "mysql" || id == "postgresql" {
-                                              let root_key = if id == "mysql" { "MYSQL_ROOT_PASSWORD" } else { "POSTGRES_PASSWORD" };
-                                              let db_key = if id == "mysql" { "MYSQL_DATABASE" } else { "POSTGRES_DB" };
+                                         let kind = svc.kind(&id).to_string();
+                                         if kind == "mysql" || kind == "postgresql" {
+                                              let root_key = if kind == "mysql" { "MYSQL_ROOT_PASSWORD" } else { "POSTGRES_PASSWORD" };
+                                              let db_key = if kind == "mysql" { "MYSQL_DATABASE" } else { "POSTGRES_DB" };
 
                                               ui.label(RichText::new("Database Settings").strong().color(COLOR_ACCENT));
                                               egui::Grid::new("db_settings").show(ui, |ui| {
@@ -985,8 +1945,224 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
                                               ui.add_space(8.0);
                                          }
 
+                                         // Python / Jupyter Configuration
+                                         if id == "python" || id == "jupyter" {
+                                              ui.label(RichText::new("Python Runtime").strong().color(COLOR_ACCENT));
+                                              let folder = if id == "python" { "app" } else { "notebooks" };
+                                              ui.label(RichText::new(format!("Place requirements.txt in {}/ to have it installed on start.", folder)).size(12.0).color(COLOR_TEXT_DIM));
+                                              egui::Grid::new(format!("py_settings_{}", id)).show(ui, |ui| {
+                                                  if id == "python" {
+                                                      ui.label("Start Command:");
+                                                      let mut cmd = svc.settings.get("command").cloned().unwrap_or_default();
+                                                      if ui.add(egui::TextEdit::singleline(&mut cmd).desired_width(320.0).hint_text("python -m http.server")).changed() {
+                                                          svc.settings.insert("command".to_string(), cmd);
+                                                          something_changed = true;
+                                                      }
+                                                      ui.end_row();
+
+                                                      ui.label("Container Port:");
+                                                      let mut port = svc.settings.get("container_port").cloned().unwrap_or_default();
+                                                      if ui.add(egui::TextEdit::singleline(&mut port).desired_width(80.0).hint_text("8000")).changed() {
+                                                          svc.settings.insert("container_port".to_string(), port);
+                                                          something_changed = true;
+                                                      }
+                                                  } else {
+                                                      ui.label("Access Token:");
+                                                      let mut token = svc.env_vars.get("JUPYTER_TOKEN").cloned().unwrap_or_default();
+                                                      if ui.add(egui::TextEdit::singleline(&mut token)).changed() {
+                                                          svc.env_vars.insert("JUPYTER_TOKEN".to_string(), token);
+                                                          something_changed = true;
+                                                      }
+                                                  }
+                                                  ui.end_row();
+                                              });
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // SQL Server Configuration
+                                         if id == "mssql" {
+                                              ui.label(RichText::new("SQL Server").strong().color(COLOR_ACCENT));
+                                              egui::Grid::new("mssql_settings").show(ui, |ui| {
+                                                  ui.label("SA Password:");
+                                                  let mut password = svc.env_vars.get("MSSQL_SA_PASSWORD").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut password).password(true).desired_width(200.0)).changed() {
+                                                      svc.env_vars.insert("MSSQL_SA_PASSWORD".to_string(), password.clone());
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+                                                  if let Some(problem) = compose::mssql_password_problem(&password) {
+                                                      ui.label("");
+                                                      ui.label(RichText::new(format!("⚠ The password {}", problem)).size(12.0).color(COLOR_WARNING));
+                                                      ui.end_row();
+                                                  }
+
+                                                  ui.label("Edition:");
+                                                  let mut edition = svc.settings.get("edition").cloned().unwrap_or_else(|| "Developer".to_string());
+                                                  egui::ComboBox::from_id_salt("mssql_edition")
+                                                      .selected_text(&edition)
+                                                      .show_ui(ui, |ui| {
+                                                          for e in MSSQL_EDITIONS {
+                                                              if ui.selectable_value(&mut edition, e.to_string(), *e).changed() {
+                                                                  svc.settings.insert("edition".to_string(), edition.clone());
+                                                                  something_changed = true;
+                                                              }
+                                                          }
+                                                      });
+                                                  ui.end_row();
+                                              });
+                                              let mut eula = svc.settings.get("accept_eula").is_some_and(|v| v == "true");
+                                              ui.horizontal(|ui| {
+                                                  if ui.checkbox(&mut eula, "I accept the SQL Server license terms").changed() {
+                                                      svc.settings.insert("accept_eula".to_string(), eula.to_string());
+                                                      something_changed = true;
+                                                  }
+                                                  ui.hyperlink_to("Read the EULA", "https://go.microsoft.com/fwlink/?linkid=857698");
+                                              });
+                                              if !eula {
+                                                  ui.label(RichText::new("The container exits at once until the license is accepted.").size(12.0).color(COLOR_TEXT_DIM));
+                                              }
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // Search Engine Configuration
+                                         if id == "opensearch" || id == "solr" {
+                                              ui.label(RichText::new("Search Engine").strong().color(COLOR_ACCENT));
+                                              egui::Grid::new("search_settings").show(ui, |ui| {
+                                                  ui.label("Heap Size:");
+                                                  let mut heap = svc.settings.get("heap_size").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut heap).desired_width(80.0).hint_text(DEFAULT_SEARCH_HEAP)).on_hover_text("JVM heap, e.g. 512m or 1g; keep it under half the memory Docker may use").changed() {
+                                                      svc.settings.insert("heap_size".to_string(), heap);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+                                                  if id == "solr" {
+                                                      ui.label("Core:");
+                                                      let mut core = svc.settings.get("core").cloned().unwrap_or_default();
+                                                      if ui.add(egui::TextEdit::singleline(&mut core).desired_width(160.0).hint_text(DEFAULT_SOLR_CORE)).on_hover_text("Created with the default configset on first start").changed() {
+                                                          svc.settings.insert("core".to_string(), core);
+                                                          something_changed = true;
+                                                      }
+                                                      ui.end_row();
+                                                  }
+                                              });
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // DbGate Configuration
+                                         if id == "dbgate" {
+                                              ui.label(RichText::new("Connections").strong().color(COLOR_ACCENT));
+                                              ui.label(RichText::new("Every enabled MySQL, PostgreSQL, SQL Server and Redis service, instances included, is added as a connection when DbGate starts.").size(12.0).color(COLOR_TEXT_DIM));
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // SFTP Configuration
+                                         if id == "sftp" {
+                                              let (user, password) = compose::sftp_credentials(svc);
+                                              ui.label(RichText::new("SFTP Access").strong().color(COLOR_ACCENT));
+                                              ui.label(RichText::new(format!("Serves www/ as /www. Connect with sftp -P {} {}@localhost", svc.port, user)).size(12.0).color(COLOR_TEXT_DIM));
+                                              egui::Grid::new("sftp_settings").show(ui, |ui| {
+                                                  ui.label("User:");
+                                                  let mut name = svc.settings.get("user").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut name).desired_width(140.0).hint_text("dockstack")).changed() {
+                                                      svc.settings.insert("user".to_string(), name);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+
+                                                  ui.label("Password:");
+                                                  ui.horizontal(|ui| {
+                                                      ui.label(RichText::new(&password).monospace());
+                                                      if ui.small_button("📋").on_hover_text("Copy password").clicked() {
+                                                          ui.ctx().copy_text(password.clone());
+                                                      }
+                                                      if ui.small_button("🔄 Regenerate").clicked() {
+                                                          svc.settings.insert("password".to_string(), generate_password());
+                                                          something_changed = true;
+                                                      }
+                                                  });
+                                                  ui.end_row();
+
+                                                  ui.label("File Owner UID:");
+                                                  let mut uid = svc.settings.get("uid").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut uid).desired_width(80.0).hint_text("1000")).on_hover_text("Uploaded files belong to this user id; use your own (`id -u`) on Linux").changed() {
+                                                      svc.settings.insert("uid".to_string(), uid);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+                                              });
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // Mail Catcher Configuration
+                                         if id == "mailpit" {
+                                              ui.label(RichText::new("Mail Catcher").strong().color(COLOR_ACCENT));
+                                              ui.label(RichText::new("PHP, Python and custom services get MAIL_HOST/MAIL_PORT and php.ini SMTP settings pointing here.").size(12.0).color(COLOR_TEXT_DIM));
+                                              egui::Grid::new("mailpit_settings").show(ui, |ui| {
+                                                  ui.label("Host SMTP Port:");
+                                                  let mut smtp: u16 = svc.settings.get("smtp_port").and_then(|p| p.parse().ok()).unwrap_or(1025);
+                                                  if ui.add(egui::DragValue::new(&mut smtp).range(1..=65535)).changed() {
+                                                      svc.settings.insert("smtp_port".to_string(), smtp.to_string());
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+                                              });
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
+                                         // Plugin Configuration
+                                         if let Some(plugin) = svc.plugin.as_deref().and_then(|p| installed_plugins.iter().find(|i| i.id == p)) {
+                                              something_changed |= render_plugin_settings(ui, &id, plugin, svc);
+                                         }
+
+                                         // Custom Service Configuration
+                                         if svc.is_custom {
+                                              ui.label(RichText::new("Container").strong().color(COLOR_ACCENT));
+                                              egui::Grid::new(format!("custom_settings_{}", id)).show(ui, |ui| {
+                                                  ui.label("Container Port:");
+                                                  let mut port = svc.settings.get("container_port").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut port).desired_width(80.0).hint_text(svc.port.to_string())).changed() {
+                                                      svc.settings.insert("container_port".to_string(), port);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+
+                                                  ui.label("Command:");
+                                                  let mut cmd = svc.settings.get("command").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::singleline(&mut cmd).desired_width(320.0).hint_text("Image default")).changed() {
+                                                      svc.settings.insert("command".to_string(), cmd);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+
+                                                  ui.label("Volumes:");
+                                                  let mut volumes = svc.settings.get("volumes").cloned().unwrap_or_default();
+                                                  if ui.add(egui::TextEdit::multiline(&mut volumes).desired_width(320.0).desired_rows(3).hint_text("./data:/data\ncache:/var/cache")).changed() {
+                                                      svc.settings.insert("volumes".to_string(), volumes);
+                                                      something_changed = true;
+                                                  }
+                                                  ui.end_row();
+                                              });
+                                              ui.label(RichText::new("One source:target[:mode] per line. Relative paths are resolved from the project directory; bare names become named volumes.").size(12.0).color(COLOR_TEXT_DIM));
+                                              ui.add_space(8.0);
+                                              ui.separator();
+                                              ui.add_space(8.0);
+                                         }
+
                                          // Environment Variables
                                          ui.label(RichText::new("Environment Variables").strong());
+                                         ui.label(RichText::new("Values and settings may use ${PROJECT_NAME}, ${DOMAIN} and ${PORT:service}, filled in when the compose file is generated.").size(12.0).color(COLOR_TEXT_DIM));
                                          let mut vars: Vec<(String, String)> = svc.env_vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
                                          let mut env_changed = false;
                                          let mut to_remove = None;
@@ -1025,160 +2201,535 @@ pub fn render_services(ui: &mut egui::Ui, config: &mut AppConfig, containers: &[
     if let Some(id) = service_to_remove {
         if let Some(project) = config.active_project_mut() {
             project.services.remove(&id);
+            project.custom_ports.remove(&id);
+            project.php_routes.retain(|r| r.service != id);
+            actions
+                .audit
+                .push((project.name.clone(), format!("Removed service {}", id)));
             something_changed = true;
         }
     }
 
+    if let Some((kind, name)) = add_instance {
+        if let Some(project) = config.active_project_mut() {
+            if let Some(name) = project.add_named_instance(&kind, &name) {
+                actions.audit.push((
+                    project.name.clone(),
+                    format!("Added {} instance {}", kind, name),
+                ));
+                something_changed = true;
+            }
+        }
+    }
+
+    if let Some(server) = web_server_toggled {
+        if let Some(project) = config.active_project_mut() {
+            if let Some(off) = project.sync_web_topology(&server) {
+                actions
+                    .audit
+                    .push((project.name.clone(), format!("Disabled service {}", off)));
+            }
+        }
+    }
+
+    let prompt_id = egui::Id::new("dependency_prompt");
+    if let Some(project) = config.active_project_mut() {
+        if let Some(service) = just_enabled {
+            let prompt = DependencyPrompt::new(project, &service);
+            ui.data_mut(|d| match prompt {
+                Some(prompt) => d.insert_temp(prompt_id, prompt),
+                None => d.remove::<DependencyPrompt>(prompt_id),
+            });
+        }
+        if let Some(mut prompt) = ui.data(|d| d.get_temp::<DependencyPrompt>(prompt_id)) {
+            let open = render_dependency_prompt(ui.ctx(), &mut prompt, project, &mut actions.audit);
+            something_changed |= prompt.changed;
+            prompt.changed = false;
+            ui.data_mut(|d| {
+                if open {
+                    d.insert_temp(prompt_id, prompt);
+                } else {
+                    d.remove::<DependencyPrompt>(prompt_id);
+                }
+            });
+        }
+    }
+
+    if let Some(version) = add_php_version {
+        if let Some(project) = config.active_project_mut() {
+            if let Some(name) = project.add_instance("php", &version) {
+                actions.audit.push((
+                    project.name.clone(),
+                    format!("Added PHP {} as {}", version, name),
+                ));
+                something_changed = true;
+            }
+        }
+    }
+
     if something_changed {
-        config.save();
+        actions.save_config = true;
     }
 }
 
-pub fn render_containers(ui: &mut egui::Ui, containers: &[ContainerInfo]) {
-    if containers.is_empty() {
-        ui.label(RichText::new("No containers found.").color(COLOR_TEXT_MUTED));
-    } else {
-        egui::Grid::new("container_list")
-            .striped(true)
-            .spacing(Vec2::new(20.0, 12.0))
-            .min_row_height(32.0)
-            .show(ui, |ui| {
-                ui.label(
-                    RichText::new("NAME")
-                        .size(12.0)
-                        .strong()
-                        .color(COLOR_TEXT_MUTED),
-                );
-                ui.label(
-                    RichText::new("IMAGE")
-                        .size(12.0)
-                        .strong()
-                        .color(COLOR_TEXT_MUTED),
-                );
+fn render_crash_loops(ui: &mut egui::Ui, containers: &[ContainerInfo]) {
+    card_frame(ui, |ui| {
+        ui.set_width(ui.available_width());
+        for c in containers.iter().filter(|c| c.is_crash_looping()) {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("⚠").size(18.0).color(COLOR_WARNING));
                 ui.label(
-                    RichText::new("STATE")
-                        .size(12.0)
+                    RichText::new(format!("{} is crash looping", c.name))
                         .strong()
-                        .color(COLOR_TEXT_MUTED),
+                        .color(COLOR_WARNING),
                 );
                 ui.label(
-                    RichText::new("PORTS")
-                        .size(12.0)
-                        .strong()
-                        .color(COLOR_TEXT_MUTED),
+                    RichText::new(format!(
+                        "restarted {} times, last exit code {}",
+                        c.restart_count, c.exit_code
+                    ))
+                    .size(12.0)
+                    .color(COLOR_TEXT_DIM),
                 );
-                ui.end_row();
-
-                for c in containers {
-                    let running = c.state.contains("running");
-                    ui.horizontal(|ui| {
+            });
+            if let Some(log) = &c.crash_log {
+                egui::CollapsingHeader::new("Last logs")
+                    .id_salt(("crash_log", &c.id))
+                    .show(ui, |ui| {
                         ui.label(
-                            RichText::new(if running { "●" } else { "○" })
-                                .size(10.0)
-                                .color(if running {
-                                    COLOR_SUCCESS
-                                } else {
-                                    COLOR_TEXT_MUTED
-                                }),
+                            RichText::new(log)
+                                .monospace()
+                                .size(11.0)
+                                .color(COLOR_TEXT_DIM),
                         );
-                        ui.label(RichText::new(&c.name).size(13.0).color(COLOR_TEXT));
                     });
-                    ui.label(RichText::new(&c.image).size(13.0).color(COLOR_ACCENT));
-                    ui.label(RichText::new(&c.state).size(13.0).color(if running {
-                        COLOR_SUCCESS
-                    } else {
-                        COLOR_TEXT_DIM
-                    }));
-                    ui.label(
-                        RichText::new(utils::truncate_string(&c.ports, 50))
-                            .size(11.0)
-                            .color(COLOR_TEXT_DIM),
-                    );
-                    ui.end_row();
-                }
-            });
-    }
+            }
+        }
+    });
 }
 
-pub fn render_logs(ui: &mut egui::Ui, logs: &[String], clear_logs: &mut bool) {
-    ui.add_space(10.0);
-    ui.horizontal(|ui| {
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-            if ui
-                .button(RichText::new("🗑 Clear Output").size(12.0))
+/// Actions requested from the All Projects overview, by project id.
+#[derive(Default)]
+pub struct ProjectsActions {
+    pub open: Option<String>,
+    pub start: Option<String>,
+    pub stop: Option<String>,
+}
+
+/// Every project with its containers and memory, from the last fleet listing.
+/// `active_status` covers the active project while it starts or stops.
+pub fn render_projects_overview(
+    ui: &mut egui::Ui,
+    config: &AppConfig,
+    fleet: &HashMap<String, ProjectContainers>,
+    container_stats: &[ContainerStats],
+    active_status: &ServiceStatus,
+    actions: &mut ProjectsActions,
+) {
+    let empty = ProjectContainers::default();
+    let memory = |containers: &ProjectContainers| -> u64 {
+        container_stats
+            .iter()
+            .filter(|s| containers.running.contains(&s.name))
+            .map(ContainerStats::memory_bytes)
+            .sum()
+    };
+    let projects = config.projects_by_recency();
+    let listed: Vec<&ProjectContainers> =
+        projects.iter().filter_map(|p| fleet.get(&p.id)).collect();
+    let running_stacks = listed.iter().filter(|c| !c.running.is_empty()).count();
+    let running_containers: usize = listed.iter().map(|c| c.running.len()).sum();
+    let total_memory: u64 = listed.iter().map(|c| memory(c)).sum();
+    ui.label(
+        RichText::new(format!(
+            "{} of {} stacks running · {} containers · {}",
+            running_stacks,
+            projects.len(),
+            running_containers,
+            utils::format_bytes(total_memory)
+        ))
+        .color(COLOR_TEXT_DIM),
+    );
+    ui.add_space(16.0);
+
+    card_frame(ui, |ui| {
+        egui::Grid::new("projects_overview")
+            .striped(true)
+            .spacing(Vec2::new(20.0, 12.0))
+            .min_row_height(32.0)
+            .show(ui, |ui| {
+                for heading in ["PROJECT", "STATUS", "CONTAINERS", "MEMORY", ""] {
+                    ui.label(
+                        RichText::new(heading)
+                            .size(12.0)
+                            .strong()
+                            .color(COLOR_TEXT_MUTED),
+                    );
+                }
+                ui.end_row();
+
+                for project in &projects {
+                    let active = config.active_project_id.as_deref() == Some(project.id.as_str());
+                    let containers = fleet.get(&project.id).unwrap_or(&empty);
+                    let running = containers.running.len();
+                    let total = running + containers.stopped;
+                    let busy = active
+                        && matches!(
+                            active_status,
+                            ServiceStatus::Starting | ServiceStatus::Stopping
+                        );
+
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(project.display_name())
+                                .size(13.0)
+                                .color(COLOR_TEXT),
+                        )
+                        .on_hover_text(&project.directory);
+                        if active {
+                            ui.label(
+                                RichText::new("ACTIVE")
+                                    .size(9.0)
+                                    .strong()
+                                    .color(COLOR_PRIMARY),
+                            );
+                        }
+                    });
+                    let (kind, state) = if busy && *active_status == ServiceStatus::Starting {
+                        (StatusKind::Busy, "Starting…")
+                    } else if busy {
+                        (StatusKind::Busy, "Stopping…")
+                    } else if total == 0 {
+                        (StatusKind::Off, "Not created")
+                    } else if running == total {
+                        (StatusKind::Ok, "Running")
+                    } else if running > 0 {
+                        (StatusKind::Warning, "Partly running")
+                    } else {
+                        (StatusKind::Off, "Stopped")
+                    };
+                    ui.horizontal(|ui| {
+                        status_icon(ui, kind, state);
+                        ui.label(RichText::new(state).size(13.0).color(COLOR_TEXT_DIM));
+                    });
+                    ui.label(
+                        RichText::new(format!("{} / {} running", running, total))
+                            .size(13.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                    let used = memory(containers);
+                    ui.label(
+                        RichText::new(if used > 0 {
+                            utils::format_bytes(used)
+                        } else {
+                            "—".to_string()
+                        })
+                        .size(13.0)
+                        .color(COLOR_TEXT_DIM),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(!busy, |ui| {
+                            if running > 0 {
+                                if ui.small_button("⏹ Stop").clicked() {
+                                    actions.stop = Some(project.id.clone());
+                                }
+                            } else if ui.small_button("▶ Start").clicked() {
+                                actions.start = Some(project.id.clone());
+                            }
+                        });
+                        if !active && ui.small_button("Open").clicked() {
+                            actions.open = Some(project.id.clone());
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Actions requested from the Containers panel, handled by the app after rendering.
+#[derive(Default)]
+pub struct ContainersActions {
+    /// (container, label) whose output should open in its own Logs sub-tab.
+    pub attach: Option<(String, String)>,
+    /// Container to copy a Markdown report of.
+    pub copy_report: Option<String>,
+}
+
+/// `copied` is the container whose report was copied last.
+pub fn render_containers(
+    ui: &mut egui::Ui,
+    containers: &[ContainerInfo],
+    copied: Option<&str>,
+    actions: &mut ContainersActions,
+) {
+    if containers.is_empty() {
+        ui.label(RichText::new("No containers found.").color(COLOR_TEXT_MUTED));
+    } else {
+        egui::Grid::new("container_list")
+            .striped(true)
+            .spacing(Vec2::new(20.0, 12.0))
+            .min_row_height(32.0)
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new("NAME")
+                        .size(12.0)
+                        .strong()
+                        .color(COLOR_TEXT_MUTED),
+                );
+                ui.label(
+                    RichText::new("IMAGE")
+                        .size(12.0)
+                        .strong()
+                        .color(COLOR_TEXT_MUTED),
+                );
+                ui.label(
+                    RichText::new("STATE")
+                        .size(12.0)
+                        .strong()
+                        .color(COLOR_TEXT_MUTED),
+                );
+                ui.label(
+                    RichText::new("PORTS")
+                        .size(12.0)
+                        .strong()
+                        .color(COLOR_TEXT_MUTED),
+                );
+                ui.label("");
+                ui.end_row();
+
+                for c in containers {
+                    let running = c.state.contains("running");
+                    ui.horizontal(|ui| {
+                        let kind = if c.is_crash_looping() {
+                            StatusKind::Error
+                        } else if running {
+                            StatusKind::Ok
+                        } else if c.state == "restarting" {
+                            StatusKind::Busy
+                        } else if c.exit_code != 0 {
+                            StatusKind::Warning
+                        } else {
+                            StatusKind::Off
+                        };
+                        status_icon(ui, kind, &c.state);
+                        ui.label(RichText::new(&c.name).size(13.0).color(COLOR_TEXT));
+                    });
+                    ui.label(RichText::new(&c.image).size(13.0).color(COLOR_ACCENT));
+                    if c.is_crash_looping() {
+                        ui.label(
+                            RichText::new(format!("⚠ crash loop (exit {})", c.exit_code))
+                                .size(13.0)
+                                .color(COLOR_WARNING),
+                        )
+                        .on_hover_text(format!("Restarted {} times", c.restart_count));
+                    } else {
+                        ui.label(RichText::new(&c.state).size(13.0).color(if running {
+                            COLOR_SUCCESS
+                        } else {
+                            COLOR_TEXT_DIM
+                        }));
+                    }
+                    ui.label(
+                        RichText::new(utils::truncate_string(&c.ports, 50))
+                            .size(11.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("📎 Attach Output")
+                            .on_hover_text("Follow this container's output in its own Logs tab")
+                            .clicked()
+                        {
+                            let label = if c.service.is_empty() {
+                                &c.name
+                            } else {
+                                &c.service
+                            };
+                            actions.attach = Some((c.name.clone(), label.clone()));
+                        }
+                        let report = if copied == Some(c.name.as_str()) {
+                            "✔ Copied"
+                        } else {
+                            "📋 Copy Report"
+                        };
+                        if ui
+                            .small_button(report)
+                            .on_hover_text(
+                                "Copy image, state, inspect highlights and the last 50 log \
+                                 lines as Markdown, with secrets masked",
+                            )
+                            .clicked()
+                        {
+                            actions.copy_report = Some(c.name.clone());
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        if containers.iter().any(|c| c.is_crash_looping()) {
+            ui.add_space(16.0);
+            render_crash_loops(ui, containers);
+        }
+    }
+}
+
+/// Per-session filter state for the Logs tab.
+#[derive(Default)]
+pub struct LogViewState {
+    pub hidden_sources: HashSet<String>,
+    pub search: String,
+    pub new_file_path: String,
+    pub app_log: bool, // Showing DockStack's own log instead of the stack's
+    pub app_log_level: Option<log::Level>, // Most verbose level shown; `None` for all
+    pub attached: Option<String>, // Attached container whose output is shown
+    pub paused: HashMap<String, Vec<String>>, // Attached output frozen when paused, by container
+}
+
+/// Switch between the stack output, DockStack's own log and the output of each
+/// attached container. `detach` is set to a container whose tab was closed.
+pub fn render_log_tabs(
+    ui: &mut egui::Ui,
+    state: &mut LogViewState,
+    attached: &[AttachedOutput],
+    detach: &mut Option<String>,
+) {
+    ui.add_space(10.0);
+    ui.horizontal_wrapped(|ui| {
+        let stack = !state.app_log && state.attached.is_none();
+        if ui.selectable_label(stack, "📋 Stack Output").clicked() {
+            state.app_log = false;
+            state.attached = None;
+        }
+        if ui
+            .selectable_label(state.app_log, "🐞 App Log")
+            .on_hover_text("DockStack's own log, e.g. why an operation failed")
+            .clicked()
+        {
+            state.app_log = true;
+            state.attached = None;
+        }
+        for output in attached {
+            ui.separator();
+            let selected = state.attached.as_deref() == Some(output.container.as_str());
+            let icon = if output.ended { "⏹" } else { "📎" };
+            if ui
+                .selectable_label(selected, format!("{} {}", icon, output.label))
+                .on_hover_text(&output.container)
                 .clicked()
             {
-                *clear_logs = true;
+                state.app_log = false;
+                state.attached = Some(output.container.clone());
+            }
+            if ui.small_button("✖").on_hover_text("Detach").clicked() {
+                *detach = Some(output.container.clone());
+            }
+        }
+    });
+}
+
+/// One attached container's own output, with a pause that freezes the view
+/// while lines keep arriving underneath.
+pub fn render_attached_output(
+    ui: &mut egui::Ui,
+    output: &AttachedOutput,
+    state: &mut LogViewState,
+    clear: &mut bool,
+) {
+    let paused = state.paused.contains_key(&output.container);
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("🔍").size(12.0));
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search)
+                .hint_text("Filter lines...")
+                .desired_width(220.0),
+        );
+        ui.add_space(12.0);
+        if output.ended {
+            ui.label(RichText::new("Output ended").color(COLOR_TEXT_MUTED))
+                .on_hover_text("The container stopped or was removed");
+        } else if paused {
+            ui.label(RichText::new("Paused").color(COLOR_WARNING));
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button(RichText::new("🗑 Clear").size(12.0)).clicked() {
+                *clear = true;
+                if let Some(frozen) = state.paused.get_mut(&output.container) {
+                    frozen.clear();
+                }
+            }
+            let toggle = if paused { "▶ Resume" } else { "⏸ Pause" };
+            if ui.button(RichText::new(toggle).size(12.0)).clicked() {
+                if paused {
+                    state.paused.remove(&output.container);
+                } else {
+                    let lines = output.lines.iter().cloned().collect();
+                    state.paused.insert(output.container.clone(), lines);
+                }
             }
         });
     });
     ui.add_space(16.0);
 
+    let lines: Vec<&String> = match state.paused.get(&output.container) {
+        Some(frozen) => frozen.iter().collect(),
+        None => output.lines.iter().collect(),
+    };
     egui::Frame::new()
         .fill(COLOR_BG_APP)
         .stroke(Stroke::new(1.0, COLOR_BORDER))
         .corner_radius(egui::CornerRadius::same(8))
         .inner_margin(12.0)
         .show(ui, |ui| {
-            ScrollArea::vertical()
-                .auto_shrink([false; 2])
-                .stick_to_bottom(true)
-                .show(ui, |ui| {
-                    ui.set_min_width(ui.available_width());
-                    for line in logs {
-                        let color = if line.contains("ERROR") {
-                            COLOR_ERROR
-                        } else if line.contains("WARN") {
-                            COLOR_WARNING
-                        } else if line.starts_with("[DockStack]") {
-                            COLOR_PRIMARY
-                        } else {
-                            COLOR_TEXT_DIM
-                        };
-
-                        ui.label(
-                            RichText::new(line)
-                                .size(12.0)
-                                .family(egui::FontFamily::Monospace)
-                                .color(color),
-                        );
-                    }
-                });
+            if lines.is_empty() {
+                ui.label(RichText::new("No output yet.").color(COLOR_TEXT_MUTED));
+                return;
+            }
+            let rows: Vec<&String> = lines
+                .into_iter()
+                .filter(|line| state.search.is_empty() || line.contains(state.search.as_str()))
+                .collect();
+            show_log_rows(ui, ScrollArea::both(), rows.len(), |ui, i| {
+                log_line(ui, None, rows[i], COLOR_TEXT);
+            });
         });
 }
 
-pub fn render_terminal(
+/// DockStack's `log` records, newest at the bottom, as formatted lines with their level.
+pub fn render_app_log(
     ui: &mut egui::Ui,
-    output_lines: &[String],
-    input_buffer: &mut String,
-    send_input: &mut bool,
-    clear_terminal: &mut bool,
-    start_terminal: &mut bool,
-    terminal_running: bool,
+    records: &[(String, log::Level)],
+    state: &mut LogViewState,
+    clear: &mut bool,
 ) {
     ui.add_space(10.0);
     ui.horizontal(|ui| {
-        ui.heading(
-            RichText::new("Terminal")
-                .size(28.0)
-                .color(COLOR_TEXT)
-                .strong(),
+        ui.label(RichText::new("🔍").size(12.0));
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search)
+                .hint_text("Filter lines...")
+                .desired_width(220.0),
         );
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-            if !terminal_running {
-                if ui
-                    .button(RichText::new("▶ Start Shell").color(COLOR_SUCCESS))
-                    .clicked()
-                {
-                    *start_terminal = true;
+        ui.add_space(12.0);
+        ui.label("Level:");
+        egui::ComboBox::from_id_salt("app_log_level")
+            .selected_text(state.app_log_level.map_or("All", |l| l.as_str()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.app_log_level, None, "All");
+                for level in [
+                    log::Level::Error,
+                    log::Level::Warn,
+                    log::Level::Info,
+                    log::Level::Debug,
+                ] {
+                    ui.selectable_value(&mut state.app_log_level, Some(level), level.as_str());
                 }
-            } else if ui
-                .button(RichText::new("⏹ Reset").color(COLOR_ERROR))
-                .clicked()
-            { /* logic to kill */
-            }
-            if ui.button("Clear").clicked() {
-                *clear_terminal = true;
+            });
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button(RichText::new("🗑 Clear").size(12.0)).clicked() {
+                *clear = true;
             }
         });
     });
@@ -1190,50 +2741,338 @@ pub fn render_terminal(
         .corner_radius(egui::CornerRadius::same(8))
         .inner_margin(12.0)
         .show(ui, |ui| {
-            // Output area
-            ScrollArea::vertical()
-                .auto_shrink([false, false])
-                .max_height(ui.available_height() - 40.0)
-                .stick_to_bottom(true)
-                .show(ui, |ui| {
-                    ui.set_min_width(ui.available_width());
-                    for line in output_lines {
-                        let col = if line.starts_with("$") {
-                            COLOR_PRIMARY
-                        } else {
-                            COLOR_TEXT_DIM
-                        };
-                        ui.label(
-                            RichText::new(line)
-                                .size(12.0)
-                                .family(egui::FontFamily::Monospace)
-                                .color(col),
-                        );
-                    }
-                });
+            if records.is_empty() {
+                ui.label(RichText::new("No log records yet.").color(COLOR_TEXT_MUTED));
+                return;
+            }
+            let rows: Vec<&(String, log::Level)> = records
+                .iter()
+                .filter(|(line, _)| state.search.is_empty() || line.contains(state.search.as_str()))
+                .collect();
+            show_log_rows(ui, ScrollArea::both(), rows.len(), |ui, i| {
+                let (line, level) = rows[i];
+                let color = match level {
+                    log::Level::Error => COLOR_ERROR,
+                    log::Level::Warn => COLOR_WARNING,
+                    log::Level::Info => COLOR_TEXT,
+                    log::Level::Debug | log::Level::Trace => COLOR_TEXT_DIM,
+                };
+                log_line(ui, None, line, color);
+            });
+        });
+}
 
-            ui.separator();
+/// Font size of log and terminal lines.
+const LOG_FONT_SIZE: f32 = 12.0;
 
-            // Input area
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("❯").color(COLOR_SUCCESS).strong());
-                let response = ui.add(
-                    egui::TextEdit::singleline(input_buffer)
-                        .frame(false)
-                        .desired_width(ui.available_width())
-                        .font(egui::FontId::monospace(13.0))
-                        .text_color(COLOR_TEXT),
-                );
+/// Lay out only the rows scrolled into view, so buffers of tens of thousands
+/// of lines cost the same per frame as a screenful. Every row must be a
+/// single unwrapped line; see `log_line`.
+fn show_log_rows(
+    ui: &mut egui::Ui,
+    area: ScrollArea,
+    rows: usize,
+    mut add_row: impl FnMut(&mut egui::Ui, usize),
+) {
+    let row_height = ui.fonts(|f| f.row_height(&egui::FontId::monospace(LOG_FONT_SIZE)));
+    area.auto_shrink([false; 2])
+        .stick_to_bottom(true)
+        .show_rows(ui, row_height, rows, |ui, range| {
+            ui.set_min_width(ui.available_width());
+            for i in range {
+                add_row(ui, i);
+            }
+        });
+}
 
-                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                    *send_input = true;
-                    response.request_focus();
+/// One log line, optionally prefixed with a colored `[source]` tag. Long lines
+/// extend to the right instead of wrapping, which keeps rows a fixed height.
+fn log_line(ui: &mut egui::Ui, tag: Option<(&str, Color32)>, text: &str, color: Color32) {
+    let font = egui::FontId::monospace(LOG_FONT_SIZE);
+    let mut job = egui::text::LayoutJob::default();
+    if let Some((source, tag_color)) = tag {
+        let format = egui::TextFormat::simple(font.clone(), tag_color);
+        job.append(&format!("[{}] ", source), 0.0, format);
+    }
+    job.append(text, 0.0, egui::TextFormat::simple(font, color));
+    ui.add(egui::Label::new(job).extend());
+}
+
+/// Colors handed out to newly added host log files.
+const LOG_SOURCE_COLORS: [Color32; 4] = [COLOR_INFO, COLOR_SECONDARY, COLOR_SUCCESS, COLOR_ACCENT];
+
+const SOURCE_DOCKSTACK: &str = "DockStack";
+const SOURCE_CONTAINERS: &str = "Containers";
+
+/// Which filter source a buffered log line belongs to, the text after its
+/// prefix, and the tag color for host log files.
+fn log_line_source<'a>(
+    line: &'a str,
+    file_labels: &'a [(String, Color32)],
+) -> (&'a str, &'a str, Option<Color32>) {
+    if line.starts_with("[DockStack]") {
+        return (SOURCE_DOCKSTACK, line, None);
+    }
+    if let Some((label, rest)) = line.strip_prefix('[').and_then(|r| r.split_once("] ")) {
+        if let Some((name, color)) = file_labels.iter().find(|(l, _)| l == label) {
+            return (name.as_str(), rest, Some(*color));
+        }
+    }
+    (SOURCE_CONTAINERS, line, None)
+}
+
+pub fn render_logs(
+    ui: &mut egui::Ui,
+    logs: &[String],
+    log_files: Option<&mut Vec<LogSource>>,
+    state: &mut LogViewState,
+    clear_logs: &mut bool,
+    sources_changed: &mut bool,
+) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("🔍").size(12.0));
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search)
+                .hint_text("Filter lines...")
+                .desired_width(220.0),
+        );
+        ui.add_space(12.0);
+        for source in [SOURCE_DOCKSTACK, SOURCE_CONTAINERS] {
+            let mut shown = !state.hidden_sources.contains(source);
+            if ui.checkbox(&mut shown, source).changed() {
+                if shown {
+                    state.hidden_sources.remove(source);
+                } else {
+                    state.hidden_sources.insert(source.to_string());
                 }
-            });
+            }
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button(RichText::new("🗑 Clear Output").size(12.0))
+                .clicked()
+            {
+                *clear_logs = true;
+            }
         });
-}
+    });
 
-pub fn render_ports(ui: &mut egui::Ui, port_infos: &[PortInfo], scan_ports: &mut bool) {
+    // Host log files registered for this project
+    let mut file_labels: Vec<(String, Color32)> = Vec::new();
+    if let Some(files) = log_files {
+        ui.add_space(8.0);
+        let mut remove = None;
+        let labels: Vec<String> = files.iter().map(|f| f.label(files)).collect();
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                RichText::new("HOST FILES")
+                    .size(10.0)
+                    .strong()
+                    .color(COLOR_TEXT_MUTED),
+            );
+            for (i, (file, label)) in files.iter_mut().zip(labels).enumerate() {
+                if ui.color_edit_button_srgb(&mut file.color).changed() {
+                    *sources_changed = true;
+                }
+                if ui
+                    .checkbox(&mut file.enabled, &label)
+                    .on_hover_text(&file.path)
+                    .changed()
+                {
+                    *sources_changed = true;
+                }
+                let mut shown = !state.hidden_sources.contains(&label);
+                if ui
+                    .small_button(if shown { "👁" } else { "—" })
+                    .on_hover_text("Show or hide lines from this file")
+                    .clicked()
+                {
+                    shown = !shown;
+                    if shown {
+                        state.hidden_sources.remove(&label);
+                    } else {
+                        state.hidden_sources.insert(label.clone());
+                    }
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text("Stop tailing this file")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+                ui.add_space(12.0);
+                let [r, g, b] = file.color;
+                file_labels.push((label, Color32::from_rgb(r, g, b)));
+            }
+        });
+        if let Some(i) = remove {
+            files.remove(i);
+            *sources_changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.new_file_path)
+                    .hint_text("storage/logs/laravel.log")
+                    .desired_width(280.0),
+            );
+            if ui.button("📂").on_hover_text("Browse").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    state.new_file_path = path.to_string_lossy().to_string();
+                }
+            }
+            let path = state.new_file_path.trim();
+            if ui
+                .add_enabled(!path.is_empty(), egui::Button::new("➕ Tail File"))
+                .clicked()
+            {
+                let c = LOG_SOURCE_COLORS[files.len() % LOG_SOURCE_COLORS.len()];
+                files.push(LogSource {
+                    path: path.to_string(),
+                    color: [c.r(), c.g(), c.b()],
+                    enabled: true,
+                });
+                state.new_file_path.clear();
+                *sources_changed = true;
+            }
+        });
+    }
+    ui.add_space(16.0);
+
+    egui::Frame::new()
+        .fill(COLOR_BG_APP)
+        .stroke(Stroke::new(1.0, COLOR_BORDER))
+        .corner_radius(egui::CornerRadius::same(8))
+        .inner_margin(12.0)
+        .show(ui, |ui| {
+            // Filtering is a cheap scan; only the visible rows are laid out
+            let rows: Vec<(&str, &str, Option<Color32>)> = logs
+                .iter()
+                .filter(|line| state.search.is_empty() || line.contains(state.search.as_str()))
+                .map(|line| log_line_source(line, &file_labels))
+                .filter(|(source, _, _)| !state.hidden_sources.contains(*source))
+                .collect();
+            show_log_rows(ui, ScrollArea::both(), rows.len(), |ui, i| {
+                let (source, text, tag_color) = rows[i];
+                let color = if text.contains("ERROR") {
+                    COLOR_ERROR
+                } else if text.contains("WARN") {
+                    COLOR_WARNING
+                } else if source == SOURCE_DOCKSTACK {
+                    COLOR_PRIMARY
+                } else {
+                    COLOR_TEXT_DIM
+                };
+                log_line(ui, tag_color.map(|c| (source, c)), text, color);
+            });
+        });
+}
+
+/// Actions requested from the Terminal tab.
+#[derive(Default)]
+pub struct TerminalActions {
+    pub start: bool,
+    /// Enter was pressed in the input line.
+    pub send_input: bool,
+    pub clear: bool,
+    /// Where to save the transcript of the lines shown.
+    pub save_transcript: Option<std::path::PathBuf>,
+}
+
+pub fn render_terminal(
+    ui: &mut egui::Ui,
+    output_lines: &[TerminalLine],
+    input_buffer: &mut String,
+    terminal_running: bool,
+    actions: &mut TerminalActions,
+) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.heading(
+            RichText::new("Terminal")
+                .size(28.0)
+                .color(COLOR_TEXT)
+                .strong(),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if !terminal_running {
+                if ui
+                    .button(RichText::new("▶ Start Shell").color(COLOR_SUCCESS))
+                    .clicked()
+                {
+                    actions.start = true;
+                }
+            } else if ui
+                .button(RichText::new("⏹ Reset").color(COLOR_ERROR))
+                .clicked()
+            { /* logic to kill */
+            }
+            if ui.button("Clear").clicked() {
+                actions.clear = true;
+            }
+            if ui
+                .add_enabled(
+                    !output_lines.is_empty(),
+                    egui::Button::new("💾 Save Transcript…"),
+                )
+                .on_hover_text("Save the lines shown, with the time each arrived")
+                .clicked()
+            {
+                actions.save_transcript = rfd::FileDialog::new()
+                    .add_filter("Log", &["log", "txt"])
+                    .set_file_name(format!(
+                        "dockstack-terminal-{}.log",
+                        chrono::Local::now().format("%Y%m%d-%H%M%S")
+                    ))
+                    .save_file();
+            }
+        });
+    });
+    ui.add_space(16.0);
+
+    egui::Frame::new()
+        .fill(COLOR_BG_APP)
+        .stroke(Stroke::new(1.0, COLOR_BORDER))
+        .corner_radius(egui::CornerRadius::same(8))
+        .inner_margin(12.0)
+        .show(ui, |ui| {
+            // Output area
+            let area = ScrollArea::both().max_height(ui.available_height() - 40.0);
+            show_log_rows(ui, area, output_lines.len(), |ui, i| {
+                let line = &output_lines[i].text;
+                let col = if line.starts_with("$") {
+                    COLOR_PRIMARY
+                } else {
+                    COLOR_TEXT_DIM
+                };
+                log_line(ui, None, line, col);
+            });
+
+            ui.separator();
+
+            // Input area
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("❯").color(COLOR_SUCCESS).strong());
+                let response = ui.add(
+                    egui::TextEdit::singleline(input_buffer)
+                        .frame(false)
+                        .hint_text("dc ps  ·  dc exec php bash  (dc = docker compose for the active project)")
+                        .desired_width(ui.available_width())
+                        .font(egui::FontId::monospace(13.0))
+                        .text_color(COLOR_TEXT),
+                );
+
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    actions.send_input = true;
+                    response.request_focus();
+                }
+            });
+        });
+}
+
+pub fn render_ports(ui: &mut egui::Ui, port_infos: &[PortInfo], scan_ports: &mut bool) {
     ScrollArea::vertical().show(ui, |ui| {
         ui.add_space(10.0);
         ui.horizontal(|ui| {
@@ -1249,188 +3088,3266 @@ pub fn render_ports(ui: &mut egui::Ui, port_infos: &[PortInfo], scan_ports: &mut
                 }
             });
         });
-        ui.label(
-            RichText::new("Detect conflicts before starting your services")
-                .size(14.0)
+        ui.label(
+            RichText::new("Detect conflicts before starting your services")
+                .size(14.0)
+                .color(COLOR_TEXT_DIM),
+        );
+        ui.add_space(24.0);
+
+        egui::Grid::new("port_grid")
+            .spacing(Vec2::new(32.0, 12.0))
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new("PORT").strong().color(COLOR_TEXT_MUTED));
+                ui.label(RichText::new("STATUS").strong().color(COLOR_TEXT_MUTED));
+                ui.label(RichText::new("PROCESS").strong().color(COLOR_TEXT_MUTED));
+                ui.label(RichText::new("ACTION").strong().color(COLOR_TEXT_MUTED));
+                ui.end_row();
+
+                for info in port_infos {
+                    ui.label(
+                        RichText::new(format!("{}", info.port))
+                            .size(14.0)
+                            .strong()
+                            .color(COLOR_TEXT),
+                    );
+                    if info.in_use {
+                        ui.label(RichText::new("BUSY").size(12.0).color(COLOR_ERROR));
+                        ui.label(
+                            RichText::new(&info.process)
+                                .size(12.0)
+                                .color(COLOR_TEXT_DIM),
+                        );
+                        ui.label(
+                            RichText::new("Kill / Change Port")
+                                .size(12.0)
+                                .color(COLOR_WARNING),
+                        );
+                    } else {
+                        ui.label(RichText::new("FREE").size(12.0).color(COLOR_SUCCESS));
+                        ui.label("-");
+                        ui.label("-");
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Per-session filter state for the History tab.
+#[derive(Default)]
+pub struct HistoryViewState {
+    pub search: String,
+    pub all_projects: bool,
+}
+
+pub fn render_history(
+    ui: &mut egui::Ui,
+    entries: &[AuditEntry],
+    project: Option<&str>,
+    state: &mut HistoryViewState,
+    log_path: &std::path::Path,
+) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.heading(
+            RichText::new("History")
+                .size(28.0)
+                .color(COLOR_TEXT)
+                .strong(),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button("📄 Open Log File").clicked() {
+                utils::open_url(&log_path.to_string_lossy());
+            }
+        });
+    });
+    ui.label(
+        RichText::new("Every start, stop, teardown and change made through DockStack")
+            .size(14.0)
+            .color(COLOR_TEXT_DIM),
+    );
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search)
+                .hint_text("Filter actions")
+                .desired_width(240.0),
+        );
+        ui.add_space(12.0);
+        ui.checkbox(&mut state.all_projects, "All projects");
+    });
+    ui.add_space(12.0);
+
+    let search = state.search.to_lowercase();
+    let shown: Vec<_> = entries
+        .iter()
+        .rev()
+        .filter(|e| state.all_projects || project.is_none_or(|p| e.project == p))
+        .filter(|e| search.is_empty() || e.action.to_lowercase().contains(&search))
+        .collect();
+    if shown.is_empty() {
+        ui.label(RichText::new("Nothing recorded yet.").color(COLOR_TEXT_MUTED));
+        return;
+    }
+
+    ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("history_grid")
+            .spacing(Vec2::new(24.0, 8.0))
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new("WHEN").strong().color(COLOR_TEXT_MUTED));
+                if state.all_projects {
+                    ui.label(RichText::new("PROJECT").strong().color(COLOR_TEXT_MUTED));
+                }
+                ui.label(RichText::new("ACTION").strong().color(COLOR_TEXT_MUTED));
+                ui.label(RichText::new("USER").strong().color(COLOR_TEXT_MUTED));
+                ui.end_row();
+
+                for entry in shown {
+                    let when = entry
+                        .time()
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    ui.label(
+                        RichText::new(when)
+                            .monospace()
+                            .size(12.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                    if state.all_projects {
+                        let name = if entry.project.is_empty() {
+                            "—"
+                        } else {
+                            &entry.project
+                        };
+                        ui.label(RichText::new(name).size(12.0).color(COLOR_TEXT));
+                    }
+                    let color = if entry.action.starts_with("Destroyed")
+                        || entry.action.starts_with("Restored snapshot")
+                    {
+                        COLOR_WARNING
+                    } else {
+                        COLOR_TEXT
+                    };
+                    ui.label(RichText::new(&entry.action).size(12.0).color(color));
+                    ui.label(
+                        RichText::new(&entry.user)
+                            .size(12.0)
+                            .color(COLOR_TEXT_MUTED),
+                    );
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+const PROJECT_ICONS: [&str; 10] = ["📂", "🚀", "🛒", "🏢", "🧪", "🐘", "🐍", "💎", "🌐", "🎮"];
+
+fn render_appearance_settings(ui: &mut egui::Ui, project: &mut ProjectConfig, changed: &mut bool) {
+    egui::Grid::new("project_appearance")
+        .num_columns(2)
+        .spacing([16.0, 8.0])
+        .show(ui, |ui| {
+            ui.label("Icon:");
+            ui.horizontal_wrapped(|ui| {
+                for icon in PROJECT_ICONS {
+                    let selected = project.display_icon() == icon;
+                    if ui.selectable_label(selected, icon).clicked() {
+                        project.icon = icon.to_string();
+                        *changed = true;
+                    }
+                }
+                *changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut project.icon)
+                            .desired_width(40.0)
+                            .hint_text("📂"),
+                    )
+                    .on_hover_text("Any emoji or short text")
+                    .changed();
+            });
+            ui.end_row();
+
+            ui.label("Accent:");
+            ui.horizontal(|ui| {
+                let mut custom = project.accent.is_some();
+                if ui.checkbox(&mut custom, "Custom color").changed() {
+                    project.accent =
+                        custom.then(|| [COLOR_PRIMARY.r(), COLOR_PRIMARY.g(), COLOR_PRIMARY.b()]);
+                    *changed = true;
+                }
+                if let Some(accent) = &mut project.accent {
+                    *changed |= ui.color_edit_button_srgb(accent).changed();
+                }
+            });
+            ui.end_row();
+        });
+}
+
+/// Colors offered for new tags, in order.
+const TAG_COLORS: [Color32; 6] = [
+    COLOR_PRIMARY,
+    COLOR_ACCENT,
+    COLOR_SUCCESS,
+    COLOR_WARNING,
+    COLOR_SECONDARY,
+    COLOR_INFO,
+];
+
+fn render_tag_editor(
+    ui: &mut egui::Ui,
+    tags: &mut Vec<ProjectTag>,
+    known_tags: &[ProjectTag],
+    changed: &mut bool,
+) {
+    let mut remove = None;
+    ui.horizontal_wrapped(|ui| {
+        for (i, tag) in tags.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                *changed |= ui.color_edit_button_srgb(&mut tag.color).changed();
+                tag_chip(ui, &tag.name, tag.color, true);
+                if ui.small_button("✖").on_hover_text("Remove tag").clicked() {
+                    remove = Some(i);
+                }
+                ui.add_space(8.0);
+            });
+        }
+    });
+    if let Some(i) = remove {
+        tags.remove(i);
+        *changed = true;
+    }
+
+    let new_tag_id = ui.id().with("new_tag");
+    let mut new_tag: String = ui.data(|d| d.get_temp(new_tag_id)).unwrap_or_default();
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        let input = ui.add(
+            egui::TextEdit::singleline(&mut new_tag)
+                .desired_width(160.0)
+                .hint_text("client-acme"),
+        );
+        let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let name = new_tag.trim().to_string();
+        let exists = tags.iter().any(|t| t.name.eq_ignore_ascii_case(&name));
+        if (ui.button("➕ Add Tag").clicked() || submitted) && !name.is_empty() && !exists {
+            // Keep the color the tag already has on other projects
+            let color = known_tags
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(&name))
+                .map(|t| t.color)
+                .unwrap_or_else(|| {
+                    let c = TAG_COLORS[known_tags.len() % TAG_COLORS.len()];
+                    [c.r(), c.g(), c.b()]
+                });
+            tags.push(ProjectTag { name, color });
+            new_tag.clear();
+            *changed = true;
+        }
+    });
+
+    let suggestions: Vec<_> = known_tags
+        .iter()
+        .filter(|k| !tags.iter().any(|t| t.name.eq_ignore_ascii_case(&k.name)))
+        .collect();
+    if !suggestions.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                RichText::new("Used elsewhere:")
+                    .size(11.0)
+                    .color(COLOR_TEXT_MUTED),
+            );
+            for tag in suggestions {
+                if tag_chip(ui, &tag.name, tag.color, false).clicked() {
+                    tags.push(tag.clone());
+                    *changed = true;
+                }
+            }
+        });
+    }
+    ui.data_mut(|d| d.insert_temp(new_tag_id, new_tag));
+}
+
+fn render_network_settings(ui: &mut egui::Ui, network: &mut NetworkConfig, changed: &mut bool) {
+    egui::Grid::new("network_settings")
+        .num_columns(2)
+        .spacing(Vec2::new(16.0, 8.0))
+        .show(ui, |ui| {
+            ui.label("IPv4 subnet:");
+            ui.horizontal(|ui| {
+                *changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut network.subnet)
+                            .hint_text("auto, e.g. 172.28.0.0/16")
+                            .desired_width(200.0),
+                    )
+                    .changed();
+                cidr_hint(ui, &network.subnet);
+            });
+            ui.end_row();
+
+            ui.label("IPv6:");
+            ui.horizontal(|ui| {
+                *changed |= ui.checkbox(&mut network.ipv6, "Enable").changed();
+                if network.ipv6 {
+                    *changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut network.ipv6_subnet)
+                                .hint_text("fd00:dead:beef::/48")
+                                .desired_width(200.0),
+                        )
+                        .changed();
+                    cidr_hint(ui, &network.ipv6_subnet);
+                }
+            });
+            ui.end_row();
+
+            ui.label("External networks:");
+            ui.vertical(|ui| {
+                let mut remove = None;
+                for (i, name) in network.external.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(name).monospace().color(COLOR_PRIMARY));
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    network.external.remove(i);
+                    *changed = true;
+                }
+
+                let new_id = egui::Id::new("new_external_network");
+                let mut new_name =
+                    ui.data_mut(|d| d.get_temp::<String>(new_id).unwrap_or_default());
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut new_name)
+                            .hint_text("existing network name")
+                            .desired_width(200.0),
+                    );
+                    let name = new_name.trim().to_string();
+                    if ui
+                        .add_enabled(
+                            !name.is_empty() && !network.external.contains(&name),
+                            egui::Button::new("➕ Join"),
+                        )
+                        .on_hover_text(
+                            "Every service also joins this network; it must already exist",
+                        )
+                        .clicked()
+                    {
+                        network.external.push(name);
+                        new_name.clear();
+                        *changed = true;
+                    }
+                });
+                ui.data_mut(|d| d.insert_temp(new_id, new_name));
+            });
+            ui.end_row();
+        });
+}
+
+fn cidr_hint(ui: &mut egui::Ui, value: &str) {
+    if !value.trim().is_empty() && !is_valid_cidr(value) {
+        ui.label(
+            RichText::new("✘ not a CIDR block, ignored")
+                .size(11.0)
+                .color(COLOR_ERROR),
+        );
+    }
+}
+
+/// LAN addresses of the project's web UIs, with QR codes and firewall hints.
+fn render_lan_urls(ui: &mut egui::Ui, project: &ProjectConfig) {
+    let ip_id = egui::Id::new("lan_ip");
+    let lan_ip = ui.ctx().memory_mut(|m| {
+        m.data
+            .get_temp_mut_or_insert_with(ip_id, utils::lan_ip)
+            .clone()
+    });
+    ui.add_space(8.0);
+    let ip = match lan_ip {
+        Ok(ip) => ip,
+        Err(e) => {
+            ui.label(RichText::new(e.to_string()).color(COLOR_WARNING));
+            return;
+        }
+    };
+    ui.label(
+        RichText::new(format!("LAN IP: {}", ip))
+            .strong()
+            .color(COLOR_PRIMARY),
+    );
+
+    let registry = get_service_registry();
+    for info in registry.iter().filter(|i| {
+        matches!(
+            i.category,
+            ServiceCategory::WebServer | ServiceCategory::Admin
+        )
+    }) {
+        let published = project
+            .services
+            .get(&info.name)
+            .is_some_and(|s| s.enabled && !s.internal);
+        if !published {
+            continue;
+        }
+        let url = format!("http://{}:{}", ip, project.host_port(&info.name));
+        egui::CollapsingHeader::new(format!("{}  {}", info.display_name, url))
+            .id_salt(("lan_qr", &info.name))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    qr_code(ui, &url, 160.0);
+                    ui.hyperlink_to("Open", &url);
+                });
+            });
+    }
+
+    ui.add_space(8.0);
+    let hint = if cfg!(target_os = "windows") {
+        "Windows Defender Firewall may block inbound connections; allow Docker Desktop on private networks."
+    } else if cfg!(target_os = "macos") {
+        "If the macOS firewall is on, allow incoming connections for Docker in System Settings > Network > Firewall."
+    } else {
+        "If ufw or firewalld is active, allow these ports (e.g. `sudo ufw allow 8080/tcp`). Only use on trusted networks."
+    };
+    ui.label(
+        RichText::new(format!("⚠ {}", hint))
+            .size(12.0)
+            .color(COLOR_WARNING),
+    );
+}
+
+/// The Browse Images dialog: query, results and the service being set up.
+#[derive(Default)]
+pub struct ImageBrowserState {
+    pub open: bool,
+    pub query: String,
+    pub searched: String, // Query of the search in flight or shown
+    pub pending: bool,
+    pub results: Option<Result<Vec<HubImage>, String>>,
+    pub selected: Option<HubImage>,
+    pub name: String,
+    pub tag: String,
+    pub host_port: u16,
+    pub container_port: u16,
+}
+
+/// Actions requested from the Browse Images dialog.
+#[derive(Default)]
+pub struct ImageBrowserActions {
+    pub search: Option<String>,
+    /// Service name and config to add to the active project.
+    pub create: Option<(String, ServiceConfig)>,
+}
+
+/// Search Docker Hub and turn a result into a custom service.
+pub fn render_image_browser(
+    ctx: &egui::Context,
+    state: &mut ImageBrowserState,
+    actions: &mut ImageBrowserActions,
+) {
+    let mut open = state.open;
+    egui::Window::new("Browse Images")
+        .id(egui::Id::new("image_browser_window"))
+        .collapsible(false)
+        .default_size([560.0, 480.0])
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if let Some(image) = state.selected.clone() {
+                render_image_service_form(ui, state, &image, actions);
+                return;
+            }
+            ui.horizontal(|ui| {
+                let input = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text("n8n, grafana, wiremock…")
+                        .desired_width(320.0),
+                );
+                let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let query = state.query.trim();
+                if (ui
+                    .add_enabled(!query.is_empty(), egui::Button::new("Search"))
+                    .clicked()
+                    || submitted)
+                    && !query.is_empty()
+                {
+                    state.searched = query.to_string();
+                    state.pending = true;
+                    actions.search = Some(state.searched.clone());
+                }
+                if state.pending {
+                    ui.spinner();
+                }
+            });
+            ui.add_space(8.0);
+            match &state.results {
+                None => {
+                    ui.label(
+                        RichText::new("Search Docker Hub for an image to run as a service.")
+                            .color(COLOR_TEXT_DIM),
+                    );
+                }
+                Some(Err(e)) => {
+                    ui.label(RichText::new(e).color(COLOR_ERROR));
+                }
+                Some(Ok(images)) if images.is_empty() => {
+                    ui.label(
+                        RichText::new(format!("No images match \"{}\".", state.searched))
+                            .color(COLOR_TEXT_DIM),
+                    );
+                }
+                Some(Ok(images)) => {
+                    let mut chosen = None;
+                    ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                        for image in images {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&image.name).strong().color(COLOR_TEXT));
+                                if image.official {
+                                    ui.label(
+                                        RichText::new("OFFICIAL")
+                                            .size(9.0)
+                                            .strong()
+                                            .color(COLOR_SUCCESS),
+                                    );
+                                }
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.small_button("➕ Use").clicked() {
+                                            chosen = Some(image.clone());
+                                        }
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "⭐ {}   ⬇ {}",
+                                                hub::compact_count(image.stars),
+                                                hub::compact_count(image.pulls)
+                                            ))
+                                            .size(11.0)
+                                            .color(COLOR_TEXT_DIM),
+                                        );
+                                    },
+                                );
+                            });
+                            if !image.description.is_empty() {
+                                ui.label(
+                                    RichText::new(&image.description)
+                                        .size(11.0)
+                                        .color(COLOR_TEXT_DIM),
+                                );
+                            }
+                            ui.separator();
+                        }
+                    });
+                    if let Some(image) = chosen {
+                        state.name = hub::service_name(&image.name);
+                        state.tag = "latest".to_string();
+                        state.host_port = PortScanner::find_available_port(8080);
+                        state.container_port = 80;
+                        state.selected = Some(image);
+                    }
+                }
+            }
+        });
+    state.open &= open;
+}
+
+/// Name, tag and ports of the service made from the chosen image.
+fn render_image_service_form(
+    ui: &mut egui::Ui,
+    state: &mut ImageBrowserState,
+    image: &HubImage,
+    actions: &mut ImageBrowserActions,
+) {
+    ui.label(RichText::new(&image.name).size(16.0).strong());
+    if !image.description.is_empty() {
+        ui.label(RichText::new(&image.description).color(COLOR_TEXT_DIM));
+    }
+    ui.add_space(8.0);
+    egui::Grid::new("image_service_form")
+        .num_columns(2)
+        .spacing([12.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Service name:");
+            ui.text_edit_singleline(&mut state.name);
+            ui.end_row();
+            ui.label("Tag:");
+            ui.text_edit_singleline(&mut state.tag);
+            ui.end_row();
+            ui.label("Host port:");
+            ui.add(egui::DragValue::new(&mut state.host_port).range(1..=65535));
+            ui.end_row();
+            ui.label("Container port:");
+            ui.add(egui::DragValue::new(&mut state.container_port).range(1..=65535))
+                .on_hover_text("The port the image listens on; see its Docker Hub page");
+            ui.end_row();
+        });
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        let name = hub::service_name(&state.name);
+        if ui.button("➕ Add Service").clicked() {
+            let mut settings = HashMap::new();
+            settings.insert(
+                "container_port".to_string(),
+                state.container_port.to_string(),
+            );
+            let tag = state.tag.trim();
+            let svc = ServiceConfig {
+                enabled: true,
+                is_custom: true,
+                display_name: Some(image.name.clone()),
+                image: Some(image.name.clone()),
+                version: if tag.is_empty() { "latest" } else { tag }.to_string(),
+                port: state.host_port,
+                settings,
+                ..ServiceConfig::default()
+            };
+            actions.create = Some((name, svc));
+            state.selected = None;
+            state.open = false;
+        }
+        if ui.button("Back").clicked() {
+            state.selected = None;
+        }
+        ui.hyperlink_to("Docker Hub page", hub_page(&image.name));
+    });
+}
+
+/// `redis` → `https://hub.docker.com/_/redis`, `n8nio/n8n` → `…/r/n8nio/n8n`.
+fn hub_page(image: &str) -> String {
+    if image.contains('/') {
+        format!("https://hub.docker.com/r/{}", image)
+    } else {
+        format!("https://hub.docker.com/_/{}", image)
+    }
+}
+
+/// Settings a plugin declares for its service. Returns whether one changed.
+fn render_plugin_settings(
+    ui: &mut egui::Ui,
+    id: &str,
+    plugin: &ServicePlugin,
+    svc: &mut ServiceConfig,
+) -> bool {
+    if plugin.settings.is_empty() {
+        return false;
+    }
+    let mut changed = false;
+    ui.label(RichText::new(&plugin.name).strong().color(COLOR_ACCENT));
+    egui::Grid::new(format!("plugin_settings_{}", id)).show(ui, |ui| {
+        for setting in &plugin.settings {
+            ui.label(format!("{}:", setting.label));
+            let mut value = svc.settings.get(&setting.key).cloned().unwrap_or_default();
+            let mut response = ui.add(
+                egui::TextEdit::singleline(&mut value)
+                    .desired_width(200.0)
+                    .hint_text(&setting.default),
+            );
+            if !setting.help.is_empty() {
+                response = response.on_hover_text(&setting.help);
+            }
+            if response.changed() {
+                svc.settings.insert(setting.key.clone(), value);
+                changed = true;
+            }
+            ui.end_row();
+        }
+    });
+    ui.add_space(8.0);
+    ui.separator();
+    ui.add_space(8.0);
+    changed
+}
+
+/// The service catalog dialog: its filter and the project entries are added to.
+#[derive(Default)]
+pub struct CatalogViewState {
+    pub open: bool,
+    pub filter: String,
+    pub project: Option<String>, // Project id; the active project when unset
+}
+
+/// Actions requested from the service catalog dialog.
+#[derive(Default)]
+pub struct CatalogActions {
+    /// Project id and the entry to add to it.
+    pub add: Option<(String, CatalogEntry)>,
+}
+
+pub fn render_service_catalog(
+    ctx: &egui::Context,
+    config: &AppConfig,
+    catalog: &[CatalogEntry],
+    state: &mut CatalogViewState,
+    actions: &mut CatalogActions,
+) {
+    let mut open = state.open;
+    egui::Window::new("Service Catalog")
+        .id(egui::Id::new("service_catalog_window"))
+        .collapsible(false)
+        .default_size([560.0, 480.0])
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let projects = config.projects_by_recency();
+            let target = state
+                .project
+                .clone()
+                .or_else(|| config.active_project_id.clone());
+            let project = projects.iter().find(|p| Some(&p.id) == target.as_ref());
+            ui.horizontal(|ui| {
+                ui.label("Add to:");
+                egui::ComboBox::from_id_salt("catalog_project")
+                    .selected_text(project.map_or("Choose a project", |p| p.name.as_str()))
+                    .show_ui(ui, |ui| {
+                        for p in &projects {
+                            if ui
+                                .selectable_label(Some(&p.id) == target.as_ref(), &p.name)
+                                .clicked()
+                            {
+                                state.project = Some(p.id.clone());
+                            }
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.filter)
+                        .hint_text("Filter…")
+                        .desired_width(180.0),
+                );
+            });
+            ui.add_space(8.0);
+            let filter = state.filter.trim().to_lowercase();
+            let entries: Vec<&CatalogEntry> = catalog
+                .iter()
+                .filter(|e| {
+                    filter.is_empty()
+                        || [&e.name, &e.category, &e.description, &e.image]
+                            .iter()
+                            .any(|field| field.to_lowercase().contains(&filter))
+                })
+                .collect();
+            if entries.is_empty() {
+                ui.label(RichText::new("No catalog entries match.").color(COLOR_TEXT_DIM));
+            }
+            ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                let mut category = None;
+                for entry in entries {
+                    if category != Some(&entry.category) {
+                        category = Some(&entry.category);
+                        ui.add_space(4.0);
+                        ui.label(
+                            RichText::new(entry.category.to_uppercase())
+                                .size(10.0)
+                                .color(COLOR_TEXT_MUTED)
+                                .strong()
+                                .extra_letter_spacing(1.2),
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&entry.icon).size(16.0));
+                        ui.label(RichText::new(&entry.name).strong().color(COLOR_TEXT));
+                        ui.label(
+                            RichText::new(format!("{}:{}", entry.image, entry.version))
+                                .size(11.0)
+                                .color(COLOR_TEXT_MUTED),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let Some(project) = project else {
+                                return;
+                            };
+                            let added = project
+                                .services
+                                .values()
+                                .any(|s| s.image.as_deref() == Some(entry.image.as_str()));
+                            if ui
+                                .small_button("➕ Add")
+                                .on_hover_text(format!("Add {} to {}", entry.name, project.name))
+                                .clicked()
+                            {
+                                actions.add = Some((project.id.clone(), entry.clone()));
+                            }
+                            if added {
+                                ui.label(
+                                    RichText::new("In project").size(11.0).color(COLOR_TEXT_DIM),
+                                );
+                                status_icon(ui, StatusKind::Ok, "In project");
+                            }
+                        });
+                    });
+                    if !entry.description.is_empty() {
+                        ui.label(
+                            RichText::new(&entry.description)
+                                .size(11.0)
+                                .color(COLOR_TEXT_DIM),
+                        );
+                    }
+                    ui.separator();
+                }
+            });
+            ui.add_space(4.0);
+            ui.label(
+                RichText::new(format!(
+                    "Add or override entries in {}",
+                    catalog::user_catalog_path().display()
+                ))
+                .size(11.0)
+                .color(COLOR_TEXT_MUTED),
+            );
+        });
+    state.open &= open;
+}
+
+/// Draft, last response and in-flight flag for the API client tab.
+#[derive(Default)]
+pub struct RequestViewState {
+    /// Project the draft belongs to; switching projects starts a fresh draft.
+    pub project_id: String,
+    pub draft: SavedRequest,
+    pub response: Option<Result<HttpResponse, DockStackError>>,
+    pub pending: bool,
+}
+
+pub fn render_requests(
+    ui: &mut egui::Ui,
+    project: Option<&mut ProjectConfig>,
+    state: &mut RequestViewState,
+    send: &mut bool,
+    collection_changed: &mut bool,
+) {
+    let Some(project) = project else {
+        ui.label(RichText::new("No active project.").color(COLOR_TEXT_MUTED));
+        return;
+    };
+
+    if state.project_id != project.id {
+        state.project_id = project.id.clone();
+        state.draft = SavedRequest::default();
+        state.response = None;
+    }
+    if state.draft.method.is_empty() {
+        state.draft.method = "GET".to_string();
+    }
+    if state.draft.url.is_empty() {
+        let scheme = if project.ssl_enabled { "https" } else { "http" };
+        let host = if project.domain.is_empty() {
+            "localhost"
+        } else {
+            project.domain.as_str()
+        };
+        state.draft.url = format!("{}://{}/", scheme, host);
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("request_method")
+            .selected_text(state.draft.method.as_str())
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for method in METHODS {
+                    ui.selectable_value(&mut state.draft.method, method.to_string(), *method);
+                }
+            });
+        ui.add(
+            egui::TextEdit::singleline(&mut state.draft.url)
+                .desired_width(ui.available_width() - 110.0)
+                .font(egui::TextStyle::Monospace),
+        );
+        if state.pending {
+            ui.add(egui::Spinner::new());
+        } else if primary_button(ui, "Send").clicked() {
+            *send = true;
+        }
+    });
+    ui.add_space(12.0);
+
+    ui.columns(2, |columns| {
+        // Left: request editor and collection
+        let ui = &mut columns[0];
+        ui.label(
+            RichText::new("HEADERS")
+                .size(10.0)
+                .strong()
+                .color(COLOR_TEXT_MUTED),
+        );
+        ui.add(
+            egui::TextEdit::multiline(&mut state.draft.headers)
+                .hint_text("Content-Type: application/json")
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .font(egui::TextStyle::Monospace),
+        );
+        ui.add_space(8.0);
+        ui.label(
+            RichText::new("BODY")
+                .size(10.0)
+                .strong()
+                .color(COLOR_TEXT_MUTED),
+        );
+        ui.add(
+            egui::TextEdit::multiline(&mut state.draft.body)
+                .desired_rows(8)
+                .desired_width(f32::INFINITY)
+                .font(egui::TextStyle::Monospace),
+        );
+        ui.add_space(12.0);
+
+        ui.label(
+            RichText::new("SAVED REQUESTS")
+                .size(10.0)
+                .strong()
+                .color(COLOR_TEXT_MUTED),
+        );
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.draft.name)
+                    .hint_text("Request name")
+                    .desired_width(200.0),
+            );
+            let name = state.draft.name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("💾 Save"))
+                .clicked()
+            {
+                state.draft.name = name.to_string();
+                match project
+                    .saved_requests
+                    .iter_mut()
+                    .find(|r| r.name == state.draft.name)
+                {
+                    Some(existing) => *existing = state.draft.clone(),
+                    None => project.saved_requests.push(state.draft.clone()),
+                }
+                *collection_changed = true;
+            }
+        });
+        let mut remove = None;
+        for (i, saved) in project.saved_requests.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(
+                        state.draft.name == saved.name,
+                        format!("{}  {}", saved.method, saved.name),
+                    )
+                    .on_hover_text(&saved.url)
+                    .clicked()
+                {
+                    state.draft = saved.clone();
+                }
+                if ui.small_button("✖").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            project.saved_requests.remove(i);
+            *collection_changed = true;
+        }
+
+        // Right: response viewer
+        let ui = &mut columns[1];
+        ui.label(
+            RichText::new("RESPONSE")
+                .size(10.0)
+                .strong()
+                .color(COLOR_TEXT_MUTED),
+        );
+        match &state.response {
+            None => {
+                ui.label(
+                    RichText::new("Send a request to see the response.").color(COLOR_TEXT_MUTED),
+                );
+            }
+            Some(Err(e)) => {
+                ui.label(RichText::new(e.to_string()).color(COLOR_ERROR));
+            }
+            Some(Ok(resp)) => {
+                let status_color = match resp.status {
+                    200..=299 => COLOR_SUCCESS,
+                    300..=399 => COLOR_INFO,
+                    400..=499 => COLOR_WARNING,
+                    _ => COLOR_ERROR,
+                };
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("{} {}", resp.status, resp.status_text))
+                            .strong()
+                            .color(status_color),
+                    );
+                    ui.label(
+                        RichText::new(format!("{} ms", resp.elapsed.as_millis()))
+                            .size(12.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                    ui.label(
+                        RichText::new(utils::format_bytes(resp.body.len() as u64))
+                            .size(12.0)
+                            .color(COLOR_TEXT_DIM),
+                    );
+                });
+                egui::CollapsingHeader::new(format!("Headers ({})", resp.headers.len()))
+                    .id_salt("response_headers")
+                    .show(ui, |ui| {
+                        for (name, value) in &resp.headers {
+                            ui.label(
+                                RichText::new(format!("{}: {}", name, value))
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(COLOR_TEXT_DIM),
+                            );
+                        }
+                    });
+                egui::Frame::new()
+                    .fill(COLOR_BG_APP)
+                    .stroke(Stroke::new(1.0, COLOR_BORDER))
+                    .corner_radius(egui::CornerRadius::same(8))
+                    .inner_margin(12.0)
+                    .show(ui, |ui| {
+                        ScrollArea::both()
+                            .id_salt("response_body")
+                            .auto_shrink([false; 2])
+                            .show(ui, |ui| {
+                                ui.label(RichText::new(&resp.body).monospace().size(12.0));
+                            });
+                    });
+            }
+        }
+    });
+}
+
+/// Stands in for a tab while it is shown in a window of its own.
+pub fn render_detached_notice(ui: &mut egui::Ui, name: &str, bring_back: &mut bool) {
+    card_frame(ui, |ui| {
+        ui.label(
+            RichText::new(format!("{} is open in its own window", name))
+                .size(16.0)
+                .strong(),
+        );
+        ui.separator();
+        ui.label(RichText::new("Close that window, or bring it back here.").color(COLOR_TEXT_DIM));
+        ui.add_space(8.0);
+        if ui.button("⤓ Bring Back").clicked() {
+            *bring_back = true;
+        }
+    });
+}
+
+pub fn render_monitor(
+    ui: &mut egui::Ui,
+    _sys_stats: &SystemStats,
+    container_stats: &[ContainerStats],
+    cpu_history: &[f32],
+    mem_history: &[f32],
+    export: &mut Option<std::path::PathBuf>,
+) {
+    ScrollArea::vertical().show(ui, |ui| {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading(
+                RichText::new("Live Monitor")
+                    .size(28.0)
+                    .color(COLOR_TEXT)
+                    .strong(),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let name = format!(
+                    "dockstack-metrics-{}",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S")
+                );
+                for (label, ext) in [("⬇ Export JSON", "json"), ("⬇ Export CSV", "csv")] {
+                    if ui
+                        .button(label)
+                        .on_hover_text("Save the last 10 minutes of host and container metrics")
+                        .clicked()
+                    {
+                        *export = rfd::FileDialog::new()
+                            .add_filter(ext.to_uppercase(), &[ext])
+                            .set_file_name(format!("{}.{}", name, ext))
+                            .save_file();
+                    }
+                }
+            });
+        });
+        ui.add_space(24.0);
+
+        ui.horizontal(|ui| {
+            card_frame(ui, |ui| {
+                ui.set_min_width(300.0);
+                ui.label(
+                    RichText::new("CPU History")
+                        .size(14.0)
+                        .color(COLOR_TEXT_DIM),
+                );
+                sparkline(
+                    ui,
+                    cpu_history,
+                    120.0,
+                    COLOR_PRIMARY,
+                    Vec2::new(280.0, 80.0),
+                );
+            });
+            card_frame(ui, |ui| {
+                ui.set_min_width(300.0);
+                ui.label(
+                    RichText::new("Memory History")
+                        .size(14.0)
+                        .color(COLOR_TEXT_DIM),
+                );
+                sparkline(
+                    ui,
+                    mem_history,
+                    120.0,
+                    COLOR_SECONDARY,
+                    Vec2::new(280.0, 80.0),
+                );
+            });
+        });
+
+        ui.add_space(24.0);
+
+        if !container_stats.is_empty() {
+            ui.label(RichText::new("Container Live Usage").size(16.0).strong());
+            ui.add_space(12.0);
+            egui::Grid::new("monitor_grid")
+                .striped(true)
+                .spacing(Vec2::new(24.0, 12.0))
+                .show(ui, |ui| {
+                    ui.label(RichText::new("NAME").strong().color(COLOR_TEXT_MUTED));
+                    ui.label(RichText::new("CPU").strong().color(COLOR_TEXT_MUTED));
+                    ui.label(RichText::new("MEM").strong().color(COLOR_TEXT_MUTED));
+                    ui.end_row();
+
+                    for s in container_stats {
+                        ui.label(RichText::new(&s.name).color(COLOR_TEXT));
+                        ui.label(RichText::new(&s.cpu_percent).color(COLOR_PRIMARY));
+                        ui.label(RichText::new(&s.mem_usage).color(COLOR_SECONDARY));
+                        ui.end_row();
+                    }
+                });
+        }
+    });
+}
+/// Actions requested from the doctor report.
+#[derive(Default)]
+pub struct DoctorActions {
+    pub fix: Option<DoctorFix>,
+    pub rerun: bool,
+    /// Where to save the report as text.
+    pub save_report: Option<std::path::PathBuf>,
+}
+
+/// Checks of a doctor report with their fix buttons, plus copy/save of the text report.
+pub fn render_doctor_report(ui: &mut egui::Ui, report: &DoctorReport, actions: &mut DoctorActions) {
+    let (summary, color) = match report.worst() {
+        CheckStatus::Pass => ("Everything looks healthy", COLOR_SUCCESS),
+        CheckStatus::Warn => ("Some checks need attention", COLOR_WARNING),
+        CheckStatus::Fail => ("Problems found", COLOR_ERROR),
+    };
+    ui.label(RichText::new(summary).strong().color(color));
+    ui.label(
+        RichText::new(format!(
+            "{} · {}",
+            report.project,
+            report.created_at.format("%Y-%m-%d %H:%M:%S")
+        ))
+        .size(12.0)
+        .color(COLOR_TEXT_DIM),
+    );
+    ui.add_space(8.0);
+
+    ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+        egui::Grid::new("doctor_checks")
+            .striped(true)
+            .spacing(Vec2::new(16.0, 8.0))
+            .show(ui, |ui| {
+                for check in &report.checks {
+                    let color = match check.status {
+                        CheckStatus::Pass => COLOR_SUCCESS,
+                        CheckStatus::Warn => COLOR_WARNING,
+                        CheckStatus::Fail => COLOR_ERROR,
+                    };
+                    ui.label(
+                        RichText::new(check.status.label())
+                            .strong()
+                            .monospace()
+                            .color(color),
+                    );
+                    ui.label(RichText::new(&check.name).strong().color(COLOR_TEXT));
+                    ui.vertical(|ui| {
+                        ui.label(RichText::new(&check.detail).color(COLOR_TEXT_DIM));
+                        if let Some(hint) = check.hint {
+                            ui.label(RichText::new(hint).size(12.0).color(COLOR_TEXT_MUTED));
+                        }
+                    });
+                    match &check.fix {
+                        Some(fix) => {
+                            if ui.button(fix.label()).clicked() {
+                                actions.fix = Some(fix.clone());
+                            }
+                        }
+                        None => {
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        if ui.button("🔄 Run Again").clicked() {
+            actions.rerun = true;
+        }
+        if ui.button("📋 Copy Report").clicked() {
+            ui.ctx().copy_text(report.to_text());
+        }
+        if ui.button("💾 Save Report…").clicked() {
+            let path = rfd::FileDialog::new()
+                .add_filter("Text", &["txt"])
+                .set_file_name(format!(
+                    "dockstack-doctor-{}.txt",
+                    report.created_at.format("%Y%m%d-%H%M%S")
+                ))
+                .save_file();
+            actions.save_report = path;
+        }
+    });
+}
+
+/// Findings of an image scan, most severe first, down to `min_severity`.
+pub fn render_scan_report(ui: &mut egui::Ui, report: &ScanReport, min_severity: &mut Severity) {
+    let severity_color = |severity: Severity| match severity {
+        Severity::Critical => COLOR_ERROR,
+        Severity::High => COLOR_WARNING,
+        Severity::Medium => COLOR_INFO,
+        Severity::Low | Severity::Unknown => COLOR_TEXT_DIM,
+    };
+    let (summary, color) = if report.findings.is_empty() && report.errors.is_empty() {
+        ("No known vulnerabilities", COLOR_SUCCESS)
+    } else if report.count(Severity::Critical) > 0 {
+        ("Critical vulnerabilities found", COLOR_ERROR)
+    } else {
+        ("Vulnerabilities found", COLOR_WARNING)
+    };
+    ui.label(RichText::new(summary).strong().color(color));
+    ui.label(
+        RichText::new(format!(
+            "{} · {} images · {} · {}",
+            report.project,
+            report.images,
+            report.scanner.label(),
+            report.created_at.format("%Y-%m-%d %H:%M:%S")
+        ))
+        .size(12.0)
+        .color(COLOR_TEXT_DIM),
+    );
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        for severity in Severity::ALL {
+            let text = format!("{} {}", report.count(severity), severity.label());
+            let selected = *min_severity == severity;
+            let button = ui
+                .selectable_label(
+                    selected,
+                    RichText::new(text)
+                        .monospace()
+                        .color(severity_color(severity)),
+                )
+                .on_hover_text("Show findings of this severity and above");
+            if button.clicked() {
+                *min_severity = severity;
+            }
+        }
+    });
+    ui.add_space(8.0);
+
+    let shown: Vec<_> = report
+        .findings
+        .iter()
+        .filter(|f| f.severity >= *min_severity)
+        .collect();
+    if shown.is_empty() {
+        ui.label(RichText::new("Nothing at this severity.").color(COLOR_TEXT_DIM));
+    }
+    ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+        egui::Grid::new("scan_findings")
+            .striped(true)
+            .spacing(Vec2::new(16.0, 6.0))
+            .show(ui, |ui| {
+                for header in [
+                    "Severity",
+                    "Vulnerability",
+                    "Package",
+                    "Installed",
+                    "Fixed in",
+                ] {
+                    ui.label(RichText::new(header).strong().color(COLOR_TEXT_DIM));
+                }
+                ui.label(RichText::new("Services").strong().color(COLOR_TEXT_DIM));
+                ui.end_row();
+                for finding in shown {
+                    ui.label(
+                        RichText::new(finding.severity.label())
+                            .monospace()
+                            .color(severity_color(finding.severity)),
+                    );
+                    ui.label(RichText::new(&finding.id).monospace().color(COLOR_TEXT))
+                        .on_hover_text(&finding.title);
+                    ui.label(&finding.package);
+                    ui.label(RichText::new(&finding.installed).color(COLOR_TEXT_DIM));
+                    let fixed = if finding.fixed.is_empty() {
+                        RichText::new("no fix yet").color(COLOR_TEXT_MUTED)
+                    } else {
+                        RichText::new(&finding.fixed).color(COLOR_SUCCESS)
+                    };
+                    ui.label(fixed);
+                    ui.label(RichText::new(finding.services.join(", ")).color(COLOR_TEXT_DIM))
+                        .on_hover_text(&finding.image);
+                    ui.end_row();
+                }
+            });
+    });
+    for error in &report.errors {
+        ui.label(RichText::new(format!("⚠ Not scanned: {}", error)).color(COLOR_WARNING));
+    }
+}
+
+/// Inputs of the Settings panel's Projects card and the clone in flight, if any.
+#[derive(Default)]
+pub struct NewProjectState {
+    pub name: String,
+    pub git_url: String,
+    pub git_available: bool,
+    pub clone_progress: Option<String>, // Latest git output while a clone runs
+}
+
+/// Actions requested from the Settings panel, handled by the app after rendering.
+#[derive(Default)]
+pub struct SettingsActions {
+    pub generate_ssl: bool,
+    pub remove_ssl: bool,
+    pub scan_orphans: bool,
+    pub remove_orphans: bool,
+    pub discover_containers: bool,
+    /// Compose file of a discovered project to import.
+    pub adopt: Option<std::path::PathBuf>,
+    /// Standalone containers to rebuild as a new project.
+    pub capture: Option<Vec<String>>,
+    /// Git URL to clone as a new project.
+    pub clone_repo: Option<String>,
+    pub create_snapshot: bool,
+    pub restore_snapshot: Option<std::path::PathBuf>,
+    pub run_doctor: bool,
+    pub update_gitignore: bool,
+    pub pull_images: bool,
+    pub export_diagnostics: Option<std::path::PathBuf>,
+    /// File to write the project's Kubernetes manifests to.
+    pub export_kubernetes: Option<std::path::PathBuf>,
+    /// CI file to write for the project's services, and where.
+    pub export_ci: Option<(CiFormat, std::path::PathBuf)>,
+    pub write_devcontainer: bool,
+    pub update_image_lock: bool,
+    /// Log or terminal buffer sizes were edited; apply them to the running buffers.
+    pub buffers_changed: bool,
+    /// The proxy settings were edited; give them to the next docker commands.
+    pub proxy_changed: bool,
+    /// A registry was added or removed; log in to the new list from now on.
+    pub registries_changed: bool,
+    /// Registry whose keyring password to delete.
+    pub remove_registry: Option<RegistryConfig>,
+    /// Registry to `docker login` to, with a new password to save first; empty
+    /// keeps the saved one.
+    pub login_registry: Option<(RegistryConfig, String)>,
+    pub run_maintenance: bool,
+    /// The status page was turned on or off, or moved to another port.
+    pub status_page_changed: bool,
+    /// Changes made in place, for the History tab: (project name, action).
+    pub audit: Vec<(String, String)>,
+    /// Settings were edited in place; write the config to disk.
+    pub save_config: bool,
+}
+
+/// Per-branch environment overlays of the active project. Returns whether they changed.
+fn render_branch_env(ui: &mut egui::Ui, project: &mut ProjectConfig, branch: Option<&str>) -> bool {
+    let mut changed = false;
+    let mut services: Vec<String> = project.enabled_services();
+    services.sort();
+    card_frame(ui, |ui| {
+        ui.label(RichText::new("Branch Environments").size(16.0).strong());
+        ui.separator();
+        let current = match branch {
+            Some(branch) => format!("Current branch: {}.", branch),
+            None => "The project directory is not a git repository.".to_string(),
+        };
+        ui.label(
+            RichText::new(format!(
+                "{} Variables below are added to a service's environment while the branch \
+                 matches (`feature/*` matches every feature branch).",
+                current
+            ))
+            .color(COLOR_TEXT_DIM),
+        );
+        ui.add_space(8.0);
+
+        let mut remove = None;
+        for (i, overlay) in project.branch_env.iter_mut().enumerate() {
+            let active = branch.is_some_and(|b| overlay.matches(b));
+            ui.horizontal(|ui| {
+                ui.label("Branch:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut overlay.branch)
+                            .desired_width(160.0)
+                            .hint_text("feature/*"),
+                    )
+                    .changed();
+                ui.label("Service:");
+                egui::ComboBox::from_id_salt(("branch_env_service", i))
+                    .selected_text(overlay.service.as_str())
+                    .show_ui(ui, |ui| {
+                        for service in &services {
+                            changed |= ui
+                                .selectable_value(&mut overlay.service, service.clone(), service)
+                                .changed();
+                        }
+                    });
+                if active {
+                    ui.label(RichText::new("● active").color(COLOR_SUCCESS));
+                }
+                if ui.button("🗑").on_hover_text("Remove overlay").clicked() {
+                    remove = Some(i);
+                }
+            });
+            changed |= ui
+                .add(
+                    egui::TextEdit::multiline(&mut overlay.vars)
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("MYSQL_DATABASE=app_feature")
+                        .font(egui::TextStyle::Monospace),
+                )
+                .changed();
+            ui.add_space(8.0);
+        }
+        if let Some(i) = remove {
+            project.branch_env.remove(i);
+            changed = true;
+        }
+        if ui.button("➕ Add Overlay").clicked() {
+            project.branch_env.push(BranchEnv {
+                branch: branch.unwrap_or_default().to_string(),
+                service: services.first().cloned().unwrap_or_default(),
+                vars: String::new(),
+            });
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Companions offered, and conflicts pointed out, after a service is enabled.
+#[derive(Clone)]
+struct DependencyPrompt {
+    service: String,
+    needs: Vec<(&'static str, Vec<&'static str>)>, // Reason, services that would meet it
+    hints: Vec<String>,
+    changed: bool, // A companion was enabled this frame
+}
+
+impl DependencyPrompt {
+    fn new(project: &ProjectConfig, service: &str) -> Option<Self> {
+        let needs: Vec<_> = services::missing_requirements(project, service)
+            .into_iter()
+            .map(|(requirement, options)| (requirement.reason, options))
+            .collect();
+        let hints = services::conflict_hints(project, service);
+        (!needs.is_empty() || !hints.is_empty()).then(|| Self {
+            service: service.to_string(),
+            needs,
+            hints,
+            changed: false,
+        })
+    }
+}
+
+/// Offer to enable the companions the prompt's service needs. Returns whether
+/// the prompt stays open.
+fn render_dependency_prompt(
+    ctx: &egui::Context,
+    prompt: &mut DependencyPrompt,
+    project: &mut ProjectConfig,
+    audit: &mut Vec<(String, String)>,
+) -> bool {
+    let title = services::get_service_info(&prompt.service)
+        .map(|i| i.display_name)
+        .unwrap_or_else(|| prompt.service.clone());
+    let mut open = true;
+    let mut dismissed = false;
+    egui::Window::new(format!("Enabled {}", title))
+        .id(egui::Id::new("dependency_prompt_window"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let mut met = None;
+            for (i, (reason, options)) in prompt.needs.iter().enumerate() {
+                ui.label(RichText::new(format!("{}.", reason)).color(COLOR_TEXT));
+                ui.horizontal(|ui| {
+                    for option in options {
+                        let name = services::get_service_info(option)
+                            .map(|i| i.display_name)
+                            .unwrap_or_else(|| option.to_string());
+                        if ui.button(format!("➕ Enable {}", name)).clicked() {
+                            if let Some(svc) = project.services.get_mut(*option) {
+                                svc.enabled = true;
+                                audit.push((
+                                    project.name.clone(),
+                                    format!("Enabled service {}", name),
+                                ));
+                                if let Some(off) = project.sync_web_topology(option) {
+                                    audit.push((
+                                        project.name.clone(),
+                                        format!("Disabled service {}", off),
+                                    ));
+                                }
+                                met = Some(i);
+                            }
+                        }
+                    }
+                });
+                ui.add_space(8.0);
+            }
+            if let Some(i) = met {
+                prompt.needs.remove(i);
+                prompt.changed = true;
+            }
+            for hint in &prompt.hints {
+                ui.label(RichText::new(format!("⚠ {}", hint)).color(COLOR_WARNING));
+            }
+            ui.add_space(8.0);
+            if ui.button("Dismiss").clicked() {
+                dismissed = true;
+            }
+        });
+    open && !dismissed && !(prompt.needs.is_empty() && prompt.hints.is_empty())
+}
+
+/// Random alphanumeric password for generated service logins.
+fn generate_password() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// New name for the service `id`, and whether to copy its volumes, once confirmed.
+fn render_rename(ui: &mut egui::Ui, id: &str) -> Option<(String, String, bool)> {
+    let state_id = ui.id().with("rename_service");
+    let (mut name, mut copy_data): (String, bool) = ui
+        .data(|d| d.get_temp(state_id))
+        .unwrap_or_else(|| (id.to_string(), true));
+    let mut rename = None;
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut name).desired_width(140.0));
+        let valid = !name.trim().is_empty() && name.trim() != id;
+        if ui
+            .add_enabled(valid, egui::Button::new("✏ Rename"))
+            .on_hover_text("The old container is removed when the stack next starts")
+            .clicked()
+        {
+            rename = Some((id.to_string(), name.trim().to_string(), copy_data));
+        }
+    });
+    ui.checkbox(&mut copy_data, "Copy data to the renamed volumes");
+    if rename.is_some() {
+        ui.data_mut(|d| d.remove::<(String, bool)>(state_id));
+    } else {
+        ui.data_mut(|d| d.insert_temp(state_id, (name, copy_data)));
+    }
+    rename
+}
+
+/// Extra PHP-FPM versions and the paths or hosts nginx sends to them. Sets
+/// `add_version` when a version should be added. Returns whether routes changed.
+fn render_php_routes(
+    ui: &mut egui::Ui,
+    routes: &mut Vec<PhpRoute>,
+    php_services: &[String],
+    add_version: &mut Option<String>,
+) -> bool {
+    let mut changed = false;
+    ui.label(
+        RichText::new("PHP Versions & Routes")
+            .strong()
+            .color(COLOR_ACCENT),
+    );
+    ui.label(
+        RichText::new(
+            "Run another PHP-FPM version next to this one and send a path or host name to it. \
+             Everything else stays on this service.",
+        )
+        .size(12.0)
+        .color(COLOR_TEXT_DIM),
+    );
+    ui.horizontal(|ui| {
+        let version_id = ui.id().with("new_php_version");
+        let mut version: String = ui.data(|d| d.get_temp(version_id)).unwrap_or_default();
+        ui.label("Version:");
+        ui.add(
+            egui::TextEdit::singleline(&mut version)
+                .desired_width(100.0)
+                .hint_text("7.4-fpm"),
+        );
+        let valid = !version.trim().is_empty();
+        if ui
+            .add_enabled(valid, egui::Button::new("➕ Add PHP Version"))
+            .clicked()
+        {
+            *add_version = Some(version.trim().to_string());
+            version.clear();
+        }
+        ui.data_mut(|d| d.insert_temp(version_id, version));
+    });
+
+    let mut remove = None;
+    egui::Grid::new("php_routes").num_columns(4).show(ui, |ui| {
+        for (i, route) in routes.iter_mut().enumerate() {
+            egui::ComboBox::from_id_salt(("php_route", i))
+                .selected_text(route.service.as_str())
+                .show_ui(ui, |ui| {
+                    for service in php_services {
+                        changed |= ui
+                            .selectable_value(&mut route.service, service.clone(), service)
+                            .changed();
+                    }
+                });
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut route.path)
+                        .desired_width(140.0)
+                        .hint_text("/legacy/"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut route.host)
+                        .desired_width(180.0)
+                        .hint_text("legacy.shop.test"),
+                )
+                .changed();
+            if ui.button("🗑").on_hover_text("Remove route").clicked() {
+                remove = Some(i);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(i) = remove {
+        routes.remove(i);
+        changed = true;
+    }
+    let instances: Vec<&String> = php_services.iter().filter(|s| *s != "php").collect();
+    if let Some(first) = instances.first() {
+        if ui.button("➕ Add Route").clicked() {
+            routes.push(PhpRoute {
+                service: first.to_string(),
+                ..PhpRoute::default()
+            });
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Named environment profiles of the active project. Returns whether they changed.
+fn render_profiles(ui: &mut egui::Ui, project: &mut ProjectConfig) -> bool {
+    let mut changed = false;
+    let mut services: Vec<String> = project.enabled_services();
+    services.sort();
+    card_frame(ui, |ui| {
+        ui.label(RichText::new("Environment Profiles").size(16.0).strong());
+        ui.separator();
+        ui.label(
+            RichText::new(
+                "Variants such as test or staging, picked on the Dashboard. A profile runs the \
+                 ticked services (all when none are ticked) with its own ports and variables; \
+                 its name is added to the container names.",
+            )
+            .color(COLOR_TEXT_DIM),
+        );
+        ui.add_space(8.0);
+
+        let mut remove = None;
+        for (i, profile) in project.profiles.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                let was_active = project.active_profile == profile.name;
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut profile.name)
+                        .desired_width(140.0)
+                        .hint_text("staging"),
+                );
+                if response.changed() {
+                    if was_active {
+                        project.active_profile = profile.name.clone();
+                    }
+                    changed = true;
+                }
+                if ui.button("🗑").on_hover_text("Remove profile").clicked() {
+                    remove = Some(i);
+                }
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Services:");
+                for service in &services {
+                    let mut on = profile.services.contains(service);
+                    if ui.checkbox(&mut on, service).changed() {
+                        if on {
+                            profile.services.push(service.clone());
+                        } else {
+                            profile.services.retain(|s| s != service);
+                        }
+                        changed = true;
+                    }
+                }
+            });
+            let mut remove_override = None;
+            for (j, o) in profile.overrides.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label("Service:");
+                    egui::ComboBox::from_id_salt(("profile_override", i, j))
+                        .selected_text(o.service.as_str())
+                        .show_ui(ui, |ui| {
+                            for service in &services {
+                                changed |= ui
+                                    .selectable_value(&mut o.service, service.clone(), service)
+                                    .changed();
+                            }
+                        });
+                    ui.label("Port:");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut o.port).range(0..=65535))
+                        .on_hover_text("0 keeps the service's own port")
+                        .changed();
+                    if ui.button("🗑").on_hover_text("Remove override").clicked() {
+                        remove_override = Some(j);
+                    }
+                });
+                changed |= ui
+                    .add(
+                        egui::TextEdit::multiline(&mut o.vars)
+                            .desired_rows(2)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("APP_ENV=staging")
+                            .font(egui::TextStyle::Monospace),
+                    )
+                    .changed();
+            }
+            if let Some(j) = remove_override {
+                profile.overrides.remove(j);
+                changed = true;
+            }
+            if ui.button("➕ Add Override").clicked() {
+                profile.overrides.push(ProfileOverride {
+                    service: services.first().cloned().unwrap_or_default(),
+                    ..ProfileOverride::default()
+                });
+                changed = true;
+            }
+            ui.add_space(8.0);
+            ui.separator();
+        }
+        if let Some(i) = remove {
+            project.profiles.remove(i);
+            changed = true;
+        }
+        if ui.button("➕ Add Profile").clicked() {
+            project.profiles.push(EnvProfile {
+                name: format!("profile-{}", project.profiles.len() + 1),
+                ..EnvProfile::default()
+            });
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Whether generated files are committed, and the project's `.gitignore` coverage.
+/// Returns whether the project changed.
+fn render_generated_files(
+    ui: &mut egui::Ui,
+    project: &mut ProjectConfig,
+    facts: &ProjectFacts,
+    actions: &mut SettingsActions,
+) -> bool {
+    let mut changed = false;
+    card_frame(ui, |ui| {
+        ui.label(RichText::new("Generated Files").size(16.0).strong());
+        ui.separator();
+        changed |= ui
+            .checkbox(&mut project.commit_generated, "Commit generated files")
+            .on_hover_text(
+                "Keep docker-compose.yml and the server configs in the repository. \
+                 Mounts are written relative to the project directory so the files \
+                 are the same on every machine.",
+            )
+            .changed();
+        changed |= ui
+            .checkbox(&mut project.lock_images, "Lock image digests")
+            .on_hover_text(
+                "Start every image at the digest recorded in dockstack.lock, so everyone \
+                 committing the lock runs identical images. New tags are resolved on the \
+                 next start.",
+            )
+            .changed();
+        if let Some(lock) = facts.image_lock.as_ref().filter(|_| project.lock_images) {
+            ui.horizontal(|ui| {
+                let missing = lock.missing(project).len();
+                let status = if missing == 0 {
+                    format!("{} pins {} images.", LOCK_FILE, lock.images.len())
+                } else {
+                    format!("{} new images are resolved on the next start.", missing)
+                };
+                ui.label(RichText::new(status).color(COLOR_TEXT_DIM));
+                actions.update_image_lock = ui
+                    .button("🔄 Update Lock")
+                    .on_hover_text("Pull every tag again and record the digests it resolves to")
+                    .clicked();
+            });
+        }
+        if ui
+            .button("☸ Export to Kubernetes…")
+            .on_hover_text(
+                "Experimental: Deployments, Services and volume claims built from the \
+                 project's services, to start a cluster config from",
+            )
+            .clicked()
+        {
+            actions.export_kubernetes = rfd::FileDialog::new()
+                .add_filter("YAML", &["yaml", "yml"])
+                .set_directory(&project.directory)
+                .set_file_name(format!(
+                    "{}-k8s.yaml",
+                    compose::compose_project_name(project)
+                ))
+                .save_file();
+        }
+        ui.horizontal(|ui| {
+            ui.label("CI job with the same services:");
+            for format in [CiFormat::GitHubActions, CiFormat::GitLabCi] {
+                if ui.button(format!("{}…", format.label())).clicked() {
+                    let root = std::path::Path::new(&project.directory);
+                    let default = root.join(format.default_path());
+                    let mut dialog = rfd::FileDialog::new().add_filter("YAML", &["yaml", "yml"]);
+                    if let (Some(dir), Some(name)) = (default.parent(), default.file_name()) {
+                        // `.github/workflows` may not exist yet
+                        let dir = if dir.is_dir() { dir } else { root };
+                        dialog = dialog
+                            .set_directory(dir)
+                            .set_file_name(name.to_string_lossy());
+                    }
+                    let dest = dialog.save_file();
+                    actions.export_ci = dest.map(|dest| (format, dest));
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            let primary = devcontainer::primary_service(project).unwrap_or_default();
+            ui.label("Dev Container service:");
+            egui::ComboBox::from_id_salt("devcontainer_service")
+                .selected_text(primary.as_str())
+                .show_ui(ui, |ui| {
+                    for service in devcontainer::services(project) {
+                        if ui.selectable_label(service == primary, &service).clicked() {
+                            project.devcontainer_service = service;
+                            changed = true;
+                        }
+                    }
+                });
+            actions.write_devcontainer = ui
+                .add_enabled(
+                    !primary.is_empty(),
+                    egui::Button::new("🧊 Write devcontainer.json"),
+                )
+                .on_hover_text(
+                    "Lets VS Code open the project as a Dev Container attached to this \
+                     service. It shares DockStack's containers and leaves them running.",
+                )
+                .clicked();
+        });
+        ui.add_space(8.0);
+        if !facts.git_repo {
+            ui.label(
+                RichText::new("The project directory is not a git repository.")
+                    .color(COLOR_TEXT_DIM),
+            );
+            return;
+        }
+        let missing = &facts.missing_gitignore;
+        if missing.is_empty() {
+            ui.label(RichText::new("✓ .gitignore covers DockStack's files").color(COLOR_SUCCESS));
+            return;
+        }
+        ui.label(
+            RichText::new(format!(".gitignore does not list {}", missing.join(", ")))
+                .color(COLOR_WARNING),
+        );
+        if ui.button("📝 Add to .gitignore").clicked() {
+            actions.update_gitignore = true;
+        }
+    });
+    changed
+}
+
+/// Discovered containers, grouped by compose project with an Adopt button per project.
+fn render_foreign_containers(
+    ui: &mut egui::Ui,
+    foreign: Option<&[ForeignContainer]>,
+    actions: &mut SettingsActions,
+) {
+    let Some(foreign) = foreign else {
+        return;
+    };
+    ui.add_space(8.0);
+    if foreign.is_empty() {
+        ui.label(RichText::new("No other running containers.").color(COLOR_SUCCESS));
+        return;
+    }
+    let standalone: Vec<String> = foreign
+        .iter()
+        .filter(|c| c.compose_project.is_none())
+        .map(|c| c.name.clone())
+        .collect();
+    if !standalone.is_empty() {
+        let label = format!("📸 Capture {} Standalone Container(s)", standalone.len());
+        if ui
+            .button(label)
+            .on_hover_text(
+                "Rebuild image, port, environment and volumes from docker inspect \
+                 into a new project",
+            )
+            .clicked()
+        {
+            actions.capture = Some(standalone);
+        }
+        ui.add_space(8.0);
+    }
+    egui::Grid::new("foreign_grid")
+        .striped(true)
+        .spacing(Vec2::new(24.0, 8.0))
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new("COMPOSE PROJECT")
+                    .strong()
+                    .color(COLOR_TEXT_MUTED),
+            );
+            ui.label(RichText::new("NAME").strong().color(COLOR_TEXT_MUTED));
+            ui.label(RichText::new("IMAGE").strong().color(COLOR_TEXT_MUTED));
+            ui.label(RichText::new("STATUS").strong().color(COLOR_TEXT_MUTED));
+            ui.label("");
+            ui.end_row();
+
+            let mut previous: Option<&str> = None;
+            for container in foreign {
+                let project = container.compose_project.as_deref();
+                let first_of_project = project.is_some() && project != previous;
+                previous = project;
+                ui.label(RichText::new(project.unwrap_or("—")).color(COLOR_ACCENT));
+                ui.label(RichText::new(&container.name).color(COLOR_TEXT));
+                ui.label(
+                    RichText::new(&container.image)
+                        .monospace()
+                        .color(COLOR_TEXT_DIM),
+                );
+                ui.label(RichText::new(&container.status).color(COLOR_TEXT_DIM));
+                match &container.compose_file {
+                    Some(file) if first_of_project => {
+                        if ui
+                            .button("📥 Adopt")
+                            .on_hover_text(file.display().to_string())
+                            .clicked()
+                        {
+                            actions.adopt = Some(file.clone());
+                        }
+                    }
+                    None if first_of_project => {
+                        ui.label(RichText::new("compose file not found").color(COLOR_TEXT_MUTED));
+                    }
+                    _ => {
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+fn windows_shell_label(id: &str) -> &'static str {
+    match id {
+        "cmd" => "Command Prompt",
+        "git-bash" => "Git Bash",
+        _ => "PowerShell",
+    }
+}
+
+pub fn render_settings(
+    ui: &mut egui::Ui,
+    config: &mut AppConfig,
+    new_project: &mut NewProjectState,
+    facts: &ProjectFacts,
+    scans: CleanupScans,
+    stack_stopped: bool,
+    actions: &mut SettingsActions,
+) {
+    ScrollArea::vertical().show(ui, |ui| {
+        ui.add_space(10.0);
+        ui.heading(
+            RichText::new("Settings")
+                .size(28.0)
+                .color(COLOR_TEXT)
+                .strong(),
+        );
+        ui.add_space(24.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("General").size(16.0).strong());
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut config.stop_on_exit, "Stop Containers on Exit");
+                ui.add_space(8.0);
+                ui.label(RichText::new("Automatically stop running Docker services when closing DockStack.").color(COLOR_TEXT_DIM));
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut config.bind_localhost, "Localhost Only for New Projects")
+                    .changed()
+                {
+                    actions.save_config = true;
+                }
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new("Publish ports on 127.0.0.1 so dev databases aren't reachable from the network.")
+                        .color(COLOR_TEXT_DIM),
+                );
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut config.notifications, "Background Notifications")
+                    .changed()
+                {
+                    actions.save_config = true;
+                }
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new("Notify when a start, stop or pull finishes while the window is in the background.")
+                        .color(COLOR_TEXT_DIM),
+                );
+            });
+        });
+
+        ui.add_space(16.0);
+
+        let mirror_prefix = config.mirror.prefix();
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Startup").size(16.0).strong());
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Health-wait timeout:");
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut project.startup_timeout_secs)
+                                .range(5..=600)
+                                .suffix(" s"),
+                        )
+                        .changed();
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(
+                            "After starting, wait this long for containers to be running and healthy.",
+                        )
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Pull images:");
+                    egui::ComboBox::from_id_salt("pull_policy")
+                        .selected_text(&project.pull_policy)
+                        .show_ui(ui, |ui| {
+                            for policy in PULL_POLICIES {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut project.pull_policy,
+                                        policy.to_string(),
+                                        *policy,
+                                    )
+                                    .changed();
+                            }
+                        });
+                    if ui.button("⬇ Pull Images Now").clicked() {
+                        actions.pull_images = true;
+                    }
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new("\"always\" fetches fresh images on every start.")
+                            .color(COLOR_TEXT_DIM),
+                    );
+                });
+                if !mirror_prefix.is_empty() || !project.image_mirror.is_empty() {
+                    let mut use_mirror = !project.image_mirror.is_empty();
+                    let label = format!("Pull Docker Hub images through {}", mirror_prefix);
+                    if ui
+                        .checkbox(&mut use_mirror, label)
+                        .on_hover_text("Set up under Registry Mirror below")
+                        .changed()
+                    {
+                        project.image_mirror = match use_mirror {
+                            true => mirror_prefix.clone(),
+                            false => String::new(),
+                        };
+                        changed = true;
+                    }
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Web servers:");
+                    let label = |topology: &str| match topology {
+                        "apache" => "Apache only",
+                        "nginx-apache" => "Nginx in front of Apache",
+                        _ => "Nginx only",
+                    };
+                    let mut topology = project.web_topology.clone();
+                    egui::ComboBox::from_id_salt("web_topology")
+                        .selected_text(label(&topology))
+                        .show_ui(ui, |ui| {
+                            for option in WEB_TOPOLOGIES {
+                                ui.selectable_value(
+                                    &mut topology,
+                                    option.to_string(),
+                                    label(option),
+                                );
+                            }
+                        });
+                    if topology != project.web_topology {
+                        project.set_web_topology(&topology);
+                        actions.audit.push((
+                            project.name.clone(),
+                            format!("Switched web servers to {}", label(&topology)),
+                        ));
+                        changed = true;
+                    }
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(
+                            "Nginx in front terminates HTTPS and passes every request to Apache.",
+                        )
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("File sync:");
+                    egui::ComboBox::from_id_salt("file_sync")
+                        .selected_text(&project.file_sync)
+                        .show_ui(ui, |ui| {
+                            for mode in FILE_SYNC_MODES {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut project.file_sync,
+                                        mode.to_string(),
+                                        *mode,
+                                    )
+                                    .changed();
+                            }
+                        });
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(
+                            "\"mutagen\" syncs www/ and app/ into volumes; faster on macOS, needs Mutagen installed.",
+                        )
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .checkbox(&mut project.code_in_directory, "Code in project directory")
+                        .on_hover_text(
+                            "Mount the project directory itself instead of its www/ and app/, \
+                             for existing codebases",
+                        )
+                        .changed();
+                    ui.add_space(8.0);
+                    ui.label("Web root:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut project.web_root)
+                                .desired_width(120.0)
+                                .hint_text("public"),
+                        )
+                        .on_hover_text("Subdirectory of the code that nginx and Apache serve")
+                        .changed();
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("SELinux relabel:");
+                    egui::ComboBox::from_id_salt("selinux_relabel")
+                        .selected_text(&project.selinux_relabel)
+                        .show_ui(ui, |ui| {
+                            for mode in SELINUX_RELABEL_MODES {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut project.selinux_relabel,
+                                        mode.to_string(),
+                                        *mode,
+                                    )
+                                    .changed();
+                            }
+                        });
+                    ui.add_space(8.0);
+                    let detected = if compose::selinux_enforcing() {
+                        "SELinux is enforcing here"
+                    } else {
+                        "SELinux is not enforcing here"
+                    };
+                    ui.label(
+                        RichText::new(format!(
+                            "Adds :z (shared) or :Z (private) to project bind mounts. {}.",
+                            detected
+                        ))
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            if render_branch_env(ui, project, facts.branch.as_deref()) {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            if render_profiles(ui, project) {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            if render_generated_files(ui, project, facts, actions) {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Appearance").size(16.0).strong());
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Shown in the sidebar, tray and window title so you can tell projects apart.",
+                    )
+                    .color(COLOR_TEXT_DIM),
+                );
+                ui.add_space(8.0);
+                render_appearance_settings(ui, project, &mut changed);
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        let known_tags = config.all_tags();
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Tags").size(16.0).strong());
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Group projects by client or stack type. Tags show in the sidebar \
+                         and filter the workspace menu.",
+                    )
+                    .color(COLOR_TEXT_DIM),
+                );
+                ui.add_space(8.0);
+                render_tag_editor(ui, &mut project.tags, &known_tags, &mut changed);
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Network Exposure").size(16.0).strong());
+                ui.separator();
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .checkbox(&mut project.bind_localhost, "Bind to Localhost")
+                        .changed();
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(
+                            "Publish ports on 127.0.0.1 only. Individual services can opt out in their advanced settings.",
+                        )
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .checkbox(&mut project.expose_on_lan, "Expose on LAN")
+                        .changed();
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(
+                            "Publish ports on all interfaces so phones and tablets can reach the stack. Restart to apply.",
+                        )
+                        .color(COLOR_TEXT_DIM),
+                    );
+                });
+                if project.expose_on_lan {
+                    render_lan_urls(ui, project);
+                }
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Network").size(16.0).strong());
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Pick a subnet that doesn't clash with your VPN. Changes apply the next time the stack starts from stopped.",
+                    )
+                    .color(COLOR_TEXT_DIM),
+                );
+                ui.add_space(8.0);
+                render_network_settings(ui, &mut project.network, &mut changed);
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        if let Some(project) = config.active_project_mut() {
+            let mut changed = false;
+            card_frame(ui, |ui| {
+                ui.label(RichText::new("Environment").size(16.0).strong());
+                ui.separator();
+                ui.label(
+                    RichText::new(
+                        "Set in every service, e.g. APP_ENV or HTTP_PROXY. A service's own \
+                         variable of the same name wins. Restart to apply.",
+                    )
+                    .color(COLOR_TEXT_DIM),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Timezone:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut project.timezone)
+                                .desired_width(180.0)
+                                .hint_text("UTC"),
+                        )
+                        .changed();
+                    if let Some(host) = utils::host_timezone() {
+                        if host != project.timezone
+                            && ui.button(format!("Use {}", host)).clicked()
+                        {
+                            project.timezone = host;
+                            changed = true;
+                        }
+                    }
+                    ui.add_space(16.0);
+                    ui.label("Locale:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut project.locale)
+                                .desired_width(140.0)
+                                .hint_text("en_US.UTF-8"),
+                        )
+                        .changed();
+                });
+                ui.label(
+                    RichText::new(
+                        "Sets TZ and LANG in every service, and date.timezone and \
+                         intl.default_locale in php.ini.",
+                    )
+                    .color(COLOR_TEXT_DIM),
+                );
+                ui.add_space(8.0);
+                changed |= ui
+                    .add(
+                        egui::TextEdit::multiline(&mut project.env)
+                            .desired_rows(4)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("TZ=Europe/Berlin\nAPP_ENV=local")
+                            .font(egui::TextStyle::Monospace),
+                    )
+                    .changed();
+                ui.add_space(4.0);
+                changed |= ui
+                    .checkbox(&mut project.use_proxy, "Use the HTTP proxy")
+                    .on_hover_text(
+                        "Set HTTP_PROXY, HTTPS_PROXY and NO_PROXY in every service and custom \
+                         image build, from the Proxy settings below",
+                    )
+                    .changed();
+            });
+            if changed {
+                actions.save_config = true;
+            }
+            ui.add_space(16.0);
+        }
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Projects").size(16.0).strong());
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("New Project Name:");
+                ui.text_edit_singleline(&mut new_project.name);
+                if ui.button("Create").clicked() && !new_project.name.is_empty() {
+                    // Logic handled in parent or here
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("From Git URL:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut new_project.git_url)
+                        .hint_text("https://github.com/org/shop.git"),
+                );
+                let url = new_project.git_url.trim();
+                let idle = new_project.clone_progress.is_none();
+                let enabled = new_project.git_available && idle && !url.is_empty();
+                let clone = ui
+                    .add_enabled(enabled, egui::Button::new("⬇ Clone"))
+                    .on_hover_text(
+                        "Clone into the projects folder, create a project from its compose \
+                         file (or serve it from www/) and start it",
+                    );
+                if !new_project.git_available {
+                    clone.on_disabled_hover_text("git was not found on PATH");
+                } else if clone.clicked() {
+                    actions.clone_repo = Some(url.to_string());
+                }
+            });
+            if let Some(progress) = &new_project.clone_progress {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new(progress).color(COLOR_TEXT_DIM));
+                });
+            }
+
+            ui.add_space(8.0);
+            let active = config
+                .active_project()
+                .map(|p| (p.id.clone(), p.name.clone()));
+            let archive = ui
+                .add_enabled(
+                    stack_stopped && active.is_some(),
+                    egui::Button::new("🗄 Archive Current Project"),
+                )
+                .on_hover_text("Hide it from the pickers and block starts; its files are kept")
+                .on_disabled_hover_text("Stop the stack before archiving the project");
+            if archive.clicked() {
+                if let Some((id, name)) = active {
+                    config.set_archived(&id, true);
+                    actions.audit.push((name, "Archived project".to_string()));
+                }
+            }
+
+            let archived: Vec<_> = config
+                .archived_projects()
+                .into_iter()
+                .map(|p| (p.id.clone(), p.name.clone(), p.directory.clone()))
+                .collect();
+            if !archived.is_empty() {
+                ui.add_space(12.0);
+                ui.label(
+                    RichText::new("ARCHIVED")
+                        .size(10.0)
+                        .color(COLOR_TEXT_MUTED)
+                        .strong(),
+                );
+                for (id, name, directory) in archived {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("♻ Restore").clicked() {
+                            config.set_archived(&id, false);
+                            actions
+                                .audit
+                                .push((name.clone(), "Restored project from archive".to_string()));
+                        }
+                        ui.label(RichText::new(name).strong());
+                        ui.label(RichText::new(directory).size(11.0).color(COLOR_TEXT_DIM));
+                    });
+                }
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("SSL / HTTPS").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Generate locally trusted certificates for your development domains.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Generate Certs").clicked() {
+                    actions.generate_ssl = true;
+                }
+                if ui.button("Remove Certs").clicked() {
+                    actions.remove_ssl = true;
+                }
+            });
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Snapshots").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Archive the project's volumes and configuration into backups/, or roll back to a previous archive.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("📸 Create Snapshot").clicked() {
+                    actions.create_snapshot = true;
+                }
+                let restore = ui
+                    .add_enabled(stack_stopped, egui::Button::new("♻ Restore Snapshot…"))
+                    .on_disabled_hover_text("Stop the stack before restoring a snapshot");
+                if restore.clicked() {
+                    let mut dialog = rfd::FileDialog::new().add_filter("Snapshot", &["gz"]);
+                    if let Some(project) = config.active_project() {
+                        let backups = std::path::Path::new(&project.directory).join("backups");
+                        dialog = dialog.set_directory(backups);
+                    }
+                    actions.restore_snapshot = dialog.pick_file();
+                }
+            });
+            if !stack_stopped {
+                ui.label(
+                    RichText::new("Tip: stop the stack first for a consistent database snapshot.")
+                        .size(11.0)
+                        .color(COLOR_WARNING),
+                );
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Diagnostics").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Bundle logs, Docker version and info, the compose file and recent events \
+                     into a zip for bug reports. Passwords, tokens and keys are redacted.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("📦 Export Diagnostics…").clicked() {
+                    let name = format!(
+                        "dockstack-diagnostics-{}.zip",
+                        chrono::Local::now().format("%Y%m%d-%H%M%S")
+                    );
+                    actions.export_diagnostics = rfd::FileDialog::new()
+                        .add_filter("Zip archive", &["zip"])
+                        .set_file_name(name)
+                        .save_file();
+                }
+                if ui.button("📂 Crash Reports").clicked() {
+                    let dir = crate::diagnostics::crash_reports_dir();
+                    std::fs::create_dir_all(&dir).ok();
+                    utils::open_url(&dir.to_string_lossy());
+                }
+            });
+            if ui
+                .checkbox(&mut config.crash_reports, "Write a crash report when DockStack panics")
+                .changed()
+            {
+                crate::diagnostics::set_crash_reports(config.crash_reports);
+                actions.save_config = true;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Buffers").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "How much output is kept in memory. The oldest lines are dropped once a buffer is full.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let buffers = &mut config.buffers;
+            let mut changed = false;
+            egui::Grid::new("buffer_sizes")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Log lines:");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut buffers.log_lines).range(500..=100_000))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Terminal lines:");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut buffers.terminal_lines).range(100..=50_000))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Docker event queue:");
+                    let queue = ui
+                        .add(egui::DragValue::new(&mut buffers.event_queue).range(100..=100_000))
+                        .on_hover_text("Applied the next time DockStack starts");
+                    changed |= queue.changed();
+                    ui.end_row();
+                });
+            if changed {
+                actions.buffers_changed = true;
+                actions.save_config = true;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Proxy").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "For networks that reach the internet only through an HTTP proxy. Projects \
+                     using it get these in every service and image build.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let proxy = &mut config.proxy;
+            let mut changed = ui
+                .checkbox(&mut proxy.enabled, "Use a proxy")
+                .on_hover_text("Also turns it on for new projects")
+                .changed();
+            ui.add_enabled_ui(proxy.enabled, |ui| {
+                egui::Grid::new("proxy_settings")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        let fields = [
+                            ("HTTP proxy:", &mut proxy.http, "http://proxy.corp:3128"),
+                            ("HTTPS proxy:", &mut proxy.https, "http://proxy.corp:3128"),
+                            ("No proxy:", &mut proxy.no_proxy, ".corp,10.0.0.0/8"),
+                        ];
+                        for (label, value, hint) in fields {
+                            ui.label(label);
+                            changed |= ui
+                                .add(
+                                    egui::TextEdit::singleline(value)
+                                        .desired_width(280.0)
+                                        .hint_text(hint),
+                                )
+                                .changed();
+                            ui.end_row();
+                        }
+                    });
+            });
+            ui.label(
+                RichText::new(
+                    "Docker pulls images itself: set the same proxy in Docker Desktop under \
+                     Resources → Proxies, or in the docker service's environment on Linux.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            if changed {
+                actions.proxy_changed = true;
+                actions.save_config = true;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Registries").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Private registries DockStack logs in to before pulling a project's images. \
+                     Passwords and tokens are kept in the system keyring.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let mut remove = None;
+            for (i, registry) in config.registries.iter().enumerate() {
+                let password_id = ui.id().with(("registry_password", i));
+                let mut password: String = ui.data(|d| d.get_temp(password_id)).unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(&registry.host).strong().color(COLOR_TEXT));
+                    ui.label(RichText::new(&registry.username).color(COLOR_TEXT_DIM));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut password)
+                            .password(true)
+                            .desired_width(180.0)
+                            .hint_text("New password or token"),
+                    );
+                    if ui
+                        .button("🔑 Save & Test")
+                        .on_hover_text(
+                            "Runs docker login; leave the password empty to test the saved one",
+                        )
+                        .clicked()
+                    {
+                        let password = std::mem::take(&mut password);
+                        actions.login_registry = Some((registry.clone(), password));
+                    }
+                    if ui
+                        .button("🗑")
+                        .on_hover_text("Remove registry and its password")
+                        .clicked()
+                    {
+                        remove = Some(i);
+                    }
+                    if registry.verified_at.is_empty() {
+                        ui.label(RichText::new("Not tested").color(COLOR_TEXT_MUTED));
+                    } else {
+                        let text = format!("✔ Logged in {}", registry.verified_at);
+                        ui.label(RichText::new(text).color(COLOR_SUCCESS));
+                    }
+                });
+                ui.data_mut(|d| d.insert_temp(password_id, password));
+            }
+            if let Some(i) = remove {
+                actions.remove_registry = Some(config.registries.remove(i));
+                actions.registries_changed = true;
+                actions.save_config = true;
+            }
+
+            let new_id = ui.id().with("new_registry");
+            let mut new: (String, String) = ui.data(|d| d.get_temp(new_id)).unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut new.0)
+                        .desired_width(200.0)
+                        .hint_text("ghcr.io"),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut new.1)
+                        .desired_width(140.0)
+                        .hint_text("Username"),
+                );
+                let host = new.0.trim().trim_end_matches('/').to_string();
+                let valid = !host.is_empty()
+                    && !new.1.trim().is_empty()
+                    && !config.registries.iter().any(|r| r.host == host);
+                if ui.add_enabled(valid, egui::Button::new("➕ Add Registry")).clicked() {
+                    config.registries.push(RegistryConfig {
+                        host,
+                        username: new.1.trim().to_string(),
+                        verified_at: String::new(),
+                    });
+                    new = Default::default();
+                    actions.registries_changed = true;
+                    actions.save_config = true;
+                }
+            });
+            ui.data_mut(|d| d.insert_temp(new_id, new));
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Registry Mirror").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "A pull-through cache spares slow links and Docker Hub's rate limit. Either \
+                     the Docker engine uses it for every Docker Hub pull (Mirror URL), or \
+                     projects name it in their images (Image prefix, e.g. a Harbor proxy cache).",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let mirror = &mut config.mirror;
+            let mut changed = false;
+            let mut prefix_changed = false;
+            egui::Grid::new("mirror_settings")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Mirror URL:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut mirror.url)
+                                .desired_width(280.0)
+                                .hint_text("https://mirror.corp"),
+                        )
+                        .changed();
+                    ui.end_row();
+                    ui.label("Image prefix:");
+                    prefix_changed = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut mirror.image_prefix)
+                                .desired_width(280.0)
+                                .hint_text("harbor.corp/dockerhub"),
+                        )
+                        .on_hover_text("Projects pulling through the mirror switch to it too")
+                        .changed();
+                    ui.end_row();
+                });
+            changed |= ui
+                .checkbox(&mut mirror.enabled, "Pull through the image prefix in new projects")
+                .changed();
+            if !mirror.url.trim().is_empty() {
+                ui.add_space(8.0);
+                let location = if cfg!(target_os = "linux") {
+                    "Add this to /etc/docker/daemon.json and restart Docker \
+                     (`sudo systemctl restart docker`):"
+                } else {
+                    "Add this in Docker Desktop under Settings → Docker Engine, then Apply & \
+                     Restart:"
+                };
+                ui.label(RichText::new(location).color(COLOR_TEXT_DIM));
+                let daemon_json = mirror.daemon_json();
+                ui.label(RichText::new(&daemon_json).monospace().color(COLOR_TEXT));
+                if ui.button("📋 Copy").clicked() {
+                    ui.ctx().copy_text(daemon_json);
+                }
+            }
+            if prefix_changed {
+                config.sync_mirror_prefix();
+            }
+            if changed || prefix_changed {
+                actions.save_config = true;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Maintenance").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Periodically frees disk space Docker holds on to, across all projects on \
+                     this engine. Volumes are never touched.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let maintenance = &mut config.maintenance;
+            let mut changed = ui
+                .checkbox(&mut maintenance.enabled, "Run maintenance on a schedule")
+                .changed();
+            egui::Grid::new("maintenance_settings")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Every:");
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut maintenance.interval_hours)
+                                .range(1..=24 * 30)
+                                .suffix(" h"),
+                        )
+                        .changed();
+                    ui.end_row();
+                    ui.label("Keep anything newer than:");
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut maintenance.retention_hours)
+                                .range(0..=24 * 90)
+                                .suffix(" h"),
+                        )
+                        .on_hover_text("0 prunes regardless of age")
+                        .changed();
+                    ui.end_row();
+                });
+            changed |= ui
+                .checkbox(&mut maintenance.images, "Dangling images")
+                .changed();
+            changed |= ui
+                .checkbox(&mut maintenance.containers, "Stopped DockStack containers")
+                .changed();
+            changed |= ui
+                .checkbox(&mut maintenance.build_cache, "Unused build cache")
+                .changed();
+            ui.horizontal(|ui| {
+                if ui.button("🧹 Run Now").clicked() {
+                    actions.run_maintenance = true;
+                }
+                if maintenance.last_run > 0 {
+                    let last_run = chrono::DateTime::from_timestamp(maintenance.last_run, 0)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"))
+                        .map(|t| t.to_string())
+                        .unwrap_or_default();
+                    let text = format!("Last run {}. {}", last_run, maintenance.last_summary);
+                    ui.label(RichText::new(text).color(COLOR_TEXT_DIM));
+                }
+            });
+            if changed {
+                actions.save_config = true;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Status Page").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "A read-only web page with the active project's services, their state and \
+                     URLs, for teammates on this machine or through a port forward. It only \
+                     listens on localhost.",
+                )
                 .color(COLOR_TEXT_DIM),
-        );
-        ui.add_space(24.0);
+            );
+            ui.add_space(8.0);
+            let page = &mut config.status_page;
+            let mut changed = ui.checkbox(&mut page.enabled, "Serve the status page").changed();
+            ui.horizontal(|ui| {
+                ui.label("Port:");
+                let port = ui.add(egui::DragValue::new(&mut page.port).range(1024..=65535));
+                // Rebind once the new port is settled, not on every step of a drag
+                changed |= port.drag_stopped() || (port.changed() && !port.dragged());
+            });
+            if page.enabled {
+                let url = page.url();
+                ui.horizontal(|ui| {
+                    ui.hyperlink_to(RichText::new(&url).strong().color(COLOR_PRIMARY), &url);
+                    if ui.small_button("📋").on_hover_text("Copy URL").clicked() {
+                        ui.ctx().copy_text(url.clone());
+                    }
+                });
+                ui.label(
+                    RichText::new(format!(
+                        "From another machine: ssh -L {0}:localhost:{0} <this host>",
+                        page.port
+                    ))
+                    .size(11.0)
+                    .color(COLOR_TEXT_MUTED),
+                );
+            }
+            if changed {
+                actions.status_page_changed = true;
+                actions.save_config = true;
+            }
+        });
 
-        egui::Grid::new("port_grid")
-            .spacing(Vec2::new(32.0, 12.0))
-            .striped(true)
-            .show(ui, |ui| {
-                ui.label(RichText::new("PORT").strong().color(COLOR_TEXT_MUTED));
-                ui.label(RichText::new("STATUS").strong().color(COLOR_TEXT_MUTED));
-                ui.label(RichText::new("PROCESS").strong().color(COLOR_TEXT_MUTED));
-                ui.label(RichText::new("ACTION").strong().color(COLOR_TEXT_MUTED));
-                ui.end_row();
+        ui.add_space(16.0);
 
-                for info in port_infos {
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Service Plugins").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Each .toml descriptor in the plugins folder adds a service type to every \
+                     project. Plugins are loaded when DockStack starts.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            let installed = plugins::installed();
+            if installed.is_empty() {
+                ui.label(RichText::new("No plugins installed.").color(COLOR_TEXT_MUTED));
+            }
+            for plugin in installed.iter() {
+                ui.horizontal(|ui| {
+                    ui.label(&plugin.icon);
+                    ui.label(RichText::new(&plugin.name).strong().color(COLOR_TEXT));
                     ui.label(
-                        RichText::new(format!("{}", info.port))
-                            .size(14.0)
-                            .strong()
-                            .color(COLOR_TEXT),
+                        RichText::new(plugin.path.display().to_string())
+                            .size(11.0)
+                            .color(COLOR_TEXT_MUTED),
                     );
-                    if info.in_use {
-                        ui.label(RichText::new("BUSY").size(12.0).color(COLOR_ERROR));
-                        ui.label(
-                            RichText::new(&info.process)
-                                .size(12.0)
-                                .color(COLOR_TEXT_DIM),
-                        );
-                        ui.label(
-                            RichText::new("Kill / Change Port")
-                                .size(12.0)
-                                .color(COLOR_WARNING),
-                        );
-                    } else {
-                        ui.label(RichText::new("FREE").size(12.0).color(COLOR_SUCCESS));
-                        ui.label("-");
-                        ui.label("-");
-                    }
-                    ui.end_row();
-                }
-            });
-    });
-}
+                });
+            }
+            ui.add_space(4.0);
+            if ui.button("📂 Open Plugins Folder").clicked() {
+                utils::open_directory(&plugins::plugins_dir().to_string_lossy());
+            }
+        });
 
-pub fn render_monitor(
-    ui: &mut egui::Ui,
-    _sys_stats: &SystemStats,
-    container_stats: &[ContainerStats],
-    cpu_history: &[f32],
-    mem_history: &[f32],
-) {
-    ScrollArea::vertical().show(ui, |ui| {
-        ui.add_space(10.0);
-        ui.heading(
-            RichText::new("Live Monitor")
-                .size(28.0)
-                .color(COLOR_TEXT)
-                .strong(),
-        );
-        ui.add_space(24.0);
+        ui.add_space(16.0);
 
-        ui.horizontal(|ui| {
-            card_frame(ui, |ui| {
-                ui.set_min_width(300.0);
-                ui.label(
-                    RichText::new("CPU History")
-                        .size(14.0)
-                        .color(COLOR_TEXT_DIM),
-                );
-                sparkline(
-                    ui,
-                    cpu_history,
-                    120.0,
-                    COLOR_PRIMARY,
-                    Vec2::new(280.0, 80.0),
-                );
-            });
-            card_frame(ui, |ui| {
-                ui.set_min_width(300.0);
-                ui.label(
-                    RichText::new("Memory History")
-                        .size(14.0)
-                        .color(COLOR_TEXT_DIM),
-                );
-                sparkline(
-                    ui,
-                    mem_history,
-                    120.0,
-                    COLOR_SECONDARY,
-                    Vec2::new(280.0, 80.0),
-                );
-            });
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Automation Hooks").size(16.0).strong());
+            ui.separator();
+            ui.label(
+                RichText::new(
+                    "Rhai scripts in the hooks folder react to the active project's events: \
+                     on_stack_started, on_stack_stopped, on_container_unhealthy and \
+                     on_backup_finished. They can exec in the project's containers, send \
+                     notifications and write files in the project directory.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            if ui
+                .checkbox(&mut config.hooks_enabled, "Run hook scripts")
+                .changed()
+            {
+                actions.save_config = true;
+            }
+            for script in hooks::scripts() {
+                let name = script.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(RichText::new(format!("📜 {}", name)).color(COLOR_TEXT));
+            }
+            ui.add_space(4.0);
+            if ui.button("📂 Open Hooks Folder").clicked() {
+                utils::open_directory(&hooks::hooks_dir().to_string_lossy());
+            }
         });
 
-        ui.add_space(24.0);
+        ui.add_space(16.0);
 
-        if !container_stats.is_empty() {
-            ui.label(RichText::new("Container Live Usage").size(16.0).strong());
-            ui.add_space(12.0);
-            egui::Grid::new("monitor_grid")
-                .striped(true)
-                .spacing(Vec2::new(24.0, 12.0))
+        card_frame(ui, |ui| {
+            ui.label(RichText::new("Terminal").size(16.0).strong());
+            ui.separator();
+            let term = &mut config.terminal;
+            let mut changed = false;
+            egui::Grid::new("terminal_settings")
+                .num_columns(2)
+                .spacing([12.0, 8.0])
                 .show(ui, |ui| {
-                    ui.label(RichText::new("NAME").strong().color(COLOR_TEXT_MUTED));
-                    ui.label(RichText::new("CPU").strong().color(COLOR_TEXT_MUTED));
-                    ui.label(RichText::new("MEM").strong().color(COLOR_TEXT_MUTED));
+                    ui.label("Shell:");
+                    if cfg!(target_os = "windows") {
+                        let shell = &mut term.windows_shell;
+                        egui::ComboBox::from_id_salt("windows_shell")
+                            .selected_text(windows_shell_label(shell))
+                            .show_ui(ui, |ui| {
+                                for id in WINDOWS_SHELLS {
+                                    let label = windows_shell_label(id);
+                                    changed |= ui
+                                        .selectable_value(shell, id.to_string(), label)
+                                        .changed();
+                                }
+                            });
+                    } else {
+                        let shell = &mut term.unix_shell;
+                        let auto = format!("Auto ({})", terminal::default_unix_shell());
+                        egui::ComboBox::from_id_salt("unix_shell")
+                            .selected_text(if shell.is_empty() {
+                                auto.as_str()
+                            } else {
+                                shell.as_str()
+                            })
+                            .show_ui(ui, |ui| {
+                                changed |=
+                                    ui.selectable_value(shell, String::new(), auto).changed();
+                                for path in terminal::installed_shells() {
+                                    changed |=
+                                        ui.selectable_value(shell, path.clone(), path).changed();
+                                }
+                            });
+                    }
                     ui.end_row();
 
-                    for s in container_stats {
-                        ui.label(RichText::new(&s.name).color(COLOR_TEXT));
-                        ui.label(RichText::new(&s.cpu_percent).color(COLOR_PRIMARY));
-                        ui.label(RichText::new(&s.mem_usage).color(COLOR_SECONDARY));
+                    if !cfg!(target_os = "windows") {
+                        ui.label("");
+                        changed |= ui
+                            .checkbox(&mut term.login_shell, "Login shell")
+                            .on_hover_text("Start with -l so ~/.profile and friends are loaded")
+                            .changed();
                         ui.end_row();
                     }
+
+                    ui.label("PATH additions:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::multiline(&mut term.extra_path)
+                                .desired_width(320.0)
+                                .desired_rows(2)
+                                .hint_text("~/.local/bin"),
+                        )
+                        .on_hover_text("One directory per line, searched before the inherited PATH")
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Variables:");
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::multiline(&mut term.env)
+                                .desired_width(320.0)
+                                .desired_rows(2)
+                                .hint_text("DOCKER_HOST=unix:///run/user/1000/docker.sock"),
+                        )
+                        .on_hover_text("One KEY=VALUE per line")
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("");
+                    changed |= ui
+                        .checkbox(&mut term.record_transcripts, "Record session transcripts")
+                        .on_hover_text(format!(
+                            "Write each session's output, timestamped, to the transcripts \
+                             folder. The last {} sessions are kept.",
+                            terminal::KEPT_TRANSCRIPTS
+                        ))
+                        .changed();
+                    ui.end_row();
                 });
-        }
-    });
-}
-pub fn render_settings(
-    ui: &mut egui::Ui,
-    _config: &mut AppConfig,
-    new_project_name: &mut String,
-    gen_ssl: &mut bool,
-    rem_ssl: &mut bool,
-) {
-    ScrollArea::vertical().show(ui, |ui| {
-        ui.add_space(10.0);
-        ui.heading(
-            RichText::new("Settings")
-                .size(28.0)
-                .color(COLOR_TEXT)
-                .strong(),
-        );
-        ui.add_space(24.0);
+            if changed {
+                actions.save_config = true;
+            }
+            ui.label(
+                RichText::new("Applies to the next shell started in the Terminal tab.")
+                    .size(11.0)
+                    .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(4.0);
+            if ui.button("📂 Open Transcripts Folder").clicked() {
+                utils::open_directory(&terminal::transcripts_dir().to_string_lossy());
+            }
+        });
+
+        ui.add_space(16.0);
 
         card_frame(ui, |ui| {
-            ui.label(RichText::new("General").size(16.0).strong());
+            ui.label(RichText::new("Accessibility").size(16.0).strong());
             ui.separator();
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut _config.stop_on_exit, "Stop Containers on Exit");
-                ui.add_space(8.0);
-                ui.label(RichText::new("Automatically stop running Docker services when closing DockStack.").color(COLOR_TEXT_DIM));
-            });
+            if ui
+                .checkbox(&mut config.theme.high_contrast, "High-contrast theme")
+                .on_hover_text(
+                    "Brighter borders and text, and status colors that stay distinguishable \
+                     with color vision deficiencies",
+                )
+                .changed()
+            {
+                crate::ui::theme::apply_theme(ui.ctx(), config.theme.high_contrast);
+                actions.save_config = true;
+            }
+            ui.label(
+                RichText::new(
+                    "Status is also shown by shape: ✔ running, ○ stopped, ⚠ warning, ✖ error.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
         });
 
         ui.add_space(16.0);
 
         card_frame(ui, |ui| {
-            ui.label(RichText::new("Projects").size(16.0).strong());
+            ui.label(RichText::new("Maintenance").size(16.0).strong());
             ui.separator();
+            ui.label(
+                RichText::new(
+                    "Check Docker, ports, files, certificates and leftovers for the active project.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
+            if ui.button("🩺 Run Doctor").clicked() {
+                actions.run_doctor = true;
+            }
+            ui.add_space(12.0);
+            ui.label(
+                RichText::new(
+                    "Find containers, networks and volumes left behind by deleted projects.",
+                )
+                .color(COLOR_TEXT_DIM),
+            );
+            ui.add_space(8.0);
             ui.horizontal(|ui| {
-                ui.label("New Project Name:");
-                ui.text_edit_singleline(new_project_name);
-                if ui.button("Create").clicked() && !new_project_name.is_empty() {
-                    // Logic handled in parent or here
+                if ui.button("🔍 Scan for Orphans").clicked() {
+                    actions.scan_orphans = true;
+                }
+                let has_orphans = scans.orphans.is_some_and(|o| !o.is_empty());
+                if ui
+                    .add_enabled(
+                        has_orphans,
+                        egui::Button::new(RichText::new("🗑 Remove All").color(COLOR_ERROR)),
+                    )
+                    .clicked()
+                {
+                    actions.remove_orphans = true;
                 }
             });
+
+            let Some(orphans) = scans.orphans else {
+                return;
+            };
+            ui.add_space(8.0);
+            if orphans.is_empty() {
+                ui.label(RichText::new("No orphaned resources found.").color(COLOR_SUCCESS));
+                return;
+            }
+            egui::Grid::new("orphan_grid")
+                .striped(true)
+                .spacing(Vec2::new(24.0, 8.0))
+                .show(ui, |ui| {
+                    ui.label(RichText::new("TYPE").strong().color(COLOR_TEXT_MUTED));
+                    ui.label(RichText::new("NAME").strong().color(COLOR_TEXT_MUTED));
+                    ui.label(RichText::new("PROJECT ID").strong().color(COLOR_TEXT_MUTED));
+                    ui.end_row();
+
+                    for orphan in orphans {
+                        ui.label(RichText::new(orphan.kind.label()).color(COLOR_ACCENT));
+                        ui.label(RichText::new(&orphan.name).color(COLOR_TEXT));
+                        ui.label(
+                            RichText::new(&orphan.project_id)
+                                .monospace()
+                                .color(COLOR_TEXT_DIM),
+                        );
+                        ui.end_row();
+                    }
+                });
         });
 
         ui.add_space(16.0);
-
         card_frame(ui, |ui| {
-            ui.label(RichText::new("SSL / HTTPS").size(16.0).strong());
+            ui.label(RichText::new("Other Containers").size(16.0).strong());
             ui.separator();
             ui.label(
                 RichText::new(
-                    "Generate locally trusted certificates for your development domains.",
+                    "Running containers DockStack does not manage. Compose projects whose file is \
+                     still on disk can be adopted as DockStack projects.",
                 )
                 .color(COLOR_TEXT_DIM),
             );
             ui.add_space(8.0);
-            ui.horizontal(|ui| {
-                if ui.button("Generate Certs").clicked() {
-                    *gen_ssl = true;
-                }
-                if ui.button("Remove Certs").clicked() {
-                    *rem_ssl = true;
-                }
-            });
+            if ui.button("🔍 Discover Containers").clicked() {
+                actions.discover_containers = true;
+            }
+            render_foreign_containers(ui, scans.foreign, actions);
         });
     });
 }
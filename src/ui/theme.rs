@@ -34,7 +34,17 @@ pub const COLOR_SIDEBAR: Color32 = COLOR_BG_PANEL;
 pub const COLOR_SIDEBAR_ACTIVE: Color32 = Color32::from_rgb(25, 30, 45); // Solid dark navy
 pub const COLOR_SIDEBAR_BORDER: Color32 = Color32::from_rgb(0, 180, 220); // Muted cyan for border
 
-pub fn apply_theme(ctx: &egui::Context) {
+fn high_contrast_id() -> egui::Id {
+    egui::Id::new("dockstack_high_contrast")
+}
+
+/// Whether the high-contrast variant was applied by `apply_theme`.
+pub fn is_high_contrast(ctx: &egui::Context) -> bool {
+    ctx.data(|d| d.get_temp(high_contrast_id()))
+        .unwrap_or(false)
+}
+
+pub fn apply_theme(ctx: &egui::Context, high_contrast: bool) {
     let mut style = (*ctx.style()).clone();
 
     // Spacing & Layout - Premium Flow
@@ -102,7 +112,23 @@ pub fn apply_theme(ctx: &egui::Context) {
     style.visuals.widgets.open.bg_stroke = Stroke::new(1.0, COLOR_BORDER_LIGHT);
     style.visuals.widgets.open.corner_radius = corner_radius;
 
+    if high_contrast {
+        // Every edge visible and no low-contrast grey text in default widgets
+        let edge = Stroke::new(1.5, Color32::from_gray(210));
+        style.visuals.override_text_color = Some(Color32::WHITE);
+        style.visuals.widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+        style.visuals.widgets.noninteractive.bg_stroke = edge;
+        style.visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+        style.visuals.widgets.inactive.bg_stroke = edge;
+        style.visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+        style.visuals.widgets.active.bg_stroke = Stroke::new(2.5, Color32::WHITE);
+        style.visuals.widgets.open.bg_stroke = edge;
+        style.visuals.selection.bg_fill = COLOR_PRIMARY.gamma_multiply(0.45);
+        style.visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+    }
+
     ctx.set_style(style);
+    ctx.data_mut(|d| d.insert_temp(high_contrast_id(), high_contrast));
 
     // Font setup (using default egui fonts but configured if we had assets)
     let fonts = FontDefinitions::default();
@@ -2,27 +2,130 @@
 use crate::ui::theme::*;
 use egui::{Color32, Pos2, RichText, Stroke, Ui, Vec2};
 
-/// Draw a status indicator dot
-pub fn status_dot(ui: &mut Ui, running: bool) -> egui::Response {
-    let size = Vec2::new(10.0, 10.0);
-    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+/// What a status indicator means. Every kind has its own shape, so telling
+/// states apart never depends on color alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusKind {
+    Ok,
+    Off,
+    Busy,
+    Warning,
+    Error,
+}
+
+impl StatusKind {
+    pub fn color(&self, high_contrast: bool) -> Color32 {
+        match (self, high_contrast) {
+            (Self::Ok, false) => COLOR_SUCCESS,
+            (Self::Off, false) => COLOR_TEXT_MUTED,
+            (Self::Busy, false) => COLOR_PRIMARY,
+            (Self::Warning, false) => COLOR_WARNING,
+            (Self::Error, false) => COLOR_ERROR,
+            // Okabe-Ito colors stay apart under the common color vision deficiencies
+            (Self::Ok, true) => Color32::from_rgb(86, 180, 233),
+            (Self::Off, true) => Color32::from_gray(200),
+            (Self::Busy, true) => Color32::WHITE,
+            (Self::Warning, true) => Color32::from_rgb(240, 228, 66),
+            (Self::Error, true) => Color32::from_rgb(230, 159, 0),
+        }
+    }
+}
+
+/// Status indicator: check (ok), ring (off), spinner (busy), triangle (warning)
+/// or cross (error). `label` is shown on hover and announced by screen readers.
+pub fn status_icon(ui: &mut Ui, kind: StatusKind, label: &str) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, label));
 
     if ui.is_rect_visible(rect) {
-        let center = rect.center();
-        let color = if running {
-            COLOR_SUCCESS
-        } else {
-            COLOR_TEXT_MUTED
-        };
-
-        if running {
-            ui.painter()
-                .circle_filled(center, 6.0, COLOR_SUCCESS.gamma_multiply(0.3));
+        let color = kind.color(is_high_contrast(ui.ctx()));
+        let painter = ui.painter();
+        let c = rect.center();
+        let stroke = Stroke::new(1.8, color);
+        let cutout = Stroke::new(1.8, COLOR_BG_APP);
+        match kind {
+            StatusKind::Ok => {
+                painter.circle_filled(c, 6.0, color);
+                let tick = vec![
+                    c + Vec2::new(-3.0, 0.0),
+                    c + Vec2::new(-1.0, 2.5),
+                    c + Vec2::new(3.0, -2.5),
+                ];
+                painter.add(egui::Shape::line(tick, cutout));
+            }
+            StatusKind::Off => {
+                painter.circle_stroke(c, 4.5, stroke);
+            }
+            StatusKind::Busy => {
+                let start = ui.input(|i| i.time) as f32 * 5.0;
+                let arc = (0..=8)
+                    .map(|i| c + 4.5 * Vec2::angled(start + i as f32 * 0.55))
+                    .collect();
+                painter.add(egui::Shape::line(arc, stroke));
+                ui.ctx().request_repaint();
+            }
+            StatusKind::Warning => {
+                let triangle = vec![
+                    c + Vec2::new(0.0, -6.0),
+                    c + Vec2::new(6.0, 5.0),
+                    c + Vec2::new(-6.0, 5.0),
+                ];
+                painter.add(egui::Shape::convex_polygon(triangle, color, Stroke::NONE));
+                painter.line_segment([c + Vec2::new(0.0, -2.5), c + Vec2::new(0.0, 1.5)], cutout);
+            }
+            StatusKind::Error => {
+                painter.circle_filled(c, 6.0, color);
+                let d = 2.5;
+                painter.line_segment([c + Vec2::new(-d, -d), c + Vec2::new(d, d)], cutout);
+                painter.line_segment([c + Vec2::new(-d, d), c + Vec2::new(d, -d)], cutout);
+            }
         }
-        ui.painter().circle_filled(center, 4.0, color);
     }
 
-    response
+    response.on_hover_text(label)
+}
+
+/// Outline `response` while it has keyboard focus. Custom-painted widgets call
+/// this, since egui only draws focus for its own widgets.
+pub fn focus_ring(ui: &Ui, response: &egui::Response, corner_radius: u8) {
+    if response.has_focus() {
+        ui.painter().rect_stroke(
+            response.rect.expand(2.0),
+            egui::CornerRadius::same(corner_radius),
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Outside,
+        );
+    }
+}
+
+/// Keep keyboard focus inside a dialog: `response` takes it whenever it is
+/// outside the dialog's layer, i.e. when the dialog opens or Tab wanders off.
+pub fn autofocus(ui: &Ui, response: &egui::Response) {
+    let ctx = ui.ctx();
+    let outside = ctx
+        .memory(|m| m.focused())
+        .and_then(|id| ctx.read_response(id))
+        .is_none_or(|focused| focused.layer_id != response.layer_id);
+    if outside {
+        response.request_focus();
+    }
+}
+
+/// Small pill showing a project tag in its color. Filled when `selected`.
+pub fn tag_chip(ui: &mut Ui, name: &str, color: [u8; 3], selected: bool) -> egui::Response {
+    let color = Color32::from_rgb(color[0], color[1], color[2]);
+    let (fill, text) = if selected {
+        (color, COLOR_BG_APP)
+    } else {
+        (color.gamma_multiply(0.15), color)
+    };
+    ui.add(
+        egui::Button::new(RichText::new(name).size(10.0).color(text))
+            .fill(fill)
+            .stroke(Stroke::new(1.0, color.gamma_multiply(0.6)))
+            .corner_radius(egui::CornerRadius::same(8))
+            .small(),
+    )
 }
 
 /// Draw a card container
@@ -141,8 +244,9 @@ pub fn section_header(ui: &mut Ui, text: &str) {
     ui.add_space(2.0);
 }
 
-/// Styled toggle switch
-pub fn toggle_switch(ui: &mut Ui, on: &mut bool) -> egui::Response {
+/// Styled toggle switch. Focusable with Tab and flipped with Space or Enter;
+/// `label` names it for screen readers.
+pub fn toggle_switch(ui: &mut Ui, on: &mut bool, label: &str) -> egui::Response {
     let desired_size = Vec2::new(36.0, 20.0);
     let (rect, mut response) = ui.allocate_exact_size(desired_size, egui::Sense::click());
 
@@ -150,6 +254,9 @@ pub fn toggle_switch(ui: &mut Ui, on: &mut bool) -> egui::Response {
         *on = !*on;
         response.mark_changed();
     }
+    response.widget_info(|| {
+        egui::WidgetInfo::selected(egui::WidgetType::Checkbox, ui.is_enabled(), *on, label)
+    });
 
     if ui.is_rect_visible(rect) {
         let how_on = ui.ctx().animate_bool_with_time(response.id, *on, 0.15);
@@ -173,6 +280,7 @@ pub fn toggle_switch(ui: &mut Ui, on: &mut bool) -> egui::Response {
             .rect_filled(rect, egui::CornerRadius::same(10), bg_color);
         ui.painter()
             .circle_filled(circle_center, 7.0, Color32::WHITE);
+        focus_ring(ui, &response, 12);
     }
 
     response
@@ -244,3 +352,40 @@ pub fn service_card_compact(
             });
         });
 }
+
+/// Draw `data` as a QR code, `size` points wide. The code is rendered into a
+/// texture once per `data` and kept in the UI memory.
+pub fn qr_code(ui: &mut Ui, data: &str, size: f32) {
+    let id = egui::Id::new(("qr_code", data));
+    let cached = ui.data(|d| d.get_temp::<Option<egui::TextureHandle>>(id));
+    let texture = cached.unwrap_or_else(|| {
+        let texture = qr_texture(ui.ctx(), data);
+        ui.data_mut(|d| d.insert_temp(id, texture.clone()));
+        texture
+    });
+    let Some(texture) = texture else {
+        ui.label(RichText::new("(URL too long for a QR code)").color(COLOR_TEXT_MUTED));
+        return;
+    };
+    ui.add(
+        egui::Image::new(&texture)
+            .fit_to_exact_size(Vec2::splat(size))
+            .corner_radius(4.0),
+    );
+}
+
+/// One pixel per module, black on white; `None` when `data` does not fit a QR code.
+fn qr_texture(ctx: &egui::Context, data: &str) -> Option<egui::TextureHandle> {
+    let code = qrcode::QrCode::new(data.as_bytes()).ok()?;
+    let modules = code.width();
+    // Quiet zone of two modules on each side keeps phone scanners happy
+    let side = modules + 4;
+    let mut image = egui::ColorImage::new([side, side], Color32::WHITE);
+    for (i, color) in code.to_colors().iter().enumerate() {
+        if *color == qrcode::Color::Dark {
+            image.pixels[(i / modules + 2) * side + i % modules + 2] = Color32::BLACK;
+        }
+    }
+    let name = format!("qr_code {}", data);
+    Some(ctx.load_texture(name, image, egui::TextureOptions::NEAREST))
+}
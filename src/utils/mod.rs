@@ -32,6 +32,53 @@ pub fn open_url(url: &str) {
     }
 }
 
+/// Percent-encode a value for use in a URL query string.
+pub fn url_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// The address other devices on the local network can reach this machine at.
+/// Connecting a UDP socket sends no packets; it only selects the outbound interface.
+pub fn lan_ip() -> crate::error::Result<std::net::IpAddr> {
+    use crate::error::DockStackError;
+    let context = "No network connection detected";
+    let no_route = |e: std::io::Error| DockStackError::io(context, &e);
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").map_err(no_route)?;
+    socket.connect("192.0.2.1:80").map_err(no_route)?;
+    let ip = socket.local_addr().map_err(no_route)?.ip();
+    if ip.is_loopback() || ip.is_unspecified() {
+        return Err(DockStackError::Config(format!(
+            "{}: only {} is reachable",
+            context, ip
+        )));
+    }
+    Ok(ip)
+}
+
+/// IANA name of this machine's timezone, e.g. `Europe/Berlin`, from `TZ` or the
+/// zoneinfo file `/etc/localtime` links to. None on Windows.
+pub fn host_timezone() -> Option<String> {
+    if let Ok(tz) = std::env::var("TZ") {
+        let tz = tz.trim_start_matches(':');
+        if !tz.is_empty() && !tz.starts_with('/') {
+            return Some(tz.to_string());
+        }
+    }
+    let target = std::fs::read_link("/etc/localtime").ok()?;
+    let target = target.to_string_lossy();
+    let (_, name) = target.split_once("zoneinfo/")?;
+    Some(name.to_string())
+}
+
 pub fn open_directory(path: &str) {
     let path_buf = std::path::PathBuf::from(path);
     if !path_buf.exists() {
@@ -68,3 +115,15 @@ pub fn load_icon() -> Option<egui::IconData> {
     }
 }
 
+/// Append `line` to a shared line buffer, dropping the oldest lines past `capacity`.
+pub fn push_capped<T>(
+    lines: &mut std::collections::VecDeque<T>,
+    line: T,
+    capacity: &std::sync::atomic::AtomicUsize,
+) {
+    lines.push_back(line);
+    let capacity = capacity.load(std::sync::atomic::Ordering::Relaxed).max(1);
+    while lines.len() > capacity {
+        lines.pop_front();
+    }
+}